@@ -0,0 +1,70 @@
+//! Times the recursive `fib` function from the README, compiled with and without register operands.
+//! Run with `cargo bench --bench fib`, optionally passing the argument to fib, e.g. `cargo bench --bench fib -- 30`
+
+use std::time::{Duration, Instant};
+
+use sphinx::BuildOptions;
+use sphinx::builtins;
+use sphinx::source::ModuleSource;
+use sphinx::codegen::Program;
+use sphinx::runtime::{Module, VirtualMachine};
+
+
+const RUNS: usize = 5;
+
+fn fib_source(n: u32) -> String {
+    format!(r#"
+fun fib(n)
+    if n < 2 then
+        return n
+    end
+    
+    fib(n - 2) + fib(n - 1)
+end
+
+assert fib(10) == 55
+fib({})
+"#, n)
+}
+
+fn run_once(n: u32, options: BuildOptions) -> Duration {
+    let source = ModuleSource::String(fib_source(n));
+    let (build, _) = sphinx::build_module_with(&source, options).expect("build failed");
+    let program = Program::load(build.program);
+    
+    let main_env = builtins::create_prelude();
+    let main_module = Module::with_env(Some(source), program.data, main_env);
+    
+    let vm = VirtualMachine::new(main_module, &program.main);
+    
+    let start = Instant::now();
+    vm.run().expect("fib failed");
+    start.elapsed()
+}
+
+fn bench(name: &str, n: u32, options: BuildOptions) -> Duration {
+    let mut times = Vec::new();
+    for _ in 0..RUNS {
+        let elapsed = run_once(n, options);
+        println!("{}: fib({}) in {:.3?}", name, n, elapsed);
+        times.push(elapsed);
+    }
+    
+    let best = *times.iter().min().unwrap();
+    let mean = times.iter().sum::<Duration>() / u32::try_from(times.len()).unwrap();
+    println!("{}: best: {:.3?}, mean: {:.3?}", name, best, mean);
+    best
+}
+
+fn main() {
+    let n = std::env::args().skip(1)
+        .find_map(|arg| arg.parse::<u32>().ok())
+        .unwrap_or(27);
+    
+    // the stack-only build is the baseline that register operands are measured against
+    let stack_only = BuildOptions { stack_only: true, ..BuildOptions::default() };
+    let baseline = bench("stack only", n, stack_only);
+    let registers = bench("registers", n, BuildOptions::default());
+    
+    println!("speedup: {:.2}x", baseline.as_secs_f64() / registers.as_secs_f64());
+}
@@ -0,0 +1,119 @@
+//! Times numeric loops that spend most of their time dispatching arithmetic and comparisons.
+//! Run with `cargo bench --bench loops`, optionally passing the iteration count, e.g. `cargo bench --bench loops -- 1000000`
+
+use std::time::{Duration, Instant};
+
+use sphinx::BuildOptions;
+use sphinx::builtins;
+use sphinx::source::ModuleSource;
+use sphinx::codegen::Program;
+use sphinx::runtime::{Module, VirtualMachine};
+
+
+const RUNS: usize = 5;
+
+fn while_source(n: u32) -> String {
+    format!(r#"
+fun run(n)
+    var total = 0
+    var i = 0
+    while i < n do
+        total += i % 7
+        i += 1
+    end
+    total
+end
+
+assert run(10) == 24
+run({})
+"#, n)
+}
+
+fn for_range_source(n: u32) -> String {
+    format!(r#"
+fun run(n)
+    var total = 0
+    for i in range(n) do
+        total += i * 3
+    end
+    total
+end
+
+assert run(10) == 135
+run({})
+"#, n)
+}
+
+fn collatz_source(n: u32) -> String {
+    format!(r#"
+fun steps(start)
+    var n = start
+    var count = 0
+    while n != 1 do
+        if n % 2 == 0 then n = n / 2 else n = 3 * n + 1 end
+        count += 1
+    end
+    count
+end
+
+fun run(n)
+    var best = 0
+    var i = 1
+    while i < n do
+        let s = steps(i)
+        if s > best then best = s end
+        i += 1
+    end
+    best
+end
+
+assert run(10) == 19
+run({})
+"#, n / 100)
+}
+
+fn run_once(source: &str, options: BuildOptions) -> Duration {
+    let source = ModuleSource::String(source.to_string());
+    let (build, _) = sphinx::build_module_with(&source, options).expect("build failed");
+    let program = Program::load(build.program);
+    
+    let main_env = builtins::create_prelude();
+    let main_module = Module::with_env(Some(source), program.data, main_env);
+    
+    let vm = VirtualMachine::new(main_module, &program.main);
+    
+    let start = Instant::now();
+    vm.run().expect("loop failed");
+    start.elapsed()
+}
+
+fn bench(name: &str, source: &str, options: BuildOptions) -> Duration {
+    let mut times = Vec::new();
+    for _ in 0..RUNS {
+        times.push(run_once(source, options));
+    }
+    
+    let best = *times.iter().min().unwrap();
+    let mean = times.iter().sum::<Duration>() / u32::try_from(times.len()).unwrap();
+    println!("{}: best: {:.3?}, mean: {:.3?}", name, best, mean);
+    best
+}
+
+fn main() {
+    let n = std::env::args().skip(1)
+        .find_map(|arg| arg.parse::<u32>().ok())
+        .unwrap_or(3_000_000);
+    
+    let workloads = [
+        ("while", while_source(n)),
+        ("for range", for_range_source(n)),
+        ("collatz", collatz_source(n)),
+    ];
+    
+    let stack_only = BuildOptions { stack_only: true, ..BuildOptions::default() };
+    for (name, source) in workloads.iter() {
+        let baseline = bench(&format!("{} (stack only)", name), source, stack_only);
+        let registers = bench(&format!("{} (registers)", name), source, BuildOptions::default());
+        println!("{}: speedup from registers: {:.2}x", name, baseline.as_secs_f64() / registers.as_secs_f64());
+    }
+}
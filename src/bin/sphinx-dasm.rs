@@ -1,9 +1,12 @@
-use std::path::PathBuf;
+use std::io::BufReader;
+use std::fs::File;
+use std::path::{Path, PathBuf};
 use clap::{Command, Arg, ArgMatches, crate_version};
 
 use sphinx::frontend;
-use sphinx::{BuildErrors, build_module};
+use sphinx::{BuildErrors, BuildOptions, build_module_with};
 use sphinx::source::ModuleSource;
+use sphinx::codegen::{OptLevel, BytecodeReader, BytecodeError};
 use sphinx::runtime::strings::StringInterner;
 use sphinx::debug::symbol::DebugSymbolResolver;
 use sphinx::debug::dasm::Disassembler;
@@ -29,14 +32,22 @@ fn main() {
         .arg(
             Arg::new("bytecode")
             .short('d')
-            .help("disassemble compiled bytecode (not implemented yet)")
-            .value_name("CMD")
+            .help("disassemble a compiled bytecode file")
+            .value_name("FILE")
         )
         .arg(
             Arg::new("cmd")
             .short('c')
             .help("disassemble a snippet then exit")
             .value_name("CMD")
+        )
+        .arg(
+            Arg::new("opt_level")
+            .short('O')
+            .help("optimization level to compile with: 0, 1 or 2")
+            .value_name("LEVEL")
+            .possible_values(["0", "1", "2"])
+            .default_value("0")
         );
         
     let version = app.get_version().unwrap();
@@ -50,9 +61,10 @@ fn main() {
     } else if let Some(s) = args.value_of("source") {
         source = ModuleSource::File(PathBuf::from(s));
         name = s;
-    } else if let Some(_s) = args.value_of("bytecode") {
-        // TODO read compiled bytecode and go directly to disassembly
-        unimplemented!()
+    } else if let Some(s) = args.value_of("bytecode") {
+        println!("\nSphinx Version {}\n", version);
+        disassemble_bytecode(Path::new(s));
+        return;
     } else {
         println!("No input.");
         return;
@@ -67,7 +79,11 @@ fn main() {
     
     
     // build module
-    let build_result = build_module(&source);
+    let options = BuildOptions {
+        opt_level: args.value_of_t::<OptLevel>("opt_level").unwrap_or_else(|error| error.exit()),
+        ..BuildOptions::default()
+    };
+    let build_result = build_module_with(&source, options);
     if let Err(error) = build_result {
         match error {
             BuildErrors::Source(error) => {
@@ -87,7 +103,7 @@ fn main() {
         return;
     }
     
-    let build = build_result.unwrap();
+    let (build, _) = build_result.unwrap();
     let symbols = build.symbols.values().flat_map(|table| table.symbols());
     let symbol_table = source.resolve_symbols(symbols);
     
@@ -106,6 +122,27 @@ fn main() {
     println!("{}", dasm);
 }
 
+// the source text is not available, so symbols can only be shown as offsets
+fn disassemble_bytecode(path: &Path) {
+    let result = File::open(path)
+        .map_err(BytecodeError::from)
+        .and_then(|file| BytecodeReader::new(BufReader::new(file)).read_program());
+    
+    let build = match result {
+        Ok(build) => build,
+        Err(error) => {
+            println!("Error reading \"{}\": {}.", path.display(), error);
+            return;
+        }
+    };
+    
+    let dasm = Disassembler::new(&build.program)
+        .with_symbols(&build.symbols);
+    
+    println!("== \"{}\" ==", path.display());
+    println!("{}", dasm);
+}
+
 fn parse_and_print_ast(_args: &ArgMatches, name: &str, source: &ModuleSource) {
     let source_text = match source.read_text() {
         Ok(source_text) => source_text,
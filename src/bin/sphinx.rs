@@ -1,5 +1,6 @@
-use std::io::{self, Write};
-use std::path::PathBuf;
+use std::io::{self, Read, Write, BufReader, BufWriter};
+use std::fs::File;
+use std::path::{Path, PathBuf};
 use clap::{Command, Arg, crate_version};
 
 use sphinx::frontend;
@@ -8,7 +9,9 @@ use sphinx::parser::stmt::{StmtMeta, Stmt, StmtList, ControlFlow};
 use sphinx::parser::expr::Expr;
 use sphinx::parser::primary::Atom;
 use sphinx::parser::pattern::{Pattern, MatchAction, Assignment};
-use sphinx::codegen::{Program, CompiledProgram};
+use sphinx::BuildOptions;
+use sphinx::codegen::{Program, CompiledProgram, OptLevel, BytecodeWriter, BytecodeReader, BytecodeError};
+use sphinx::codegen::bytecode;
 use sphinx::runtime::{Module, VirtualMachine, Gc};
 use sphinx::runtime::module::NamespaceEnv;
 use sphinx::runtime::strings::StringInterner;
@@ -42,17 +45,53 @@ fn main() {
         .arg(
             Arg::new("compile_only")
             .short('d')
-            .help("Produce compiled bytecode instead of executing (not implemented)")
+            .help("Produce compiled bytecode instead of executing")
+        )
+        .arg(
+            Arg::new("output")
+            .short('o')
+            .help("Output path for compiled bytecode (defaults to the input path with the \"sphc\" extension)")
+            .value_name("FILE")
+            .requires("compile_only")
+        )
+        .arg(
+            Arg::new("search_path")
+            .short('I')
+            .help("Add a directory to the module search path")
+            .value_name("DIR")
+            .multiple_occurrences(true)
         )
         .arg(
             Arg::new("debug")
             .long("debug")
             .help("Enable step-through debugging")
+        )
+        .arg(
+            Arg::new("check")
+            .long("check")
+            .help("Run the static type checker and print any warnings before executing")
+        )
+        .arg(
+            Arg::new("opt_level")
+            .short('O')
+            .help("Optimization level: 0 (none), 1 (fold constants) or 2 (also rewrite the compiled bytecode)")
+            .value_name("LEVEL")
+            .possible_values(["0", "1", "2"])
+            .default_value("0")
         );
     
     let version = app.get_version().unwrap();
     let args = app.get_matches();
     
+    let options = BuildOptions {
+        check_types: args.is_present("check"),
+        opt_level: args.value_of_t::<OptLevel>("opt_level").unwrap_or_else(|error| error.exit()),
+        ..BuildOptions::default()
+    };
+    
+    let search_paths = args.values_of("search_path")
+        .map_or_else(Vec::new, |paths| paths.map(PathBuf::from).collect());
+    
     let source;
     if let Some(s) = args.value_of("cmd") {
         source = ModuleSource::String(s.to_string());
@@ -66,16 +105,22 @@ fn main() {
     }
     
     if args.is_present("compile_only") {
-        unimplemented!()
+        let output = args.value_of("output").map(PathBuf::from);
+        compile_bytecode(&source, output, options);
     }
     else if args.is_present("interactive") {
-        if let Some(build) = build_program(&source) {
+        if let Some(build) = load_program(&source, options) {
             let program = Program::load(build.program);
             
             let repl_env = builtins::create_prelude();
             let main_module = Module::with_env(Some(source), program.data, repl_env);
             
-            let vm = VirtualMachine::new(main_module, &program.main);
+            let mut vm = VirtualMachine::new(main_module, &program.main);
+            vm.loader_mut().set_opt_level(options.opt_level);
+            for path in search_paths.iter() {
+                vm.loader_mut().add_search_path(path);
+            }
+            
             if args.is_present("debug") {
                 run_debugger(vm);
             } else if let Err(error) = vm.run() {
@@ -85,13 +130,18 @@ fn main() {
             Repl::new(version.to_string(), repl_env).run()
         }
     }
-    else if let Some(build) = build_program(&source) {
+    else if let Some(build) = load_program(&source, options) {
         let program = Program::load(build.program);
         
         let main_env = builtins::create_prelude();
         let main_module = Module::with_env(Some(source), program.data, main_env);
         
-        let vm = VirtualMachine::new(main_module, &program.main);
+        let mut vm = VirtualMachine::new(main_module, &program.main);
+        vm.loader_mut().set_opt_level(options.opt_level);
+        for path in search_paths.iter() {
+            vm.loader_mut().add_search_path(path);
+        }
+        
         if args.is_present("debug") {
             run_debugger(vm);
         } else if let Err(error) = vm.run() {
@@ -101,17 +151,80 @@ fn main() {
 }
 
 
-fn build_program(source: &ModuleSource) -> Option<CompiledProgram> {
-    match sphinx::build_module(source) {
+fn build_program(source: &ModuleSource, options: BuildOptions) -> Option<CompiledProgram> {
+    match sphinx::build_module_with(source, options) {
         Err(errors) => {
             sphinx::print_build_errors(&errors, source);
             None
         },
         
+        Ok((program, warnings)) => {
+            sphinx::print_type_warnings(&warnings, source);
+            sphinx::print_compile_warnings(&program.warnings, source);
+            Some(program)
+        }
+    }
+}
+
+// files that start with the bytecode signature are loaded directly, skipping the parser and compiler
+fn load_program(source: &ModuleSource, options: BuildOptions) -> Option<CompiledProgram> {
+    if let ModuleSource::File(path) = source {
+        let is_bytecode = File::open(path)
+            .and_then(|mut file| {
+                let mut magic = [0; bytecode::MAGIC.len()];
+                file.read_exact(&mut magic).map(|_| bytecode::is_bytecode(&magic))
+            })
+            .unwrap_or(false);
+        
+        if is_bytecode {
+            return read_bytecode(path);
+        }
+    }
+    
+    build_program(source, options)
+}
+
+fn read_bytecode(path: &Path) -> Option<CompiledProgram> {
+    let result = File::open(path)
+        .map_err(BytecodeError::from)
+        .and_then(|file| BytecodeReader::new(BufReader::new(file)).read_program());
+    
+    match result {
+        Err(error) => {
+            println!("Error reading \"{}\": {}.", path.display(), error);
+            None
+        },
+        
         Ok(program) => Some(program)
     }
 }
 
+fn compile_bytecode(source: &ModuleSource, output: Option<PathBuf>, options: BuildOptions) {
+    let output = match (output, source) {
+        (Some(output), _) => output,
+        (None, ModuleSource::File(path)) => path.with_extension(bytecode::BYTECODE_EXTENSION),
+        (None, ModuleSource::String(..)) => {
+            println!("An output path is required when compiling a snippet.");
+            return;
+        },
+    };
+    
+    let build = match build_program(source, options) {
+        Some(build) => build,
+        None => return,
+    };
+    
+    let result = File::create(&output).and_then(|file| {
+        BytecodeWriter::new(BufWriter::new(file))
+            .with_symbols(true)
+            .write_program(&build)
+    });
+    
+    if let Err(error) = result {
+        println!("Error writing \"{}\": {}.", output.display(), error);
+    }
+}
+
 fn run_debugger(vm: VirtualMachine) {
     for status in vm.run_steps() {
         match status {
@@ -240,7 +353,11 @@ impl Repl {
             Self::repl_ast_transform(&mut interner, &mut ast);
             
             let build = match sphinx::compile_ast(interner, ast) {
-                Ok(build) => build,
+                Ok(build) => {
+                    let resolver = BufferedResolver::new(input);
+                    frontend::print_source_errors(&resolver, &build.warnings);
+                    build
+                },
                 
                 Err(errors) => {
                     let resolver = BufferedResolver::new(input);
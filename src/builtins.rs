@@ -3,11 +3,15 @@ use crate::runtime::module::NamespaceEnv;
 
 mod iter;
 mod primitive;
+mod collections;
 mod misc;
+mod coroutine;
 
 use iter::create_iter_builtins;
 use primitive::{create_primitive_ctors, create_metamethod_builtins};
+use collections::create_collection_builtins;
 use misc::create_misc_builtins;
+use coroutine::create_coroutine_builtins;
 
 // thread_local! {
 //     pub static PRELUDE: Gc<NamespaceEnv> = {
@@ -24,7 +28,9 @@ pub fn create_prelude() -> Gc<NamespaceEnv> {
     
     create_metamethod_builtins(env);
     create_primitive_ctors(env);
+    create_collection_builtins(env);
     create_iter_builtins(env);
+    create_coroutine_builtins(env);
     create_misc_builtins(env);
     
     env
@@ -0,0 +1,41 @@
+use crate::runtime::Gc;
+use crate::runtime::module::NamespaceEnv;
+use crate::runtime::types::{List, Dict};
+
+
+// list and dict constructors
+pub fn create_collection_builtins(env: Gc<NamespaceEnv>) {
+
+    // creates a new list containing the values produced by an iterable
+    let list = native_function!(list, env, vm(vm), defaults(iterable = Variant::Nil) => {
+        let list = match iterable {
+            Variant::Nil => List::new(),
+            iterable => List::from(vm.iter_collect(iterable)?),
+        };
+        Ok(Variant::from(list))
+    });
+    
+    // creates a new dict from an iterable of (key, value) pairs, or by copying another dict
+    let dict = native_function!(dict, env, vm(vm), defaults(iterable = Variant::Nil) => {
+        let dict = match iterable {
+            Variant::Nil => Dict::new(),
+            Variant::Dict(other) => {
+                let dict = Dict::new();
+                for (key, value) in other.items() {
+                    dict.insert(key.try_into()?, value);
+                }
+                dict
+            }
+            iterable => {
+                let items = vm.iter_collect(iterable)?;
+                Dict::from_iterable(&Variant::from(items.into_boxed_slice()))?
+            }
+        };
+        Ok(Variant::from(dict))
+    });
+    
+    namespace_insert!(env.borrow_mut(), {
+        fun _ = list;
+        fun _ = dict;
+    });
+}
@@ -0,0 +1,39 @@
+use crate::runtime::Gc;
+use crate::runtime::module::NamespaceEnv;
+use crate::runtime::types::Type;
+use crate::runtime::vm::Coroutine;
+use crate::runtime::errors::RuntimeError;
+
+
+pub fn create_coroutine_builtins(env: Gc<NamespaceEnv>) {
+
+    // wraps a callable so that it can be suspended and resumed
+    let coroutine = native_function!(coroutine, env, params(callee) => {
+        if !matches!(callee.type_tag(), Type::Function | Type::Metatable) {
+            return Err(RuntimeError::type_mismatch(Type::Function, callee));
+        }
+        Ok(Variant::Coroutine(Gc::new(Coroutine::new(*callee))))
+    });
+    
+    // runs a coroutine until it suspends or returns, producing the suspended or returned value.
+    let resume = native_function!(resume, env, vm(vm), params(co), variadic(args) => {
+        match co {
+            Variant::Coroutine(coroutine) => vm.resume_coroutine(*coroutine, args),
+            _ => Err(RuntimeError::type_mismatch(Type::Coroutine, co)),
+        }
+    });
+    
+    // suspends the running coroutine, producing the arguments it is resumed with next.
+    let suspend = native_function!(suspend, env, vm(vm), variadic(values) => {
+        vm.suspend(values)?;
+        
+        // replaced by the resume arguments
+        Ok(Variant::Nil)
+    });
+    
+    namespace_insert!(env.borrow_mut(), {
+        fun _ = coroutine;
+        fun _ = resume;
+        fun _ = suspend;
+    });
+}
@@ -4,6 +4,7 @@ use crate::runtime::{Gc, Variant};
 use crate::runtime::gc::GcTrace;
 use crate::runtime::module::NamespaceEnv;
 use crate::runtime::types::UserIterator;
+use crate::runtime::vm::VirtualMachine;
 use crate::runtime::iter::IterState;
 use crate::runtime::errors::{RuntimeError, ExecResult};
 
@@ -61,14 +62,23 @@ unsafe impl GcTrace for Zip {
 }
 
 impl Zip {
-    fn new<'a>(iterables: &[Variant]) -> ExecResult<Self> {
+    fn new(vm: &mut VirtualMachine<'_>, iterables: &[Variant]) -> ExecResult<Self> {
         let iters = iterables.iter()
-            .map(Variant::iter_init)
+            .map(|iterable| vm.iter_init(iterable))
             .collect::<Result<Vec<IterState>,_>>()?
             .into_boxed_slice();
         
         Ok(Self { iters: RefCell::new(iters) })
     }
+    
+    fn has_values(&self) -> ExecResult<Variant> {
+        for iter in self.iters.borrow().iter() {
+            if !iter.has_value()? {
+                return Ok(Variant::BoolFalse);
+            }
+        }
+        Ok(Variant::BoolTrue)
+    }
 }
 
 impl UserIterator for Zip {
@@ -79,13 +89,23 @@ impl UserIterator for Zip {
         if state.is_some() {
             iters.iter_mut().try_for_each(IterState::advance)?;
         }
+        drop(iters);
         
-        for iter in iters.iter() {
-            if !iter.has_value()? {
-                return Ok(Variant::BoolFalse);
+        self.has_values()
+    }
+    
+    fn next_state_vm(&self, vm: &mut VirtualMachine<'_>, state: Option<&Variant>) -> ExecResult<Variant> {
+        if state.is_some() {
+            // don't hold a borrow while the VM runs, a generator could end up advancing this iterator too
+            let len = self.iters.borrow().len();
+            for idx in 0..len {
+                let iter = self.iters.borrow()[idx].clone();
+                let next_state = vm.iter_next(&iter)?;
+                self.iters.borrow_mut()[idx] = IterState::new(*iter.get_iter(), next_state);
             }
         }
-        Ok(Variant::BoolTrue)
+        
+        self.has_values()
     }
     
     fn get_item(&self, _: &Variant) -> ExecResult<Variant> {
@@ -129,8 +149,8 @@ pub fn create_iter_builtins(env: Gc<NamespaceEnv>) {
     });
     
     // yields tuples containg an element from each iterable until the first iterable is exhausted.
-    let zip = native_function!(zip, env, variadic(iterables) => {
-        let iter = Box::new(Zip::new(iterables)?);
+    let zip = native_function!(zip, env, vm(vm), variadic(iterables) => {
+        let iter = Box::new(Zip::new(vm, iterables)?);
         Ok(Variant::Iterator(Gc::from_box(iter)))
     });
     
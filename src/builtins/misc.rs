@@ -1,5 +1,6 @@
 use crate::runtime::Gc;
 use crate::runtime::module::NamespaceEnv;
+use crate::runtime::strings::static_symbol;
 use crate::runtime::errors::RuntimeError;
 
 
@@ -9,11 +10,11 @@ pub fn create_misc_builtins(env: Gc<NamespaceEnv>) {
         Ok(Variant::from(value.fmt_repr()?))
     });
     
-    let print = native_function!(print, env, variadic(values) => {
+    let print = native_function!(print, env, variadic(values), keywords(sep = static_symbol!(" ")) => {
         if let Some((first, rest)) = values.split_first() {
             print!("{}", first.fmt_str()?);
             for value in rest.iter() {
-                print!(" ");
+                print!("{}", sep.fmt_str()?);
                 print!("{}", value.fmt_str()?);
             }
         }
@@ -46,10 +47,17 @@ pub fn create_misc_builtins(env: Gc<NamespaceEnv>) {
         Ok(Variant::Nil)
     });
     
+    // Creates an error value that can be thrown
+    let error = native_function!(error, env, params(message) => {
+        let error = RuntimeError::user_error(message.fmt_str()?);
+        Ok(Variant::Error(Gc::new(*error)))
+    });
+    
     namespace_insert!(env.borrow_mut(), {
         fun _ = globals;
         fun _ = repr;
         fun _ = print;
         fun _ = help;
+        fun _ = error;
     });
 }
@@ -2,6 +2,7 @@ use crate::language::{IntType, FloatType};
 use crate::runtime::Gc;
 use crate::runtime::module::NamespaceEnv;
 use crate::runtime::types::{int_from_str, float_from_str};
+use crate::runtime::iter::IterState;
 use crate::runtime::errors::RuntimeError;
 
 
@@ -110,18 +111,19 @@ pub fn create_metamethod_builtins(env: Gc<NamespaceEnv>) {
     });
     
     // produces a tuple (item, next_state) for a given iterator state
-    let next = native_function!(next, env, params(value), defaults(state = Variant::Nil) => {
+    let next = native_function!(next, env, vm(vm), params(value), defaults(state = Variant::Nil) => {
+        let iter = IterState::new(*value, *state);
         let result = vec![ 
-            value.iter_get(state)?,
-            value.iter_next(state)?,
+            iter.get_value()?,
+            vm.iter_next(&iter)?,
         ];
         
         Ok(Variant::from(result.into_boxed_slice()))
     });
     
     // produces a tuple (iterator, init_state)
-    let iter = native_function!(iter, env, params(value) => {
-        let iter = value.iter_init()?;
+    let iter = native_function!(iter, env, vm(vm), params(value) => {
+        let iter = vm.iter_init(value)?;
         
         let result = vec![
             *iter.get_iter(),
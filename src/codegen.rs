@@ -1,31 +1,40 @@
 use core::iter;
+use core::str::FromStr;
+use string_interner::Symbol as _;
 
 use crate::language::{IntType, FloatType, InternSymbol, Access};
 use crate::parser::stmt::{StmtMeta, Stmt, Label, StmtList, ControlFlow};
-use crate::parser::expr::{Expr, ExprMeta, ExprBlock, ConditionalBranch};
-use crate::parser::primary::{Atom, Primary, AccessItem};
+use crate::parser::expr::{Expr, ExprMeta, ExprBlock, ConditionalBranch, MatchCase, CatchClause, TableItem, TableField, DictItem};
+use crate::parser::primary::{Atom, Primary, AccessItem, KeywordArg, StringPart};
 use crate::parser::pattern::{Pattern, MatchAction};
-use crate::parser::fundefs::{FunctionDef, SignatureDef};
+use crate::parser::fundefs::{FunctionDef, SignatureDef, DefaultDef};
+use crate::parser::classdefs::ClassDef;
+use crate::parser::annotation::TypeExpr;
 use crate::parser::operator::{UnaryOp, BinaryOp};
 use crate::runtime::strings::{StringInterner};
 use crate::runtime::errors::ErrorKind;
 use crate::debug::symbol::{DebugSymbol, ChunkSymbols, DebugSymbolTable};
 
 mod scope;
+mod fold;
+mod peephole;
 
 pub mod chunk;
 pub mod consts;
 pub mod funproto;
 pub mod opcodes;
 pub mod errors;
+pub mod bytecode;
 
 pub use opcodes::{OpCode, LocalIndex};
 pub use chunk::{UnloadedProgram, Program, ProgramData, Chunk};
 pub use consts::{ConstID, Constant};
 pub use funproto::{FunctionID, FunctionProto, UpvalueTarget};
-pub use errors::{CompileResult, CompileError};
+pub use errors::{CompileResult, CompileError, CompileWarning};
+pub use bytecode::{BytecodeWriter, BytecodeReader, BytecodeError};
 
 use scope::{ScopeTracker, ScopeTag, Scope, LocalName, InsertLocal, ControlFlowTarget};
+use fold::ConstValue;
 use chunk::{ChunkBuilder, ChunkInfo, ChunkBuf};
 use funproto::{UnloadedFunction, UnloadedSignature, UnloadedParam};
 
@@ -45,6 +54,7 @@ enum Jump {
     IfTrue,
     PopIfFalse,
     PopIfTrue,
+    TryBegin,  // not really a jump, but the handler offset is patched the same way
 }
 
 impl Jump {
@@ -68,9 +78,25 @@ const fn get_jump_opcode(jump: Jump, offset: JumpOffset) -> OpCode {
         
         (Jump::PopIfFalse, JumpOffset::Long(..))   => OpCode::PopLongJumpIfFalse,
         (Jump::PopIfTrue,  JumpOffset::Long(..))   => OpCode::PopLongJumpIfTrue,
+        
+        (Jump::TryBegin, JumpOffset::Short(..))    => OpCode::TryBegin,
+        (Jump::TryBegin, JumpOffset::Long(..))     => OpCode::LongTryBegin,
     }
 }
 
+const fn get_jump_type(opcode: OpCode) -> Option<Jump> {
+    let jump = match opcode {
+        OpCode::Jump | OpCode::LongJump => Jump::Uncond,
+        OpCode::JumpIfFalse | OpCode::LongJumpIfFalse => Jump::IfFalse,
+        OpCode::JumpIfTrue | OpCode::LongJumpIfTrue => Jump::IfTrue,
+        OpCode::PopJumpIfFalse | OpCode::PopLongJumpIfFalse => Jump::PopIfFalse,
+        OpCode::PopJumpIfTrue | OpCode::PopLongJumpIfTrue => Jump::PopIfTrue,
+        OpCode::TryBegin | OpCode::LongTryBegin => Jump::TryBegin,
+        _ => return None,
+    };
+    Some(jump)
+}
+
 // represents the site of a dummy jump instruction that will be patched with a target later
 #[derive(Debug)]
 struct JumpSite {
@@ -85,6 +111,29 @@ struct JumpSite {
 pub struct CompiledProgram {
     pub program: UnloadedProgram,
     pub symbols: ChunkSymbols,
+    pub warnings: Vec<CompileWarning>,
+}
+
+
+/// How much optimization the compiler performs. Each level includes the ones below it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OptLevel {
+    #[default]
+    None,
+    Fold,      // evaluate operators whose operands are all literals at compile time
+    Peephole,  // rewrite the bytecode of each chunk after it is compiled
+}
+
+impl FromStr for OptLevel {
+    type Err = String;
+    fn from_str(level: &str) -> Result<Self, Self::Err> {
+        match level {
+            "0" => Ok(Self::None),
+            "1" => Ok(Self::Fold),
+            "2" => Ok(Self::Peephole),
+            _ => Err(format!("invalid optimization level \"{}\"", level)),
+        }
+    }
 }
 
 
@@ -93,7 +142,10 @@ pub struct Compiler {
     builder: ChunkBuilder,
     scopes: ScopeTracker,
     errors: Vec<CompileError>,
+    warnings: Vec<CompileWarning>,
     symbols: ChunkSymbols,
+    opt_level: OptLevel,
+    stack_only: bool,
 }
 
 impl Compiler {
@@ -106,10 +158,24 @@ impl Compiler {
             builder: ChunkBuilder::with_strings(strings),
             scopes: ScopeTracker::new(),
             errors: Vec::new(),
+            warnings: Vec::new(),
             symbols,
+            opt_level: OptLevel::default(),
+            stack_only: false,
         }
     }
     
+    pub fn with_opt_level(mut self, opt_level: OptLevel) -> Self {
+        self.opt_level = opt_level;
+        self
+    }
+    
+    /// Compile binary operators without register operands, so that the register instructions can be compared against
+    pub fn with_stack_only(mut self, stack_only: bool) -> Self {
+        self.stack_only = stack_only;
+        self
+    }
+    
     fn new_chunk(&mut self, info: ChunkInfo) -> CompileResult<Chunk> {
         let chunk_id = self.builder.new_chunk(info)?;
         self.symbols.entry(chunk_id)
@@ -140,9 +206,14 @@ impl Compiler {
             self.get_chunk(Chunk::Main)
                 .finish();
             
+            if self.opt_level >= OptLevel::Peephole {
+                self.optimize_chunks();
+            }
+            
             let output = CompiledProgram {
                 program: self.builder.build(),
                 symbols: self.symbols,
+                warnings: self.warnings,
             };
             
             Ok(output)
@@ -150,6 +221,14 @@ impl Compiler {
             Err(self.errors)
         }
     }
+    
+    fn optimize_chunks(&mut self) {
+        let chunk_ids = self.builder.chunk_ids().collect::<Vec<Chunk>>();
+        for chunk_id in chunk_ids {
+            let symbols = self.symbols.entry(chunk_id).or_default();
+            peephole::optimize_chunk(self.builder.chunk_mut(chunk_id), symbols);
+        }
+    }
 }
 
 struct CodeGenerator<'c> {
@@ -232,6 +311,11 @@ impl CodeGenerator<'_> {
         self.symbols.pop().flatten()
     }
     
+    fn warn(&mut self, message: impl ToString) {
+        let warning = CompileWarning::new(message, self.current_symbol());
+        self.compiler.warnings.push(warning);
+    }
+    
     fn emit_symbol(&mut self, symbol: DebugSymbol) {
         let chunk_id = self.chunk_id;
         let offset = self.current_offset();
@@ -582,7 +666,22 @@ impl CodeGenerator<'_> {
                 self.emit_instr(OpCode::Pop);
             }
             
-            Stmt::Expression(expr) => {
+            Stmt::Throw(expr) => {
+                self.compile_expr(expr)?;
+                self.emit_instr(OpCode::Throw);
+            }
+            
+            Stmt::Yield(expr) => {
+                match expr {
+                    Some(expr) => self.compile_expr(expr)?,
+                    None => self.emit_instr(OpCode::Nil),
+                }
+                self.emit_instr(OpCode::Yield);
+            }
+            
+            Stmt::Delete(target) => self.compile_delete(target)?,
+            
+            Stmt::Expression(expr) => if !self.try_compile_assign_register(expr)? {
                 self.compile_expr(expr)?;
                 self.emit_instr(OpCode::Pop);
             },
@@ -645,11 +744,29 @@ impl CodeGenerator<'_> {
             ControlFlow::Return { expr, symbol } => {
                 self.push_symbol(*symbol);
                 
+                if self.scopes().iter_scopes().any(|scope| scope.tag() == ScopeTag::Finally) {
+                    return Err("\"return\" is not allowed inside of a \"finally\" clause".into());
+                }
+                
                 match expr {
+                    Some(expr) if self.is_tail_call(expr) => {
+                        // the call is the last thing the function does, so the callee can replace this frame
+                        self.compile_expr(expr)?;
+                        let offset = self.current_offset() - 1;
+                        debug_assert!(self.chunk().as_slice()[offset] == u8::from(OpCode::Call));
+                        self.chunk_mut().as_mut_slice()[offset] = u8::from(OpCode::TailCall);
+                    }
                     Some(expr) => self.compile_expr(expr)?,
                     None => self.emit_instr(OpCode::Nil),
                 }
                 
+                if let Some(return_type) = self.scopes().return_type().cloned() {
+                    self.emit_type_check(&return_type)?;
+                }
+                
+                // the return value stays on the stack while any finally clauses are run
+                self.emit_try_exits(0)?;
+                
                 self.emit_instr(OpCode::Return);
                 self.pop_symbol();
             }
@@ -657,6 +774,23 @@ impl CodeGenerator<'_> {
         Ok(())
     }
     
+    // a returned call is in tail position if nothing else needs to happen after it returns
+    fn is_tail_call(&self, expr: &Expr) -> bool {
+        let is_call = match expr {
+            Expr::Primary(primary) => match primary.path().last() {
+                Some(AccessItem::Invoke(_, kwargs)) => kwargs.is_empty(),
+                Some(AccessItem::InvokeTable(..)) => true,
+                _ => false,
+            },
+            _ => false,
+        };
+        
+        is_call
+            && matches!(self.chunk_id(), Chunk::Function(..))
+            && self.scopes().return_type().is_none()
+            && !self.scopes().iter_scopes().any(|scope| scope.tag() == ScopeTag::Try)
+    }
+    
     fn compile_break_control(&mut self, label: Option<&Label>, expr: Option<&Expr>) -> CompileResult<()> {
         // find the target scope
         let target_depth = match self.scopes().resolve_control_flow(ControlFlowTarget::Break(label.copied())) {
@@ -669,6 +803,9 @@ impl CodeGenerator<'_> {
             },
         };
         
+        self.check_finally_exit(target_depth, "break")?;
+        self.emit_try_exits(target_depth)?;
+        
         // drop all scopes up to and including the target
         let scope_drop: Vec<ScopeDrop> = self.scopes().iter_scopes()
             .take_while(|scope| scope.depth() >= target_depth)
@@ -676,15 +813,11 @@ impl CodeGenerator<'_> {
             .collect();
         
         let (target, through_scopes) = scope_drop.split_last().unwrap();
+        
+        // since break/continue must come last in a list of statements, an expression block
+        // will not have pushed its value yet when we jump out of it
         for scope in through_scopes.iter() {
             self.emit_scope_drop(scope);
-            
-            // expression blocks leave their value on the stack
-            // (this is helped by the fact that break/contine must come last in a list of statements)
-            // so if we break through an expression block we need to pop its value
-            if scope.tag.is_expr_block() {
-                self.emit_instr(OpCode::Pop);
-            }
         }
         self.emit_scope_drop(target); // drop target scope
         
@@ -723,6 +856,9 @@ impl CodeGenerator<'_> {
             }
         };
         
+        self.check_finally_exit(target_depth, "continue")?;
+        self.emit_try_exits(target_depth)?;
+        
         // drop all scopes up to and including the target
         let scope_drop: Vec<ScopeDrop> = self.scopes().iter_scopes()
             .take_while(|scope| scope.depth() >= target_depth)
@@ -731,13 +867,6 @@ impl CodeGenerator<'_> {
         
         for scope in scope_drop.iter() {
             self.emit_scope_drop(scope);
-            
-            // expression blocks leave their value on the stack
-            // (this is helped by the fact that break/contine must come last in a list of statements)
-            // so if we jump out of an expression block we need to pop its value
-            if scope.tag.is_expr_block() {
-                self.emit_instr(OpCode::Pop);
-            }
         }
         
         // emit jump site, register with scope
@@ -807,14 +936,13 @@ impl CodeGenerator<'_> {
         self.emit_instr(OpCode::IterInit);
         
         // first iteration conditional jump
-        let continue_target = self.current_offset();
         let end_jump_site = self.emit_dummy_jump(Jump::IfFalse);
         
         let loop_target = self.current_offset();
         
-        // advance iterator and assign value
+        // assign value
         // default to "let" for loop variables (unlike normal assignment, which defaults to "local")
-        self.emit_instr(OpCode::IterNext);
+        self.emit_instr(OpCode::IterGet);
         self.compile_assignment(MatchAction::DeclImmutable, pattern)?; 
         self.emit_instr(OpCode::Pop);
         
@@ -822,6 +950,10 @@ impl CodeGenerator<'_> {
         self.compile_stmt_block(body)?;
         let loop_scope = self.emit_end_scope();
         
+        // the iterator is only advanced after the body, so that a generator doesn't run ahead of the loop
+        let continue_target = self.current_offset();
+        self.emit_instr(OpCode::IterNext);
+        
         // rest iteration conditional jump
         // should have just [ ... iter state[N] ] on the stack here
         self.emit_jump_instr(Jump::IfTrue, loop_target)?;
@@ -847,6 +979,14 @@ enum Unpack {
     Dynamic,
 }
 
+// a binary operand that can be read directly by an instruction
+#[derive(Clone, Copy)]
+enum RegisterOperand {
+    Register(u8),
+    Immediate(i8),
+    Constant(u8),
+}
+
 impl CodeGenerator<'_> {
     fn compile_expr_with_symbol(&mut self, expr: &ExprMeta) -> CompileResult<()> {
         let symbol = expr.debug_symbol();
@@ -880,7 +1020,11 @@ impl CodeGenerator<'_> {
             
             Expr::Tuple(items) => self.compile_tuple(items)?,
             
-            Expr::Table(_fields) => unimplemented!(),
+            Expr::Table(items) => self.compile_table(items)?,
+            
+            Expr::List(items) => self.compile_list(items)?,
+            
+            Expr::Dict(items) => self.compile_dict(items)?,
             
             // unpacking is only allowed in invocation, tuple literals, and by itself in parentheses
             // note: assignment uses *packing*, not unpacking, which is the Pattern dual of packing.
@@ -889,8 +1033,17 @@ impl CodeGenerator<'_> {
             
             Expr::Block { label, suite } => self.compile_block_expression(label.as_ref(), suite)?,
             Expr::IfExpr { branches, else_clause } => self.compile_if_expression(branches, else_clause.as_ref().map(|expr| &**expr))?,
+            Expr::Match { subject, cases } => self.compile_match_expression(subject, cases)?,
+            Expr::Try { suite, catch, finally } => self.compile_try_expression(suite, catch.as_deref(), finally.as_deref())?,
             
             Expr::FunctionDef(fundef) => self.compile_function_def(fundef)?,
+            
+            Expr::ClassDef(classdef) => self.compile_class_def(classdef)?,
+            
+            Expr::Import(path) => {
+                self.emit_load_const(Constant::from(*path))?;
+                self.emit_instr(OpCode::Import);
+            }
         }
         Ok(())
     }
@@ -915,6 +1068,69 @@ impl CodeGenerator<'_> {
         Ok(())
     }
     
+    fn compile_list(&mut self, expr_list: &[ExprMeta]) -> CompileResult<()> {
+        match self.compile_unpack_sequence(expr_list)? {
+            Unpack::Empty => self.emit_instr_byte(OpCode::List, 0),
+            
+            Unpack::Static(len) => {
+                if let Ok(len) = u8::try_from(len) {
+                    self.emit_instr_byte(OpCode::List, len);
+                } else {
+                    self.compile_integer(len)?;
+                    self.emit_instr(OpCode::ListN);
+                }
+            }
+            
+            Unpack::Dynamic => {
+                self.emit_instr(OpCode::ListN);
+            }
+        }
+        Ok(())
+    }
+    
+    fn compile_dict(&mut self, items: &[DictItem]) -> CompileResult<()> {
+        self.emit_instr(OpCode::Dict);
+        
+        for item in items.iter() {
+            self.compile_expr_with_symbol(&item.key)?;
+            self.compile_expr_with_symbol(&item.value)?;
+            self.emit_instr(OpCode::InsertItem);
+        }
+        
+        Ok(())
+    }
+    
+    fn compile_table(&mut self, items: &[TableItem]) -> CompileResult<()> {
+        self.emit_instr(OpCode::Object);
+        
+        for item in items.iter() {
+            match &item.field {
+                TableField::Attribute(access, name) => {
+                    self.compile_expr_with_symbol(&item.value)?;
+                    if let Some(annotation) = &item.annotation {
+                        self.emit_type_check(annotation)?;
+                    }
+                    self.emit_load_const(Constant::from(*name))?;
+                    match access {
+                        Access::ReadOnly => self.emit_instr(OpCode::InsertAttr),
+                        Access::ReadWrite => self.emit_instr(OpCode::InsertAttrMut),
+                    }
+                }
+                
+                TableField::Index(index) => {
+                    self.compile_expr_with_symbol(index)?;
+                    self.compile_expr_with_symbol(&item.value)?;
+                    if let Some(annotation) = &item.annotation {
+                        self.emit_type_check(annotation)?;
+                    }
+                    self.emit_instr(OpCode::InsertItem);
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
     // compiles to a sequence of values
     fn compile_unpack_sequence(&mut self, seq: &[ExprMeta]) -> CompileResult<Unpack> {
         if seq.is_empty() {
@@ -940,16 +1156,24 @@ impl CodeGenerator<'_> {
                     
                     // store unpack len in accumulator
                     if let Some(local_index) = unpack_len {
-                        self.emit_load_local_index(local_index);
-                        self.emit_instr(OpCode::Add);
-                        self.emit_assign_local(local_index);
+                        match self.try_register_index(local_index) {
+                            Some(register) => self.emit_instr_data(
+                                OpCode::BinarySRStore, &[u8::from(OpCode::Add), register, register]
+                            ),
+                            None => {
+                                self.emit_load_local_index(local_index);
+                                self.emit_instr(OpCode::Add);
+                                self.emit_assign_local(local_index);
+                                self.emit_instr(OpCode::Pop);
+                            }
+                        }
                     } else {
                         self.emit_begin_scope(None, ScopeTag::Temporary);
                         let local_index = self.emit_create_temporary(Access::ReadWrite)?;
                         unpack_len = Some(local_index);
+                        self.emit_instr(OpCode::Pop);
                     }
                     
-                    self.emit_instr(OpCode::Pop);
                     self.pop_symbol();
                 }
                 
@@ -962,7 +1186,6 @@ impl CodeGenerator<'_> {
         }
         
         // if the last item is an unpack expression, it does not need to use the local accumulator
-        // TODO should there be a dedicated accumulator register?
         match last.variant() {
             Expr::Unpack(None) => return Err("need a value to unpack".into()),
             
@@ -975,8 +1198,13 @@ impl CodeGenerator<'_> {
                 self.emit_instr(OpCode::IterUnpack);
                 
                 if let Some(local_index) = unpack_len {
-                    self.emit_load_local_index(local_index);
-                    self.emit_instr(OpCode::Add);
+                    match self.try_register_index(local_index) {
+                        Some(register) => self.emit_instr_data(OpCode::BinarySR, &[u8::from(OpCode::Add), register]),
+                        None => {
+                            self.emit_load_local_index(local_index);
+                            self.emit_instr(OpCode::Add);
+                        }
+                    }
                     
                     debug_assert!(self.scopes().is_temporary_scope());
                     self.emit_end_scope();
@@ -1026,12 +1254,13 @@ impl CodeGenerator<'_> {
             Atom::FloatLiteral(value) => self.compile_float(*value)?,
             
             Atom::StringLiteral(value) => self.emit_load_const(Constant::from(*value))?,
+            Atom::InterpolatedString(parts) => self.compile_interpolated_string(parts)?,
             Atom::Identifier(name) => self.compile_name_lookup(name)?,
             
-            // Atom::Self_ => unimplemented!(),
-            // Atom::Super => unimplemented!(),
+            Atom::Self_ => self.compile_self_lookup()?,
+            Atom::Super => return Err("\"super\" can only be used to access a method, e.g. \"super.name\"".into()),
             
-            Atom::Group { modifier, inner } => {
+            Atom::Group { modifier, inner, annotation } => {
                 // modifiers are not allowed outside of assignment
                 if modifier.is_some() {
                     return Err("assignment modifiers are not allowed outside of an assignment expression".into())
@@ -1051,11 +1280,47 @@ impl CodeGenerator<'_> {
                     _ => self.compile_expr(inner)?,
                 }
                 
+                if let Some(annotation) = annotation {
+                    self.emit_type_check(annotation)?;
+                }
             },
         }
         Ok(())
     }
     
+    // each part is formatted as a string, then they are all concatenated together
+    fn compile_interpolated_string(&mut self, parts: &[StringPart]) -> CompileResult<()> {
+        let mut pending = 0;
+        for part in parts.iter() {
+            // concatenate in chunks if there are too many parts for a single instruction
+            if pending == u8::MAX {
+                self.emit_instr_byte(OpCode::Concat, pending);
+                pending = 1;
+            }
+            
+            match part {
+                StringPart::Literal(value) => self.emit_load_const(Constant::from(*value))?,
+                
+                StringPart::Expr { expr, spec } => {
+                    self.compile_expr_with_symbol(expr)?;
+                    
+                    if let Some(spec) = spec {
+                        self.emit_load_const(Constant::from(*spec))?;
+                        self.emit_instr(OpCode::FmtSpec);
+                    } else {
+                        self.emit_instr(OpCode::FmtStr);
+                    }
+                },
+            }
+            pending += 1;
+        }
+        
+        if pending > 1 {
+            self.emit_instr_byte(OpCode::Concat, pending);
+        }
+        Ok(())
+    }
+    
     fn compile_integer(&mut self, value: IntType) -> CompileResult<()> {
         if let Ok(value) = u8::try_from(value) {
             self.emit_instr_byte(OpCode::UInt8, value);
@@ -1087,29 +1352,89 @@ impl CodeGenerator<'_> {
         }
         
         // Otherwise, it must be a Global variable
-        self.emit_load_const(Constant::from(*name))?;
-        self.emit_instr(OpCode::LoadGlobal);
+        let cid = self.get_or_make_const(Constant::from(*name))?;
+        if let Ok(cid) = u8::try_from(cid) {
+            self.emit_instr_byte(OpCode::LoadGlobal, cid);
+        } else {
+            self.emit_instr_data(OpCode::LoadGlobal16, &cid.to_le_bytes());
+        }
         Ok(())
     }
     
+    fn compile_self_lookup(&mut self) -> CompileResult<()> {
+        let local_name = LocalName::Symbol(self.self_symbol());
+        
+        if self.try_emit_load_local(&local_name).is_some() {
+            return Ok(());
+        }
+        
+        if self.try_emit_load_upval(&local_name)?.is_some() {
+            return Ok(());
+        }
+        
+        Err("\"self\" is only valid inside a method".into())
+    }
+    
+    // the name of the implicit first parameter of a method
+    fn self_symbol(&mut self) -> InternSymbol {
+        let string_id = self.builder_mut().get_or_insert_str("self");
+        InternSymbol::try_from_usize(string_id).unwrap()
+    }
+    
     fn compile_primary(&mut self, primary: &Primary) -> CompileResult<()> {
-        self.compile_atom(primary.atom())?;
+        let mut path = primary.path().iter();
+        
+        if let Atom::Super = primary.atom() {
+            // super.name => the method "name" of the base class, bound to self
+            match path.next() {
+                Some(AccessItem::Attribute(name)) => self.compile_super_access(name)?,
+                _ => return Err("\"super\" can only be used to access a method, e.g. \"super.name\"".into()),
+            }
+        } else {
+            self.compile_atom(primary.atom())?;
+        }
         
-        for item in primary.path().iter() {
+        for item in path {
             match item {
-                AccessItem::Attribute(_name) => unimplemented!(),
-                AccessItem::Index(_index) => unimplemented!(),
-                AccessItem::Invoke(args) => self.compile_invocation(args)?,
-                AccessItem::InvokeTable(_table) => unimplemented!(),
+                AccessItem::Attribute(name) => {
+                    self.emit_load_const(Constant::from(*name))?;
+                    self.emit_instr(OpCode::LoadAttr);
+                }
+                AccessItem::Index(index) => {
+                    self.compile_expr_with_symbol(index)?;
+                    self.emit_instr(OpCode::LoadItem);
+                }
+                AccessItem::Invoke(args, kwargs) => self.compile_invocation(args, kwargs)?,
+                AccessItem::InvokeTable(items) => {
+                    // invoke the receiver with the table as the only argument
+                    self.compile_table(items)?;
+                    self.emit_instr_byte(OpCode::UInt8, 1);
+                    self.emit_instr(OpCode::Call);
+                }
             }
         }
         
         Ok(())
     }
     
-    fn compile_invocation(&mut self, args: &[ExprMeta]) -> CompileResult<()> {
+    fn compile_super_access(&mut self, name: &InternSymbol) -> CompileResult<()> {
+        // [ self base name ] => [ method ]
+        self.compile_self_lookup()?;
+        
+        if self.try_emit_load_local(&LocalName::Super).is_none()
+            && self.try_emit_load_upval(&LocalName::Super)?.is_none() {
+            return Err("\"super\" is only valid inside a method".into());
+        }
+        
+        self.emit_load_const(Constant::from(*name))?;
+        self.emit_instr(OpCode::LoadSuper);
+        Ok(())
+    }
+    
+    fn compile_invocation(&mut self, args: &[ExprMeta], kwargs: &[KeywordArg]) -> CompileResult<()> {
         // prepare argument list:
         // [ callobj arg[0] ... arg[n] nargs ] => [ ret_value ] 
+        // [ callobj arg[0] ... arg[n] nargs name[0] kwarg[0] ... name[m] kwarg[m] ] => [ ret_value ]
 
         // process argument unpacking
         match self.compile_unpack_sequence(args)? {
@@ -1117,13 +1442,30 @@ impl CodeGenerator<'_> {
             Unpack::Static(len) => self.compile_integer(len)?,
             Unpack::Dynamic => { } // nothing to do
         }
-
-        self.emit_instr(OpCode::Call);
+        
+        if kwargs.is_empty() {
+            self.emit_instr(OpCode::Call);
+            return Ok(());
+        }
+        
+        let count = u8::try_from(kwargs.len())
+            .map_err(|_| "keyword argument limit exceeded")?;
+        
+        for kwarg in kwargs.iter() {
+            self.emit_load_const(Constant::from(kwarg.name))?;
+            self.compile_expr_with_symbol(&kwarg.value)?;
+        }
+        
+        self.emit_instr_byte(OpCode::CallKeywords, count);
 
         Ok(())
     }
     
     fn compile_unary_op(&mut self, op: UnaryOp, expr: &Expr) -> CompileResult<()> {
+        if let Some(value) = self.try_fold_unary(op, expr) {
+            return self.emit_const_value(value);
+        }
+        
         self.compile_expr(expr)?;
         match op {
             UnaryOp::Neg => self.emit_instr(OpCode::Neg),
@@ -1135,6 +1477,9 @@ impl CodeGenerator<'_> {
     }
     
     fn compile_binary_op(&mut self, op: BinaryOp, lhs: &Expr, rhs: &Expr) -> CompileResult<()> {
+        if let Some(value) = self.try_fold_binary(op, lhs, rhs) {
+            return self.emit_const_value(value);
+        }
         
         if matches!(op, BinaryOp::And) {
             return self.compile_shortcircuit_and(lhs, rhs);
@@ -1143,78 +1488,343 @@ impl CodeGenerator<'_> {
             return self.compile_shortcircuit_or(lhs, rhs);
         }
         
-        self.compile_expr(lhs)?;
-        self.compile_expr(rhs)?;
-        self.emit_binary_op(op);
+        self.compile_binary_operands(op, lhs, rhs, None)
+    }
+    
+    // Locals double as registers, so operands that are local variables or small integers can be read
+    // directly by the instruction instead of being loaded onto the stack first. If `dest` is given, the
+    // result is stored in that register instead of being pushed.
+    fn compile_binary_operands(&mut self, op: BinaryOp, lhs: &Expr, rhs: &Expr, dest: Option<u8>) -> CompileResult<()> {
+        let opcode = u8::from(Self::binary_opcode(op));
+        
+        // a register is read when the instruction executes, so the lhs can only be a register 
+        // if evaluating the rhs can't assign to it
+        let rhs_operand =
+            if self.compiler.stack_only { None }
+            else { self.try_register_operand(rhs) };
+        let lhs_register = rhs_operand.and_then(|_| self.try_register(lhs));
+        
+        match (lhs_register, rhs_operand) {
+            (Some(lhs), Some(RegisterOperand::Register(rhs))) => 
+                self.emit_register_instr(OpCode::BinaryRR, OpCode::BinaryRRStore, &[opcode, lhs, rhs], dest),
+            
+            (Some(lhs), Some(RegisterOperand::Immediate(rhs))) => 
+                self.emit_register_instr(OpCode::BinaryRI, OpCode::BinaryRIStore, &[opcode, lhs, rhs.to_le_bytes()[0]], dest),
+            
+            (None, Some(RegisterOperand::Register(rhs))) => {
+                self.compile_expr(lhs)?;
+                self.emit_register_instr(OpCode::BinarySR, OpCode::BinarySRStore, &[opcode, rhs], dest);
+            }
+            
+            (None, Some(RegisterOperand::Immediate(rhs))) => {
+                self.compile_expr(lhs)?;
+                self.emit_register_instr(OpCode::BinarySI, OpCode::BinarySIStore, &[opcode, rhs.to_le_bytes()[0]], dest);
+            }
+            
+            (Some(lhs), Some(RegisterOperand::Constant(rhs))) => 
+                self.emit_register_instr(OpCode::BinaryRC, OpCode::BinaryRCStore, &[opcode, lhs, rhs], dest),
+            
+            (None, Some(RegisterOperand::Constant(rhs))) => {
+                self.compile_expr(lhs)?;
+                self.emit_register_instr(OpCode::BinarySC, OpCode::BinarySCStore, &[opcode, rhs], dest);
+            }
+            
+            (_, None) => match (dest, self.try_register(lhs)) {
+                (Some(dest), Some(lhs)) if !Self::can_assign(rhs) => {
+                    self.compile_expr(rhs)?;
+                    self.emit_instr_data(OpCode::BinaryRSStore, &[opcode, lhs, dest]);
+                }
+                
+                _ => {
+                    self.compile_expr(lhs)?;
+                    self.compile_expr(rhs)?;
+                    self.emit_binary_op(op);
+                    if let Some(dest) = dest {
+                        self.emit_assign_local(LocalIndex::from(dest));
+                        self.emit_instr(OpCode::Pop);
+                    }
+                }
+            }
+        }
         
         Ok(())
     }
     
+    // emit an instruction that takes register operands, or its store form if the result goes to a register
+    fn emit_register_instr(&mut self, opcode: OpCode, store: OpCode, data: &[u8], dest: Option<u8>) {
+        if let Some(dest) = dest {
+            let data = [data, &[dest]].concat();
+            self.emit_instr_data(store, &data);
+        } else {
+            self.emit_instr_data(opcode, data);
+        }
+    }
+    
+    // whether evaluating an expression might assign to a variable. Operators can't call back into
+    // the VM, so an expression that is made of only operators and simple atoms never does.
+    fn can_assign(expr: &Expr) -> bool {
+        match expr {
+            Expr::Atom(Atom::Group { modifier: None, inner, annotation: None }) => Self::can_assign(inner),
+            Expr::Atom(Atom::InterpolatedString(..) | Atom::Group {..}) => true,
+            Expr::Atom(..) => false,
+            Expr::UnaryOp(_, expr) => Self::can_assign(expr),
+            Expr::BinaryOp(_, exprs) => Self::can_assign(&exprs.0) || Self::can_assign(&exprs.1),
+            _ => true,
+        }
+    }
+    
+    fn try_register(&self, expr: &Expr) -> Option<u8> {
+        if let Expr::Atom(Atom::Identifier(name)) = expr {
+            let local = self.scopes().resolve_local(&LocalName::Symbol(*name))?;
+            return u8::try_from(local.index()).ok();
+        }
+        None
+    }
+    
+    // temporaries allocated from the scope tracker can be used as registers too
+    fn try_register_index(&self, index: LocalIndex) -> Option<u8> {
+        if self.compiler.stack_only { None }
+        else { u8::try_from(index).ok() }
+    }
+    
+    fn try_register_operand(&mut self, expr: &Expr) -> Option<RegisterOperand> {
+        let value = match expr {
+            Expr::Atom(Atom::IntegerLiteral(value)) => Some(ConstValue::Integer(*value)),
+            Expr::Atom(Atom::FloatLiteral(value)) => Some(ConstValue::Float(*value)),
+            Expr::UnaryOp(op, expr) => self.try_fold_unary(*op, expr),
+            Expr::BinaryOp(op, exprs) => self.try_fold_binary(*op, &exprs.0, &exprs.1),
+            _ => None,
+        };
+        
+        // numbers that don't fit in an immediate are read from the constant table instead
+        match value {
+            Some(ConstValue::Integer(value)) => match i8::try_from(value) {
+                Ok(value) => Some(RegisterOperand::Immediate(value)),
+                Err(..) => self.try_constant_operand(Constant::from(value)),
+            },
+            Some(ConstValue::Float(value)) => self.try_constant_operand(Constant::from(value)),
+            _ => self.try_register(expr).map(RegisterOperand::Register),
+        }
+    }
+    
+    fn try_constant_operand(&mut self, value: Constant) -> Option<RegisterOperand> {
+        let cid = self.get_or_make_const(value).ok()?;
+        u8::try_from(cid).ok().map(RegisterOperand::Constant)
+    }
+    
     fn emit_binary_op(&mut self, op: BinaryOp) {
+        self.emit_instr(Self::binary_opcode(op))
+    }
+    
+    fn binary_opcode(op: BinaryOp) -> OpCode {
         match op {
             BinaryOp::And | BinaryOp::Or => unreachable!(),
             
-            BinaryOp::Mul => self.emit_instr(OpCode::Mul),
-            BinaryOp::Div => self.emit_instr(OpCode::Div),
-            BinaryOp::Mod => self.emit_instr(OpCode::Mod),
-            BinaryOp::Add => self.emit_instr(OpCode::Add),
-            BinaryOp::Sub => self.emit_instr(OpCode::Sub),
-            
-            BinaryOp::BitAnd => self.emit_instr(OpCode::And),
-            BinaryOp::BitXor => self.emit_instr(OpCode::Xor),
-            BinaryOp::BitOr  => self.emit_instr(OpCode::Or),
-            
-            BinaryOp::LShift  => self.emit_instr(OpCode::Shl),
-            BinaryOp::RShift => self.emit_instr(OpCode::Shr),
-            
-            BinaryOp::LT => self.emit_instr(OpCode::LT),
-            BinaryOp::GT => self.emit_instr(OpCode::GT),
-            BinaryOp::LE => self.emit_instr(OpCode::LE),
-            BinaryOp::GE => self.emit_instr(OpCode::GE),
-            BinaryOp::EQ => self.emit_instr(OpCode::EQ),
-            BinaryOp::NE => self.emit_instr(OpCode::NE),
-        };
+            BinaryOp::Exp => OpCode::Exp,
+            BinaryOp::Mul => OpCode::Mul,
+            BinaryOp::Div => OpCode::Div,
+            BinaryOp::Mod => OpCode::Mod,
+            BinaryOp::Add => OpCode::Add,
+            BinaryOp::Sub => OpCode::Sub,
+            
+            BinaryOp::BitAnd => OpCode::And,
+            BinaryOp::BitXor => OpCode::Xor,
+            BinaryOp::BitOr  => OpCode::Or,
+            
+            BinaryOp::LShift => OpCode::Shl,
+            BinaryOp::RShift => OpCode::Shr,
+            
+            BinaryOp::LT => OpCode::LT,
+            BinaryOp::GT => OpCode::GT,
+            BinaryOp::LE => OpCode::LE,
+            BinaryOp::GE => OpCode::GE,
+            BinaryOp::EQ => OpCode::EQ,
+            BinaryOp::NE => OpCode::NE,
+        }
+    }
+}
+
+///////// Constant Folding /////////
+impl CodeGenerator<'_> {
+    fn try_fold_expr(&mut self, expr: &Expr) -> Option<ConstValue> {
+        match expr {
+            Expr::Atom(Atom::Group { modifier: None, inner, annotation: None }) => self.try_fold_expr(inner),
+            Expr::Atom(atom) => ConstValue::from_atom(atom),
+            Expr::UnaryOp(op, expr) => self.try_fold_unary(*op, expr),
+            Expr::BinaryOp(op, exprs) => self.try_fold_binary(*op, &exprs.0, &exprs.1),
+            _ => None,
+        }
+    }
+    
+    fn try_fold_unary(&mut self, op: UnaryOp, expr: &Expr) -> Option<ConstValue> {
+        if self.compiler.opt_level < OptLevel::Fold {
+            return None;
+        }
+        
+        let operand = self.try_fold_expr(expr)?;
+        fold::fold_unary(op, operand)
+    }
+    
+    fn try_fold_binary(&mut self, op: BinaryOp, lhs: &Expr, rhs: &Expr) -> Option<ConstValue> {
+        if self.compiler.opt_level < OptLevel::Fold {
+            return None;
+        }
+        
+        let lhs = self.try_fold_expr(lhs)?;
+        let rhs = self.try_fold_expr(rhs)?;
+        fold::fold_binary(op, lhs, rhs, self.builder_mut())
+    }
+    
+    fn emit_const_value(&mut self, value: ConstValue) -> CompileResult<()> {
+        match value {
+            ConstValue::Nil => self.emit_instr(OpCode::Nil),
+            ConstValue::Bool(true) => self.emit_instr(OpCode::True),
+            ConstValue::Bool(false) => self.emit_instr(OpCode::False),
+            ConstValue::Integer(value) => self.compile_integer(value)?,
+            ConstValue::Float(value) => self.compile_float(value)?,
+            ConstValue::String(index) => self.emit_load_const(Constant::String(index))?,
+        }
+        Ok(())
     }
 }
 
 ///////// Declarations and Assignments /////////
 impl CodeGenerator<'_> {
+    // An assignment to a local whose value is discarded can store the result of a binary operator directly
+    // in the local's register, instead of pushing the result, storing it and then popping it.
+    // Returns false if nothing was emitted, so that the assignment can be compiled as an expression.
+    fn try_compile_assign_register(&mut self, expr: &Expr) -> CompileResult<bool> {
+        let assign = match expr {
+            Expr::Assignment(assign) if !self.compiler.stack_only => assign,
+            _ => return Ok(false),
+        };
+        
+        let name = match (&assign.lhs, assign.action) {
+            (Pattern::Identifier(name), MatchAction::AssignLocal | MatchAction::AssignNonLocal) => name,
+            _ => return Ok(false),
+        };
+        
+        // anything that can't be written to is left to compile_assign_identifier() to report
+        let dest = self.scopes().resolve_local(&LocalName::Symbol(*name))
+            .filter(|local| local.mode().can_write())
+            .and_then(|local| u8::try_from(local.index()).ok());
+        let dest = match dest {
+            Some(dest) => dest,
+            None => return Ok(false),
+        };
+        
+        match (assign.op, &assign.rhs) {
+            (Some(op), rhs) => {
+                let target = Expr::Atom(Atom::Identifier(*name));
+                self.compile_binary_operands(op, &target, rhs, Some(dest))?;
+            }
+            
+            (None, Expr::BinaryOp(op, exprs)) if !matches!(op, BinaryOp::And | BinaryOp::Or) => {
+                let (lhs, rhs) = &**exprs;
+                if let Some(value) = self.try_fold_binary(*op, lhs, rhs) {
+                    self.emit_const_value(value)?;
+                    self.emit_assign_local(LocalIndex::from(dest));
+                    self.emit_instr(OpCode::Pop);
+                } else {
+                    self.compile_binary_operands(*op, lhs, rhs, Some(dest))?;
+                }
+            }
+            
+            _ => return Ok(false),
+        }
+        
+        Ok(true)
+    }
+    
     fn compile_update_assignment(&mut self, op: BinaryOp, action: MatchAction, lhs: &Pattern, rhs: &Expr) -> CompileResult<()> {
         
-        let local_only = match action {
-            MatchAction::AssignLocal => true,
-            MatchAction::AssignNonLocal => false,
+        let allow_nonlocal = match action {
+            MatchAction::AssignLocal => false,
+            MatchAction::AssignNonLocal => true,
             
             MatchAction::DeclImmutable | MatchAction::DeclMutable
                 => return Err("update-assignment is invalid when declaring a variable".into()),
         };
         
-        // TODO suport Attribute and Index LValues as well
         match lhs {
             Pattern::Identifier(name) => {
-                self.compile_name_lookup(name)?;
+                let target = Expr::Atom(Atom::Identifier(*name));
+                self.compile_binary_operands(op, &target, rhs, None)?;
+                
+                self.compile_assign_identifier(name, allow_nonlocal)
+            },
+            
+            // the receiver (and index) are stored in temporaries so that they are only evaluated once
+            Pattern::Attribute(target) => {
+                self.emit_begin_scope(None, ScopeTag::Temporary);
+                
+                self.compile_primary(&target.receiver)?;
+                let receiver = self.emit_create_temporary(Access::ReadOnly)?;
+                
+                self.emit_load_const(Constant::from(target.name))?;
+                self.emit_instr(OpCode::LoadAttr);
                 self.compile_expr(rhs)?;
                 self.emit_binary_op(op);
                 
-                self.compile_assign_identifier(name, local_only)
+                self.emit_load_local_index(receiver);
+                self.emit_load_const(Constant::from(target.name))?;
+                self.emit_instr(OpCode::StoreAttr);
+                
+                self.emit_end_scope();
+                Ok(())
             },
             
-            Pattern::Attribute(_target) => unimplemented!(),
-            
-            Pattern::Index(_target) => unimplemented!(),
-            
-            Pattern::Tuple {..} | Pattern::Pack(..)
-                => Err("can't update-assign to this".into()),
+            Pattern::Index(target) => {
+                self.emit_begin_scope(None, ScopeTag::Temporary);
+                
+                self.compile_primary(&target.receiver)?;
+                let receiver = self.emit_create_temporary(Access::ReadOnly)?;
+                
+                self.compile_expr_with_symbol(&target.index)?;
+                let index = self.emit_create_temporary(Access::ReadOnly)?;
+                
+                self.emit_instr(OpCode::LoadItem);
+                self.compile_expr(rhs)?;
+                self.emit_binary_op(op);
+                
+                self.emit_load_local_index(receiver);
+                self.emit_load_local_index(index);
+                self.emit_instr(OpCode::StoreItem);
+                
+                self.emit_end_scope();
+                Ok(())
+            },
+            
+            Pattern::Tuple {..} | Pattern::Pack(..)
+                => Err("can't update-assign to this".into()),
+            
+            Pattern::Annotated {..} 
+                => Err("type annotations are not allowed in update-assignment".into()),
             
             Pattern::Modifier {..} => unreachable!(),
+            
+            Pattern::Wildcard | Pattern::Literal(..) | Pattern::Object(..) | Pattern::Alternative(..)
+                => Err("case patterns can only be used in a match expression".into()),
         }
     }
     
     fn compile_assignment(&mut self, mut action: MatchAction, mut lhs: &Pattern) -> CompileResult<()> {
         
-        while let Pattern::Modifier { modifier, pattern } = lhs {
-            action = *modifier;
-            lhs = pattern;
+        loop {
+            match lhs {
+                Pattern::Modifier { modifier, pattern } => {
+                    action = *modifier;
+                    lhs = pattern;
+                }
+                
+                // the value being assigned is on top of the stack
+                Pattern::Annotated { pattern, annotation } => {
+                    self.emit_type_check(annotation)?;
+                    lhs = pattern;
+                }
+                
+                _ => break,
+            }
         }
         
         match lhs {
@@ -1225,6 +1835,9 @@ impl CodeGenerator<'_> {
                 self.compile_assign_tuple(action, item)
             }
             
+            Pattern::Wildcard | Pattern::Literal(..) | Pattern::Object(..) | Pattern::Alternative(..)
+                => Err("case patterns can only be used in a match expression".into()),
+            
             lhs => {
                 match action {
                     MatchAction::AssignLocal => self.compile_assign_variable(lhs, false),
@@ -1246,6 +1859,7 @@ impl CodeGenerator<'_> {
             
             Pattern::Tuple {..} => unreachable!(),
             Pattern::Modifier {..} => unreachable!(),
+            Pattern::Annotated {..} => unreachable!(),
             
             _ => Err("not a variable name".into()),
         }
@@ -1279,9 +1893,19 @@ impl CodeGenerator<'_> {
         match lhs {
             Pattern::Identifier(name) => self.compile_assign_identifier(name, allow_nonlocal),
             
-            Pattern::Attribute(_target) => unimplemented!(),
+            Pattern::Attribute(target) => {
+                self.compile_primary(&target.receiver)?;
+                self.emit_load_const(Constant::from(target.name))?;
+                self.emit_instr(OpCode::StoreAttr);
+                Ok(())
+            },
             
-            Pattern::Index(_target) => unimplemented!(),
+            Pattern::Index(target) => {
+                self.compile_primary(&target.receiver)?;
+                self.compile_expr_with_symbol(&target.index)?;
+                self.emit_instr(OpCode::StoreItem);
+                Ok(())
+            },
             
             _ => panic!("invalid assignment target"),
         }
@@ -1333,8 +1957,52 @@ impl CodeGenerator<'_> {
         }
 
         // ...finally, try to assign to a global, which are late bound
-        self.emit_load_const(Constant::from(*name))?;
-        self.emit_instr(OpCode::StoreGlobal);
+        let cid = self.get_or_make_const(Constant::from(*name))?;
+        if let Ok(cid) = u8::try_from(cid) {
+            self.emit_instr_byte(OpCode::StoreGlobal, cid);
+        } else {
+            self.emit_instr_data(OpCode::StoreGlobal16, &cid.to_le_bytes());
+        }
+        Ok(())
+    }
+    
+    // only globals can be deleted, local variables are unbound when their scope ends
+    fn compile_delete(&mut self, target: &Pattern) -> CompileResult<()> {
+        match target {
+            Pattern::Identifier(name) => {
+                let local_name = LocalName::Symbol(*name);
+                if !self.scopes().is_global_scope() {
+                    if self.scopes().resolve_local(&local_name).is_some() {
+                        return Err("can't delete a local variable".into());
+                    }
+                    
+                    if self.scopes().is_call_frame() && self.scopes_mut().resolve_or_create_upval(&local_name)?.is_some() {
+                        return Err("can't delete a non-local variable".into());
+                    }
+                }
+                
+                self.emit_load_const(Constant::from(*name))?;
+                self.emit_instr(OpCode::DropGlobal);
+            },
+            
+            Pattern::Attribute(target) => {
+                self.compile_primary(&target.receiver)?;
+                self.emit_load_const(Constant::from(target.name))?;
+                self.emit_instr(OpCode::DropAttr);
+            },
+            
+            Pattern::Index(target) => {
+                self.compile_primary(&target.receiver)?;
+                self.compile_expr_with_symbol(&target.index)?;
+                self.emit_instr(OpCode::DropItem);
+            },
+            
+            Pattern::Tuple(items) => for item in items.iter() {
+                self.compile_delete(item)?;
+            },
+            
+            _ => return Err("can't delete this".into()),
+        }
         Ok(())
     }
     
@@ -1387,11 +2055,11 @@ impl CodeGenerator<'_> {
             let error_jump = self.emit_dummy_jump(Jump::IfFalse);
             error_jump_sites.push(error_jump);
             
-            // advance the iterator and put the item on the stack
-            self.emit_instr(OpCode::IterNext);
-            
+            // put the item on the stack and advance the iterator
+            self.emit_instr(OpCode::IterGet);
             self.compile_assignment(action, target)?;
             self.emit_instr(OpCode::Pop);
+            self.emit_instr(OpCode::IterNext);
         }
         
         let temp_scope;
@@ -1493,11 +2161,11 @@ impl CodeGenerator<'_> {
             let error_jump = self.emit_dummy_jump(Jump::IfFalse);
             error_jump_sites.push(error_jump);
             
-            // advance the iterator and put the item on the stack
-            self.emit_instr(OpCode::IterNext);
-            
+            // put the item on the stack and advance the iterator
+            self.emit_instr(OpCode::IterGet);
             self.compile_assignment(action, target)?;
             self.emit_instr(OpCode::Pop);
+            self.emit_instr(OpCode::IterNext);
         }
         
         // if the iterator is finished we've succeeded
@@ -1550,7 +2218,7 @@ impl CodeGenerator<'_> {
         let mut end_jump_sites = Vec::new();
         
         // if there is no else branch, the last non-else branch won't have a jump to end, and should not pop the condition
-        // this is because if-expressions without an else clause evaluate to their condition when not entered
+        // when it is not entered. This is because if-expressions without an else clause evaluate to their condition
         let (last_branch, rest) = branches.split_last().unwrap();
         let iter_branches = rest.iter()
             .map(|branch| (false, branch))
@@ -1561,9 +2229,9 @@ impl CodeGenerator<'_> {
             
             self.compile_expr(branch.condition())?;
             
-            // need to keep condition value on the stack in case there is a break/continue
-            // inside the statement list
+            // the condition is only kept on the stack if the branch is not entered
             let branch_jump_site = self.emit_dummy_jump(Jump::IfFalse);
+            self.emit_instr(OpCode::Pop);
             
             self.emit_begin_scope(None, ScopeTag::Branch);
             self.compile_expr_block(branch.suite())?;
@@ -1571,13 +2239,15 @@ impl CodeGenerator<'_> {
             
             // site for the jump to the end of if-expression
             if !is_final_branch {
-                self.emit_instr(OpCode::Pop);
                 let jump_site = self.emit_dummy_jump(Jump::Uncond);
                 end_jump_sites.push(jump_site);
             }
             
             // target for the jump from the conditional of the now compiled branch
             self.patch_jump_instr(&branch_jump_site, self.current_offset())?;
+            if !is_final_branch {
+                self.emit_instr(OpCode::Pop);
+            }
         }
         
         // else clause
@@ -1598,6 +2268,126 @@ impl CodeGenerator<'_> {
         Ok(())
     }
     
+    /*
+        TRY_BEGIN -> handler
+        (try suite)
+        TRY_END
+        JUMP -> finally
+    handler: [ error ]
+        TRY_BEGIN -> reraise    (only if there is a finally clause)
+        (bind error, catch suite)
+        TRY_END
+        JUMP -> finally
+    reraise: [ error ]
+        (finally suite)
+        RERAISE
+    finally: [ value ]
+        (finally suite)
+    */
+    fn compile_try_expression(&mut self, suite: &ExprBlock, catch: Option<&CatchClause>, finally: Option<&StmtList>) -> CompileResult<()> {
+        let mut finally_jump_sites = Vec::new();
+        
+        let handler_site = self.emit_dummy_jump(Jump::TryBegin);
+        
+        let try_scope = self.emit_begin_scope(None, ScopeTag::Try);
+        if let Some(finally) = finally {
+            try_scope.set_finally(finally.clone());
+        }
+        self.compile_expr_block(suite)?;
+        self.emit_end_scope();
+        
+        self.emit_instr(OpCode::TryEnd);
+        finally_jump_sites.push(self.emit_dummy_jump(Jump::Uncond));
+        
+        self.patch_jump_instr(&handler_site, self.current_offset())?;
+        
+        if let Some(catch) = catch {
+            if let Some(finally) = finally {
+                let reraise_site = self.emit_dummy_jump(Jump::TryBegin);
+                
+                self.emit_begin_scope(None, ScopeTag::Try).set_finally(finally.clone());
+                self.compile_catch_clause(catch)?;
+                self.emit_end_scope();
+                
+                self.emit_instr(OpCode::TryEnd);
+                finally_jump_sites.push(self.emit_dummy_jump(Jump::Uncond));
+                
+                self.patch_jump_instr(&reraise_site, self.current_offset())?;
+            } else {
+                self.emit_begin_scope(None, ScopeTag::Catch);
+                self.compile_catch_clause(catch)?;
+                self.emit_end_scope();
+            }
+        }
+        
+        if let Some(finally) = finally {
+            self.compile_finally_clause(finally)?;
+            self.emit_instr(OpCode::Reraise);
+        }
+        
+        let finally_target = self.current_offset();
+        for jump_site in finally_jump_sites.iter() {
+            self.patch_jump_instr(jump_site, finally_target)?;
+        }
+        
+        if let Some(finally) = finally {
+            self.compile_finally_clause(finally)?;
+        }
+        
+        Ok(())
+    }
+    
+    fn compile_catch_clause(&mut self, catch: &CatchClause) -> CompileResult<()> {
+        // [ error ] => [ value ]
+        self.compile_decl_local_name(Access::ReadOnly, *catch.name())?;
+        self.emit_instr(OpCode::Pop);
+        self.compile_expr_block(catch.suite())
+    }
+    
+    // finally clauses do not produce a value
+    fn compile_finally_clause(&mut self, finally: &StmtList) -> CompileResult<()> {
+        self.emit_begin_scope(None, ScopeTag::Finally);
+        self.compile_stmt_block(finally)?;
+        self.emit_end_scope();
+        Ok(())
+    }
+    
+    fn check_finally_exit(&self, target_depth: usize, name: &str) -> CompileResult<()> {
+        let exits_finally = self.scopes().iter_scopes()
+            .take_while(|scope| scope.depth() > target_depth)
+            .any(|scope| scope.tag() == ScopeTag::Finally);
+        
+        if exits_finally {
+            let message = format!("\"{}\" can't jump out of a \"finally\" clause", name);
+            return Err(message.into());
+        }
+        Ok(())
+    }
+    
+    // when control flow leaves a try block, the error handler must be removed and the finally clause run.
+    // this is done before any scopes are dropped, since the finally clause was compiled assuming that
+    // the locals of the enclosing scopes are still there.
+    fn emit_try_exits(&mut self, target_depth: usize) -> CompileResult<()> {
+        let try_exits: Vec<(usize, Option<StmtList>)> = self.scopes().iter_scopes()
+            .take_while(|scope| scope.depth() >= target_depth)
+            .filter(|scope| scope.tag().is_try_block())
+            .map(|scope| (scope.depth(), scope.finally().cloned()))
+            .collect();
+        
+        for (depth, finally) in try_exits.iter() {
+            self.emit_instr(OpCode::TryEnd);
+            
+            if let Some(finally) = finally {
+                // names declared inside the try block should not be visible to the finally clause
+                self.scopes_mut().set_scopes_hidden(*depth, true);
+                let result = self.compile_finally_clause(finally);
+                self.scopes_mut().set_scopes_hidden(*depth, false);
+                result?;
+            }
+        }
+        Ok(())
+    }
+    
     fn compile_shortcircuit_and(&mut self, lhs: &Expr, rhs: &Expr) -> CompileResult<()> {
         self.compile_expr(lhs)?;
         
@@ -1625,6 +2415,405 @@ impl CodeGenerator<'_> {
     }
 }
 
+///////// Match Expressions /////////
+
+// a jump taken when a case pattern does not match. records how many values are left on the
+// stack and how many locals the case pattern has bound, so that they can be discarded.
+struct MatchFail {
+    site: JumpSite,
+    stack: usize,
+    locals: usize,
+}
+
+impl CodeGenerator<'_> {
+    /*
+        (subject)
+        INSERT_LOCAL; POP
+    case:
+        LOAD_LOCAL subject
+        (case pattern)          -> fail
+        (guard)                 -> fail
+        (case suite)
+        JUMP -> end
+    fail:
+        DROP; DP_LOCALS         (whatever the failed pattern left behind)
+        ... (next case)
+        NIL
+    end: [ value ]
+    */
+    fn compile_match_expression(&mut self, subject: &Expr, cases: &[MatchCase]) -> CompileResult<()> {
+        if !cases.iter().any(MatchCase::is_irrefutable) {
+            self.warn("match expression is not exhaustive, add \"case _\" to handle any other values");
+        }
+        
+        self.emit_begin_scope(None, ScopeTag::Match);
+        
+        // the subject is stored in an anonymous local so that each case can load it
+        self.compile_expr(subject)?;
+        let subject_index = match self.scopes_mut().insert_local(Access::ReadOnly, LocalName::Anonymous)? {
+            InsertLocal::CreateNew(local_index) => {
+                self.emit_instr(OpCode::InsertLocal);
+                local_index
+            }
+            InsertLocal::HideExisting(local_index) => {
+                self.emit_assign_local(local_index);
+                local_index
+            }
+        };
+        self.emit_instr(OpCode::Pop);
+        
+        let mut end_jump_sites = Vec::new();
+        let mut exhausted = false;
+        for (idx, case) in cases.iter().enumerate() {
+            Self::check_case_bindings(case.pattern())?;
+            
+            self.emit_begin_scope(None, ScopeTag::Match);
+            self.emit_load_local_index(subject_index);
+            
+            let mut fails = Vec::new();
+            self.compile_case_pattern(case.pattern(), 0, &mut fails)?;
+            
+            // if the guard captures any of the bound names, the upvalues must be closed when it fails
+            let mut guard_fail = None;
+            if let Some(guard) = case.guard() {
+                self.compile_expr(guard)?;
+                
+                let scope_drop = ScopeDrop::from(self.scopes().iter_scopes().next().unwrap());
+                if scope_drop.close_upvals.is_empty() {
+                    self.emit_match_fail(0, &mut fails);
+                } else {
+                    guard_fail = Some((self.emit_dummy_jump(Jump::PopIfFalse), scope_drop));
+                }
+            }
+            
+            self.compile_expr_block(case.suite())?;
+            self.emit_end_scope();
+            
+            // if the last case can't fail there is nothing to jump over
+            if idx + 1 == cases.len() && fails.is_empty() && guard_fail.is_none() {
+                exhausted = true;
+                break;
+            }
+            end_jump_sites.push(self.emit_dummy_jump(Jump::Uncond));
+            
+            if let Some((guard_site, scope_drop)) = guard_fail {
+                self.patch_jump_instr(&guard_site, self.current_offset())?;
+                self.emit_scope_drop(&scope_drop);
+                
+                let next_case_site = self.emit_dummy_jump(Jump::Uncond);
+                self.patch_match_fails(fails, 0)?;
+                self.patch_jump_instr(&next_case_site, self.current_offset())?;
+            } else {
+                self.patch_match_fails(fails, 0)?;
+            }
+        }
+        
+        // the value of a match expression where no case was taken
+        if !exhausted {
+            self.emit_instr(OpCode::Nil);
+        }
+        
+        let end_target = self.current_offset();
+        for jump_site in end_jump_sites.iter() {
+            self.patch_jump_instr(jump_site, end_target)?;
+        }
+        
+        self.emit_end_scope();
+        
+        Ok(())
+    }
+    
+    fn check_case_bindings(pattern: &Pattern) -> CompileResult<()> {
+        let mut names = Vec::new();
+        pattern.bindings(&mut names);
+        
+        for (idx, name) in names.iter().enumerate() {
+            if names[..idx].contains(name) {
+                return Err("can't bind the same name more than once in a case pattern".into());
+            }
+        }
+        Ok(())
+    }
+    
+    // [ value ] => []
+    // when the pattern does not match, a jump is added to `fails`. `stack` is the number of values
+    // below the matched value that need to be discarded if that happens.
+    fn compile_case_pattern(&mut self, pattern: &Pattern, stack: usize, fails: &mut Vec<MatchFail>) -> CompileResult<()> {
+        match pattern {
+            Pattern::Wildcard => self.emit_instr(OpCode::Pop),
+            
+            Pattern::Identifier(name) => {
+                self.compile_decl_local_name(Access::ReadOnly, *name)?;
+                self.emit_instr(OpCode::Pop);
+            },
+            
+            Pattern::Literal(atom) => {
+                self.compile_atom(atom)?;
+                self.emit_instr(OpCode::EQ);
+                self.emit_match_fail(stack, fails);
+            },
+            
+            Pattern::Tuple(items) => self.compile_tuple_case_pattern(items, stack, fails)?,
+            
+            Pattern::Object(fields) => {
+                for field in fields.iter() {
+                    self.emit_load_const(Constant::from(field.name))?;
+                    self.emit_instr(OpCode::MatchAttr);
+                    self.emit_match_fail(stack + 1, fails);
+                    self.compile_case_pattern(&field.pattern, stack + 1, fails)?;
+                }
+                self.emit_instr(OpCode::Pop);
+            },
+            
+            Pattern::Alternative(alternatives) => self.compile_alternative_case_pattern(alternatives, stack, fails)?,
+            
+            Pattern::Pack(..) => return Err("\"...\" can only be used inside a tuple pattern".into()),
+            
+            _ => return Err("invalid case pattern".into()),
+        }
+        Ok(())
+    }
+    
+    fn compile_tuple_case_pattern(&mut self, items: &[Pattern], stack: usize, fails: &mut Vec<MatchFail>) -> CompileResult<()> {
+        let mut pack_items = items.iter().enumerate()
+            .filter(|(_, item)| matches!(item, Pattern::Pack(..)));
+        
+        let pack_idx = pack_items.next().map(|(idx, _)| idx);
+        if pack_items.next().is_some() {
+            return Err("tuple patterns can only contain one \"...\"".into());
+        }
+        
+        // [ sequence ] => [ item[0] ... item[N-1] ]
+        if let Some(pack_idx) = pack_idx {
+            let pre_len = u8::try_from(pack_idx)
+                .map_err(|_| "unpack length limit exceeded")?;
+            let post_len = u8::try_from(items.len() - pack_idx - 1)
+                .map_err(|_| "unpack length limit exceeded")?;
+            self.emit_instr_data(OpCode::MatchSeqPack, &[pre_len, post_len]);
+        } else {
+            let len = u8::try_from(items.len())
+                .map_err(|_| "unpack length limit exceeded")?;
+            self.emit_instr_byte(OpCode::MatchSeq, len);
+        }
+        self.emit_match_fail(stack, fails);
+        
+        // the last item is on top of the stack
+        for (idx, item) in items.iter().enumerate().rev() {
+            match item {
+                Pattern::Pack(None) => self.emit_instr(OpCode::Pop),
+                Pattern::Pack(Some(pattern)) => self.compile_case_pattern(pattern, stack + idx, fails)?,
+                pattern => self.compile_case_pattern(pattern, stack + idx, fails)?,
+            }
+        }
+        Ok(())
+    }
+    
+    // each alternative but the last is tried on a copy of the value
+    fn compile_alternative_case_pattern(&mut self, alternatives: &[Pattern], stack: usize, fails: &mut Vec<MatchFail>) -> CompileResult<()> {
+        let mut names = Vec::new();
+        for pattern in alternatives.iter() {
+            pattern.bindings(&mut names);
+        }
+        if !names.is_empty() {
+            return Err("alternative patterns can't bind names".into());
+        }
+        
+        let locals = self.scopes().iter_scopes().next().unwrap().locals().len();
+        let (last, rest) = alternatives.split_last().unwrap();
+        
+        let mut matched_sites = Vec::new();
+        for pattern in rest.iter() {
+            self.emit_instr(OpCode::Clone);
+            
+            let mut alternative_fails = Vec::new();
+            self.compile_case_pattern(pattern, 0, &mut alternative_fails)?;
+            
+            self.emit_instr(OpCode::Pop);
+            matched_sites.push(self.emit_dummy_jump(Jump::Uncond));
+            
+            self.patch_match_fails(alternative_fails, locals)?;
+        }
+        
+        self.compile_case_pattern(last, stack, fails)?;
+        
+        let matched_target = self.current_offset();
+        for jump_site in matched_sites.iter() {
+            self.patch_jump_instr(jump_site, matched_target)?;
+        }
+        Ok(())
+    }
+    
+    fn emit_match_fail(&mut self, stack: usize, fails: &mut Vec<MatchFail>) {
+        let locals = self.scopes().iter_scopes().next().unwrap().locals().len();
+        let site = self.emit_dummy_jump(Jump::PopIfFalse);
+        fails.push(MatchFail { site, stack, locals });
+    }
+    
+    // emits the code that discards whatever was left behind by each failed match, 
+    // so that control reaches the end of the emitted code with `locals` bound in the current scope.
+    // failures that leave the same things behind share the same code.
+    fn patch_match_fails(&mut self, fails: Vec<MatchFail>, locals: usize) -> CompileResult<()> {
+        let mut cleanups: Vec<((usize, usize), Vec<JumpSite>)> = Vec::new();
+        for fail in fails.into_iter() {
+            let cleanup = (fail.stack, fail.locals - locals);
+            match cleanups.iter_mut().find(|(other, _)| *other == cleanup) {
+                Some((_, jump_sites)) => jump_sites.push(fail.site),
+                None => cleanups.push((cleanup, vec![ fail.site ])),
+            }
+        }
+        
+        // if there is nothing to discard, jump straight to the end
+        let (no_cleanup, cleanups): (Vec<_>, Vec<_>) = cleanups.into_iter()
+            .partition(|(cleanup, _)| *cleanup == (0, 0));
+        
+        let mut done_sites = Vec::new();
+        for (idx, ((stack, locals), jump_sites)) in cleanups.iter().enumerate() {
+            let cleanup_target = self.current_offset();
+            for jump_site in jump_sites.iter() {
+                self.patch_jump_instr(jump_site, cleanup_target)?;
+            }
+            
+            self.emit_drop_values(*stack);
+            self.emit_scope_drop(&ScopeDrop {
+                tag: ScopeTag::Match,
+                locals: *locals,
+                close_upvals: Vec::new(),
+            });
+            
+            // the last cleanup can fall through
+            if idx + 1 < cleanups.len() {
+                done_sites.push(self.emit_dummy_jump(Jump::Uncond));
+            }
+        }
+        
+        let done_target = self.current_offset();
+        let jump_sites = no_cleanup.iter()
+            .flat_map(|(_, jump_sites)| jump_sites.iter())
+            .chain(done_sites.iter());
+        
+        for jump_site in jump_sites {
+            self.patch_jump_instr(jump_site, done_target)?;
+        }
+        Ok(())
+    }
+    
+    fn emit_drop_values(&mut self, count: usize) {
+        let mut discard = count;
+        while discard > u8::MAX.into() {
+            self.emit_instr_byte(OpCode::Drop, u8::MAX);
+            discard -= usize::from(u8::MAX);
+        }
+        
+        match discard {
+            0 => { },
+            1 => self.emit_instr(OpCode::Pop),
+            _ => self.emit_instr_byte(OpCode::Drop, u8::try_from(discard).unwrap()),
+        }
+    }
+}
+
+///////// Class Definitions /////////
+impl CodeGenerator<'_> {
+    fn compile_class_def(&mut self, classdef: &ClassDef) -> CompileResult<()> {
+        // the base class is kept in a local so that methods can refer to it using "super"
+        self.emit_begin_scope(None, ScopeTag::Class);
+        
+        if let Some(base) = &classdef.base {
+            self.compile_expr_with_symbol(base)?;
+        } else {
+            self.emit_instr(OpCode::Nil);
+        }
+        
+        match self.scopes_mut().insert_local(Access::ReadOnly, LocalName::Super)? {
+            InsertLocal::CreateNew(..) => self.emit_instr(OpCode::InsertLocal),
+            InsertLocal::HideExisting(..) => unreachable!(),
+        }
+        
+        // [ base name ] => [ class ]
+        if let Some(name) = classdef.name {
+            self.emit_load_const(Constant::from(name))?;
+        } else {
+            self.emit_instr(OpCode::Nil);
+        }
+        self.emit_instr(OpCode::Class);
+        
+        // [ class value name ] => [ class ]
+        for field in classdef.fields.iter() {
+            if let Some(expr) = &field.default {
+                self.compile_expr_with_symbol(expr)?;
+            } else {
+                self.emit_instr(OpCode::Nil);
+            }
+            
+            self.emit_load_const(Constant::from(field.name))?;
+            match field.mode {
+                Access::ReadOnly => self.emit_instr(OpCode::InsertField),
+                Access::ReadWrite => self.emit_instr(OpCode::InsertFieldMut),
+            }
+        }
+        
+        // [ class method name ] => [ class ]
+        for method in classdef.methods.iter() {
+            self.push_symbol(Some(method.symbol));
+            self.compile_function_def(&method.fundef)?;
+            self.pop_symbol();
+            
+            let name = method.fundef.signature.name.unwrap();
+            self.emit_load_const(Constant::from(name))?;
+            self.emit_instr(OpCode::InsertMethod);
+        }
+        
+        self.emit_end_scope();
+        Ok(())
+    }
+}
+
+///////// Type Annotations /////////
+impl CodeGenerator<'_> {
+    // [ value ] => [ value ], raises a TypeError if the value does not satisfy the annotation
+    fn emit_type_check(&mut self, annotation: &TypeExpr) -> CompileResult<()> {
+        if annotation.is_any() {
+            return Ok(());
+        }
+        
+        self.compile_type_expr(annotation)?;
+        self.emit_instr(OpCode::AssertType);
+        Ok(())
+    }
+    
+    // see runtime/types/annotation.rs for how annotations are represented at runtime
+    fn compile_type_expr(&mut self, annotation: &TypeExpr) -> CompileResult<()> {
+        match annotation {
+            TypeExpr::Nil => self.emit_instr(OpCode::Nil),
+            
+            TypeExpr::Builtin(builtin) => {
+                let name = self.builder_mut().get_or_insert_str(builtin.name());
+                self.emit_load_const(Constant::String(name))?;
+            }
+            
+            TypeExpr::Class(path) => {
+                let (name, attrs) = path.split_first().expect("empty class path");
+                self.compile_name_lookup(name)?;
+                for attr in attrs.iter() {
+                    self.emit_load_const(Constant::from(*attr))?;
+                    self.emit_instr(OpCode::LoadAttr);
+                }
+            }
+            
+            TypeExpr::Union(items) => {
+                let len = u8::try_from(items.len())
+                    .map_err(|_| "type union is too large")?;
+                
+                for item in items.iter() {
+                    self.compile_type_expr(item)?;
+                }
+                self.emit_instr_byte(OpCode::Tuple, len);
+            }
+        }
+        Ok(())
+    }
+}
+
 ///////// Function Definitions /////////
 impl CodeGenerator<'_> {
     fn compile_function_def(&mut self, fundef: &FunctionDef) -> CompileResult<()> {
@@ -1642,7 +2831,9 @@ impl CodeGenerator<'_> {
         
         // and a new local scope
         // don't need to emit new scope instructions, should handled by function call
-        chunk_gen.scopes_mut().push_frame(symbol.as_ref());
+        // the return type of a generator function describes the generator, not the values returned by its body
+        let return_type = fundef.return_type.clone().filter(|_| !fundef.generator);
+        chunk_gen.scopes_mut().push_frame(symbol.as_ref(), return_type.clone());
         
         // don't need to generate IN_LOCAL instructions for these, the VM should include them automatically
         chunk_gen.scopes_mut().insert_local(Access::ReadOnly, LocalName::Receiver)?;
@@ -1651,6 +2842,11 @@ impl CodeGenerator<'_> {
         // prepare argument list
         chunk_gen.compile_function_preamble(fundef)?;
         
+        // generators suspend once their arguments are ready, the body runs when they are resumed
+        if fundef.generator {
+            chunk_gen.emit_instr(OpCode::Generator);
+        }
+        
         // function body
         chunk_gen.compile_stmt_block(fundef.body.stmt_list())?;
         
@@ -1661,6 +2857,10 @@ impl CodeGenerator<'_> {
             chunk_gen.emit_instr(OpCode::Nil);
         }
         
+        if let Some(return_type) = &return_type {
+            chunk_gen.emit_type_check(return_type)?;
+        }
+        
         // end the function scope
         // don't need to drop locals explicitly, that will be done when the VMCallFrame returns
         let frame = chunk_gen.scopes_mut().pop_frame();
@@ -1693,132 +2893,99 @@ impl CodeGenerator<'_> {
     }
     
     fn compile_function_preamble(&mut self, fundef: &FunctionDef) -> CompileResult<()> {
-        // process default and variadic arguments
-        // this ensures that exactly `signature.param_count()` values are on the stack
+        // the VM arranges the arguments so that exactly `signature.param_count()` values are on the stack,
+        // in the same order that the parameters are declared here
         
         let signature = &fundef.signature;
         if signature.param_count() == 0 {
             return Ok(())
         }
         
-        for param in signature.required.iter() {
-            self.scopes_mut().insert_local(param.mode, LocalName::Symbol(param.name))?;
+        let params = signature.required.iter().map(|param| (param.name, param.mode))
+            .chain(signature.default.iter().map(|param| (param.name, param.mode)))
+            .chain(signature.variadic.iter().map(|param| (param.name, param.mode)))
+            .chain(signature.keyword.iter().map(|param| (param.name, param.mode)))
+            .chain(signature.keyword_default.iter().map(|param| (param.name, param.mode)))
+            .chain(signature.kwargs.iter().map(|param| (param.name, param.mode)));
+        
+        for (name, mode) in params {
+            self.scopes_mut().insert_local(mode, LocalName::Symbol(name))?;
         }
         
-        if !signature.default.is_empty() {
-            self.compile_default_args(signature)?;
-            for param in signature.default.iter() {
-                self.scopes_mut().insert_local(param.mode, LocalName::Symbol(param.name))?;
-            }
+        self.emit_instr(OpCode::InsertArgs);
+        
+        // evaluate default values for any parameters that did not receive an argument
+        for param in signature.default.iter().chain(signature.keyword_default.iter()) {
+            self.compile_default_arg(param)?;
         }
         
+        // check annotated parameters, now that all of them have values
+        for param in signature.required.iter().chain(signature.keyword.iter()) {
+            if let Some(annotation) = &param.annotation {
+                self.compile_param_check(param.name, annotation)?;
+            }
+        }
+        for param in signature.default.iter().chain(signature.keyword_default.iter()) {
+            if let Some(annotation) = &param.annotation {
+                self.compile_param_check(param.name, annotation)?;
+            }
+        }
         if let Some(param) = &signature.variadic {
-            self.compile_variadic_arg(signature)?;
-            self.scopes_mut().insert_local(param.mode, LocalName::Symbol(param.name))?;
+            if let Some(annotation) = &param.annotation {
+                self.compile_variadic_check(param.name, annotation)?;
+            }
         }
-        
-        self.emit_instr(OpCode::InsertArgs);
 
         Ok(())
     }
     
-    fn compile_default_args(&mut self, signature: &SignatureDef) -> CompileResult<()> {
-        debug_assert!(!signature.default.is_empty());
+    fn compile_default_arg(&mut self, param: &DefaultDef) -> CompileResult<()> {
+        let index = self.try_emit_load_local(&LocalName::Symbol(param.name)).unwrap();
+        self.emit_instr(OpCode::ArgMissing);
+        let skip_jump_site = self.emit_dummy_jump(Jump::PopIfFalse);
         
-        let mut jump_sites = Vec::new();
-        let mut jump_targets = Vec::new();
-        
-        // depending on the number of arguments, jump into the default argument sequence
-        let required_count = u8::try_from(signature.required.len())
-            .map_err(|_| "parameter count limit exceeded")?;
-        let default_count = u8::try_from(signature.default.len())
-            .map_err(|_| "parameter count limit exceeded")?;
-        
-        // "defaults passed" = NArgs - required_count
-        self.try_emit_load_local(&LocalName::NArgs).unwrap();
-        if required_count != 0 {
-            self.emit_instr_byte(OpCode::UInt8, required_count);
-            self.emit_instr(OpCode::Sub);
-        }
-        
-        /*
-            NArgs - required count:
-            
-            <  0   => impossible, missing required argument
-               0   => jump to default[0]
-               1   => jump to default[1]
-               ...
-               N-1 => jump to default[N-1]
-            >= N   => jump to end
-        */
-        
-        for count in 0..default_count {
-            self.emit_instr(OpCode::Clone);
-            self.emit_instr_byte(OpCode::UInt8, count);
-            self.emit_instr(OpCode::EQ);
-            
-            let jump_site = self.emit_dummy_jump(Jump::PopIfTrue);
-            jump_sites.insert(count.into(), jump_site);
-        }
-        
-        // if we get here, jump unconditionally to the end
-        let jump_site = self.emit_dummy_jump(Jump::Uncond);
-        jump_sites.insert(default_count.into(), jump_site);
-        
-        // generate default arguments
-        for (idx, param) in signature.default.iter().enumerate() {
-            jump_targets.insert(idx, self.current_offset());
-            
-            let symbol = param.default.debug_symbol();
-            let expr = param.default.variant();
-            self.push_symbol(Some(*symbol));
-            self.compile_expr(expr)?;
-            self.pop_symbol();
-        }
+        let symbol = param.default.debug_symbol();
+        self.push_symbol(Some(*symbol));
+        self.compile_expr(param.default.variant())?;
+        self.pop_symbol();
         
-        jump_targets.insert(default_count.into(), self.current_offset());
-        self.emit_instr(OpCode::Pop);  // drop "defaults passed"
+        self.emit_assign_local(index);
+        self.emit_instr(OpCode::Pop);
         
-        // patch all jumps
-        debug_assert!(jump_sites.len() == jump_targets.len());
-        for (jump_site, jump_target) in jump_sites.iter().zip(jump_targets.into_iter()) {
-            self.patch_jump_instr(jump_site, jump_target)?;
+        self.patch_jump_instr(&skip_jump_site, self.current_offset())?;
+        Ok(())
+    }
+    
+    fn compile_param_check(&mut self, name: InternSymbol, annotation: &TypeExpr) -> CompileResult<()> {
+        if annotation.is_any() {
+            return Ok(());
         }
         
+        self.try_emit_load_local(&LocalName::Symbol(name)).unwrap();
+        self.emit_type_check(annotation)?;
+        self.emit_instr(OpCode::Pop);
         Ok(())
     }
     
-    fn compile_variadic_arg(&mut self, signature: &SignatureDef) -> CompileResult<()> {
-        debug_assert!(signature.variadic.is_some());
-        
-        let positional_count = u8::try_from(signature.required.len() + signature.default.len())
-            .map_err(|_| "parameter count limit exceeded")?;
-        
-        // "variadic count" = NArgs - required_count - default_count
-        self.try_emit_load_local(&LocalName::NArgs).unwrap();
-        if positional_count != 0 {
-            self.emit_instr_byte(OpCode::UInt8, positional_count);
-            self.emit_instr(OpCode::Sub);
+    // the annotation of a variadic parameter applies to each of the extra arguments
+    fn compile_variadic_check(&mut self, name: InternSymbol, annotation: &TypeExpr) -> CompileResult<()> {
+        if annotation.is_any() {
+            return Ok(());
         }
         
-        // check if "variadic count" > 0
-        self.emit_instr(OpCode::Clone);
-        self.emit_instr_byte(OpCode::UInt8, 0);
-        self.emit_instr(OpCode::GT);
-        
-        let tuple_jump_site = self.emit_dummy_jump(Jump::PopIfTrue);
+        self.try_emit_load_local(&LocalName::Symbol(name)).unwrap();
+        self.emit_instr(OpCode::IterInit);
+        let end_jump_site = self.emit_dummy_jump(Jump::IfFalse);
         
-        // if we get here then the variadic arg is empty
+        let loop_target = self.current_offset();
+        self.emit_instr(OpCode::IterGet);
+        self.emit_type_check(annotation)?;
         self.emit_instr(OpCode::Pop);
-        self.emit_instr(OpCode::Empty);
-        
-        let end_jump_site = self.emit_dummy_jump(Jump::Uncond);
-        
-        self.patch_jump_instr(&tuple_jump_site, self.current_offset())?;
-        self.emit_instr(OpCode::TupleN);
+        self.emit_instr(OpCode::IterNext);
+        self.emit_jump_instr(Jump::IfTrue, loop_target)?;
         
         self.patch_jump_instr(&end_jump_site, self.current_offset())?;
-        
+        self.emit_instr_byte(OpCode::Drop, 2); // drop [ iter state ]
         Ok(())
     }
     
@@ -1846,11 +3013,32 @@ impl CodeGenerator<'_> {
             variadic.replace(UnloadedParam { name, mode: param.mode });
         }
         
+        let mut keyword = Vec::new();
+        for param in signature.keyword.iter() {
+            let name = self.get_or_make_const(Constant::from(param.name))?;
+            keyword.push(UnloadedParam { name, mode: param.mode });
+        }
+        
+        let mut keyword_default = Vec::new();
+        for param in signature.keyword_default.iter() {
+            let name = self.get_or_make_const(Constant::from(param.name))?;
+            keyword_default.push(UnloadedParam { name, mode: param.mode });
+        }
+        
+        let mut kwargs = None;
+        if let Some(param) = &signature.kwargs {
+            let name = self.get_or_make_const(Constant::from(param.name))?;
+            kwargs.replace(UnloadedParam { name, mode: param.mode });
+        }
+        
         let signature = UnloadedSignature {
             name,
             required: required.into_boxed_slice(),
             default: default.into_boxed_slice(),
             variadic,
+            keyword: keyword.into_boxed_slice(),
+            keyword_default: keyword_default.into_boxed_slice(),
+            kwargs,
         };
         
         Ok(signature)
@@ -0,0 +1,608 @@
+//! Binary serialization of compiled programs.
+//!
+//! All integers are little endian. The layout of a bytecode file is:
+//!
+//! ```text
+//! header      magic: [u8; 4], version: u16, flags: u8
+//! main        len: u32, bytes
+//! chunks      len: u32, bytes
+//! chunk index count: u32, (offset: u32, length: u32, info)*
+//! strings     count: u32, (len: u32, utf8 bytes)*
+//! constants   count: u32, (tag: u8, value)*
+//! functions   count: u32, (fun_id: u16, signature, upvalues)*
+//! symbols     (only if FLAG_DEBUG_SYMBOLS is set) count: u32, (chunk, count: u32, (offset: u32, symbol)*)*
+//! ```
+//!
+//! Any change to the layout must increment `FORMAT_VERSION`.
+
+use core::fmt;
+use std::io::{self, Read, Write};
+use std::error::Error;
+
+use crate::language::{IntType, FloatType, Access};
+use crate::runtime::errors::ErrorKind;
+use crate::codegen::CompiledProgram;
+use crate::codegen::chunk::{Chunk, ChunkInfo, ChunkIndex, StringIndex, UnloadedProgram};
+use crate::codegen::consts::{Constant, ConstID};
+use crate::codegen::funproto::{UnloadedFunction, UnloadedSignature, UnloadedParam, UpvalueTarget, FunctionID};
+use crate::debug::symbol::{DebugSymbol, DebugSymbolTable, ChunkSymbols};
+
+
+pub const MAGIC: [u8; 4] = *b"SPHX";
+pub const FORMAT_VERSION: u16 = 8;
+
+/// File extension used for compiled bytecode
+pub const BYTECODE_EXTENSION: &str = "sphc";
+
+const FLAG_DEBUG_SYMBOLS: u8 = 1 << 0;
+
+// ErrorKinds are serialized by their position in this table, so new kinds must be appended at the end
+const ERROR_KINDS: [ErrorKind; 23] = [
+    ErrorKind::InvalidUnaryOperand,
+    ErrorKind::InvalidBinaryOperand,
+    ErrorKind::OverflowError,
+    ErrorKind::DivideByZero,
+    ErrorKind::NegativeShiftCount,
+    ErrorKind::NameNotDefined,
+    ErrorKind::AttributeNotFound,
+    ErrorKind::KeyNotFound,
+    ErrorKind::CantAssignImmutable,
+    ErrorKind::UnhashableValue,
+    ErrorKind::MissingArguments,
+    ErrorKind::TooManyArguments,
+    ErrorKind::MethodNotSupported,
+    ErrorKind::AssertFailed,
+    ErrorKind::InvalidValue,
+    ErrorKind::UnpackError,
+    ErrorKind::ImportError,
+    ErrorKind::Unspecified,
+    ErrorKind::UserError,
+    ErrorKind::IndexError,
+    ErrorKind::TypeError,
+    ErrorKind::DuplicateArgument,
+    ErrorKind::UnknownArgument,
+];
+
+
+/// Returns true if the bytes begin with the bytecode file signature
+pub fn is_bytecode(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+
+#[derive(Debug)]
+pub enum BytecodeError {
+    IOError(io::Error),
+    NotBytecode,
+    UnsupportedVersion(u16),
+    Malformed(&'static str),
+}
+
+impl From<io::Error> for BytecodeError {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::UnexpectedEof => Self::Malformed("unexpected end of file"),
+            _ => Self::IOError(error),
+        }
+    }
+}
+
+impl Error for BytecodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::IOError(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BytecodeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IOError(error) => write!(fmt, "{}", error),
+            Self::NotBytecode => fmt.write_str("not a bytecode file"),
+            Self::UnsupportedVersion(version) => write!(
+                fmt, "unsupported bytecode version {} (expected version {})", version, FORMAT_VERSION
+            ),
+            Self::Malformed(message) => write!(fmt, "malformed bytecode: {}", message),
+        }
+    }
+}
+
+pub type BytecodeResult<T> = Result<T, BytecodeError>;
+
+
+// Writer
+
+pub struct BytecodeWriter<W> where W: Write {
+    writer: W,
+    symbols: bool,
+}
+
+impl<W> BytecodeWriter<W> where W: Write {
+    pub fn new(writer: W) -> Self {
+        Self { writer, symbols: false }
+    }
+    
+    /// Include debug symbols in the output. These are needed for tracebacks and disassembly
+    /// to refer back to the original source code.
+    pub fn with_symbols(mut self, symbols: bool) -> Self {
+        self.symbols = symbols; self
+    }
+    
+    pub fn into_inner(self) -> W { self.writer }
+    
+    pub fn write_program(&mut self, build: &CompiledProgram) -> io::Result<()> {
+        let program = &build.program;
+        
+        self.writer.write_all(&MAGIC)?;
+        self.write_u16(FORMAT_VERSION)?;
+        self.write_u8(if self.symbols { FLAG_DEBUG_SYMBOLS } else { 0 })?;
+        
+        self.write_bytes(&program.main)?;
+        self.write_bytes(&program.chunks)?;
+        
+        self.write_len(program.chunk_index.len())?;
+        for index in program.chunk_index.iter() {
+            let range = index.as_range();
+            self.write_len(range.start)?;
+            self.write_len(range.len())?;
+            self.write_chunk_info(index.info())?;
+        }
+        
+        self.write_len(program.string_index.len())?;
+        for (_, string) in program.iter_strings() {
+            self.write_bytes(string.as_bytes())?;
+        }
+        
+        self.write_len(program.consts.len())?;
+        for constant in program.consts.iter() {
+            self.write_const(constant)?;
+        }
+        
+        self.write_len(program.functions.len())?;
+        for function in program.functions.iter() {
+            self.write_function(function)?;
+        }
+        
+        if self.symbols {
+            self.write_symbols(&build.symbols)?;
+        }
+        
+        self.writer.flush()
+    }
+    
+    fn write_chunk_info(&mut self, info: &ChunkInfo) -> io::Result<()> {
+        match info {
+            ChunkInfo::ModuleMain => self.write_u8(0),
+            ChunkInfo::Function { symbol } => {
+                self.write_u8(1)?;
+                self.write_option(symbol.as_ref(), Self::write_debug_symbol)
+            }
+        }
+    }
+    
+    // IntType and FloatType are narrower on 32-bit targets, but constants are always stored with 64 bits
+    #[allow(clippy::useless_conversion)]
+    fn write_const(&mut self, constant: &Constant) -> io::Result<()> {
+        match constant {
+            Constant::Integer(value) => {
+                self.write_u8(0)?;
+                self.writer.write_all(&i64::from(*value).to_le_bytes())
+            }
+            Constant::Float(bytes) => {
+                let value = f64::from(FloatType::from_le_bytes(*bytes));
+                self.write_u8(1)?;
+                self.writer.write_all(&value.to_le_bytes())
+            }
+            Constant::String(string_id) => {
+                self.write_u8(2)?;
+                self.write_len(*string_id)
+            }
+            Constant::Error { error, message } => {
+                let kind = ERROR_KINDS.iter().position(|kind| kind == error)
+                    .expect("error kind missing from serialization table");
+                
+                self.write_u8(3)?;
+                self.write_u8(kind as u8)?;
+                self.write_len(*message)
+            }
+        }
+    }
+    
+    fn write_function(&mut self, function: &UnloadedFunction) -> io::Result<()> {
+        self.write_u16(function.fun_id)?;
+        
+        let signature = &function.signature;
+        self.write_option(signature.name.as_ref(), |this, name| this.write_u16(*name))?;
+        
+        self.write_len(signature.required.len())?;
+        for param in signature.required.iter() {
+            self.write_param(param)?;
+        }
+        
+        self.write_len(signature.default.len())?;
+        for param in signature.default.iter() {
+            self.write_param(param)?;
+        }
+        
+        self.write_option(signature.variadic.as_ref(), Self::write_param)?;
+        
+        self.write_len(signature.keyword.len())?;
+        for param in signature.keyword.iter() {
+            self.write_param(param)?;
+        }
+        
+        self.write_len(signature.keyword_default.len())?;
+        for param in signature.keyword_default.iter() {
+            self.write_param(param)?;
+        }
+        
+        self.write_option(signature.kwargs.as_ref(), Self::write_param)?;
+        
+        self.write_len(function.upvalues.len())?;
+        for upval in function.upvalues.iter() {
+            match upval {
+                UpvalueTarget::Local(index) => {
+                    self.write_u8(0)?;
+                    self.write_u16(*index)?;
+                }
+                UpvalueTarget::Upvalue(index) => {
+                    self.write_u8(1)?;
+                    self.write_u16(*index)?;
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    fn write_param(&mut self, param: &UnloadedParam) -> io::Result<()> {
+        self.write_u16(param.name)?;
+        match param.mode {
+            Access::ReadOnly => self.write_u8(0),
+            Access::ReadWrite => self.write_u8(1),
+        }
+    }
+    
+    fn write_symbols(&mut self, symbols: &ChunkSymbols) -> io::Result<()> {
+        self.write_len(symbols.len())?;
+        for (chunk_id, table) in symbols.iter() {
+            match chunk_id {
+                Chunk::Main => self.write_u8(0)?,
+                Chunk::Function(fun_id) => {
+                    self.write_u8(1)?;
+                    self.write_u16(*fun_id)?;
+                }
+            }
+            
+            self.write_len(table.iter().count())?;
+            for (offset, symbol) in table.iter() {
+                self.write_len(offset)?;
+                self.write_debug_symbol(symbol)?;
+            }
+        }
+        Ok(())
+    }
+    
+    fn write_debug_symbol(&mut self, symbol: &DebugSymbol) -> io::Result<()> {
+        self.writer.write_all(&symbol.start().to_le_bytes())?;
+        self.writer.write_all(&symbol.len().to_le_bytes())
+    }
+    
+    fn write_option<T>(&mut self, value: Option<&T>, write: impl FnOnce(&mut Self, &T) -> io::Result<()>) -> io::Result<()> {
+        match value {
+            None => self.write_u8(0),
+            Some(value) => {
+                self.write_u8(1)?;
+                write(self, value)
+            }
+        }
+    }
+    
+    fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.write_len(bytes.len())?;
+        self.writer.write_all(bytes)
+    }
+    
+    fn write_len(&mut self, len: usize) -> io::Result<()> {
+        let len = u32::try_from(len)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "program is too large to serialize"))?;
+        self.writer.write_all(&len.to_le_bytes())
+    }
+    
+    fn write_u16(&mut self, value: u16) -> io::Result<()> {
+        self.writer.write_all(&value.to_le_bytes())
+    }
+    
+    fn write_u8(&mut self, value: u8) -> io::Result<()> {
+        self.writer.write_all(&[value])
+    }
+}
+
+
+// Reader
+
+pub struct BytecodeReader<R> where R: Read {
+    reader: R,
+}
+
+impl<R> BytecodeReader<R> where R: Read {
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+    
+    pub fn into_inner(self) -> R { self.reader }
+    
+    /// If the bytecode was written without debug symbols, the returned symbol tables will be empty
+    pub fn read_program(&mut self) -> BytecodeResult<CompiledProgram> {
+        let mut magic = [0; MAGIC.len()];
+        self.reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(BytecodeError::NotBytecode);
+        }
+        
+        let version = self.read_u16()?;
+        if version != FORMAT_VERSION {
+            return Err(BytecodeError::UnsupportedVersion(version));
+        }
+        
+        let flags = self.read_u8()?;
+        
+        let main = self.read_bytes()?;
+        let chunks = self.read_bytes()?;
+        
+        let chunk_count = self.read_len()?;
+        let mut chunk_index = Vec::new();
+        for _ in 0..chunk_count {
+            let offset = self.read_len()?;
+            let length = self.read_len()?;
+            if offset.checked_add(length).is_none_or(|end| end > chunks.len()) {
+                return Err(BytecodeError::Malformed("chunk out of bounds"));
+            }
+            
+            let info = self.read_chunk_info()?;
+            chunk_index.push(ChunkIndex::new(info, offset, length));
+        }
+        
+        let string_count = self.read_len()?;
+        let mut strings = Vec::new();
+        let mut string_index = Vec::new();
+        for _ in 0..string_count {
+            let bytes = self.read_bytes()?;
+            if core::str::from_utf8(&bytes).is_err() {
+                return Err(BytecodeError::Malformed("invalid UTF-8 in string table"));
+            }
+            
+            string_index.push(StringIndex::new(strings.len(), bytes.len()));
+            strings.extend(bytes.iter());
+        }
+        
+        let const_count = self.read_len()?;
+        let mut consts = Vec::new();
+        for _ in 0..const_count {
+            let constant = self.read_const()?;
+            match constant {
+                Constant::String(string_id) | Constant::Error { message: string_id, .. }
+                    if string_id >= string_count => return Err(BytecodeError::Malformed("invalid string reference")),
+                _ => { },
+            }
+            consts.push(constant);
+        }
+        
+        let fun_count = self.read_len()?;
+        let mut functions = Vec::new();
+        for _ in 0..fun_count {
+            let function = self.read_function()?;
+            if usize::from(function.fun_id) >= chunk_index.len() {
+                return Err(BytecodeError::Malformed("function without a chunk"));
+            }
+            
+            let signature = &function.signature;
+            let names = signature.name.iter()
+                .chain(signature.required.iter().map(|param| &param.name))
+                .chain(signature.default.iter().map(|param| &param.name))
+                .chain(signature.variadic.iter().map(|param| &param.name))
+                .chain(signature.keyword.iter().map(|param| &param.name))
+                .chain(signature.keyword_default.iter().map(|param| &param.name))
+                .chain(signature.kwargs.iter().map(|param| &param.name));
+            
+            for name in names {
+                if !matches!(consts.get(usize::from(*name)), Some(Constant::String(..))) {
+                    return Err(BytecodeError::Malformed("invalid name constant"));
+                }
+            }
+            
+            functions.push(function);
+        }
+        
+        let symbols = if flags & FLAG_DEBUG_SYMBOLS != 0 {
+            self.read_symbols()?
+        } else {
+            ChunkSymbols::new()
+        };
+        
+        let program = UnloadedProgram {
+            main: main.into_boxed_slice(),
+            chunks: chunks.into_boxed_slice(),
+            chunk_index: chunk_index.into_boxed_slice(),
+            strings: strings.into_boxed_slice(),
+            string_index: string_index.into_boxed_slice(),
+            consts: consts.into_boxed_slice(),
+            functions: functions.into_boxed_slice(),
+        };
+        
+        Ok(CompiledProgram { program, symbols, warnings: Vec::new() })
+    }
+    
+    fn read_chunk_info(&mut self) -> BytecodeResult<ChunkInfo> {
+        match self.read_u8()? {
+            0 => Ok(ChunkInfo::ModuleMain),
+            1 => Ok(ChunkInfo::Function {
+                symbol: self.read_option(Self::read_debug_symbol)?,
+            }),
+            _ => Err(BytecodeError::Malformed("invalid chunk info")),
+        }
+    }
+    
+    fn read_const(&mut self) -> BytecodeResult<Constant> {
+        let constant = match self.read_u8()? {
+            0 => {
+                let value = i64::from_le_bytes(self.read_array()?);
+                let value = IntType::try_from(value)
+                    .map_err(|_| BytecodeError::Malformed("integer constant out of range"))?;
+                Constant::Integer(value)
+            }
+            1 => {
+                let value = f64::from_le_bytes(self.read_array()?);
+                Constant::from(value as FloatType)
+            }
+            2 => Constant::String(self.read_len()?),
+            3 => {
+                let error = *ERROR_KINDS.get(usize::from(self.read_u8()?))
+                    .ok_or(BytecodeError::Malformed("invalid error kind"))?;
+                Constant::Error { error, message: self.read_len()? }
+            }
+            _ => return Err(BytecodeError::Malformed("invalid constant")),
+        };
+        Ok(constant)
+    }
+    
+    fn read_function(&mut self) -> BytecodeResult<UnloadedFunction> {
+        let fun_id: FunctionID = self.read_u16()?;
+        
+        let name: Option<ConstID> = self.read_option(Self::read_u16)?;
+        
+        let required_count = self.read_len()?;
+        let required = (0..required_count).map(|_| self.read_param())
+            .collect::<BytecodeResult<Vec<_>>>()?;
+        
+        let default_count = self.read_len()?;
+        let default = (0..default_count).map(|_| self.read_param())
+            .collect::<BytecodeResult<Vec<_>>>()?;
+        
+        let variadic = self.read_option(Self::read_param)?;
+        
+        let keyword_count = self.read_len()?;
+        let keyword = (0..keyword_count).map(|_| self.read_param())
+            .collect::<BytecodeResult<Vec<_>>>()?;
+        
+        let keyword_default_count = self.read_len()?;
+        let keyword_default = (0..keyword_default_count).map(|_| self.read_param())
+            .collect::<BytecodeResult<Vec<_>>>()?;
+        
+        let kwargs = self.read_option(Self::read_param)?;
+        
+        let upval_count = self.read_len()?;
+        let mut upvalues = Vec::new();
+        for _ in 0..upval_count {
+            let upval = match self.read_u8()? {
+                0 => UpvalueTarget::Local(self.read_u16()?),
+                1 => UpvalueTarget::Upvalue(self.read_u16()?),
+                _ => return Err(BytecodeError::Malformed("invalid upvalue")),
+            };
+            upvalues.push(upval);
+        }
+        
+        let signature = UnloadedSignature {
+            name,
+            required: required.into_boxed_slice(),
+            default: default.into_boxed_slice(),
+            variadic,
+            keyword: keyword.into_boxed_slice(),
+            keyword_default: keyword_default.into_boxed_slice(),
+            kwargs,
+        };
+        
+        Ok(UnloadedFunction {
+            signature,
+            upvalues: upvalues.into_boxed_slice(),
+            fun_id,
+        })
+    }
+    
+    fn read_param(&mut self) -> BytecodeResult<UnloadedParam> {
+        let name = self.read_u16()?;
+        let mode = match self.read_u8()? {
+            0 => Access::ReadOnly,
+            1 => Access::ReadWrite,
+            _ => return Err(BytecodeError::Malformed("invalid parameter")),
+        };
+        Ok(UnloadedParam { name, mode })
+    }
+    
+    fn read_symbols(&mut self) -> BytecodeResult<ChunkSymbols> {
+        let mut symbols = ChunkSymbols::new();
+        
+        let table_count = self.read_len()?;
+        for _ in 0..table_count {
+            let chunk_id = match self.read_u8()? {
+                0 => Chunk::Main,
+                1 => Chunk::Function(self.read_u16()?),
+                _ => return Err(BytecodeError::Malformed("invalid chunk id")),
+            };
+            
+            let mut table = DebugSymbolTable::new();
+            let mut last_offset = None;
+            let entry_count = self.read_len()?;
+            for _ in 0..entry_count {
+                let offset = self.read_len()?;
+                if matches!(last_offset, Some(last) if offset <= last) {
+                    return Err(BytecodeError::Malformed("symbol table out of order"));
+                }
+                last_offset = Some(offset);
+                
+                table.insert(offset, self.read_debug_symbol()?);
+            }
+            
+            symbols.insert(chunk_id, table);
+        }
+        
+        Ok(symbols)
+    }
+    
+    fn read_debug_symbol(&mut self) -> BytecodeResult<DebugSymbol> {
+        let start = u32::from_le_bytes(self.read_array()?);
+        let length = u16::from_le_bytes(self.read_array()?);
+        Ok(DebugSymbol::new(start, length))
+    }
+    
+    fn read_option<T>(&mut self, read: impl FnOnce(&mut Self) -> BytecodeResult<T>) -> BytecodeResult<Option<T>> {
+        match self.read_u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(read(self)?)),
+            _ => Err(BytecodeError::Malformed("invalid option tag")),
+        }
+    }
+    
+    fn read_bytes(&mut self) -> BytecodeResult<Vec<u8>> {
+        let len = self.read_len()?;
+        
+        // don't trust the length enough to preallocate it
+        let mut bytes = Vec::new();
+        (&mut self.reader).take(len as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != len {
+            return Err(BytecodeError::Malformed("unexpected end of file"));
+        }
+        Ok(bytes)
+    }
+    
+    fn read_len(&mut self) -> BytecodeResult<usize> {
+        let len = u32::from_le_bytes(self.read_array()?);
+        usize::try_from(len).map_err(|_| BytecodeError::Malformed("length out of range"))
+    }
+    
+    fn read_u16(&mut self) -> BytecodeResult<u16> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+    
+    fn read_u8(&mut self) -> BytecodeResult<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+    
+    fn read_array<const N: usize>(&mut self) -> BytecodeResult<[u8; N]> {
+        let mut buf = [0; N];
+        self.reader.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+}
@@ -89,6 +89,10 @@ impl ChunkBuf {
         let patch = core::iter::repeat(u8::default()).take(to_len);
         self.bytes.splice(patch_range, patch);
     }
+    
+    pub fn replace_bytes(&mut self, bytes: Vec<u8>) {
+        self.bytes = bytes;
+    }
 }
 
 
@@ -149,6 +153,13 @@ impl ChunkBuilder {
         }
     }
     
+    pub fn chunk_ids(&self) -> impl Iterator<Item=Chunk> {
+        let functions = (0..self.chunks.len())
+            .map(|id| Chunk::Function(FunctionID::try_from(id).unwrap()));
+        
+        core::iter::once(Chunk::Main).chain(functions)
+    }
+    
     // Constants
     
     pub fn get_or_insert_const(&mut self, value: Constant) -> CompileResult<ConstID> {
@@ -173,6 +184,11 @@ impl ChunkBuilder {
         symbol.to_usize()
     }
     
+    pub fn get_str(&self, index: StringID) -> Option<&str> {
+        InternSymbol::try_from_usize(index)
+            .and_then(|symbol| self.strings.resolve(symbol))
+    }
+    
     pub fn get_or_insert_error(&mut self, error: ErrorKind, message: &str) -> CompileResult<ConstID> {
         let message = self.get_or_insert_str(message);
         self.get_or_insert_const(Constant::Error { error, message })
@@ -220,7 +236,7 @@ impl ChunkBuilder {
             let index = StringIndex {
                 offset, length
             };
-            string_index.insert(symbol.to_usize(), index);
+            string_index[symbol.to_usize()] = index;
         }
         
         // truncate trailing `None` values
@@ -256,6 +272,10 @@ pub struct ChunkIndex {
 }
 
 impl ChunkIndex {
+    pub(super) fn new(info: ChunkInfo, offset: usize, length: usize) -> Self {
+        Self { info, offset, length }
+    }
+    
     pub fn as_range(&self) -> Range<usize> {
         self.offset..(self.offset + self.length)
     }
@@ -272,6 +292,10 @@ pub struct StringIndex {
 }
 
 impl StringIndex {
+    pub(super) fn new(offset: usize, length: usize) -> Self {
+        Self { offset, length }
+    }
+    
     pub fn as_range(&self) -> Range<usize> {
         self.offset..(self.offset + self.length)
     }
@@ -283,13 +307,13 @@ impl StringIndex {
 /// between threads.
 #[derive(Debug, Clone)]
 pub struct UnloadedProgram {
-    main: Box<[u8]>,
-    chunks: Box<[u8]>,
-    chunk_index: Box<[ChunkIndex]>,
-    strings: Box<[u8]>,
-    string_index: Box<[StringIndex]>,
-    consts: Box<[Constant]>,
-    functions: Box<[UnloadedFunction]>,
+    pub(super) main: Box<[u8]>,
+    pub(super) chunks: Box<[u8]>,
+    pub(super) chunk_index: Box<[ChunkIndex]>,
+    pub(super) strings: Box<[u8]>,
+    pub(super) string_index: Box<[StringIndex]>,
+    pub(super) consts: Box<[Constant]>,
+    pub(super) functions: Box<[UnloadedFunction]>,
 }
 
 impl UnloadedProgram {
@@ -315,7 +339,7 @@ impl UnloadedProgram {
     }
     
     pub fn get_string(&self, string_id: StringID) -> &str {
-        let string_idx = &self.string_index[usize::from(string_id)];
+        let string_idx = &self.string_index[string_id];
         str::from_utf8(&self.strings[string_idx.as_range()]).expect("invalid string")
     }
     
@@ -337,7 +361,7 @@ impl UnloadedProgram {
 
 
 /// Unlike `UnloadedProgram`, this is not `Send` (mainly because `StringSymbol` is not Send)
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct ProgramData {
     chunks: Box<[u8]>,
     chunk_index: Box<[ChunkIndex]>,
@@ -363,8 +387,12 @@ impl ProgramData {
         &self.consts[usize::from(index)]
     }
     
+    pub fn consts_len(&self) -> usize {
+        self.consts.len()
+    }
+    
     pub fn get_string(&self, index: StringID) -> &StringSymbol {
-        &self.strings[usize::from(index)]
+        &self.strings[index]
     }
     
     pub fn get_function(&self, index: FunctionID) -> &FunctionProto {
@@ -419,7 +447,7 @@ impl Program {
             Constant::String(symbol) => symbol,
             _ => panic!("invalid name constant")
         };
-        strings[usize::from(string_id)]
+        strings[string_id]
     }
     
     fn load_signature(signature: UnloadedSignature, consts: &[Constant], strings: &[StringSymbol]) -> Signature {
@@ -434,7 +462,17 @@ impl Program {
         let variadic = signature.variadic
             .map(|param| Self::load_parameter(param, consts, strings));
         
+        let keyword = signature.keyword.into_vec().into_iter()
+            .map(|param| Self::load_parameter(param, consts, strings)).collect();
+        
+        let keyword_default = signature.keyword_default.into_vec().into_iter()
+            .map(|param| Self::load_parameter(param, consts, strings)).collect();
+        
+        let kwargs = signature.kwargs
+            .map(|param| Self::load_parameter(param, consts, strings));
+        
         Signature::new(name, required, default, variadic)
+            .with_keywords(keyword, keyword_default, kwargs)
     }
     
     fn load_parameter(param: UnloadedParam, consts: &[Constant], strings: &[StringSymbol]) -> Parameter {
@@ -72,4 +72,31 @@ impl fmt::Display for CompileError {
         
         utils::format_error(fmt, "Compile error", message, self.source())
     }
-}
\ No newline at end of file
+}
+
+/// A problem found by the compiler that does not prevent the program from being compiled
+#[derive(Debug)]
+pub struct CompileWarning {
+    message: String,
+    symbol: Option<DebugSymbol>,
+}
+
+impl CompileWarning {
+    pub fn new(message: impl ToString, symbol: Option<DebugSymbol>) -> Self {
+        Self { message: message.to_string(), symbol }
+    }
+    
+    pub fn message(&self) -> &str { &self.message }
+}
+
+impl Error for CompileWarning { }
+
+impl SourceError for CompileWarning {
+    fn debug_symbol(&self) -> Option<&DebugSymbol> { self.symbol.as_ref() }
+}
+
+impl fmt::Display for CompileWarning {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        utils::format_error(fmt, "Compile warning", Some(self.message.as_str()), None)
+    }
+}
@@ -0,0 +1,127 @@
+//! Constant folding for operators whose operands are all literals.
+//!
+//! Numeric and boolean operators are evaluated using the same implementations as the VM, so a folded
+//! expression always produces the value that it would have produced at runtime. If evaluating an operator
+//! fails, it is not folded so that the error is still raised when the code is executed.
+
+use string_interner::Symbol as _;
+use crate::language::{IntType, FloatType};
+use crate::parser::primary::Atom;
+use crate::parser::operator::{UnaryOp, BinaryOp};
+use crate::runtime::Variant;
+use crate::runtime::errors::ExecResult;
+use crate::codegen::consts::StringID;
+use crate::codegen::chunk::ChunkBuilder;
+
+
+/// A value that is known at compile time
+#[derive(Debug, Clone, Copy)]
+pub enum ConstValue {
+    Nil,
+    Bool(bool),
+    Integer(IntType),
+    Float(FloatType),
+    String(StringID),
+}
+
+impl ConstValue {
+    pub fn from_atom(atom: &Atom) -> Option<Self> {
+        let value = match atom {
+            Atom::Nil => Self::Nil,
+            Atom::BooleanLiteral(value) => Self::Bool(*value),
+            Atom::IntegerLiteral(value) => Self::Integer(*value),
+            Atom::FloatLiteral(value) => Self::Float(*value),
+            Atom::StringLiteral(symbol) => Self::String(symbol.to_usize()),
+            _ => return None,
+        };
+        Some(value)
+    }
+    
+    fn from_variant(value: &Variant) -> Option<Self> {
+        let value = match value {
+            Variant::Nil => Self::Nil,
+            Variant::BoolTrue => Self::Bool(true),
+            Variant::BoolFalse => Self::Bool(false),
+            Variant::Integer(value) => Self::Integer(*value),
+            Variant::Float(value) => Self::Float(*value),
+            _ => return None,
+        };
+        Some(value)
+    }
+    
+    // strings are not converted since the runtime string table is not available at compile time
+    fn to_variant(self) -> Option<Variant> {
+        let value = match self {
+            Self::Nil => Variant::Nil,
+            Self::Bool(value) => Variant::from(value),
+            Self::Integer(value) => Variant::from(value),
+            Self::Float(value) => Variant::from(value),
+            Self::String(..) => return None,
+        };
+        Some(value)
+    }
+}
+
+fn from_result(result: ExecResult<Variant>) -> Option<ConstValue> {
+    result.ok().as_ref().and_then(ConstValue::from_variant)
+}
+
+pub fn fold_unary(op: UnaryOp, operand: ConstValue) -> Option<ConstValue> {
+    let operand = operand.to_variant()?;
+    let result = match op {
+        UnaryOp::Neg => operand.apply_neg(),
+        UnaryOp::Pos => operand.apply_pos(),
+        UnaryOp::Inv => operand.apply_inv(),
+        UnaryOp::Not => operand.apply_not(),
+    };
+    from_result(result)
+}
+
+pub fn fold_binary(op: BinaryOp, lhs: ConstValue, rhs: ConstValue, builder: &mut ChunkBuilder) -> Option<ConstValue> {
+    if let (ConstValue::String(lhs), ConstValue::String(rhs)) = (lhs, rhs) {
+        return fold_strings(op, lhs, rhs, builder);
+    }
+    
+    let (lhs, rhs) = (lhs.to_variant()?, rhs.to_variant()?);
+    let result = match op {
+        // these short-circuit, so they are compiled into jumps instead
+        BinaryOp::And | BinaryOp::Or => return None,
+        
+        BinaryOp::Exp => lhs.apply_pow(&rhs),
+        BinaryOp::Mul => lhs.apply_mul(&rhs),
+        BinaryOp::Div => lhs.apply_div(&rhs),
+        BinaryOp::Mod => lhs.apply_mod(&rhs),
+        BinaryOp::Add => lhs.apply_add(&rhs),
+        BinaryOp::Sub => lhs.apply_sub(&rhs),
+        
+        BinaryOp::BitAnd => lhs.apply_and(&rhs),
+        BinaryOp::BitXor => lhs.apply_xor(&rhs),
+        BinaryOp::BitOr  => lhs.apply_or(&rhs),
+        
+        BinaryOp::LShift => lhs.apply_shl(&rhs),
+        BinaryOp::RShift => lhs.apply_shr(&rhs),
+        
+        BinaryOp::LT => lhs.cmp_lt(&rhs).map(Variant::from),
+        BinaryOp::GT => lhs.cmp_gt(&rhs).map(Variant::from),
+        BinaryOp::LE => lhs.cmp_le(&rhs).map(Variant::from),
+        BinaryOp::GE => lhs.cmp_ge(&rhs).map(Variant::from),
+        BinaryOp::EQ => lhs.cmp_eq(&rhs).map(Variant::from),
+        BinaryOp::NE => lhs.cmp_ne(&rhs).map(Variant::from),
+    };
+    from_result(result)
+}
+
+// strings are interned by the compiler, so equal strings always have the same index
+fn fold_strings(op: BinaryOp, lhs: StringID, rhs: StringID, builder: &mut ChunkBuilder) -> Option<ConstValue> {
+    match op {
+        BinaryOp::EQ => Some(ConstValue::Bool(lhs == rhs)),
+        BinaryOp::NE => Some(ConstValue::Bool(lhs != rhs)),
+        
+        BinaryOp::Add => {
+            let string = [ builder.get_str(lhs)?, builder.get_str(rhs)? ].concat();
+            Some(ConstValue::String(builder.get_or_insert_str(&string)))
+        }
+        
+        _ => None,
+    }
+}
@@ -48,6 +48,9 @@ pub struct UnloadedSignature {
     pub required: Box<[UnloadedParam]>,
     pub default: Box<[UnloadedParam]>,
     pub variadic: Option<UnloadedParam>,
+    pub keyword: Box<[UnloadedParam]>,
+    pub keyword_default: Box<[UnloadedParam]>,
+    pub kwargs: Option<UnloadedParam>,
 }
 
 
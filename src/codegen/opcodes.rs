@@ -17,6 +17,10 @@ pub type UpvalueIndex = u16;
 const OP_NOP:              u8 = 0x00;
 const OP_EXIT:             u8 = 0x01;  // _ => !
 const OP_ERROR:            u8 = 0x02;  // T[ error ] => !
+const OP_THROW:            u8 = 0x03;  // T[ error ] => !
+const OP_RERAISE:          u8 = 0x04;  // T[ error ] => !
+const OP_TRY_END:          u8 = 0x05;  // _ => _
+const OP_ASSERT_TYPE:      u8 = 0x06;  // [ value type ] => [ value ]
 
 const OP_RETURN:           u8 = 0x08;  // T[ ...call frame... ret_value ] => [ ret_value ]
 
@@ -24,23 +28,69 @@ const OP_RETURN:           u8 = 0x08;  // T[ ...call frame... ret_value ] => [ r
 const OP_CALL:             u8 = 0x09;
 const OP_IN_ARGS:          u8 = 0x0A;
 
+const OP_IMPORT:           u8 = 0x0B;  // [ path ] => [ module ]
+
+// (u8); [ callee arg[0] ... arg[n] nargs name[0] kwarg[0] ... name[M] kwarg[M] ] => [ ret_value ]
+const OP_CALL_KW:          u8 = 0x0C;
+const OP_ARG_MISSING:      u8 = 0x0D;  // [ value ] => [ bool ]
+
+// T[ ...call frame... callee arg[0] ... arg[n] nargs ] => [ ...call frame... ]
+// the active frame is replaced by the callee, if it can't be then this behaves like CALL
+const OP_TAIL_CALL:        u8 = 0x0E;
+
 // 0x10-17        Immediate Values
 
 const OP_POP:              u8 = 0x10;  // [ _ ] => []
 const OP_DROP:             u8 = 0x11;  // (u8); [ value[0] ... value[N] ] => []
 const OP_DROPN:            u8 = 0x12;  // [ value[0] ... value[N] N ] => []
 const OP_CLONE:            u8 = 0x13;  // [ value ] => [ value value ]
-const OP_SWAP:             u8 = 0x14;  // (u8); [ value[A] ... value[B] ] => [ value[B] ... value[A] ]
-const OP_SHIFT:            u8 = 0x15;  // (u8); [ value[A] ... value[B] ] => [ ... value[B] value[A] ]
 
+const OP_LIST:             u8 = 0x16;  // (u8); [ item[0] ... item[N] ] => [ list ]
+const OP_LISTN:            u8 = 0x17;  // [ item[0] ... item[N] N ] => [ list ]
 const OP_TUPLE:            u8 = 0x18;  // (u8); [ item[0] ... item[N] ] => [ tuple ]
 const OP_TUPLEN:           u8 = 0x19;  // [ item[0] ... item[N] N ] => [ tuple ]
 
 // 0x18-1F        Iteration
 
 const OP_ITER_INIT:        u8 = 0x1A;  // [ iterable ] => [ iter state[0] ]
-const OP_ITER_NEXT:        u8 = 0x1B;  // [ iter state[N] ] => [ iter state[N+1] value[N] ]
+const OP_ITER_NEXT:        u8 = 0x1B;  // [ iter state[N] ] => [ iter state[N+1] ]
 const OP_ITER_UNPACK:      u8 = 0x1C;  // [ iter state[N] ] => [ value[N] ... value[M] (M-N) ]
+const OP_GENERATOR:        u8 = 0x1D;  // T[ ...call frame... ] => [ generator ]
+const OP_YIELD:            u8 = 0x1E;  // [ value ] => []
+const OP_ITER_GET:         u8 = 0x1F;  // [ iter state[N] ] => [ iter state[N] value[N] ]
+
+// 0x20-2F        Objects
+
+const OP_OBJECT:           u8 = 0x20;  // _ => [ object ]
+const OP_IN_ATTR_IM:       u8 = 0x21;  // [ object value name ] => [ object ]
+const OP_IN_ATTR_MUT:      u8 = 0x22;  // [ object value name ] => [ object ]
+const OP_IN_ITEM:          u8 = 0x23;  // [ object|dict key value ] => [ object|dict ]
+
+const OP_CLASS:            u8 = 0x24;  // [ base name ] => [ class ]
+const OP_IN_FIELD_IM:      u8 = 0x25;  // [ class value name ] => [ class ]
+const OP_IN_FIELD_MUT:     u8 = 0x26;  // [ class value name ] => [ class ]
+const OP_IN_METHOD:        u8 = 0x27;  // [ class method name ] => [ class ]
+
+const OP_LD_ATTR:          u8 = 0x28;  // [ receiver name ] => [ value ]
+const OP_ST_ATTR:          u8 = 0x29;  // [ value receiver name ] => [ value ]
+const OP_LD_ITEM:          u8 = 0x2A;  // [ receiver key ] => [ value ]
+const OP_ST_ITEM:          u8 = 0x2B;  // [ value receiver key ] => [ value ]
+const OP_LD_SUPER:         u8 = 0x2C;  // [ self base name ] => [ method ]
+const OP_DICT:             u8 = 0x2D;  // _ => [ dict ]
+const OP_DP_ATTR:          u8 = 0x2E;  // [ receiver name ] => []
+const OP_DP_ITEM:          u8 = 0x2F;  // [ receiver key ] => []
+
+// 0x30-37        Pattern Matching
+
+const OP_MATCH_SEQ:        u8 = 0x30;  // (u8); [ tuple|list ] => [ item[0] ... item[N-1] true ] or [ false ]
+const OP_MATCH_SEQ_PACK:   u8 = 0x31;  // (u8, u8); [ tuple|list ] => [ item[0] ... item[A-1] pack item[-B] ... item[-1] true ] or [ false ]
+const OP_MATCH_ATTR:       u8 = 0x32;  // [ value name ] => [ value attr true ] or [ value false ]
+
+// 0x38-3F        Strings
+
+const OP_FMT_STR:          u8 = 0x38;  // [ value ] => [ string ]
+const OP_FMT_SPEC:         u8 = 0x39;  // [ value spec ] => [ string ]
+const OP_CONCAT:           u8 = 0x3A;  // (u8); [ string[0] ... string[N] ] => [ string ]
 
 // 0x40-5F        Load/Store
 
@@ -53,9 +103,11 @@ const OP_LD_CONST_16:      u8 = 0x43;  // (u16); _ => [ value ]
 
 const OP_IN_GLOBAL_IM:     u8 = 0x48;  // [ value name ] => [ value ]
 const OP_IN_GLOBAL_MUT:    u8 = 0x49;  // [ value name ] => [ value ]
-const OP_ST_GLOBAL:        u8 = 0x4A;  // [ value name ] => [ value ]
-const OP_LD_GLOBAL:        u8 = 0x4B;  // [ name ] => [ value ]
-const OP_DP_GLOBAL:        u8 = 0x4C;  // [ name ] => []
+const OP_ST_GLOBAL:        u8 = 0x4A;  // (u8);  [ value ] => [ value ]
+const OP_ST_GLOBAL_16:     u8 = 0x4B;  // (u16); [ value ] => [ value ]
+const OP_LD_GLOBAL:        u8 = 0x4C;  // (u8);  _ => [ value ]
+const OP_LD_GLOBAL_16:     u8 = 0x4D;  // (u16); _ => [ value ]
+const OP_DP_GLOBAL:        u8 = 0x4E;  // [ name ] => []
 
 const OP_IN_LOCAL:         u8 = 0x50;  // [ value ] => [ value ];
 const OP_ST_LOCAL:         u8 = 0x51;  // (u8);  [ value ] => [ value ]
@@ -104,6 +156,7 @@ const OP_SUB:              u8 = 0x81;
 const OP_MUL:              u8 = 0x82;
 const OP_DIV:              u8 = 0x83;
 const OP_MOD:              u8 = 0x84;
+const OP_EXP:              u8 = 0x85;
 
 const OP_EQ:               u8 = 0x88;
 const OP_NE:               u8 = 0x89;
@@ -119,12 +172,34 @@ const OP_JUMP_FALSE:       u8 = 0x91;  // (i16); [ cond ] => [ cond ]
 const OP_JUMP_TRUE:        u8 = 0x92;  // (i16); [ cond ] => [ cond ]
 const OP_PJMP_FALSE:       u8 = 0x93;  // (i16); [ cond ] => []
 const OP_PJMP_TRUE:        u8 = 0x94;  // (i16); [ cond ] => []
+const OP_TRY_BEGIN:        u8 = 0x95;  // (i16); _ => _, on error: T[ ... ] => [ error ]
 
 const OP_LJUMP:            u8 = 0x98;  // (i32);
 const OP_LJUMP_FALSE:      u8 = 0x99;  // (i32); [ cond ] => [ cond ]
 const OP_LJUMP_TRUE:       u8 = 0x9A;  // (i32); [ cond ] => [ cond ]
 const OP_PLJMP_FALSE:      u8 = 0x9B;  // (i32); [ cond ] => []
 const OP_PLJMP_TRUE:       u8 = 0x9C;  // (i32); [ cond ] => []
+const OP_LTRY_BEGIN:       u8 = 0x9D;  // (i32); _ => _, on error: T[ ... ] => [ error ]
+
+// 0xA0-AF      Register Operands
+
+// Locals double as registers. These evaluate the binary operator given by the opcode of its
+// generic instruction (e.g. OP_ADD), reading operands directly from registers or an immediate value.
+const OP_BINARY_RR:        u8 = 0xA0;  // (u8, u8, u8); _ => [ reg[A] op reg[B] ]
+const OP_BINARY_RI:        u8 = 0xA1;  // (u8, u8, i8); _ => [ reg[A] op imm ]
+const OP_BINARY_SR:        u8 = 0xA2;  // (u8, u8); [ lhs ] => [ lhs op reg[B] ]
+const OP_BINARY_SI:        u8 = 0xA3;  // (u8, i8); [ lhs ] => [ lhs op imm ]
+const OP_BINARY_RC:        u8 = 0xA4;  // (u8, u8, u8); _ => [ reg[A] op const[C] ]
+const OP_BINARY_SC:        u8 = 0xA5;  // (u8, u8); [ lhs ] => [ lhs op const[C] ]
+
+// Same as above, but the result is stored in register D instead of being pushed.
+const OP_BINARY_RR_ST:     u8 = 0xA8;  // (u8, u8, u8, u8); _ => _, reg[D] = reg[A] op reg[B]
+const OP_BINARY_RI_ST:     u8 = 0xA9;  // (u8, u8, i8, u8); _ => _, reg[D] = reg[A] op imm
+const OP_BINARY_RC_ST:     u8 = 0xAA;  // (u8, u8, u8, u8); _ => _, reg[D] = reg[A] op const[C]
+const OP_BINARY_SR_ST:     u8 = 0xAB;  // (u8, u8, u8); [ lhs ] => [], reg[D] = lhs op reg[B]
+const OP_BINARY_SI_ST:     u8 = 0xAC;  // (u8, i8, u8); [ lhs ] => [], reg[D] = lhs op imm
+const OP_BINARY_SC_ST:     u8 = 0xAD;  // (u8, u8, u8); [ lhs ] => [], reg[D] = lhs op const[C]
+const OP_BINARY_RS_ST:     u8 = 0xAE;  // (u8, u8, u8); [ rhs ] => [], reg[D] = reg[A] op rhs
 
 // 0xF0-FF      Debugging/Tracing/Misc
 
@@ -141,22 +216,64 @@ pub enum OpCode {
     Nop = OP_NOP,
     Exit = OP_EXIT,
     Error = OP_ERROR,
+    Throw = OP_THROW,
+    Reraise = OP_RERAISE,
+    TryEnd = OP_TRY_END,
+    AssertType = OP_ASSERT_TYPE,
     
     Return = OP_RETURN, 
     Call = OP_CALL,
     InsertArgs = OP_IN_ARGS,
     
+    Import = OP_IMPORT,
+    
+    CallKeywords = OP_CALL_KW,
+    ArgMissing = OP_ARG_MISSING,
+    TailCall = OP_TAIL_CALL,
+    
     Pop = OP_POP,
     Drop = OP_DROP,
     DropN = OP_DROPN,
     Clone = OP_CLONE,
     
+    List = OP_LIST,
+    ListN = OP_LISTN,
     Tuple = OP_TUPLE,
     TupleN = OP_TUPLEN,
     
     IterInit = OP_ITER_INIT,
     IterNext = OP_ITER_NEXT,
+    IterGet = OP_ITER_GET,
     IterUnpack = OP_ITER_UNPACK,
+    Generator = OP_GENERATOR,
+    Yield = OP_YIELD,
+    
+    Object = OP_OBJECT,
+    InsertAttr = OP_IN_ATTR_IM,
+    InsertAttrMut = OP_IN_ATTR_MUT,
+    InsertItem = OP_IN_ITEM,
+    
+    Class = OP_CLASS,
+    InsertField = OP_IN_FIELD_IM,
+    InsertFieldMut = OP_IN_FIELD_MUT,
+    InsertMethod = OP_IN_METHOD,
+    
+    LoadAttr = OP_LD_ATTR,
+    StoreAttr = OP_ST_ATTR,
+    LoadItem = OP_LD_ITEM,
+    StoreItem = OP_ST_ITEM,
+    LoadSuper = OP_LD_SUPER,
+    Dict = OP_DICT,
+    DropAttr = OP_DP_ATTR,
+    DropItem = OP_DP_ITEM,
+    
+    MatchSeq = OP_MATCH_SEQ,
+    MatchSeqPack = OP_MATCH_SEQ_PACK,
+    MatchAttr = OP_MATCH_ATTR,
+    
+    FmtStr = OP_FMT_STR,
+    FmtSpec = OP_FMT_SPEC,
+    Concat = OP_CONCAT,
     
     LoadFunction = OP_LD_FUN,
     LoadFunction16 = OP_LD_FUN_16,
@@ -167,7 +284,10 @@ pub enum OpCode {
     InsertGlobal = OP_IN_GLOBAL_IM,
     InsertGlobalMut = OP_IN_GLOBAL_MUT,
     StoreGlobal = OP_ST_GLOBAL,
+    StoreGlobal16 = OP_ST_GLOBAL_16,
     LoadGlobal = OP_LD_GLOBAL,
+    LoadGlobal16 = OP_LD_GLOBAL_16,
+    DropGlobal = OP_DP_GLOBAL,
     
     InsertLocal = OP_IN_LOCAL,
     StoreLocal = OP_ST_LOCAL,
@@ -208,6 +328,7 @@ pub enum OpCode {
     Mul = OP_MUL,
     Div = OP_DIV,
     Mod = OP_MOD,
+    Exp = OP_EXP,
     EQ = OP_EQ,
     NE = OP_NE,
     LT = OP_LT,
@@ -220,40 +341,111 @@ pub enum OpCode {
     JumpIfTrue = OP_JUMP_TRUE,
     PopJumpIfFalse = OP_PJMP_FALSE,
     PopJumpIfTrue = OP_PJMP_TRUE,
+    TryBegin = OP_TRY_BEGIN,
     
     LongJump = OP_LJUMP,
     LongJumpIfFalse = OP_LJUMP_FALSE,
     LongJumpIfTrue = OP_LJUMP_TRUE,
     PopLongJumpIfFalse = OP_PLJMP_FALSE,
     PopLongJumpIfTrue = OP_PLJMP_TRUE,
+    LongTryBegin = OP_LTRY_BEGIN,
+    
+    BinaryRR = OP_BINARY_RR,
+    BinaryRI = OP_BINARY_RI,
+    BinarySR = OP_BINARY_SR,
+    BinarySI = OP_BINARY_SI,
+    BinaryRC = OP_BINARY_RC,
+    BinarySC = OP_BINARY_SC,
+    BinaryRRStore = OP_BINARY_RR_ST,
+    BinaryRIStore = OP_BINARY_RI_ST,
+    BinaryRCStore = OP_BINARY_RC_ST,
+    BinarySRStore = OP_BINARY_SR_ST,
+    BinarySIStore = OP_BINARY_SI_ST,
+    BinarySCStore = OP_BINARY_SC_ST,
+    BinaryRSStore = OP_BINARY_RS_ST,
     
     Inspect = DBG_INSPECT,
     Assert = DBG_ASSERT,
 }
 
+// The opcode and instruction length of every byte value, so that the VM can decode an instruction with one lookup
+static DECODE_TABLE: [Option<(OpCode, u8)>; 256] = {
+    let mut table = [None; 256];
+    let mut byte = 0;
+    while byte < table.len() {
+        if let Some(opcode) = OpCode::from_byte(byte as u8) {
+            table[byte] = Some((opcode, opcode.instr_len() as u8));
+        }
+        byte += 1;
+    }
+    table
+};
+
 impl OpCode {
     #[inline]
-    pub fn from_byte(byte: u8) -> Option<OpCode> {
+    pub const fn from_byte(byte: u8) -> Option<OpCode> {
         let opcode = match byte {
             OP_NOP => Self::Nop,
             OP_EXIT => Self::Exit,
             OP_ERROR => Self::Error,
+            OP_THROW => Self::Throw,
+            OP_RERAISE => Self::Reraise,
+            OP_TRY_END => Self::TryEnd,
+            OP_ASSERT_TYPE => Self::AssertType,
             
             OP_RETURN => Self::Return,
             OP_CALL => Self::Call,
             OP_IN_ARGS => Self::InsertArgs,
             
+            OP_IMPORT => Self::Import,
+            
+            OP_CALL_KW => Self::CallKeywords,
+            OP_ARG_MISSING => Self::ArgMissing,
+            OP_TAIL_CALL => Self::TailCall,
+            
             OP_POP => Self::Pop,
             OP_DROP => Self::Drop,
             OP_DROPN => Self::DropN,
             OP_CLONE => Self::Clone,
             
+            OP_LIST => Self::List,
+            OP_LISTN => Self::ListN,
             OP_TUPLE => Self::Tuple,
             OP_TUPLEN => Self::TupleN,
             
             OP_ITER_INIT => Self::IterInit,
             OP_ITER_NEXT => Self::IterNext,
+            OP_ITER_GET => Self::IterGet,
             OP_ITER_UNPACK => Self::IterUnpack,
+            OP_GENERATOR => Self::Generator,
+            OP_YIELD => Self::Yield,
+            
+            OP_OBJECT => Self::Object,
+            OP_IN_ATTR_IM => Self::InsertAttr,
+            OP_IN_ATTR_MUT => Self::InsertAttrMut,
+            OP_IN_ITEM => Self::InsertItem,
+            
+            OP_CLASS => Self::Class,
+            OP_IN_FIELD_IM => Self::InsertField,
+            OP_IN_FIELD_MUT => Self::InsertFieldMut,
+            OP_IN_METHOD => Self::InsertMethod,
+            
+            OP_LD_ATTR => Self::LoadAttr,
+            OP_ST_ATTR => Self::StoreAttr,
+            OP_LD_ITEM => Self::LoadItem,
+            OP_ST_ITEM => Self::StoreItem,
+            OP_LD_SUPER => Self::LoadSuper,
+            OP_DICT => Self::Dict,
+            OP_DP_ATTR => Self::DropAttr,
+            OP_DP_ITEM => Self::DropItem,
+            
+            OP_MATCH_SEQ => Self::MatchSeq,
+            OP_MATCH_SEQ_PACK => Self::MatchSeqPack,
+            OP_MATCH_ATTR => Self::MatchAttr,
+            
+            OP_FMT_STR => Self::FmtStr,
+            OP_FMT_SPEC => Self::FmtSpec,
+            OP_CONCAT => Self::Concat,
             
             OP_LD_FUN => Self::LoadFunction,
             OP_LD_FUN_16 => Self::LoadFunction16,
@@ -264,7 +456,10 @@ impl OpCode {
             OP_IN_GLOBAL_IM => Self::InsertGlobal,
             OP_IN_GLOBAL_MUT => Self::InsertGlobalMut,
             OP_ST_GLOBAL => Self::StoreGlobal,
+            OP_ST_GLOBAL_16 => Self::StoreGlobal16,
             OP_LD_GLOBAL => Self::LoadGlobal,
+            OP_LD_GLOBAL_16 => Self::LoadGlobal16,
+            OP_DP_GLOBAL => Self::DropGlobal,
             
             OP_IN_LOCAL => Self::InsertLocal,
             OP_ST_LOCAL => Self::StoreLocal,
@@ -304,6 +499,7 @@ impl OpCode {
             OP_MUL => Self::Mul,
             OP_DIV => Self::Div,
             OP_MOD => Self::Mod,
+            OP_EXP => Self::Exp,
             OP_EQ => Self::EQ,
             OP_NE => Self::NE,
             OP_LT => Self::LT,
@@ -316,12 +512,28 @@ impl OpCode {
             OP_JUMP_TRUE => Self::JumpIfTrue,
             OP_PJMP_FALSE => Self::PopJumpIfFalse,
             OP_PJMP_TRUE => Self::PopJumpIfTrue,
+            OP_TRY_BEGIN => Self::TryBegin,
             
             OP_LJUMP => Self::LongJump,
             OP_LJUMP_FALSE => Self::LongJumpIfFalse,
             OP_LJUMP_TRUE => Self::LongJumpIfTrue,
             OP_PLJMP_FALSE => Self::PopLongJumpIfFalse,
             OP_PLJMP_TRUE => Self::PopLongJumpIfTrue,
+            OP_LTRY_BEGIN => Self::LongTryBegin,
+            
+            OP_BINARY_RR => Self::BinaryRR,
+            OP_BINARY_RI => Self::BinaryRI,
+            OP_BINARY_SR => Self::BinarySR,
+            OP_BINARY_SI => Self::BinarySI,
+            OP_BINARY_RC => Self::BinaryRC,
+            OP_BINARY_SC => Self::BinarySC,
+            OP_BINARY_RR_ST => Self::BinaryRRStore,
+            OP_BINARY_RI_ST => Self::BinaryRIStore,
+            OP_BINARY_RC_ST => Self::BinaryRCStore,
+            OP_BINARY_SR_ST => Self::BinarySRStore,
+            OP_BINARY_SI_ST => Self::BinarySIStore,
+            OP_BINARY_SC_ST => Self::BinarySCStore,
+            OP_BINARY_RS_ST => Self::BinaryRSStore,
             
             DBG_INSPECT => Self::Inspect,
             DBG_ASSERT => Self::Assert,
@@ -331,12 +543,24 @@ impl OpCode {
         Some(opcode)
     }
     
+    /// Like `from_byte()`, but also produces the instruction length
+    #[inline(always)]
+    pub fn decode(byte: u8) -> Option<(OpCode, usize)> {
+        DECODE_TABLE[usize::from(byte)].map(|(opcode, len)| (opcode, usize::from(len)))
+    }
+    
     #[inline]
     pub const fn instr_len(&self) -> usize {
         match self {
             // don't really need size_of() for most of these, but it's a nice little bit of self-documentation
 
             Self::Drop           => 1 + size_of::<u8>(),
+            Self::CallKeywords   => 1 + size_of::<u8>(),
+            
+            Self::MatchSeq       => 1 + size_of::<u8>(),
+            Self::MatchSeqPack   => 1 + 2 * size_of::<u8>(),
+            
+            Self::Concat         => 1 + size_of::<u8>(),
             
             Self::LoadFunction   => 1 + size_of::<u8>(),
             Self::LoadFunction16 => 1 + size_of::<u16>(),
@@ -344,6 +568,11 @@ impl OpCode {
             Self::LoadConst      => 1 + size_of::<u8>(),
             Self::LoadConst16    => 1 + size_of::<u16>(),
             
+            Self::StoreGlobal    => 1 + size_of::<u8>(),
+            Self::StoreGlobal16  => 1 + size_of::<u16>(),
+            Self::LoadGlobal     => 1 + size_of::<u8>(),
+            Self::LoadGlobal16   => 1 + size_of::<u16>(),
+            
             Self::StoreLocal     => 1 + size_of::<u8>(),
             Self::StoreLocal16   => 1 + size_of::<u16>(),
             Self::LoadLocal      => 1 + size_of::<u8>(),
@@ -358,6 +587,7 @@ impl OpCode {
             Self::CloseUpvalue   => 1 + size_of::<u8>(),
             Self::CloseUpvalue16 => 1 + size_of::<u16>(),
             
+            Self::List           => 1 + size_of::<u8>(),
             Self::Tuple          => 1 + size_of::<u8>(),
             Self::UInt8          => 1 + size_of::<u8>(),
             Self::Int8           => 1 + size_of::<i8>(),
@@ -368,6 +598,28 @@ impl OpCode {
             Self::JumpIfTrue     => 1 + size_of::<i16>(),
             Self::PopJumpIfFalse => 1 + size_of::<i16>(),
             Self::PopJumpIfTrue  => 1 + size_of::<i16>(),
+            Self::TryBegin       => 1 + size_of::<i16>(),
+            
+            Self::LongJump           => 1 + size_of::<i32>(),
+            Self::LongJumpIfFalse    => 1 + size_of::<i32>(),
+            Self::LongJumpIfTrue     => 1 + size_of::<i32>(),
+            Self::PopLongJumpIfFalse => 1 + size_of::<i32>(),
+            Self::PopLongJumpIfTrue  => 1 + size_of::<i32>(),
+            Self::LongTryBegin   => 1 + size_of::<i32>(),
+            
+            Self::BinaryRR       => 1 + 3 * size_of::<u8>(),
+            Self::BinaryRI       => 1 + 2 * size_of::<u8>() + size_of::<i8>(),
+            Self::BinarySR       => 1 + 2 * size_of::<u8>(),
+            Self::BinarySI       => 1 + size_of::<u8>() + size_of::<i8>(),
+            Self::BinaryRC       => 1 + 3 * size_of::<u8>(),
+            Self::BinarySC       => 1 + 2 * size_of::<u8>(),
+            Self::BinaryRRStore  => 1 + 4 * size_of::<u8>(),
+            Self::BinaryRIStore  => 1 + 3 * size_of::<u8>() + size_of::<i8>(),
+            Self::BinaryRCStore  => 1 + 4 * size_of::<u8>(),
+            Self::BinarySRStore  => 1 + 3 * size_of::<u8>(),
+            Self::BinarySIStore  => 1 + 2 * size_of::<u8>() + size_of::<i8>(),
+            Self::BinarySCStore  => 1 + 3 * size_of::<u8>(),
+            Self::BinaryRSStore  => 1 + 3 * size_of::<u8>(),
             
             _ => 1,
         }
@@ -400,22 +652,64 @@ impl core::fmt::Display for OpCode {
             Self::Nop => "NOP",
             Self::Exit => "EXIT",
             Self::Error => "ERROR",
+            Self::Throw => "THROW",
+            Self::Reraise => "RERAISE",
+            Self::TryEnd => "TRY_END",
+            Self::AssertType => "ASSERT_TYPE",
             
             Self::Return => "RETURN",
             Self::Call => "CALL",
             Self::InsertArgs => "IN_ARGS",
             
+            Self::Import => "IMPORT",
+            
+            Self::CallKeywords => "CALL_KW",
+            Self::ArgMissing => "ARG_MISSING",
+            Self::TailCall => "TAIL_CALL",
+            
             Self::Pop => "POP",
             Self::Drop => "DROP",
             Self::DropN => "DROPN",
             Self::Clone => "CLONE",
             
+            Self::List => "LIST",
+            Self::ListN => "LISTN",
             Self::Tuple => "TUPLE",
             Self::TupleN => "TUPLEN",
             
             Self::IterInit => "ITER_INIT",
             Self::IterNext => "ITER_NEXT",
+            Self::IterGet => "ITER_GET",
             Self::IterUnpack => "ITER_UNPACK",
+            Self::Generator => "GENERATOR",
+            Self::Yield => "YIELD",
+            
+            Self::Object => "OBJECT",
+            Self::InsertAttr => "IN_ATTR_IM",
+            Self::InsertAttrMut => "IN_ATTR_MUT",
+            Self::InsertItem => "IN_ITEM",
+            
+            Self::Class => "CLASS",
+            Self::InsertField => "IN_FIELD_IM",
+            Self::InsertFieldMut => "IN_FIELD_MUT",
+            Self::InsertMethod => "IN_METHOD",
+            
+            Self::LoadAttr => "LD_ATTR",
+            Self::StoreAttr => "ST_ATTR",
+            Self::LoadItem => "LD_ITEM",
+            Self::StoreItem => "ST_ITEM",
+            Self::LoadSuper => "LD_SUPER",
+            Self::Dict => "DICT",
+            Self::DropAttr => "DP_ATTR",
+            Self::DropItem => "DP_ITEM",
+            
+            Self::MatchSeq => "MATCH_SEQ",
+            Self::MatchSeqPack => "MATCH_SEQ_PACK",
+            Self::MatchAttr => "MATCH_ATTR",
+            
+            Self::FmtStr => "FMT_STR",
+            Self::FmtSpec => "FMT_SPEC",
+            Self::Concat => "CONCAT",
             
             Self::LoadFunction => "LD_FUN",
             Self::LoadFunction16 => "LD_FUN_16",
@@ -426,7 +720,10 @@ impl core::fmt::Display for OpCode {
             Self::InsertGlobal => "IN_GLOBAL_IM",
             Self::InsertGlobalMut => "IN_GLOBAL_MUT",
             Self::StoreGlobal => "ST_GLOBAL",
+            Self::StoreGlobal16 => "ST_GLOBAL_16",
             Self::LoadGlobal => "LD_GLOBAL",
+            Self::LoadGlobal16 => "LD_GLOBAL_16",
+            Self::DropGlobal => "DP_GLOBAL",
             
             Self::InsertLocal => "IN_LOCAL",
             Self::StoreLocal => "ST_LOCAL",
@@ -466,6 +763,7 @@ impl core::fmt::Display for OpCode {
             Self::Mul => "MUL",
             Self::Div => "DIV",
             Self::Mod => "MOD",
+            Self::Exp => "EXP",
             Self::EQ => "CMP_EQ",
             Self::NE => "CMP_NE",
             Self::LT => "CMP_LT",
@@ -478,12 +776,28 @@ impl core::fmt::Display for OpCode {
             Self::JumpIfTrue => "JUMP_TRUE",
             Self::PopJumpIfFalse => "PJMP_FALSE",
             Self::PopJumpIfTrue => "PJMP_TRUE",
+            Self::TryBegin => "TRY_BEGIN",
             
             Self::LongJump => "LJUMP",
             Self::LongJumpIfFalse => "LJUMP_FALSE",
             Self::LongJumpIfTrue => "LJUMP_TRUE",
             Self::PopLongJumpIfFalse => "PLJMP_FALSE",
             Self::PopLongJumpIfTrue => "PLJMP_TRUE",
+            Self::LongTryBegin => "LTRY_BEGIN",
+            
+            Self::BinaryRR => "BINARY_RR",
+            Self::BinaryRI => "BINARY_RI",
+            Self::BinarySR => "BINARY_SR",
+            Self::BinarySI => "BINARY_SI",
+            Self::BinaryRC => "BINARY_RC",
+            Self::BinarySC => "BINARY_SC",
+            Self::BinaryRRStore => "BINARY_RR_ST",
+            Self::BinaryRIStore => "BINARY_RI_ST",
+            Self::BinaryRCStore => "BINARY_RC_ST",
+            Self::BinarySRStore => "BINARY_SR_ST",
+            Self::BinarySIStore => "BINARY_SI_ST",
+            Self::BinarySCStore => "BINARY_SC_ST",
+            Self::BinaryRSStore => "BINARY_RS_ST",
             
             Self::Inspect => "DBG_INSPECT",
             Self::Assert => "DBG_ASSERT",
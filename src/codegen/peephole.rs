@@ -0,0 +1,399 @@
+//! Peephole optimizations that are applied to each chunk after it has been compiled.
+//!
+//! The chunk is decoded into a list of instructions where each jump refers to the index of its target
+//! instruction instead of a byte offset. The list is rewritten until none of the passes can make any more
+//! changes, and then it is encoded again with new jump offsets. Debug symbols move with their instructions.
+//!
+//! The passes are:
+//! - branches on a `true` or `false` literal are replaced with an unconditional jump or removed
+//! - a value that is loaded or cloned and then immediately popped is removed
+//! - jumps that land on another jump are redirected to the final destination (jump threading)
+//! - unconditional jumps to the next instruction are removed
+//! - instructions that cannot be reached from the start of the chunk are removed
+
+use crate::codegen::{OpCode, Jump, JumpOffset, get_jump_opcode, get_jump_type};
+use crate::codegen::chunk::ChunkBuf;
+use crate::debug::symbol::{DebugSymbol, DebugSymbolTable};
+
+
+// upper bounds in case jumps form a cycle
+const MAX_THREAD_DEPTH: usize = 16;
+const MAX_PASSES: usize = 16;
+
+#[derive(Debug, Clone)]
+enum Op {
+    Instr(OpCode, Vec<u8>),
+    Jump(Jump, usize),  // the target is an index into the instruction list
+    Removed,
+}
+
+#[derive(Debug, Clone)]
+struct Instr {
+    op: Op,
+    symbol: Option<DebugSymbol>,
+}
+
+impl Instr {
+    fn opcode(&self) -> Option<OpCode> {
+        match self.op {
+            Op::Instr(opcode, ..) => Some(opcode),
+            _ => None,
+        }
+    }
+    
+    fn falls_through(&self) -> bool {
+        match self.op {
+            Op::Jump(jump, ..) => jump != Jump::Uncond,
+            Op::Instr(opcode, ..) => !matches!(
+                opcode, OpCode::Exit | OpCode::Return | OpCode::Error | OpCode::Throw | OpCode::Reraise
+            ),
+            Op::Removed => true,
+        }
+    }
+}
+
+
+/// Optimize a chunk in place. If the chunk can't be decoded it is left unchanged.
+pub fn optimize_chunk(chunk: &mut ChunkBuf, symbols: &mut DebugSymbolTable) {
+    let mut instrs = match decode(chunk.as_slice(), symbols) {
+        Some(instrs) => instrs,
+        None => return,
+    };
+    
+    for _ in 0..MAX_PASSES {
+        let mut changed = false;
+        changed |= fold_constant_branches(&mut instrs);
+        changed |= remove_discarded_values(&mut instrs);
+        changed |= thread_jumps(&mut instrs);
+        changed |= remove_dead_code(&mut instrs);
+        
+        match compact(instrs) {
+            Some((compacted, removed)) => {
+                instrs = compacted;
+                changed |= removed;
+            }
+            None => return,
+        }
+        
+        if !changed {
+            break;
+        }
+    }
+    
+    if let Some((bytes, new_symbols)) = encode(&instrs) {
+        chunk.replace_bytes(bytes);
+        *symbols = new_symbols;
+    }
+}
+
+
+fn decode(bytes: &[u8], symbols: &DebugSymbolTable) -> Option<Vec<Instr>> {
+    let mut instrs = Vec::new();
+    let mut index_map = vec![None; bytes.len()];  // byte offset -> instruction index
+    let mut jump_offsets = Vec::new();
+    
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let opcode = OpCode::from_byte(bytes[offset])?;
+        let end = offset + opcode.instr_len();
+        let data = bytes.get((offset + 1)..end)?;
+        
+        index_map[offset] = Some(instrs.len());
+        
+        let op = match get_jump_type(opcode) {
+            Some(jump) => {
+                let relative = match data.len() {
+                    2 => isize::from(i16::from_le_bytes(data.try_into().unwrap())),
+                    4 => isize::try_from(i32::from_le_bytes(data.try_into().unwrap())).ok()?,
+                    _ => return None,
+                };
+                jump_offsets.push((instrs.len(), end.checked_add_signed(relative)?));
+                Op::Jump(jump, 0)
+            }
+            None => Op::Instr(opcode, data.to_vec()),
+        };
+        
+        instrs.push(Instr {
+            op, symbol: symbols.lookup(offset).copied(),
+        });
+        
+        offset = end;
+    }
+    
+    // resolve jump targets, which must be the start of an instruction
+    for (index, target) in jump_offsets.into_iter() {
+        let target = index_map.get(target).copied().flatten()?;
+        if let Op::Jump(_, ref mut jump_target) = instrs[index].op {
+            *jump_target = target;
+        }
+    }
+    
+    Some(instrs)
+}
+
+fn encode(instrs: &[Instr]) -> Option<(Vec<u8>, DebugSymbolTable)> {
+    // start with every jump short, then widen the jumps that don't fit until the layout stops changing.
+    // jumps are never narrowed again, so this always terminates
+    let mut widths = instrs.iter().map(|instr| match instr.op {
+        Op::Instr(opcode, ..) => opcode.instr_len(),
+        Op::Jump(jump, ..) => jump.dummy_width(),
+        Op::Removed => 0,
+    }).collect::<Vec<usize>>();
+    
+    let mut offsets;
+    loop {
+        offsets = widths.iter()
+            .scan(0, |offset, width| {
+                let start = *offset;
+                *offset += width;
+                Some(start)
+            })
+            .collect::<Vec<usize>>();
+        
+        let mut changed = false;
+        for (index, instr) in instrs.iter().enumerate() {
+            if let Op::Jump(jump, target) = instr.op {
+                let jump_offset = jump_offset(offsets[index] + widths[index], offsets[target])?;
+                let width = get_jump_opcode(jump, jump_offset).instr_len();
+                if width > widths[index] {
+                    widths[index] = width;
+                    changed = true;
+                }
+            }
+        }
+        
+        if !changed {
+            break;
+        }
+    }
+    
+    let mut bytes = Vec::new();
+    let mut symbols = DebugSymbolTable::new();
+    for (index, instr) in instrs.iter().enumerate() {
+        if let Some(symbol) = instr.symbol {
+            symbols.insert(bytes.len(), symbol);
+        }
+        
+        match &instr.op {
+            Op::Instr(opcode, data) => {
+                bytes.push(u8::from(*opcode));
+                bytes.extend(data);
+            }
+            
+            Op::Jump(jump, target) => {
+                // a jump that was widened keeps its width even if the offset would fit in a short jump now
+                let jump_offset = match jump_offset(offsets[index] + widths[index], offsets[*target])? {
+                    JumpOffset::Short(offset) if widths[index] > jump.dummy_width() => JumpOffset::Long(offset.into()),
+                    jump_offset => jump_offset,
+                };
+                bytes.push(u8::from(get_jump_opcode(*jump, jump_offset)));
+                match jump_offset {
+                    JumpOffset::Short(offset) => bytes.extend(offset.to_le_bytes()),
+                    JumpOffset::Long(offset) => bytes.extend(offset.to_le_bytes()),
+                }
+            }
+            
+            Op::Removed => { },
+        }
+    }
+    
+    Some((bytes, symbols))
+}
+
+// Expects the *end* offset of the jump instruction
+fn jump_offset(jump_end_offset: usize, target: usize) -> Option<JumpOffset> {
+    let relative = isize::try_from(target).ok()? - isize::try_from(jump_end_offset).ok()?;
+    
+    if let Ok(offset) = i16::try_from(relative) {
+        return Some(JumpOffset::Short(offset));
+    }
+    
+    i32::try_from(relative).ok().map(JumpOffset::Long)
+}
+
+// Drop removed instructions and retarget jumps at them to the next instruction that was kept.
+// Returns None if a jump would be left without a target.
+fn compact(instrs: Vec<Instr>) -> Option<(Vec<Instr>, bool)> {
+    let mut index_map = Vec::with_capacity(instrs.len() + 1);
+    let mut next_index = 0;
+    for instr in instrs.iter() {
+        index_map.push(next_index);
+        if !matches!(instr.op, Op::Removed) {
+            next_index += 1;
+        }
+    }
+    
+    if next_index == instrs.len() {
+        return Some((instrs, false));
+    }
+    
+    let mut compacted = Vec::with_capacity(next_index);
+    for mut instr in instrs.into_iter() {
+        match instr.op {
+            Op::Removed => continue,
+            Op::Jump(_, ref mut target) => {
+                *target = index_map[*target];
+                if *target >= next_index {
+                    return None;
+                }
+            }
+            _ => { },
+        }
+        compacted.push(instr);
+    }
+    
+    Some((compacted, true))
+}
+
+fn find_jump_targets(instrs: &[Instr]) -> Vec<bool> {
+    let mut is_target = vec![false; instrs.len()];
+    for instr in instrs.iter() {
+        if let Op::Jump(_, target) = instr.op {
+            is_target[target] = true;
+        }
+    }
+    is_target
+}
+
+
+// e.g. the condition of `while true` or `if false`
+fn fold_constant_branches(instrs: &mut [Instr]) -> bool {
+    let is_target = find_jump_targets(instrs);
+    
+    let mut changed = false;
+    for index in 1..instrs.len() {
+        let cond = match instrs[index - 1].opcode() {
+            Some(OpCode::True) => true,
+            Some(OpCode::False) => false,
+            _ => continue,
+        };
+        
+        let (jump, target) = match instrs[index].op {
+            Op::Jump(jump, target) if !is_target[index] => (jump, target),
+            _ => continue,
+        };
+        
+        let taken = match jump {
+            Jump::IfFalse | Jump::PopIfFalse => !cond,
+            Jump::IfTrue | Jump::PopIfTrue => cond,
+            _ => continue,
+        };
+        
+        let pops = matches!(jump, Jump::PopIfFalse | Jump::PopIfTrue);
+        if pops {
+            instrs[index - 1].op = Op::Removed;
+        }
+        
+        instrs[index].op = if taken { Op::Jump(Jump::Uncond, target) } else { Op::Removed };
+        changed = true;
+    }
+    changed
+}
+
+// a value that is pushed and then immediately popped
+fn remove_discarded_values(instrs: &mut [Instr]) -> bool {
+    let is_target = find_jump_targets(instrs);
+    
+    let mut changed = false;
+    for index in 1..instrs.len() {
+        if is_target[index] || instrs[index].opcode() != Some(OpCode::Pop) {
+            continue;
+        }
+        
+        let discarded = matches!(
+            instrs[index - 1].opcode(),
+            Some(
+                OpCode::Clone | OpCode::Nil | OpCode::True | OpCode::False | OpCode::Empty
+                | OpCode::UInt8 | OpCode::Int8 | OpCode::Int16 | OpCode::LoadConst | OpCode::LoadConst16
+                | OpCode::LoadLocal | OpCode::LoadLocal16 | OpCode::LoadUpvalue | OpCode::LoadUpvalue16
+            )
+        );
+        
+        if discarded {
+            instrs[index - 1].op = Op::Removed;
+            instrs[index].op = Op::Removed;
+            changed = true;
+        }
+    }
+    changed
+}
+
+fn thread_jumps(instrs: &mut [Instr]) -> bool {
+    let mut changed = false;
+    for index in 0..instrs.len() {
+        let (jump, mut target) = match instrs[index].op {
+            Op::Jump(jump, target) => (jump, target),
+            _ => continue,
+        };
+        
+        for _ in 0..MAX_THREAD_DEPTH {
+            let next_target = match (jump, &instrs[target].op) {
+                (_, Op::Jump(Jump::Uncond, next)) => *next,
+                
+                // jumps that peek at the condition can follow other jumps on the same condition
+                (Jump::IfFalse, Op::Jump(Jump::IfFalse, next)) => *next,
+                (Jump::IfTrue, Op::Jump(Jump::IfTrue, next)) => *next,
+                (Jump::IfFalse, Op::Jump(Jump::IfTrue, _)) => target + 1,
+                (Jump::IfTrue, Op::Jump(Jump::IfFalse, _)) => target + 1,
+                
+                _ => break,
+            };
+            
+            if next_target == target || next_target >= instrs.len() {
+                break;
+            }
+            target = next_target;
+        }
+        
+        // an unconditional jump to the end of a function can just return
+        if jump == Jump::Uncond {
+            if let Some(opcode @ (OpCode::Return | OpCode::Exit)) = instrs[target].opcode() {
+                instrs[index].op = Op::Instr(opcode, Vec::new());
+                changed = true;
+                continue;
+            }
+        }
+        
+        if !matches!(instrs[index].op, Op::Jump(_, current) if current == target) {
+            instrs[index].op = Op::Jump(jump, target);
+            changed = true;
+        }
+    }
+    
+    // an unconditional jump to the next instruction does nothing
+    for (index, instr) in instrs.iter_mut().enumerate() {
+        if matches!(instr.op, Op::Jump(Jump::Uncond, target) if target == index + 1) {
+            instr.op = Op::Removed;
+            changed = true;
+        }
+    }
+    changed
+}
+
+fn remove_dead_code(instrs: &mut [Instr]) -> bool {
+    let mut reachable = vec![false; instrs.len()];
+    let mut pending = vec![0];
+    while let Some(mut index) = pending.pop() {
+        while index < instrs.len() && !reachable[index] {
+            reachable[index] = true;
+            
+            let instr = &instrs[index];
+            if let Op::Jump(_, target) = instr.op {
+                pending.push(target);
+            }
+            if !instr.falls_through() {
+                break;
+            }
+            index += 1;
+        }
+    }
+    
+    let mut changed = false;
+    for (instr, reachable) in instrs.iter_mut().zip(reachable) {
+        if !reachable && !matches!(instr.op, Op::Removed) {
+            instr.op = Op::Removed;
+            changed = true;
+        }
+    }
+    changed
+}
@@ -1,7 +1,8 @@
 // Scope Tracking
 
 use crate::language::{InternSymbol, Access};
-use crate::parser::stmt::Label;
+use crate::parser::stmt::{Label, StmtList};
+use crate::parser::annotation::TypeExpr;
 use crate::debug::symbol::DebugSymbol;
 use crate::codegen::JumpSite;
 use crate::codegen::opcodes::{LocalIndex, UpvalueIndex};
@@ -20,6 +21,8 @@ pub(super) enum LocalName {
     Receiver,  // inside a function call, this refers to the object that was called
     NArgs,     // inside a function call, the number of arguments passed at the call site
     
+    Super,     // inside a class definition, this refers to the base class (or nil)
+    
     Anonymous, // for internal temporaries. excluded from local variable resolution, they can only be referred to by local index
 }
 
@@ -63,6 +66,11 @@ pub(super) enum ScopeTag {
     Function,
     Global,
     Temporary,
+    Class,
+    Try,      // an error handler is active for the duration of this scope
+    Catch,
+    Finally,  // control flow may not jump out of a finally clause
+    Match,    // holds the subject of a match expression, or the names bound by a case pattern
 }
 
 impl ScopeTag {
@@ -87,6 +95,10 @@ impl ScopeTag {
         }
     }
     
+    pub(super) fn is_try_block(&self) -> bool {
+        matches!(self, Self::Try)
+    }
+    
     pub(super) fn is_expr_block(&self) -> bool {
         match self {
             Self::Block | Self:: Branch => true,
@@ -136,6 +148,8 @@ pub(super) struct Scope {
     prev_index: Option<LocalIndex>,
     locals: Vec<Local>,
     control_flow: ControlFlowTracker,
+    finally: Option<StmtList>,  // must be run when control flow leaves a Try scope
+    hidden: bool,
 }
 
 impl Scope {
@@ -171,6 +185,14 @@ impl Scope {
         &self.control_flow.break_sites
     }
     
+    pub(super) fn set_finally(&mut self, finally: StmtList) {
+        self.finally.replace(finally);
+    }
+    
+    pub(super) fn finally(&self) -> Option<&StmtList> {
+        self.finally.as_ref()
+    }
+    
     fn control_flow_mut(&mut self) -> &mut ControlFlowTracker {
         &mut self.control_flow
     }
@@ -242,6 +264,8 @@ impl NestedScopes {
             symbol: symbol.copied(),
             locals: Vec::new(),
             control_flow: ControlFlowTracker::new(label),
+            finally: None,
+            hidden: false,
         };
         
         Self {
@@ -272,6 +296,8 @@ impl NestedScopes {
             symbol: symbol.copied(),
             locals: Vec::new(),
             control_flow: ControlFlowTracker::new(label),
+            finally: None,
+            hidden: false,
         };
         
         self.nested.push(scope);
@@ -285,13 +311,19 @@ impl NestedScopes {
     fn iter_nro(&self) -> impl Iterator<Item=&Scope> {
         self.nested.iter().rev()
             .chain(std::iter::once(&self.toplevel))
-            .filter(|scope| !scope.tag().hide_from_nro())
+            .filter(|scope| !scope.hidden && !scope.tag().hide_from_nro())
     }
     
     fn iter_nro_mut(&mut self) -> impl Iterator<Item=&mut Scope> {
         self.nested.iter_mut().rev()
             .chain(std::iter::once(&mut self.toplevel))
-            .filter(|scope| !scope.tag().hide_from_nro())
+            .filter(|scope| !scope.hidden && !scope.tag().hide_from_nro())
+    }
+    
+    fn set_hidden(&mut self, min_depth: usize, hidden: bool) {
+        for scope in self.nested.iter_mut().filter(|scope| scope.depth() >= min_depth) {
+            scope.hidden = hidden;
+        }
     }
 }
 
@@ -316,18 +348,22 @@ impl Upvalue {
 pub(super) struct CallFrame {
     scopes: NestedScopes,
     upvalues: Vec<Upvalue>,
+    return_type: Option<TypeExpr>,
 }
 
 impl CallFrame {
-    fn new(symbol: Option<&DebugSymbol>) -> Self {
+    fn new(symbol: Option<&DebugSymbol>, return_type: Option<TypeExpr>) -> Self {
         Self {
             scopes: NestedScopes::new(symbol, ScopeTag::Function, None),
             upvalues: Vec::new(),
+            return_type,
         }
     }
     
     pub(super) fn upvalues(&self) -> &[Upvalue] { self.upvalues.as_slice() }
     
+    pub(super) fn return_type(&self) -> Option<&TypeExpr> { self.return_type.as_ref() }
+    
     pub(super) fn iter_locals(&self) -> impl Iterator<Item=&Local> {
         self.scopes().iter_nro().flat_map(|scope| scope.locals().iter())
     }
@@ -402,8 +438,13 @@ impl ScopeTracker {
         !self.frames.is_empty()
     }
     
-    pub(super) fn push_frame(&mut self, symbol: Option<&DebugSymbol>) {
-        self.frames.push(CallFrame::new(symbol))
+    pub(super) fn push_frame(&mut self, symbol: Option<&DebugSymbol>, return_type: Option<TypeExpr>) {
+        self.frames.push(CallFrame::new(symbol, return_type))
+    }
+    
+    // the declared return type of the current function, if any
+    pub(super) fn return_type(&self) -> Option<&TypeExpr> {
+        self.frames.last().and_then(CallFrame::return_type)
     }
     
     pub(super) fn pop_frame(&mut self) -> CallFrame {
@@ -453,6 +494,12 @@ impl ScopeTracker {
         scope
     }
     
+    /// Hide all scopes at or below the given depth from name resolution. 
+    /// Their locals still occupy their slots, so new locals will not overwrite them.
+    pub(super) fn set_scopes_hidden(&mut self, min_depth: usize, hidden: bool) {
+        self.local_scopes_mut().set_hidden(min_depth, hidden)
+    }
+    
     // local variables
     
     pub(super) fn insert_local(&mut self, mode: Access, name: LocalName) -> CompileResult<InsertLocal> {
@@ -145,6 +145,18 @@ impl<'c, 's> Disassembler<'c, 's> {
                     self.write_const(&mut line, self.program.get_const(cid))?;
                 },
                 
+                OpCode::StoreGlobal | OpCode::LoadGlobal => {
+                    let cid = ConstID::from(instr[1]);
+                    write!(line, "{:16} {: >4}    ", opcode, cid)?;
+                    self.write_const(&mut line, self.program.get_const(cid))?;
+                },
+                
+                OpCode::StoreGlobal16 | OpCode::LoadGlobal16 => {
+                    let cid =  ConstID::from_le_bytes(instr[1..=2].try_into().unwrap());
+                    write!(line, "{:16} {: >4}    ", opcode, cid)?;
+                    self.write_const(&mut line, self.program.get_const(cid))?;
+                },
+                
                 OpCode::LoadFunction => {
                     let fun_id = FunctionID::from(instr[1]);
                     write!(line, "{:16} {: >4}    ", opcode, fun_id)?;
@@ -184,11 +196,16 @@ impl<'c, 's> Disassembler<'c, 's> {
                     write!(line, "{:16} {: >4}", opcode, index)?;
                 }
                 
-                OpCode::Tuple => {
+                OpCode::List | OpCode::Tuple | OpCode::CallKeywords | OpCode::MatchSeq | OpCode::Concat => {
                     let len = instr[1];
                     write!(line, "{:16} {: >4}", opcode, len)?;
                 }
                 
+                OpCode::MatchSeqPack => {
+                    let (pre_len, post_len) = (instr[1], instr[2]);
+                    write!(line, "{:16} {: >4} {: >4}", opcode, pre_len, post_len)?;
+                }
+                
                 OpCode::UInt8 => {
                     let value = Constant::Integer(instr[1].into());
                     write!(line, "{:16}         ", opcode)?;
@@ -211,7 +228,8 @@ impl<'c, 's> Disassembler<'c, 's> {
                 OpCode::JumpIfFalse    |
                 OpCode::JumpIfTrue     |
                 OpCode::PopJumpIfFalse |
-                OpCode::PopJumpIfTrue  => {
+                OpCode::PopJumpIfTrue  |
+                OpCode::TryBegin       => {
                     let jmp = i16::from_le_bytes(instr[1..=2].try_into().unwrap());
                     let dest = i128::from(jmp) + i128::try_from(offset + opcode.instr_len()).expect("offset too large");
                     let relative = i64::from(jmp) + i64::try_from(opcode.instr_len()).unwrap();
@@ -222,13 +240,88 @@ impl<'c, 's> Disassembler<'c, 's> {
                 OpCode::LongJumpIfFalse    |
                 OpCode::LongJumpIfTrue     |
                 OpCode::PopLongJumpIfFalse |
-                OpCode::PopLongJumpIfTrue  => {
+                OpCode::PopLongJumpIfTrue  |
+                OpCode::LongTryBegin       => {
                     let jmp = i32::from_le_bytes(instr[1..=4].try_into().unwrap());
                     let dest = i128::from(jmp) + i128::try_from(offset + opcode.instr_len()).expect("offset too large");
                     let relative = i64::from(jmp) + i64::try_from(opcode.instr_len()).unwrap();
                     write!(line, "{:16} {: >4} -> {:04X}", opcode, relative, dest)?;
                 }
                 
+                OpCode::BinaryRR => {
+                    let op = Self::binary_op_name(instr[1]);
+                    let (lhs, rhs) = (instr[2], instr[3]);
+                    write!(line, "{:16} {: >4} {: >4}    {}", opcode, lhs, rhs, op)?;
+                }
+                
+                OpCode::BinaryRI => {
+                    let op = Self::binary_op_name(instr[1]);
+                    let (lhs, rhs) = (instr[2], i8::from_le_bytes([instr[3]]));
+                    write!(line, "{:16} {: >4} {: >4}    {}", opcode, lhs, rhs, op)?;
+                }
+                
+                OpCode::BinarySR => {
+                    let op = Self::binary_op_name(instr[1]);
+                    write!(line, "{:16} {: >4}         {}", opcode, instr[2], op)?;
+                }
+                
+                OpCode::BinarySI => {
+                    let op = Self::binary_op_name(instr[1]);
+                    let rhs = i8::from_le_bytes([instr[2]]);
+                    write!(line, "{:16} {: >4}         {}", opcode, rhs, op)?;
+                }
+                
+                OpCode::BinaryRC => {
+                    let op = Self::binary_op_name(instr[1]);
+                    let cid = ConstID::from(instr[3]);
+                    write!(line, "{:16} {: >4} {: >4}    {} ", opcode, instr[2], cid, op)?;
+                    self.write_const(&mut line, self.program.get_const(cid))?;
+                }
+                
+                OpCode::BinarySC => {
+                    let op = Self::binary_op_name(instr[1]);
+                    let cid = ConstID::from(instr[2]);
+                    write!(line, "{:16} {: >4}         {} ", opcode, cid, op)?;
+                    self.write_const(&mut line, self.program.get_const(cid))?;
+                }
+                
+                OpCode::BinaryRRStore => {
+                    let op = Self::binary_op_name(instr[1]);
+                    let (lhs, rhs, dst) = (instr[2], instr[3], instr[4]);
+                    write!(line, "{:16} {: >4} {: >4}    {} -> {}", opcode, lhs, rhs, op, dst)?;
+                }
+                
+                OpCode::BinaryRIStore => {
+                    let op = Self::binary_op_name(instr[1]);
+                    let (lhs, rhs, dst) = (instr[2], i8::from_le_bytes([instr[3]]), instr[4]);
+                    write!(line, "{:16} {: >4} {: >4}    {} -> {}", opcode, lhs, rhs, op, dst)?;
+                }
+                
+                OpCode::BinaryRCStore => {
+                    let op = Self::binary_op_name(instr[1]);
+                    let cid = ConstID::from(instr[3]);
+                    write!(line, "{:16} {: >4} {: >4}    {} -> {} ", opcode, instr[2], cid, op, instr[4])?;
+                    self.write_const(&mut line, self.program.get_const(cid))?;
+                }
+                
+                OpCode::BinarySRStore | OpCode::BinaryRSStore => {
+                    let op = Self::binary_op_name(instr[1]);
+                    write!(line, "{:16} {: >4}         {} -> {}", opcode, instr[2], op, instr[3])?;
+                }
+                
+                OpCode::BinarySIStore => {
+                    let op = Self::binary_op_name(instr[1]);
+                    let rhs = i8::from_le_bytes([instr[2]]);
+                    write!(line, "{:16} {: >4}         {} -> {}", opcode, rhs, op, instr[3])?;
+                }
+                
+                OpCode::BinarySCStore => {
+                    let op = Self::binary_op_name(instr[1]);
+                    let cid = ConstID::from(instr[2]);
+                    write!(line, "{:16} {: >4}         {} -> {} ", opcode, cid, op, instr[3])?;
+                    self.write_const(&mut line, self.program.get_const(cid))?;
+                }
+                
                 opcode => write!(line, "{:16}", opcode)?,
             },
             
@@ -250,6 +343,10 @@ impl<'c, 's> Disassembler<'c, 's> {
         Ok(offset + opcode.map_or(1, |op| op.instr_len()))
     }
     
+    fn binary_op_name(op: u8) -> String {
+        OpCode::from_byte(op).map_or_else(|| format!("{:#x}", op), |op| op.to_string())
+    }
+    
     fn write_unresolved_symbol(&self, fmt: &mut impl fmt::Write, symbol: Option<&DebugSymbol>) -> fmt::Result {
         match symbol {
             Some(symbol) => write!(fmt, "| ${}:{}", symbol.start(), symbol.end()),
@@ -5,7 +5,7 @@ use crate::runtime::module::{Module, Chunk};
 
 
 /// Traceback information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub enum TraceSite {
     Chunk {
         offset: usize,
@@ -13,12 +13,21 @@ pub enum TraceSite {
         chunk_id: Chunk,
     },
     Native,  // TODO reference native function?
+    
+    /// A call site whose frame has been replaced by tail calls. The original site is kept in a `Gc` 
+    /// so that ordinary sites can be pushed and popped without any drop glue.
+    TailCall {
+        site: Gc<TraceSite>,
+        elided: usize,
+    },
 }
 
 unsafe impl GcTrace for TraceSite {
     fn trace(&self) {
-        if let Self::Chunk { module, .. } = self {
-            module.mark_trace();
+        match self {
+            Self::Chunk { module, .. } => module.mark_trace(),
+            Self::TailCall { site, .. } => site.mark_trace(),
+            Self::Native => { },
         }
     }
 }
@@ -41,6 +50,10 @@ impl fmt::Display for FrameSummary<'_> {
             TraceSite::Native => {
                 write!(fmt, "<native code>")
             },
+            
+            TraceSite::TailCall { site, elided } => {
+                write!(fmt, "{}\n   ... {} frame(s) elided by tail calls", FrameSummary { trace: site }, elided)
+            },
         }
     }
 }
@@ -0,0 +1,294 @@
+//! High-level API for embedding Sphinx in a host application.
+//!
+//! The `Engine` owns a set of globals that persist between each piece of source that it runs,
+//! so that functions defined by a script can later be called from Rust.
+//!
+//! The engine also acts as a simple scheduler for script coroutines. Tasks started with `spawn()` are
+//! each resumed once per call to `run_tasks()`, which a host would typically call once per frame.
+//!
+//! Async hosts can register native functions that produce a future. Scripts call them like any other
+//! function, and the VM is paused until the future is ready when the script is run by an async method.
+
+use core::fmt;
+use core::ops::Deref;
+use core::cell::RefCell;
+use core::future::Future;
+use std::rc::{Rc, Weak};
+use std::error::Error;
+use std::path::PathBuf;
+use crate::source::ModuleSource;
+use crate::codegen::{Program, ProgramData};
+use crate::language::Access;
+use crate::builtins;
+use crate::runtime::{Variant, Gc, VirtualMachine, Module, FromVariant, IntoVariant, RuntimeError, ExecResult};
+use crate::runtime::module::NamespaceEnv;
+use crate::runtime::vm::Coroutine;
+use crate::runtime::function::{Signature, Parameter, NativeFunction, NativeFuture, NativeClosure};
+use crate::runtime::strings::StringSymbol;
+use crate::{BuildErrors, format_build_errors};
+
+
+#[derive(Debug)]
+pub enum EngineError {
+    Build(BuildErrors),
+    Runtime(Box<RuntimeError>),
+}
+
+impl From<BuildErrors> for EngineError {
+    fn from(errors: BuildErrors) -> Self { Self::Build(errors) }
+}
+
+impl From<Box<RuntimeError>> for EngineError {
+    fn from(error: Box<RuntimeError>) -> Self { Self::Runtime(error) }
+}
+
+impl Error for EngineError { }
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Build(errors) => fmt.write_str(&format_build_errors(errors)),
+            Self::Runtime(error) => write!(fmt, "{}{}", error.traceback(), error),
+        }
+    }
+}
+
+pub type EngineResult<T> = Result<T, EngineError>;
+
+
+/// A value produced by the engine. The engine keeps the value alive for as long as the handle, or any clone of it, exists.
+#[derive(Debug, Clone)]
+pub struct Handle {
+    value: Rc<Variant>,
+}
+
+impl Deref for Handle {
+    type Target = Variant;
+    fn deref(&self) -> &Variant { &self.value }
+}
+
+impl IntoVariant for Handle {
+    fn into_variant(self) -> Variant { *self.value }
+}
+
+impl IntoVariant for &Handle {
+    fn into_variant(self) -> Variant { *self.value }
+}
+
+
+/// Compiles and runs Sphinx source, and exchanges values with the host application.
+///
+/// Values are managed by the garbage collector, which only runs while the engine is executing code.
+/// Script values are handed to the host as a `Handle`, which keeps the value alive until it is dropped.
+/// Use `FromVariant` to convert values into Rust types.
+///
+/// Each run starts with a fresh module cache, so imported modules are executed again.
+pub struct Engine {
+    prelude: Gc<NamespaceEnv>,  // builtins and registered native functions, shared with imported modules
+    globals: Gc<NamespaceEnv>,
+    search_paths: Vec<PathBuf>,
+    tasks: Vec<Gc<Coroutine>>,  // coroutines driven by run_tasks(), in the order they were spawned
+    handles: RefCell<Vec<Weak<Variant>>>,  // values held by the host, rooted by every VM the engine creates
+}
+
+impl Default for Engine {
+    fn default() -> Self { Self::new() }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        let prelude = builtins::create_prelude();
+        let globals = NamespaceEnv::new();
+        globals.borrow_mut().extend(&prelude.borrow());
+        
+        Self {
+            prelude,
+            globals,
+            search_paths: Vec::new(),
+            tasks: Vec::new(),
+            handles: RefCell::new(Vec::new()),
+        }
+    }
+    
+    /// Directories that are searched when importing modules
+    pub fn add_search_path(&mut self, path: impl Into<PathBuf>) {
+        self.search_paths.push(path.into())
+    }
+    
+    /// Compile and run a string of source code. Top-level definitions are kept in the engine's globals.
+    pub fn run_source(&mut self, source: &str) -> EngineResult<()> {
+        self.run_module(ModuleSource::String(source.to_string()))
+    }
+    
+    pub fn run_file(&mut self, path: impl Into<PathBuf>) -> EngineResult<()> {
+        self.run_module(ModuleSource::File(path.into()))
+    }
+    
+    fn run_module(&mut self, source: ModuleSource) -> EngineResult<()> {
+        let build = crate::build_module(&source)?;
+        let program = Program::load(build.program);
+        
+        let module = Module::with_env(Some(source), program.data, self.globals);
+        let vm = self.setup_vm(VirtualMachine::new(module, &program.main));
+        vm.run()?;
+        Ok(())
+    }
+    
+    /// Like `run_source()`, but async native functions can be called
+    pub async fn run_source_async(&mut self, source: &str) -> EngineResult<()> {
+        let source = ModuleSource::String(source.to_string());
+        let build = crate::build_module(&source)?;
+        let program = Program::load(build.program);
+        
+        let module = Module::with_env(Some(source), program.data, self.globals);
+        let vm = self.setup_vm(VirtualMachine::new(module, &program.main));
+        vm.run_async().await?;
+        Ok(())
+    }
+    
+    /// Call a value with the given arguments
+    pub fn call(&mut self, callee: &Variant, args: &[Variant]) -> EngineResult<Handle> {
+        // an empty module used as the calling frame
+        let module = Module::with_env(None, ProgramData::default(), self.globals);
+        
+        let vm = self.setup_vm(VirtualMachine::new_call(module, *callee, args));
+        let result = vm.run()?;
+        Ok(self.handle(result))
+    }
+    
+    /// Call a function stored in a global variable
+    pub fn call_global(&mut self, name: &str, args: &[Variant]) -> EngineResult<Handle> {
+        let callee = self.lookup(name)?;
+        self.call(&callee, args)
+    }
+    
+    /// Like `call()`, but async native functions can be called
+    pub async fn call_async(&mut self, callee: &Variant, args: &[Variant]) -> EngineResult<Handle> {
+        let module = Module::with_env(None, ProgramData::default(), self.globals);
+        
+        let vm = self.setup_vm(VirtualMachine::new_call(module, *callee, args));
+        let result = vm.run_async().await?;
+        Ok(self.handle(result))
+    }
+    
+    /// Add a task that will be resumed by `run_tasks()`. Coroutines are resumed as they are, 
+    /// any other callable value is called inside of a new coroutine. Produces the task's coroutine.
+    pub fn spawn(&mut self, callee: &Variant) -> Handle {
+        let coroutine = match callee {
+            Variant::Coroutine(coroutine) => *coroutine,
+            callee => Gc::new(Coroutine::new(*callee)),
+        };
+        self.tasks.push(coroutine);
+        self.handle(Variant::Coroutine(coroutine))
+    }
+    
+    /// The number of tasks that have not finished yet
+    pub fn task_count(&self) -> usize { self.tasks.len() }
+    
+    /// Resume every task once, in the order they were spawned. The arguments are passed to each task, 
+    /// either as the arguments of its function the first time it runs or as the result of `suspend()`.
+    /// Tasks that return or fail are removed, and the errors of the failed tasks are produced.
+    pub fn run_tasks(&mut self, args: &[Variant]) -> Vec<EngineError> {
+        // an empty module used as the calling frame
+        let module = Module::with_env(None, ProgramData::default(), self.globals);
+        let mut vm = self.setup_vm(VirtualMachine::new(module, &[]));
+        
+        let tasks = core::mem::take(&mut self.tasks);
+        let mut errors = Vec::new();
+        for coroutine in tasks {
+            match vm.resume_coroutine(coroutine, args) {
+                Err(error) => errors.push(EngineError::from(error)),
+                Ok(..) if coroutine.is_finished() => { },
+                Ok(..) => self.tasks.push(coroutine),
+            }
+        }
+        errors
+    }
+    
+    fn setup_vm<'c>(&self, mut vm: VirtualMachine<'c>) -> VirtualMachine<'c> {
+        let loader = vm.loader_mut();
+        loader.set_prelude(self.prelude);
+        for path in self.search_paths.iter() {
+            loader.add_search_path(path);
+        }
+        
+        // forget the values whose handles were all dropped
+        let mut handles = self.handles.borrow_mut();
+        handles.retain(|handle| handle.strong_count() > 0);
+        for value in handles.iter().filter_map(Weak::upgrade) {
+            vm.add_root(*value);
+        }
+        // tasks are only reachable from the engine between calls to run_tasks()
+        for coroutine in self.tasks.iter() {
+            vm.add_root(Variant::Coroutine(*coroutine));
+        }
+        vm
+    }
+    
+    fn handle(&self, value: Variant) -> Handle {
+        let value = Rc::new(value);
+        self.handles.borrow_mut().push(Rc::downgrade(&value));
+        Handle { value }
+    }
+    
+    fn lookup(&self, name: &str) -> ExecResult<Variant> {
+        let name = StringSymbol::from(name);
+        self.globals.borrow().lookup(&name).copied()
+    }
+    
+    pub fn get_global(&self, name: &str) -> Option<Handle> {
+        self.lookup(name).ok().map(|value| self.handle(value))
+    }
+    
+    /// Get the value of a global variable, converted into a Rust type
+    pub fn get<T>(&self, name: &str) -> EngineResult<T> where T: FromVariant {
+        let value = self.lookup(name)?;
+        Ok(T::from_variant(&value)?)
+    }
+    
+    /// Create or overwrite a mutable global variable
+    pub fn set_global(&mut self, name: &str, value: impl IntoVariant) {
+        let name = StringSymbol::from(name);
+        self.globals.borrow_mut().create(name, Access::ReadWrite, value.into_variant());
+    }
+    
+    /// Register a Rust closure as a native function that is available to all modules run by this engine.
+    /// If the last parameter name ends with "..." the function is variadic.
+    pub fn register_fn<F>(&mut self, name: &str, params: &[&str], func: F)
+    where F: Fn(&[Variant]) -> ExecResult<Variant> + 'static {
+        self.register_native(name, params, Box::new(move |_, args| func(args)))
+    }
+    
+    /// Register a Rust closure that produces a future as an async native function. Calling it pauses the script 
+    /// until the future is ready, which is only possible when the script is run by `run_source_async()` 
+    /// or `call_async()`. Async functions can't be called from inside of a generator or coroutine.
+    pub fn register_async_fn<F, R>(&mut self, name: &str, params: &[&str], func: F)
+    where F: Fn(&[Variant]) -> R + 'static, R: Future<Output=ExecResult<Variant>> + 'static {
+        self.register_native(name, params, Box::new(move |vm, args| {
+            vm.await_future(NativeFuture::new(func(args)))?;
+            Ok(Variant::Nil)
+        }))
+    }
+    
+    fn register_native(&mut self, name: &str, params: &[&str], func: NativeClosure) {
+        let mut required = params.iter()
+            .map(|param| Parameter::new(*param, Access::ReadWrite))
+            .collect::<Vec<Parameter>>();
+        
+        let variadic = match params.last().and_then(|param| param.strip_suffix("...")) {
+            Some(param) => {
+                required.pop();
+                Some(Parameter::new(param, Access::ReadWrite))
+            }
+            None => None,
+        };
+        
+        let signature = Signature::new(Some(name), required, Vec::new(), variadic);
+        let native = NativeFunction::with_closure(signature, self.prelude, func);
+        let native = Variant::from(native);
+        
+        let name = StringSymbol::from(name);
+        self.prelude.borrow_mut().create(name, Access::ReadOnly, native);
+        self.globals.borrow_mut().create(name, Access::ReadOnly, native);
+    }
+}
@@ -63,6 +63,7 @@ pub fn all_escape_sequences() -> impl Iterator<Item=&'static dyn EscapeSequence>
                 Box::new(CharMapEscape::new('n', "\n")),
                 Box::new(CharMapEscape::new('r', "\r")),
                 Box::new(HexByteEscape::new()),
+                Box::new(UnicodeEscape::new()),
             ];
             
             escapes
@@ -89,6 +90,7 @@ pub fn create_default_lexer_rules() -> LexerBuilder {
     .add_rule(SingleCharRule::new(Token::Decorator,       '@'))
     
     .add_rule(MultiCharRule::new(Token::Ellipsis,         "..."))
+    .add_rule(MultiCharRule::new(Token::Arrow,            "->"))
     
     // Assignment and access operators
     .add_rule(SingleCharRule::new(Token::OpAssign,        '='))
@@ -125,6 +127,7 @@ pub fn create_default_lexer_rules() -> LexerBuilder {
     .add_rule(MultiCharRule::new(Token::OpXorAssign,       "^="))
     .add_rule(MultiCharRule::new(Token::OpLShiftAssign,   "<<="))
     .add_rule(MultiCharRule::new(Token::OpRShiftAssign,   ">>="))
+    .add_rule(MultiCharRule::new(Token::OpExpAssign,      "**="))
     
     .add_rule(MultiCharRule::new(Token::OpLShift,         "<<"))
     .add_rule(MultiCharRule::new(Token::OpRShift,         ">>"))
@@ -146,6 +149,8 @@ pub fn create_default_lexer_rules() -> LexerBuilder {
     .add_rule(KeywordRule::new(Token::Then,               "then"))
     .add_rule(KeywordRule::new(Token::Elif,               "elif"))
     .add_rule(KeywordRule::new(Token::Else,               "else"))
+    .add_rule(KeywordRule::new(Token::Match,              "match"))
+    .add_rule(KeywordRule::new(Token::Case,               "case"))
     .add_rule(KeywordRule::new(Token::Loop,               "loop"))
     .add_rule(KeywordRule::new(Token::While,              "while"))
     .add_rule(KeywordRule::new(Token::For,                "for"))
@@ -154,10 +159,17 @@ pub fn create_default_lexer_rules() -> LexerBuilder {
     .add_rule(KeywordRule::new(Token::Continue,           "continue"))
     .add_rule(KeywordRule::new(Token::Break,              "break"))
     .add_rule(KeywordRule::new(Token::Return,             "return"))
+    .add_rule(KeywordRule::new(Token::Yield,              "yield"))
     .add_rule(KeywordRule::new(Token::Fun,                "fun"))
     .add_rule(KeywordRule::new(Token::Class,              "class"))
-    // .add_rule(KeywordRule::new(Token::Self_,              "self"))
-    // .add_rule(KeywordRule::new(Token::Super,              "super"))
+    .add_rule(KeywordRule::new(Token::Self_,              "self"))
+    .add_rule(KeywordRule::new(Token::Super,              "super"))
+    .add_rule(KeywordRule::new(Token::Import,             "import"))
+    .add_rule(KeywordRule::new(Token::As,                 "as"))
+    .add_rule(KeywordRule::new(Token::Try,                "try"))
+    .add_rule(KeywordRule::new(Token::Catch,              "catch"))
+    .add_rule(KeywordRule::new(Token::Finally,            "finally"))
+    .add_rule(KeywordRule::new(Token::Throw,              "throw"))
     .add_rule(KeywordRule::new(Token::Assert,             "assert"))
     .add_rule(KeywordRule::new(Token::End,                "end"))
     
@@ -169,6 +181,7 @@ pub fn create_default_lexer_rules() -> LexerBuilder {
     .add_rule(PrefixedIntegerLiteralRule::new("0b", 2))
     .add_rule(FloatLiteralRule::new())
     .add_rule(StringLiteralRule::new(all_escape_sequences()))
+    .add_rule(InterpolatedStringRule::new(all_escape_sequences()))
     .add_rule(LabelRule::new("::"))
     
 }
@@ -267,12 +267,52 @@ impl<S> Lexer<S> where S: Iterator<Item=io::Result<char>> {
             }
         }
         
-        let result = self.scan_token();
+        let result = self.scan_token()
+            .and_then(|mut token| {
+                self.lex_interpolations(&mut token)?;
+                Ok(token)
+            });
         self.newline = matches!(self.last, Some('\n'));
         
         result
     }
     
+    // the expressions inside an interpolated string are lexed using a copy of this lexer's rules
+    fn lex_interpolations(&self, token: &mut TokenMeta) -> Result<(), LexerError> {
+        let segments = match token.token {
+            Token::InterpolatedString(ref mut segments) => segments,
+            _ => return Ok(()),
+        };
+        
+        for segment in segments.iter_mut() {
+            let interp = match segment {
+                StringSegment::Interpolate(interp) => interp,
+                StringSegment::Literal(..) => continue,
+            };
+            
+            let source = interp.source.chars().collect::<Vec<char>>();
+            let mut lexer = Lexer::new(
+                source.into_iter().map(Ok), 
+                self.options.clone(), 
+                self.rules.iter().cloned(),
+            );
+            
+            // make sure that debug symbols refer to the enclosing source text
+            lexer.current = token.symbol.start().saturating_add(interp.offset);
+            
+            loop {
+                let next = lexer.next_token()?;
+                let eof = matches!(next.token, Token::EOF);
+                interp.tokens.push(next);
+                if eof {
+                    break;
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
     fn scan_token(&mut self) -> Result<TokenMeta, LexerError> {
         
         //starting a new token
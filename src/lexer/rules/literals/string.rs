@@ -1,10 +1,15 @@
-use crate::lexer::Token;
+use crate::debug::TokenIndex;
+use crate::lexer::{Token, StringSegment, Interpolation};
 use crate::lexer::rules::{MatchResult, LexerRule, TokenError};
 
-// supports escape sequences that consist of a single-character tag (e.g. \t) and an optional fixed-length argument (e.g. \u0FFE, \xFE)
+// supports escape sequences that consist of a single-character tag (e.g. \t) and an optional argument.
+// the argument is either fixed-length (e.g. \xFE) or enclosed in delimiters (e.g. \u{1F600})
 pub trait EscapeSequence: Send + Sync {
     fn tag(&self) -> char;
-    fn arglen(&self) -> u8;
+    fn arglen(&self) -> u8;  // the maximum length if the argument is delimited
+    
+    // the delimiters that enclose a variable-length argument, if any
+    fn delimiters(&self) -> Option<(char, char)> { None }
     
     // produce a string that will replace the escape sequence in the source literal
     fn transform(&self, arg: &str) -> Result<String, StringEscapeError>;
@@ -30,7 +35,8 @@ impl EscapeSequence for CharMapEscape {
     }
 }
 
-// \x00 \xFF
+// \x00 \x7F
+// strings are always valid UTF-8, so only bytes that encode a character by themselves are allowed
 pub struct HexByteEscape {}
 
 impl Default for HexByteEscape {
@@ -48,19 +54,47 @@ impl EscapeSequence for HexByteEscape {
     fn transform(&self, arg: &str) -> Result<String, StringEscapeError> { 
         debug_assert!(arg.len() == 2);
         
-        let create_error = || StringEscapeError::new(StringEscapeErrorKind::InvalidEscapeArg, self.tag(), Some(arg.to_string()));
-        
         let value = u8::from_str_radix(arg, 16)
-            .map_err(|_err| create_error())?;
+            .map_err(|_err| StringEscapeError::new(StringEscapeErrorKind::InvalidEscapeArg, self.tag(), Some(arg.to_string())))?;
         
-        match char::from_u32(value.into()) {
-            Some(ch) => Ok(ch.to_string()),
-            None => Err(create_error()),
+        if !value.is_ascii() {
+            return Err(StringEscapeError::new(StringEscapeErrorKind::OutOfRange, self.tag(), Some(arg.to_string())));
         }
+        
+        Ok(char::from(value).to_string())
     }
 }
 
-// TODO unicode escapes
+// \u{0} \u{10FFFF}
+pub struct UnicodeEscape {}
+
+impl Default for UnicodeEscape {
+    fn default() -> Self { Self::new() }
+}
+
+impl UnicodeEscape {
+    pub fn new() -> Self { UnicodeEscape { } }
+}
+
+const UNICODE_ESCAPE_TAG: char = 'u';
+impl EscapeSequence for UnicodeEscape {
+    fn tag(&self) -> char { UNICODE_ESCAPE_TAG }
+    fn arglen(&self) -> u8 { 6 }
+    fn delimiters(&self) -> Option<(char, char)> { Some(('{', '}')) }
+    
+    fn transform(&self, arg: &str) -> Result<String, StringEscapeError> {
+        let create_error = |kind| StringEscapeError::new(kind, self.tag(), Some(format!("{{{}}}", arg)));
+        
+        let value = u32::from_str_radix(arg, 16)
+            .map_err(|_err| create_error(StringEscapeErrorKind::InvalidEscapeArg))?;
+        
+        // surrogates and values above 0x10FFFF are not code points
+        match char::from_u32(value) {
+            Some(ch) => Ok(ch.to_string()),
+            None => Err(create_error(StringEscapeErrorKind::OutOfRange)),
+        }
+    }
+}
 
 
 const ESCAPE_CHAR: char = '\\';
@@ -72,6 +106,8 @@ const RAW_PREFIX: char = 'r';
 struct ActiveEscape {
     escape: &'static dyn EscapeSequence,
     argbuf: String,
+    opened: bool,  // read the opening delimiter
+    closed: bool,  // read the closing delimiter
 }
 
 impl core::ops::Deref for ActiveEscape {
@@ -79,18 +115,156 @@ impl core::ops::Deref for ActiveEscape {
     fn deref(&self) -> &'static Self::Target { self.escape }
 }
 
+impl ActiveEscape {
+    fn new(escape: &'static dyn EscapeSequence) -> Self {
+        Self { escape, argbuf: String::new(), opened: false, closed: false }
+    }
+    
+    fn is_complete(&self) -> bool {
+        match self.delimiters() {
+            Some(..) => self.closed,
+            None => self.argbuf.len() >= self.arglen().into(),
+        }
+    }
+    
+    // returns false if the next char cannot be part of the escape sequence
+    fn push(&mut self, next: char) -> bool {
+        match self.delimiters() {
+            Some((open, _)) if !self.opened => {
+                self.opened = next == open;
+                self.opened
+            }
+            Some((_, close)) if next == close => {
+                self.closed = true;
+                true
+            }
+            _ if next.is_ascii_alphanumeric() && self.argbuf.len() < self.arglen().into() => {
+                self.argbuf.push(next);
+                true
+            }
+            _ => false,
+        }
+    }
+    
+    fn render_arg(&self) -> String {
+        match self.delimiters() {
+            Some((open, _)) if self.opened => format!("{}{}", open, self.argbuf),
+            _ => self.argbuf.clone(),
+        }
+    }
+}
+
+// the escape handling shared by the string literal rules
+#[derive(Clone)]
+struct EscapeProcessor {
+    escapes: Vec<&'static dyn EscapeSequence>,
+    active: Option<ActiveEscape>, // the currently active escape sequence, if any
+    error: Option<StringEscapeError>, // hold the first error to occur when processing an escape
+}
+
+impl EscapeProcessor {
+    fn new(escapes: impl Iterator<Item=&'static dyn EscapeSequence>) -> Self {
+        Self {
+            escapes: escapes.collect(),
+            active: None,
+            error: None,
+        }
+    }
+    
+    fn reset(&mut self) {
+        self.active = None;
+        self.error = None;
+    }
+    
+    fn is_active(&self) -> bool { self.active.is_some() }
+    
+    fn lookup_escape_for_tag(&self, tag: char) -> Option<&'static dyn EscapeSequence> {
+        self.escapes.iter()
+            .find(|escape| tag == escape.tag())
+            .copied()
+    }
+    
+    // returns true if the next char was consumed by an escape sequence, otherwise it should be processed as normal.
+    // any escaped output is written to the output buffer.
+    // note: if there was an error, skip all escape handling and keep reading as if a raw string
+    fn process(&mut self, prev: Option<char>, next: char, output: &mut String) -> bool {
+        if self.error.is_some() {
+            return false;
+        }
+        
+        // if we are already in an escape sequence
+        if let Some(ref mut active) = self.active {
+            if !active.is_complete() {
+                if active.push(next) {
+                    return true;
+                }
+                
+                // the escape sequence was cut short, so process the next char as normal
+                let active = self.active.take().unwrap();
+                self.error = Some(StringEscapeError::new(
+                    StringEscapeErrorKind::InvalidEscapeArg, active.tag(), Some(active.render_arg())
+                ));
+                return false;
+            }
+            
+            // if we get here, the escape is complete without adding the next char
+            // process the escape and then do not return so that the next char is processed as normal
+            self.finish(output);
+            return false;
+        }
+        
+        // check for escape sequence start
+        if let Some(ESCAPE_CHAR) = prev {
+            if let Some(escape) = self.lookup_escape_for_tag(next) {
+                self.active = Some(ActiveEscape::new(escape));
+            } else {
+                self.error = Some(StringEscapeError::new(
+                    StringEscapeErrorKind::InvalidEscapeTag, next, None
+                ));
+            }
+            return true;
+        }
+        
+        false
+    }
+    
+    // process the active escape sequence, if any, at the end of the input
+    fn finish(&mut self, output: &mut String) {
+        let active = match self.active.take() {
+            Some(active) => active,
+            None => return,
+        };
+        
+        if !active.is_complete() {
+            self.error.get_or_insert(StringEscapeError::new(
+                StringEscapeErrorKind::InvalidEscapeArg, active.tag(), Some(active.render_arg())
+            ));
+            return;
+        }
+        
+        match active.transform(active.argbuf.as_str()) {
+            Ok(escaped) => output.push_str(escaped.as_str()),
+            Err(err) => { self.error.get_or_insert(err); },
+        }
+    }
+}
+
+// String literals, including raw strings (r"...") and triple-quoted strings ("""...""")
+
 #[derive(Clone)]
 pub struct StringLiteralRule {
     raw_buf: String,
     escaped_buf: String,
+    prefix: bool,
     quote: Option<char>,
+    quote_count: u8,  // 2 after reading an empty string, which may turn out to be the start of a triple-quoted string
+    triple: bool,
+    end_quotes: u8,  // consecutive unescaped quotes at the end of a triple-quoted string
+    escaped: bool,  // the previous char in a triple-quoted string was an unescaped backslash
     closed: bool,
     
     raw: bool,
-    escape: Option<ActiveEscape>, // the currently active escape sequence, if any
-    error: Option<StringEscapeError>, // hold the first error to occur when processing an escape
-    
-    escapes: Vec<&'static dyn EscapeSequence>,
+    escapes: EscapeProcessor,
 }
 
 impl StringLiteralRule {
@@ -98,45 +272,118 @@ impl StringLiteralRule {
         StringLiteralRule {
             raw_buf: String::new(),
             escaped_buf: String::new(),
+            prefix: false,
             quote: None,
+            quote_count: 0,
+            triple: false,
+            end_quotes: 0,
+            escaped: false,
             closed: false,
             raw: false,
             
-            escape: None,
-            error: None,
-            
-            escapes: escapes.collect(),
+            escapes: EscapeProcessor::new(escapes),
         }
     }
     
-    fn lookup_escape_for_tag(&self, tag: char) -> Option<&'static dyn EscapeSequence> {
-        self.escapes.iter()
-            .find(|escape| tag == escape.tag())
-            .copied()
+    // triple-quoted strings are collected without processing escapes, since the indentation
+    // must be stripped first and newlines produced by escapes shouldn't count as the start of a line
+    fn match_triple_quoted(&mut self, next: char) -> MatchResult {
+        let quote = self.quote.unwrap();
+        
+        self.raw_buf.push(next);
+        
+        if self.escaped {
+            self.escaped = false;
+            self.end_quotes = 0;
+        } else if next == ESCAPE_CHAR && !self.raw {
+            self.escaped = true;
+            self.end_quotes = 0;
+        } else if next == quote {
+            self.end_quotes += 1;
+            if self.end_quotes == 3 {
+                self.raw_buf.truncate(self.raw_buf.len() - 3);
+                self.closed = true;
+                return MatchResult::CompleteMatch;
+            }
+        } else {
+            self.end_quotes = 0;
+        }
+        
+        MatchResult::IncompleteMatch
     }
     
-    // can't take a reference to the escape or argument by parameter since it would cause borrow issues
-    // so we take an index to get the escape
-    // and we just have to get the argument directly from self
-    fn process_escape(&mut self, escape: &'static dyn EscapeSequence, arg: &str) {
-        match escape.transform(arg) {
-            Ok(output) => self.escaped_buf.push_str(output.as_str()),
-            Err(err) => self.error = Some(err),
-        };
+    fn get_triple_quoted(&self) -> Result<String, StringEscapeError> {
+        let text = strip_indentation(self.raw_buf.as_str());
+        if self.raw {
+            return Ok(text);
+        }
+        
+        let mut escapes = self.escapes.clone();
+        escapes.reset();
+        
+        let mut output = String::new();
+        let mut prev = None;
+        for next in text.chars() {
+            if !escapes.process(prev, next, &mut output) && next != ESCAPE_CHAR {
+                output.push(next);
+            }
+            prev = Some(next);
+        }
+        escapes.finish(&mut output);
+        
+        match escapes.error {
+            Some(error) => Err(error),
+            None => Ok(output),
+        }
     }
 }
 
+// if the opening quotes end their line, remove that newline, a trailing line that only holds the 
+// closing quotes, and the indentation that is common to all lines that aren't blank
+fn strip_indentation(text: &str) -> String {
+    let text = match text.strip_prefix("\r\n").or_else(|| text.strip_prefix('\n')) {
+        Some(text) => text,
+        None => return text.to_string(),
+    };
+    
+    let mut lines = text.split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect::<Vec<&str>>();
+    
+    if lines.len() > 1 && lines.last().unwrap().trim().is_empty() {
+        lines.pop();
+    }
+    
+    let indent_len = |line: &&str| line.chars().take_while(|ch| matches!(ch, ' ' | '\t')).count();
+    let indent = lines.iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(indent_len)
+        .min()
+        .unwrap_or(0);
+    
+    let lines = lines.iter().map(|line| {
+        let strip = indent_len(line).min(indent);
+        line.char_indices().nth(strip).map_or("", |(idx, _)| &line[idx..])
+    });
+    
+    lines.collect::<Vec<&str>>().join("\n")
+}
+
 impl LexerRule for StringLiteralRule {
     
     fn reset(&mut self) {
         self.raw_buf.clear();
         self.escaped_buf.clear();
+        self.prefix = false;
         self.quote = None;
+        self.quote_count = 0;
+        self.triple = false;
+        self.end_quotes = 0;
+        self.escaped = false;
         self.closed = false;
         self.raw = false;
         
-        self.escape = None;
-        self.error = None;
+        self.escapes.reset();
     }
     
     fn current_state(&self) -> MatchResult {
@@ -144,13 +391,21 @@ impl LexerRule for StringLiteralRule {
             None if self.closed => MatchResult::NoMatch,  // did not find initial quote
             None => MatchResult::IncompleteMatch,  // initial state
             
-            Some(..) if self.closed && self.escape.is_none() => MatchResult::CompleteMatch,
+            Some(..) if self.closed && !self.escapes.is_active() => MatchResult::CompleteMatch,
             Some(..) => MatchResult::IncompleteMatch,
         }
     }
     
     fn try_match(&mut self, prev: Option<char>, next: char) -> MatchResult {
         if self.closed {
+            // an empty string followed by another quote starts a triple-quoted string
+            if self.quote_count == 2 && self.quote == Some(next) {
+                self.closed = false;
+                self.triple = true;
+                self.quote_count = 3;
+                return MatchResult::IncompleteMatch;
+            }
+            
             return MatchResult::NoMatch;  // dont accept any further input
         }
         
@@ -158,13 +413,15 @@ impl LexerRule for StringLiteralRule {
         if self.quote.is_none() {
             return match next {
                 
-                RAW_PREFIX => MatchResult::IncompleteMatch,
+                RAW_PREFIX if !self.prefix => {
+                    self.prefix = true;
+                    MatchResult::IncompleteMatch
+                },
                 
                 SINGLE_QUOTE | DOUBLE_QUOTE => {
                     self.quote = Some(next);
-                    if let Some(RAW_PREFIX) = prev {
-                        self.raw = true;
-                    }
+                    self.quote_count = 1;
+                    self.raw = self.prefix;
                     
                     MatchResult::IncompleteMatch
                 },
@@ -174,43 +431,20 @@ impl LexerRule for StringLiteralRule {
             };
         }
         
-        // note: if there was an error, skip all escape handling and keep reading as if a raw string
-        if !self.raw && self.error.is_none() {
-            
-            // if we are already in an escape sequence
-            if let Some(ref mut active) = self.escape {
-                if active.argbuf.len() < active.arglen().into() {
-                    active.argbuf.push(next);
-                    
-                    self.raw_buf.push(next);
-                    return MatchResult::IncompleteMatch;
-                }
-                
-                // if we get here, argbuf already has enough characters without adding the next one
-                // process the escape and then do not return so that the next char is processed as normal
-                
-                let active = self.escape.take().unwrap(); // take out of self.escape as we are done with it
-                self.process_escape(active.escape, active.argbuf.as_str());
-                
-            // check for escape sequence start
-            } else if let Some(ESCAPE_CHAR) = prev {
-                
-                if let Some(escape) = self.lookup_escape_for_tag(next) {
-                    self.escape = Some(ActiveEscape { escape, argbuf: String::new() });
-                } else {
-                    self.error = Some(StringEscapeError::new(
-                        StringEscapeErrorKind::InvalidEscapeTag, next, None
-                    ));
-                }
-                
-                self.raw_buf.push(next);
-                return MatchResult::IncompleteMatch;
-            }
-            
+        if self.triple {
+            return self.match_triple_quoted(next);
+        }
+        
+        if !self.raw && self.escapes.process(prev, next, &mut self.escaped_buf) {
+            self.raw_buf.push(next);
+            return MatchResult::IncompleteMatch;
         }
         
         // check for terminating quote
         if next == self.quote.unwrap() {
+            if self.raw_buf.is_empty() {
+                self.quote_count = 2;
+            }
             self.closed = true;
             return MatchResult::CompleteMatch;
         }
@@ -227,7 +461,14 @@ impl LexerRule for StringLiteralRule {
     fn get_token(&self) -> Result<Token, TokenError> {
         debug_assert!(self.current_state().is_complete_match());
         
-        if let Some(ref error) = self.error {
+        if self.triple {
+            return match self.get_triple_quoted() {
+                Ok(text) => Ok(Token::StringLiteral(text)),
+                Err(error) => Err(Box::new(error.with_raw(self.raw_buf.clone()))),
+            };
+        }
+        
+        if let Some(ref error) = self.escapes.error {
             Err(Box::new(error.clone().with_raw(self.raw_buf.clone())))
         } else if self.raw {
             Ok(Token::StringLiteral(self.raw_buf.clone()))
@@ -240,10 +481,284 @@ impl LexerRule for StringLiteralRule {
 }
 
 
+// Interpolated strings, e.g. $"hello {name}, {x + 1:08.3}"
+
+const INTERPOLATE_PREFIX: char = '$';
+const INTERPOLATE_OPEN: char = '{';
+const INTERPOLATE_CLOSE: char = '}';
+const FORMAT_SPEC_SEP: char = ':';
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum InterpolateState {
+    Start,
+    Prefix,      // read the prefix, expecting a quote
+    Literal,     // inside the literal text
+    OpenBrace,   // read a '{' in the literal text
+    CloseBrace,  // read a '}' in the literal text, which must be followed by another '}'
+    Expr,        // inside an interpolated expression
+    Spec,        // inside the format spec of an interpolated expression
+    Closed,
+    Invalid,
+}
+
+#[derive(Clone)]
+pub struct InterpolatedStringRule {
+    state: InterpolateState,
+    quote: char,
+    count: TokenIndex,  // number of chars read so far
+    
+    literal: String,
+    segments: Vec<StringSegment>,
+    
+    expr: String,
+    expr_offset: TokenIndex,
+    spec: Option<String>,
+    depth: usize,  // nesting level of brackets inside the expression
+    nested_quote: Option<char>,  // the quote of a string literal inside the expression
+    nested_escape: bool,
+    
+    escapes: EscapeProcessor,
+    error: Option<InterpolationError>,
+}
+
+impl InterpolatedStringRule {
+    pub fn new(escapes: impl Iterator<Item=&'static dyn EscapeSequence>) -> Self {
+        Self {
+            state: InterpolateState::Start,
+            quote: DOUBLE_QUOTE,
+            count: 0,
+            
+            literal: String::new(),
+            segments: Vec::new(),
+            
+            expr: String::new(),
+            expr_offset: 0,
+            spec: None,
+            depth: 0,
+            nested_quote: None,
+            nested_escape: false,
+            
+            escapes: EscapeProcessor::new(escapes),
+            error: None,
+        }
+    }
+    
+    fn finish_literal(&mut self) {
+        if !self.literal.is_empty() {
+            let literal = core::mem::take(&mut self.literal);
+            self.segments.push(StringSegment::Literal(literal));
+        }
+    }
+    
+    fn finish_expr(&mut self) {
+        let interpolation = Interpolation {
+            source: core::mem::take(&mut self.expr),
+            offset: self.expr_offset,
+            spec: self.spec.take(),
+            tokens: Vec::new(),
+        };
+        self.segments.push(StringSegment::Interpolate(interpolation));
+        self.state = InterpolateState::Literal;
+    }
+    
+    fn match_literal(&mut self, prev: Option<char>, next: char) -> MatchResult {
+        if self.escapes.process(prev, next, &mut self.literal) {
+            return MatchResult::IncompleteMatch;
+        }
+        
+        match next {
+            _ if next == self.quote => {
+                self.finish_literal();
+                self.state = InterpolateState::Closed;
+                return MatchResult::CompleteMatch;
+            }
+            
+            INTERPOLATE_OPEN => self.state = InterpolateState::OpenBrace,
+            INTERPOLATE_CLOSE => self.state = InterpolateState::CloseBrace,
+            ESCAPE_CHAR => { },
+            _ => self.literal.push(next),
+        }
+        
+        MatchResult::IncompleteMatch
+    }
+    
+    fn match_expr(&mut self, next: char) -> MatchResult {
+        if let Some(quote) = self.nested_quote {
+            if self.nested_escape {
+                self.nested_escape = false;
+            } else if next == ESCAPE_CHAR {
+                self.nested_escape = true;
+            } else if next == quote {
+                self.nested_quote = None;
+            }
+            
+            self.expr.push(next);
+            return MatchResult::IncompleteMatch;
+        }
+        
+        match next {
+            INTERPOLATE_CLOSE if self.depth == 0 => {
+                self.finish_expr();
+                return MatchResult::IncompleteMatch;
+            }
+            
+            FORMAT_SPEC_SEP if self.depth == 0 => {
+                self.spec = Some(String::new());
+                self.state = InterpolateState::Spec;
+                return MatchResult::IncompleteMatch;
+            }
+            
+            SINGLE_QUOTE | DOUBLE_QUOTE => self.nested_quote = Some(next),
+            '(' | '[' | '{' => self.depth += 1,
+            ')' | ']' | '}' => self.depth = self.depth.saturating_sub(1),
+            _ => { },
+        }
+        
+        self.expr.push(next);
+        MatchResult::IncompleteMatch
+    }
+    
+    fn match_spec(&mut self, next: char) -> MatchResult {
+        match next {
+            INTERPOLATE_CLOSE => self.finish_expr(),
+            
+            _ if next == self.quote => {
+                self.error.get_or_insert(InterpolationError::UnclosedExpression);
+                self.finish_expr();
+                self.state = InterpolateState::Closed;
+                return MatchResult::CompleteMatch;
+            }
+            
+            _ => self.spec.as_mut().unwrap().push(next),
+        }
+        
+        MatchResult::IncompleteMatch
+    }
+}
+
+impl LexerRule for InterpolatedStringRule {
+    fn reset(&mut self) {
+        self.state = InterpolateState::Start;
+        self.quote = DOUBLE_QUOTE;
+        self.count = 0;
+        
+        self.literal.clear();
+        self.segments.clear();
+        
+        self.expr.clear();
+        self.expr_offset = 0;
+        self.spec = None;
+        self.depth = 0;
+        self.nested_quote = None;
+        self.nested_escape = false;
+        
+        self.escapes.reset();
+        self.error = None;
+    }
+    
+    fn current_state(&self) -> MatchResult {
+        match self.state {
+            InterpolateState::Closed => MatchResult::CompleteMatch,
+            InterpolateState::Invalid => MatchResult::NoMatch,
+            _ => MatchResult::IncompleteMatch,
+        }
+    }
+    
+    fn try_match(&mut self, prev: Option<char>, next: char) -> MatchResult {
+        let index = self.count;
+        self.count = self.count.saturating_add(1);
+        
+        match self.state {
+            InterpolateState::Start => {
+                if next != INTERPOLATE_PREFIX {
+                    self.state = InterpolateState::Invalid;
+                    return MatchResult::NoMatch;
+                }
+                self.state = InterpolateState::Prefix;
+                MatchResult::IncompleteMatch
+            }
+            
+            InterpolateState::Prefix => {
+                if !matches!(next, SINGLE_QUOTE | DOUBLE_QUOTE) {
+                    self.state = InterpolateState::Invalid;
+                    return MatchResult::NoMatch;
+                }
+                self.quote = next;
+                self.state = InterpolateState::Literal;
+                MatchResult::IncompleteMatch
+            }
+            
+            InterpolateState::Literal => self.match_literal(prev, next),
+            
+            InterpolateState::OpenBrace => {
+                // "{{" is an escaped brace
+                if next == INTERPOLATE_OPEN {
+                    self.literal.push(next);
+                    self.state = InterpolateState::Literal;
+                    return MatchResult::IncompleteMatch;
+                }
+                
+                self.finish_literal();
+                self.expr_offset = index;
+                self.state = InterpolateState::Expr;
+                self.match_expr(next)
+            }
+            
+            InterpolateState::CloseBrace => {
+                self.state = InterpolateState::Literal;
+                if next == INTERPOLATE_CLOSE {
+                    self.literal.push(next);
+                    return MatchResult::IncompleteMatch;
+                }
+                
+                self.error.get_or_insert(InterpolationError::UnmatchedCloseBrace);
+                self.match_literal(prev, next)
+            }
+            
+            InterpolateState::Expr => self.match_expr(next),
+            InterpolateState::Spec => self.match_spec(next),
+            
+            InterpolateState::Closed | InterpolateState::Invalid => MatchResult::NoMatch,
+        }
+    }
+    
+    fn get_token(&self) -> Result<Token, TokenError> {
+        debug_assert!(self.current_state().is_complete_match());
+        
+        if let Some(ref error) = self.escapes.error {
+            return Err(Box::new(error.clone()));
+        }
+        if let Some(error) = self.error {
+            return Err(Box::new(error));
+        }
+        
+        Ok(Token::InterpolatedString(self.segments.clone().into_boxed_slice()))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum InterpolationError {
+    UnmatchedCloseBrace,
+    UnclosedExpression,
+}
+
+impl std::error::Error for InterpolationError { }
+
+impl core::fmt::Display for InterpolationError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnmatchedCloseBrace => fmt.write_str("single '}' is not allowed in an interpolated string, use '}}' instead"),
+            Self::UnclosedExpression => fmt.write_str("expected '}' after interpolated expression"),
+        }
+    }
+}
+
+
 #[derive(Debug, Clone)]
 pub enum StringEscapeErrorKind {
     InvalidEscapeTag,
     InvalidEscapeArg,
+    OutOfRange,
 }
 
 #[derive(Debug, Clone)]
@@ -275,6 +790,7 @@ impl core::fmt::Display for StringEscapeError {
         match self.kind {
             StringEscapeErrorKind::InvalidEscapeTag => write!(fmt, "unrecognized escape sequence '{}'", render_escape),
             StringEscapeErrorKind::InvalidEscapeArg => write!(fmt, "invalid escape sequence '{}'", render_escape),
+            StringEscapeErrorKind::OutOfRange => write!(fmt, "escape sequence '{}' is out of range", render_escape),
         }
     }
 }
\ No newline at end of file
@@ -1,8 +1,10 @@
 #![cfg(test)]
 
-use crate::lexer::{LexerBuilder, Token, TokenMeta, ErrorKind};
+use crate::lexer::{LexerBuilder, Token, TokenMeta, ErrorKind, StringSegment};
 use crate::lexer::rules::SingleCharRule;
 use crate::lexer::rules::literals::*;
+use crate::lexer::rules::literals::string::{StringLiteralRule, InterpolatedStringRule};
+use crate::language;
 use crate::lexer::rules::keywords::KeywordRule;
 use crate::lexer::tests::ErrorData;
 
@@ -201,4 +203,88 @@ fn lexer_test_integer_literals() {
         } "0xFACE",
         
     );
-}
\ No newline at end of file
+}
+
+#[test]
+fn lexer_test_string_literals() {
+    let source = r#" "" 'a\u{42}' r"\n" """ "x" """ "#;
+    
+    let mut lexer = LexerBuilder::new()
+        .add_rule(StringLiteralRule::new(language::all_escape_sequences()))
+        .build_once(source.chars().map(Ok));
+    
+    assert_token_sequence!(lexer,
+        
+        token if s.is_empty() && symbol.len() == 2 => {
+            token: Token::StringLiteral(s),
+            symbol,
+            ..
+        } "empty string",
+        
+        token if s == "aB" && symbol.len() == 9 => {
+            token: Token::StringLiteral(s),
+            symbol,
+            ..
+        } "unicode escape",
+        
+        token if s == "\\n" && symbol.len() == 5 => {
+            token: Token::StringLiteral(s),
+            symbol,
+            ..
+        } "raw string",
+        
+        token if s == " \"x\" " && symbol.len() == 11 => {
+            token: Token::StringLiteral(s),
+            symbol,
+            ..
+        } "triple-quoted string",
+        
+        token => {
+            token: Token::EOF,
+            ..
+        } "EOF",
+    );
+}
+
+#[test]
+fn lexer_test_interpolated_strings() {
+    let source = r#" $"a{x + 1:>4}b{{}}" $"}" "#;
+    
+    let mut lexer = LexerBuilder::new()
+        .add_rule(IdentifierRule::new())
+        .add_rule(IntegerLiteralRule::new())
+        .add_rule(SingleCharRule::new(Token::OpAdd, '+'))
+        .add_rule(InterpolatedStringRule::new(language::all_escape_sequences()))
+        .build_once(source.chars().map(Ok));
+    
+    let token = lexer.next_token().unwrap();
+    assert!(token.symbol.start() == 1 && token.symbol.len() == 19);
+    
+    let segments = match token.token {
+        Token::InterpolatedString(segments) => segments,
+        _ => panic!("expected an interpolated string"),
+    };
+    assert_eq!(segments.len(), 3);
+    assert!(matches!(&segments[0], StringSegment::Literal(s) if s == "a"));
+    assert!(matches!(&segments[2], StringSegment::Literal(s) if s == "b{}"));
+    
+    let interp = match &segments[1] {
+        StringSegment::Interpolate(interp) => interp,
+        _ => panic!("expected an interpolated expression"),
+    };
+    assert_eq!(interp.source, "x + 1");
+    assert_eq!(interp.spec.as_deref(), Some(">4"));
+    
+    // symbols of the inner tokens refer to the enclosing source
+    let tokens = &interp.tokens;
+    assert_eq!(tokens.len(), 4);
+    assert!(matches!(&tokens[0], TokenMeta { token: Token::Identifier(s), symbol, .. } if s == "x" && symbol.start() == 5));
+    assert!(matches!(&tokens[2], TokenMeta { token: Token::IntegerLiteral(1), symbol, .. } if symbol.start() == 9));
+    assert!(matches!(tokens[3].token, Token::EOF));
+    
+    // a single closing brace is not allowed
+    assert_next_token!(lexer, error {
+        kind: ErrorKind::CouldNotReadToken,
+        ..
+    });
+}
@@ -1,5 +1,5 @@
 use crate::language::{IntType, FloatType};
-use crate::debug::DebugSymbol;
+use crate::debug::{DebugSymbol, TokenIndex};
 
 // Token Types
 
@@ -17,13 +17,14 @@ pub enum Token {
     Semicolon,
     Ellipsis,
     Decorator,
+    Arrow,
     
     // Operator Symbols
     OpAdd, OpSub, OpMul, OpDiv, OpMod, OpExp,
     OpInv, OpAnd, OpOr, OpXor, OpLShift, OpRShift,
     
     OpAddAssign, OpSubAssign, OpMulAssign, OpDivAssign, OpModAssign,
-    OpAndAssign, OpOrAssign, OpXorAssign, OpLShiftAssign, OpRShiftAssign,
+    OpAndAssign, OpOrAssign, OpXorAssign, OpLShiftAssign, OpRShiftAssign, OpExpAssign,
     
     OpLT, OpLE, OpGT, OpGE, OpEQ, OpNE,
     OpAssign, OpAccess,
@@ -33,16 +34,20 @@ pub enum Token {
     True, False, Nil,
     Let, Var, Local, NonLocal, Del,
     If, Then, Elif, Else,
+    Match, Case,
     Begin, Loop, While, For, In, Do,
-    Continue, Break, Return,
+    Continue, Break, Return, Yield,
     Fun, Class,
-    // Self_, Super,
+    Self_, Super,
+    Import, As,
+    Try, Catch, Finally, Throw,
     Assert,
     End,
     
     // Literals
     Identifier(String),
     StringLiteral(String),
+    InterpolatedString(Box<[StringSegment]>),
     IntegerLiteral(IntType),
     FloatLiteral(FloatType),
     
@@ -59,4 +64,20 @@ pub struct TokenMeta {
     pub token: Token,
     pub symbol: DebugSymbol,
     pub newline: bool,  // true if this is the first token after the start of a new line
-}
\ No newline at end of file
+}
+
+
+/// A piece of an interpolated string literal
+#[derive(Clone, Debug)]
+pub enum StringSegment {
+    Literal(String),
+    Interpolate(Interpolation),
+}
+
+#[derive(Clone, Debug)]
+pub struct Interpolation {
+    pub source: String,
+    pub offset: TokenIndex,  // position of the source relative to the start of the string literal
+    pub spec: Option<String>,
+    pub tokens: Vec<TokenMeta>,  // filled in by the lexer, ending with EOF
+}
@@ -13,6 +13,7 @@ pub mod utils;
 pub mod source;
 pub mod lexer;
 pub mod parser;
+pub mod typecheck;
 
 pub mod language;
 pub mod codegen;
@@ -20,13 +21,15 @@ pub mod runtime;
 pub mod builtins;
 
 pub mod frontend;
+pub mod engine;
 pub mod debug;
 
 
 use source::{SourceText, ModuleSource, ParseContext};
 use parser::ParserError;
 use parser::stmt::StmtMeta;
-use codegen::{CompiledProgram, Compiler, CompileError};
+use typecheck::{TypeChecker, TypeWarning};
+use codegen::{CompiledProgram, Compiler, CompileError, CompileWarning, OptLevel};
 use runtime::strings::StringInterner;
 
 #[derive(Debug)]
@@ -37,6 +40,14 @@ pub enum BuildErrors {
     Compile(Box<[CompileError]>),
 }
 
+/// Options that control how a module is built
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BuildOptions {
+    pub check_types: bool,
+    pub opt_level: OptLevel,
+    pub stack_only: bool,  // don't emit register operands for binary operators
+}
+
 pub fn build_module(source: &ModuleSource) -> Result<CompiledProgram, BuildErrors> {
     let source_text = source.read_text()
         .map_err(BuildErrors::Source)?;
@@ -44,7 +55,35 @@ pub fn build_module(source: &ModuleSource) -> Result<CompiledProgram, BuildError
     build_source(source_text)
 }
 
+/// Like `build_module()`, but also runs the static type checker on the module
+pub fn build_module_checked(source: &ModuleSource) -> Result<(CompiledProgram, Vec<TypeWarning>), BuildErrors> {
+    let source_text = source.read_text()
+        .map_err(BuildErrors::Source)?;
+    
+    build_source_checked(source_text)
+}
+
+/// Build a module using the given options. Type warnings are only produced if `check_types` is set.
+pub fn build_module_with(source: &ModuleSource, options: BuildOptions) -> Result<(CompiledProgram, Vec<TypeWarning>), BuildErrors> {
+    let source_text = source.read_text()
+        .map_err(BuildErrors::Source)?;
+    
+    build(source_text, options)
+}
+
 pub fn build_source(source_text: SourceText) -> Result<CompiledProgram, BuildErrors> {
+    build(source_text, BuildOptions::default()).map(|(program, _)| program)
+}
+
+pub fn build_source_checked(source_text: SourceText) -> Result<(CompiledProgram, Vec<TypeWarning>), BuildErrors> {
+    let options = BuildOptions {
+        check_types: true,
+        ..BuildOptions::default()
+    };
+    build(source_text, options)
+}
+
+fn build(source_text: SourceText, options: BuildOptions) -> Result<(CompiledProgram, Vec<TypeWarning>), BuildErrors> {
     let mut interner = StringInterner::new();
     
     // parsing
@@ -54,14 +93,24 @@ pub fn build_source(source_text: SourceText) -> Result<CompiledProgram, BuildErr
         return Err(BuildErrors::Syntax(errors.into_boxed_slice()));
     }
     
+    let ast = parse_result.unwrap();
+    
+    // type checking
+    let warnings =
+        if options.check_types { check_ast(&interner, &ast) }
+        else { Vec::new() };
+    
     // compilation
-    let compile_result = compile_ast(interner, parse_result.unwrap());
+    let compile_result = Compiler::new(interner)
+        .with_opt_level(options.opt_level)
+        .with_stack_only(options.stack_only)
+        .compile_program(ast.iter());
     
     if let Err(errors) = compile_result {
         return Err(BuildErrors::Compile(errors.into_boxed_slice()));
     }
     
-    Ok(compile_result.unwrap())
+    Ok((compile_result.unwrap(), warnings))
 }
 
 
@@ -74,6 +123,11 @@ pub fn parse_source(interner: &mut StringInterner, source_text: SourceText) -> R
     parse_ctx.parse_ast(source_text)
 }
 
+/// Look for type errors in an AST. The resulting warnings don't prevent the AST from being compiled.
+pub fn check_ast(interner: &StringInterner, ast: &[StmtMeta]) -> Vec<TypeWarning> {
+    TypeChecker::new(interner).check_program(ast)
+}
+
 /// Produce bytecode from AST
 pub fn compile_ast(interner: StringInterner, ast: Vec<StmtMeta>) -> Result<CompiledProgram, Vec<CompileError>> {
     let compiler = Compiler::new(interner);
@@ -81,6 +135,21 @@ pub fn compile_ast(interner: StringInterner, ast: Vec<StmtMeta>) -> Result<Compi
 }
 
 
+/// Like `print_build_errors()`, but without source lines
+pub fn format_build_errors(errors: &BuildErrors) -> String {
+    match errors {
+        BuildErrors::Source(error) => format!("error reading source: {}", error),
+        
+        BuildErrors::Syntax(errors) => errors.iter()
+            .map(|error| error.to_string())
+            .collect::<Vec<String>>().join("\n"),
+        
+        BuildErrors::Compile(errors) => errors.iter()
+            .map(|error| error.to_string())
+            .collect::<Vec<String>>().join("\n"),
+    }
+}
+
 pub fn print_build_errors(errors: &BuildErrors, source: &ModuleSource) {
     match errors {
         BuildErrors::Source(error) => {
@@ -98,4 +167,22 @@ pub fn print_build_errors(errors: &BuildErrors, source: &ModuleSource) {
         }
     }
     
-}
\ No newline at end of file
+}
+
+pub fn print_type_warnings(warnings: &[TypeWarning], source: &ModuleSource) {
+    if warnings.is_empty() {
+        return;
+    }
+    
+    println!("Warnings in {}:\n", source);
+    frontend::print_source_errors(source, warnings);
+}
+
+pub fn print_compile_warnings(warnings: &[CompileWarning], source: &ModuleSource) {
+    if warnings.is_empty() {
+        return;
+    }
+    
+    println!("Warnings in {}:\n", source);
+    frontend::print_source_errors(source, warnings);
+}
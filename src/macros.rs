@@ -82,9 +82,10 @@ macro_rules! __defaults {
 macro_rules! native_function {
     
     // with default params
-    ( $func_name:tt, $env:expr $( , this ( $self_name:tt ) )? $( , vm ( $vm_name:tt ) )? $( , params ( $( $required:tt ),+ ) )? $( , defaults ( $( $default:tt = $default_value:expr ),+ ) )? $( , variadic ( $variadic:tt ) )? => $body:expr ) => {
+    ( $func_name:tt, $env:expr $( , this ( $self_name:tt ) )? $( , vm ( $vm_name:tt ) )? $( , params ( $( $required:tt ),+ ) )? $( , defaults ( $( $default:tt = $default_value:expr ),+ ) )? $( , variadic ( $variadic:tt ) )? $( , keywords ( $( $keyword:tt = $keyword_value:expr ),+ ) )? $( , kwargs ( $kwargs:tt ) )? => $body:expr ) => {
         {
             type Variant = crate::runtime::Variant;
+            type StringSymbol = crate::runtime::strings::StringSymbol;
             type Signature = crate::runtime::function::Signature;
             type Parameter = crate::runtime::function::Parameter;
             type NativeFunction = crate::runtime::function::NativeFunction;
@@ -96,15 +97,19 @@ macro_rules! native_function {
                 vec![ $( $( Parameter::new(stringify!($required), crate::language::Access::ReadWrite) ),+ )? ],
                 vec![ $( $( Parameter::new(stringify!($default), crate::language::Access::ReadWrite) ),+ )? ],
                 __variadic!( $( $variadic )? ),
+            ).with_keywords(
+                Vec::new(),
+                vec![ $( $( Parameter::new(stringify!($keyword), crate::language::Access::ReadWrite) ),+ )? ],
+                __variadic!( $( $kwargs )? ),
             );
             
-            let defaults = __defaults!( $( $( $default_value )+ )? );
+            let defaults = __defaults!( $( $( $default_value )+ )? $( $( $keyword_value )+ )? );
             
-            fn body(self_fun: &NativeFunction, _vm: &mut VirtualMachine<'_>, args: &[Variant]) -> ExecResult<Variant> {
-                const _ARGC: usize = __count!( $( $( $required )+ )? $( $( $default )+ )? );
+            fn body(self_fun: &NativeFunction, _vm: &mut VirtualMachine<'_>, args: &[Variant], kwargs: &[(StringSymbol, Variant)]) -> ExecResult<Variant> {
+                const _ARGC: usize = __count!( $( $( $required )+ )? $( $( $default )+ )? $( $( $keyword )+ )? );
                 
                 let mut _argbuf = [Variant::Nil; _ARGC];
-                let _bound = self_fun.signature().bind_args(args, self_fun.defaults(), &mut _argbuf);
+                let _bound = self_fun.signature().bind_args(args, kwargs, self_fun.defaults(), &mut _argbuf);
                 let _rest = _bound.args;
                 
                 $( let $self_name = self_fun; )?
@@ -112,6 +117,8 @@ macro_rules! native_function {
                 $( $( let ($required, _rest) = _rest.split_first().unwrap(); )+ )?
                 $( $( let ($default, _rest) = _rest.split_first().unwrap(); )+ )?
                 $( let $variadic = _bound.varargs; )?
+                $( $( let ($keyword, _rest) = _rest.split_first().unwrap(); )+ )?
+                $( let $kwargs = _bound.kwargs.as_slice(); )?
                 
                 $body
             }
@@ -3,9 +3,11 @@ use std::collections::VecDeque;
 use log::debug;
 
 use crate::language::{InternSymbol, Access};
-use crate::lexer::{TokenMeta, Token, LexerError};
+use crate::lexer::{TokenMeta, Token, LexerError, StringSegment};
 use crate::runtime::strings::StringInterner;
-use crate::debug::{SourceError, TokenIndex};
+use crate::runtime::strings::format::FormatSpec;
+use crate::runtime::types::BuiltinType;
+use crate::debug::{SourceError, TokenIndex, DebugSymbol};
 
 
 pub mod expr;
@@ -14,17 +16,21 @@ pub mod primary;
 pub mod pattern;
 pub mod operator;
 pub mod fundefs;
+pub mod classdefs;
+pub mod annotation;
 pub mod errors;
 mod tests;
 
 pub use errors::{ParserError, ParseResult};
 
-use expr::{ExprMeta, Expr, ExprBlock, ConditionalBranch, TableItem, TableField};
+use expr::{ExprMeta, Expr, ExprBlock, ConditionalBranch, MatchCase, CatchClause, TableItem, TableField, DictItem};
 use stmt::{StmtMeta, StmtList, Stmt, Label, ControlFlow};
-use primary::{Primary, Atom, AccessItem};
-use pattern::{Pattern, MatchAction, Assignment};
+use primary::{Primary, Atom, AccessItem, KeywordArg, StringPart};
+use pattern::{Pattern, MatchAction, Assignment, IndexPattern};
 use operator::{UnaryOp, BinaryOp, Precedence, PRECEDENCE_START, PRECEDENCE_END};
 use fundefs::{FunctionDef, SignatureDef, ParamDef, DefaultDef};
+use classdefs::{ClassDef, FieldDef, MethodDef};
+use annotation::TypeExpr;
 use errors::{ErrorKind, ErrorContext, ContextTag};
 
 
@@ -35,6 +41,13 @@ pub struct Parser<'h, T> where T: Iterator<Item=Result<TokenMeta, LexerError>> {
     tokens: T,
     next: Option<Result<TokenMeta, LexerError>>,
     errors: VecDeque<ParserError>,
+    yields: Vec<bool>,  // one entry for each function body being parsed, set if the body contains "yield"
+}
+
+// the "for" and "if" clauses of a comprehension, before they are desugared into loops
+enum ComprehensionClause {
+    For(Pattern, Expr),
+    If(Expr),
 }
 
 impl<T> Iterator for Parser<'_, T> where T: Iterator<Item=Result<TokenMeta, LexerError>> {
@@ -49,6 +62,7 @@ impl<'h, I> Parser<'h, I> where I: Iterator<Item=Result<TokenMeta, LexerError>>
             tokens, interner,
             next: None,
             errors: VecDeque::new(),
+            yields: Vec::new(),
         }
     }
 }
@@ -130,7 +144,7 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
                 let error = self.errors.pop_front().unwrap();
                 let error = Self::process_error(ctx, error);
                 
-                self.synchronize_stmt(false);
+                self.synchronize_stmt(&|_| false);
                 
                 Err(error)
             },
@@ -151,7 +165,7 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
         error
     }
     
-    fn catch_error_and_sync<T>(&mut self, ctx: &ErrorContext, result: ParseResult<T>, inside_block: bool) -> Option<ParseResult<T>> {
+    fn catch_error_and_sync<T>(&mut self, ctx: &ErrorContext, result: ParseResult<T>, end_block: &dyn Fn(&Token) -> bool) -> Option<ParseResult<T>> {
         match result {
             Ok(..) => Some(result),
             Err(error) => {
@@ -160,7 +174,7 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
                 }
                 
                 self.errors.push_back(error.with_symbol_from_ctx(ctx));
-                self.synchronize_stmt(inside_block);
+                self.synchronize_stmt(end_block);
                 
                 // if the next token is EOF there is no point catching an error
                 // since there is no more source code to examine anyways
@@ -176,8 +190,9 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
     }
     
     // If we hit an error we need to synchronize back to a likely-valid state before we continue parsing again
-    // To do this, just keep discarding tokens until we think we're at the start of a new statement
-    fn synchronize_stmt(&mut self, inside_block: bool) {
+    // To do this, just keep discarding tokens until we think we're at the start of a new statement,
+    // or at a token that ends the enclosing block (given by end_block)
+    fn synchronize_stmt(&mut self, end_block: &dyn Fn(&Token) -> bool) {
         // Check for either: a token that only appears at the start of a new statement
         // OR try to parse an expression. If we can do it without errors, assume we're in a good state. The expression can be discarded.
         debug!("sync to next stmt...");
@@ -200,10 +215,17 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
                 Token::EOF | Token::Semicolon |
                 Token::While  | Token::Loop | Token::For |
                 Token::Continue | Token::Break | Token::Return | 
-                Token::Label(..) | Token::Assert
+                Token::Label(..) | Token::Assert | Token::Import |
+                Token::Throw | Token::Yield | Token::Del
                     => break,
                 
-                Token::End if inside_block => break,
+                Token::End | Token::Elif | Token::Else | Token::Case | Token::Catch | Token::Finally => {
+                    if end_block(&next.token) {
+                        break;
+                    }
+                    // these can't start an expression, so they have to be skipped explicitly
+                    self.advance().unwrap();
+                },
                 
                 _ => if self.parse_expr_variant(&mut ctx).is_ok() {
                     break;
@@ -243,11 +265,36 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
             
             Token::Label(..) => self.parse_stmt_label(ctx)?,
             
+            Token::Import => self.parse_import_stmt(ctx)?,
+            
+            Token::Del => self.parse_del_stmt(ctx)?,
+            
             Token::Assert => {
                 ctx.set_start(&self.advance().unwrap());
                 Stmt::Assert(self.parse_expr_variant(ctx)?)
             }
             
+            Token::Throw => {
+                ctx.set_start(&self.advance().unwrap());
+                Stmt::Throw(self.parse_expr_variant(ctx)?)
+            }
+            
+            Token::Yield => {
+                ctx.set_start(&self.advance().unwrap());
+                
+                match self.yields.last_mut() {
+                    Some(yields) => *yields = true,
+                    None => return Err("\"yield\" is not allowed outside of a function".into()),
+                }
+                
+                let expr = 
+                    if !matches!(self.peek()?.token, Token::End | Token::Elif | Token::Else | Token::Case | Token::Catch | Token::Finally | Token::Semicolon ) {
+                        Some(self.parse_expr_variant(ctx)?)
+                    } else { None };
+                
+                Stmt::Yield(expr)
+            }
+            
             Token::Continue | Token::Break | Token::Return => {
                 let next = self.advance().unwrap();
                 
@@ -275,6 +322,60 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
         Ok(stmt)
     }
     
+    /*
+        import-statement ::= "import" module-path ( "as" IDENTIFIER )? ;
+        
+        SYNTACTIC SUGAR: import foo.bar => let bar = import foo.bar
+    */
+    fn parse_import_stmt(&mut self, ctx: &mut ErrorContext) -> ParseResult<Stmt> {
+        let (path, mut name) = self.parse_import_expr(ctx)?;
+        
+        if let Token::As = self.peek()?.token {
+            ctx.set_end(&self.advance().unwrap());
+            
+            let next = self.advance()?;
+            ctx.set_end(&next);
+            if let Token::Identifier(alias) = next.token {
+                name = self.intern_str(alias);
+            } else {
+                return Err("expected a name after \"as\"".into());
+            }
+        }
+        
+        let import_decl = Assignment {
+            action: MatchAction::DeclImmutable,
+            op: None,
+            lhs: Pattern::Identifier(name),
+            rhs: Expr::Import(path),
+        };
+        
+        Ok(Stmt::Expression(Expr::Assignment(Box::new(import_decl))))
+    }
+    
+    /*
+        del-statement ::= "del" del-target ( "," del-target )* ;
+        del-target ::= IDENTIFIER | primary index-access | primary member-access ;
+    */
+    fn parse_del_stmt(&mut self, ctx: &mut ErrorContext) -> ParseResult<Stmt> {
+        ctx.set_start(&self.advance().unwrap()); // consume "del"
+        
+        let expr = self.parse_tuple_expr(ctx)?;
+        let target = Pattern::try_from(expr)
+            .ok()
+            .filter(Self::is_del_target)
+            .ok_or_else(|| ParserError::from("can't delete this"))?;
+        
+        Ok(Stmt::Delete(target))
+    }
+    
+    fn is_del_target(pattern: &Pattern) -> bool {
+        match pattern {
+            Pattern::Identifier(..) | Pattern::Attribute(..) | Pattern::Index(..) => true,
+            Pattern::Tuple(items) => items.iter().all(Self::is_del_target),
+            _ => false,
+        }
+    }
+    
     fn parse_stmt_label(&mut self, ctx: &mut ErrorContext) -> ParseResult<Stmt> {
         let label = self.try_parse_label(ctx)?.unwrap();
         
@@ -405,7 +506,7 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
             }
             
             let parse_result = self.try_parse_control_flow(ctx);
-            control = match self.catch_error_and_sync(ctx, parse_result, true) {
+            control = match self.catch_error_and_sync(ctx, parse_result, &end_list) {
                 Some(result) => result?,
                 None => continue,
             };
@@ -435,7 +536,7 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
             }
             
             let parse_result = self.parse_stmt(ctx);
-            let stmt = match self.catch_error_and_sync(ctx, parse_result, true) {
+            let stmt = match self.catch_error_and_sync(ctx, parse_result, &end_list) {
                 Some(result) => result?,
                 None => continue,
             };
@@ -487,7 +588,7 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
                 let label = self.try_parse_label(ctx)?;
                 
                 let expr = 
-                    if !matches!(self.peek()?.token, Token::End | Token::Elif | Token::Else | Token::Semicolon ) {
+                    if !matches!(self.peek()?.token, Token::End | Token::Elif | Token::Else | Token::Case | Token::Catch | Token::Finally | Token::Semicolon ) {
                         Some(Box::new(self.parse_expr_variant(ctx)?))
                     } else { None };
                 
@@ -502,7 +603,7 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
                 ctx.set_start(&self.advance().unwrap());
                 
                 let expr = 
-                    if !matches!(self.peek()?.token, Token::End | Token::Elif | Token::Else | Token::Semicolon ) {
+                    if !matches!(self.peek()?.token, Token::End | Token::Elif | Token::Else | Token::Case | Token::Catch | Token::Finally | Token::Semicolon ) {
                         Some(Box::new(self.parse_expr_variant(ctx)?))
                     } else { None };
                 
@@ -546,7 +647,7 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
 
         pattern-annotated ::= pattern-expression ( ":" type-expression )? ; 
 
-        assignment-op ::= "=" | "+=" | "-=" | "*=" | "/=" | "%=" | "&=" | "|=" | "^=" | "<<=" | ">>=" ;
+        assignment-op ::= "=" | "+=" | "-=" | "*=" | "/=" | "%=" | "&=" | "|=" | "^=" | "<<=" | ">>=" | "**=" ;
         assignment-expression ::= pattern-annotated assignment-op expression ;
     */
     
@@ -558,6 +659,18 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
         // parse LHS
         let expr = self.parse_tuple_expr(ctx)?;
         
+        self.parse_assignment_rhs(ctx, assign, expr, false)
+    }
+    
+    // completes an assignment expression after the LHS has been parsed, if there is one.
+    // if `inner` is set the RHS is parsed as an inner expression, for use inside argument lists
+    fn parse_assignment_rhs(&mut self, ctx: &mut ErrorContext, assign: Option<MatchAction>, expr: Expr, inner: bool) -> ParseResult<Expr> {
+        
+        // only look for an annotation after an assignment keyword, since ":" is also used by dict items
+        let annotation =
+            if assign.is_some() { self.try_parse_annotation(ctx)? }
+            else { None };
+        
         let next = self.peek()?;
         if let Some(op) = Self::which_assignment_op(&next.token) {
             // consume assign_op token
@@ -565,11 +678,20 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
             ctx.set_end(&self.advance().unwrap());
             
             // LHS of assignment must be an pattern
-            let lhs = Pattern::try_from(expr)
+            let mut lhs = Pattern::try_from(expr)
                 .map_err(|_| ParserError::from("can't assign to this"))?;
             
+            if let Some(annotation) = annotation {
+                lhs = Pattern::Annotated {
+                    pattern: Box::new(lhs),
+                    annotation: Box::new(annotation),
+                };
+            }
+            
             // Parse RHS
-            let rhs = self.parse_expr_variant(ctx)?;
+            let rhs = 
+                if inner { self.parse_inner_expr(ctx)? }
+                else { self.parse_expr_variant(ctx)? };
             
             ctx.pop_extend();
             
@@ -653,6 +775,16 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
     fn parse_inner_expr(&mut self, ctx: &mut ErrorContext) -> ParseResult<Expr> {
         self.parse_binop_expr(ctx)
     }
+    
+    fn parse_inner_expr_meta(&mut self, ctx: &mut ErrorContext) -> ParseResult<ExprMeta> {
+        ctx.push(ContextTag::ExprMeta);
+        
+        let variant = self.parse_inner_expr(ctx)?;
+        let symbol = ctx.frame().as_debug_symbol().unwrap();
+        
+        ctx.pop_extend();
+        Ok(ExprMeta::new(variant, symbol))
+    }
 
     /*
         Binary operator syntax:
@@ -689,6 +821,13 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
             ctx.push_continuation(ContextTag::BinaryOpExpr, None);
             ctx.set_end(&self.advance().unwrap()); // consume binary_op token
             
+            // right associative operators recurse at the same level, so "a ** b ** c" parses as "a ** (b ** c)"
+            if binary_op.is_right_assoc() {
+                let rhs_expr = self.parse_binop_expr_levels(ctx, level)?;
+                expr = Expr::BinaryOp(binary_op, Box::new((expr, rhs_expr)));
+                break;
+            }
+            
             let rhs_expr = self.parse_binop_expr_levels(ctx, level - 1)?;
             
             expr = Expr::BinaryOp(binary_op, Box::new((expr, rhs_expr)));
@@ -736,6 +875,7 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
     
     fn which_binary_op(token: &Token) -> Option<BinaryOp> {
         let op = match token {
+            Token::OpExp => BinaryOp::Exp,
             Token::OpMul => BinaryOp::Mul,
             Token::OpDiv => BinaryOp::Div,
             Token::OpMod => BinaryOp::Mod,
@@ -775,6 +915,7 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
             Token::OpXorAssign => Some(BinaryOp::BitXor),
             Token::OpLShiftAssign => Some(BinaryOp::LShift),
             Token::OpRShiftAssign => Some(BinaryOp::RShift),
+            Token::OpExpAssign => Some(BinaryOp::Exp),
             
             _ => return None,
         };
@@ -788,10 +929,15 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
     */
     fn parse_primary_expr(&mut self, ctx: &mut ErrorContext) -> ParseResult<Expr> {
         let expr = match self.peek()?.token {
-            Token::Class => unimplemented!(),
+            Token::Class => self.parse_class_decl_expr(ctx)?,
             Token::Fun => self.parse_function_decl_expr(ctx)?,
+            Token::OpOr => self.parse_lambda_expr(ctx)?,
+            
+            Token::Import => Expr::Import(self.parse_import_expr(ctx)?.0),
             
             Token::If => self.parse_if_expr(ctx)?,
+            Token::Match => self.parse_match_expr(ctx)?,
+            Token::Try => self.parse_try_expr(ctx)?,
             Token::Begin => self.parse_block_expr(ctx, None)?,
             
             Token::OpenBrace => self.parse_table_expr(ctx)?,
@@ -828,6 +974,43 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
         Ok(expr)
     }
     
+    /*
+        import-expression ::= "import" module-path ;
+        module-path ::= IDENTIFIER ( "." IDENTIFIER )* ;
+    */
+    // returns the full module path and the last name in the path
+    fn parse_import_expr(&mut self, ctx: &mut ErrorContext) -> ParseResult<(InternSymbol, InternSymbol)> {
+        let next = self.advance()?;
+        
+        ctx.push(ContextTag::ImportExpr);
+        ctx.set_start(&next);
+        debug_assert!(matches!(next.token, Token::Import));
+        
+        let mut path = Vec::new();
+        loop {
+            let next = self.advance()?;
+            ctx.set_end(&next);
+            
+            if let Token::Identifier(name) = next.token {
+                path.push(name);
+            } else {
+                return Err("expected a module name".into());
+            }
+            
+            if let Token::OpAccess = self.peek()?.token {
+                ctx.set_end(&self.advance().unwrap());
+            } else {
+                break;
+            }
+        }
+        
+        ctx.pop_extend();
+        
+        let name = self.intern_str(path.last().unwrap());
+        let path = self.intern_str(path.join("."));
+        Ok((path, name))
+    }
+    
     fn parse_expr_label(&mut self, ctx: &mut ErrorContext) -> ParseResult<Expr> {
         let label = self.try_parse_label(ctx)?.unwrap();
         
@@ -914,6 +1097,123 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
         Ok(if_expr)
     }
     
+    /*
+        match-expression ::= "match" expression ( "case" case-pattern ( "if" expression )? "then" ( statement )* )+ "end" ;
+        
+        See parser/pattern.rs for how case patterns are converted from expressions.
+    */
+    fn parse_match_expr(&mut self, ctx: &mut ErrorContext) -> ParseResult<Expr> {
+        let next = self.advance()?;
+        
+        ctx.push(ContextTag::MatchExpr);
+        ctx.set_start(&next);
+        
+        debug_assert!(matches!(next.token, Token::Match));
+        
+        let subject = self.parse_expr_variant(ctx)?;
+        
+        let wildcard = self.intern_str("_");
+        let mut cases = Vec::new();
+        
+        loop {
+            let next = self.advance()?;
+            ctx.set_end(&next);
+            
+            match next.token {
+                Token::Case => { },
+                Token::End if !cases.is_empty() => break,
+                _ => return Err("expected \"case\" in match-expression".into()),
+            }
+            
+            ctx.push(ContextTag::Pattern);
+            let pattern = self.parse_expr_variant(ctx)?;
+            let pattern = Pattern::try_from_case(pattern, wildcard)
+                .map_err(|_| ParserError::from("invalid case pattern"))?;
+            ctx.pop_extend();
+            
+            let guard =
+                if matches!(self.peek()?.token, Token::If) {
+                    ctx.set_end(&self.advance().unwrap());
+                    Some(self.parse_expr_variant(ctx)?)
+                } else { None };
+            
+            let next = self.advance()?;
+            ctx.set_end(&next);
+            
+            if !matches!(next.token, Token::Then) {
+                return Err("expected \"then\" after case pattern".into());
+            }
+            
+            let stmt_list = self.parse_stmt_list(ctx, |token| matches!(token, Token::Case | Token::End))?;
+            cases.push(MatchCase::new(pattern, guard, ExprBlock::from(stmt_list)));
+        }
+        
+        ctx.pop_extend();
+        
+        let match_expr = Expr::Match {
+            subject: Box::new(subject),
+            cases: cases.into_boxed_slice(),
+        };
+        Ok(match_expr)
+    }
+    
+    /*
+        try-expression ::= "try" ( statement )* ( "catch" IDENTIFIER ( statement )* )? ( "finally" ( statement )* )? "end" ;
+    */
+    fn parse_try_expr(&mut self, ctx: &mut ErrorContext) -> ParseResult<Expr> {
+        let next = self.advance()?;
+        
+        ctx.push(ContextTag::TryExpr);
+        ctx.set_start(&next);
+        
+        debug_assert!(matches!(next.token, Token::Try));
+        
+        let stmt_list = self.parse_stmt_list(ctx, |token| matches!(token, Token::Catch | Token::Finally | Token::End))?;
+        let suite = ExprBlock::from(stmt_list);
+        
+        let mut catch = None;
+        let mut finally = None;
+        
+        let mut next = self.advance().unwrap();
+        ctx.set_end(&next);
+        
+        if let Token::Catch = next.token {
+            let name_token = self.advance()?;
+            ctx.set_end(&name_token);
+            
+            let name = match name_token.token {
+                Token::Identifier(name) => self.intern_str(name),
+                _ => return Err("expected a name for the error after \"catch\"".into()),
+            };
+            
+            let stmt_list = self.parse_stmt_list(ctx, |token| matches!(token, Token::Finally | Token::End))?;
+            catch.replace(CatchClause::new(name, ExprBlock::from(stmt_list)));
+            
+            next = self.advance().unwrap();
+            ctx.set_end(&next);
+        }
+        
+        if let Token::Finally = next.token {
+            let stmt_list = self.parse_stmt_list(ctx, |token| matches!(token, Token::End))?;
+            finally.replace(stmt_list);
+            
+            ctx.set_end(&self.advance().unwrap()); // consume "end"
+        }
+        
+        if catch.is_none() && finally.is_none() {
+            return Err("try-expression requires a \"catch\" or \"finally\" clause".into());
+        }
+        
+        ctx.pop_extend();
+        
+        let try_expr = Expr::Try {
+            suite: Box::new(suite),
+            catch: catch.map(Box::new),
+            finally: finally.map(Box::new),
+        };
+        Ok(try_expr)
+    }
+    
     fn parse_function_decl_expr(&mut self, ctx: &mut ErrorContext) -> ParseResult<Expr> {
         let next = self.advance()?;
         
@@ -953,6 +1253,100 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
         }
     }
     
+    /*
+        lambda-expression ::= "|" ( lambda-param ( "," lambda-param )* )? "|" expression ;
+        lambda-param ::= ( "var" | "let" )? IDENTIFIER ;
+        
+        Shorthand for an anonymous function whose body is a single expression, e.g. "|x| x + 1".
+        Since "|" is also used in type expressions, lambda parameters can't have annotations.
+    */
+    fn parse_lambda_expr(&mut self, ctx: &mut ErrorContext) -> ParseResult<Expr> {
+        let next = self.advance()?;
+        
+        ctx.push(ContextTag::LambdaExpr);
+        ctx.set_start(&next);
+        debug_assert!(matches!(next.token, Token::OpOr));
+        
+        let mut required: Vec<ParamDef> = Vec::new();
+        
+        if matches!(self.peek()?.token, Token::OpOr) {
+            ctx.set_end(&self.advance().unwrap());
+        } else {
+            loop {
+                let next = self.peek()?;
+                
+                ctx.push(ContextTag::FunParam);
+                ctx.set_start(next);
+                
+                let mode = match next.token {
+                    Token::Var => Some(Access::ReadWrite),
+                    Token::Let => Some(Access::ReadOnly),
+                    _ => None,
+                };
+                
+                if mode.is_some() {
+                    ctx.set_end(&self.advance().unwrap());
+                }
+                
+                let next = self.advance()?;
+                ctx.set_end(&next);
+                
+                let name = 
+                    if let Token::Identifier(name) = next.token { name }
+                    else { return Err("invalid parameter".into()); };
+                
+                let name = self.intern_str(name);
+                
+                if required.iter().any(|param| param.name == name) {
+                    return Err("duplicate parameter name".into());
+                }
+                
+                let mode = mode.unwrap_or(Access::ReadOnly);
+                required.push(ParamDef { name, mode, annotation: None });
+                
+                ctx.pop_extend();
+                
+                // expect either a comma "," or the closing "|"
+                let next = self.advance()?;
+                ctx.set_end(&next);
+                match next.token {
+                    Token::Comma => { },
+                    Token::OpOr => break,
+                    _ => return Err("expected closing \"|\" after parameter list".into()),
+                }
+            }
+        }
+        
+        // lambda body
+        
+        self.yields.push(false);
+        let body = self.parse_inner_expr_meta(ctx);
+        let generator = self.yields.pop().unwrap();
+        
+        let (body, symbol) = body?.take();
+        let body = StmtList::new(vec![ StmtMeta::new(Stmt::Expression(body), symbol) ], None);
+        
+        let signature = SignatureDef {
+            name: None,
+            required: required.into_boxed_slice(),
+            default: Box::new([]),
+            variadic: None,
+            keyword: Box::new([]),
+            keyword_default: Box::new([]),
+            kwargs: None,
+        };
+        
+        let fundef = FunctionDef {
+            signature,
+            return_type: None,
+            body: Box::new(ExprBlock::from(body)),
+            generator,
+        };
+        
+        ctx.pop_extend();
+        Ok(Expr::FunctionDef(fundef))
+    }
+    
     // similar to parse_primary(), except we only allow member access and index access, and convert to an Pattern after
     fn parse_function_assignment_target(&mut self, ctx: &mut ErrorContext) -> ParseResult<Pattern> {
         ctx.push(ContextTag::PrimaryExpr);
@@ -1002,14 +1396,35 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
             return Err("expected closing \")\" after parameter list".into());
         }
         
+        // return type annotation
+        
+        let mut return_type = None;
+        if matches!(self.peek()?.token, Token::Arrow) {
+            ctx.set_end(&self.advance().unwrap());
+            
+            return_type.replace(self.parse_type_expr(ctx)?);
+            
+            let next = self.advance()?;
+            ctx.set_end(&next);
+            if !matches!(next.token, Token::Colon) {
+                return Err("expected \":\" after return type".into());
+            }
+        }
+        
         // function body
         
-        let body = self.parse_stmt_list(ctx, |token| matches!(token, Token::End))?;
+        self.yields.push(false);
+        let body = self.parse_stmt_list(ctx, |token| matches!(token, Token::End));
+        let generator = self.yields.pop().unwrap();
+        
+        let body = body?;
         ctx.set_end(&self.advance().unwrap()); // consume "end"
         
         let fundef = FunctionDef {
             signature,
+            return_type,
             body: Box::new(ExprBlock::from(body)),
+            generator,
         };
         
         Ok(fundef)
@@ -1020,6 +1435,12 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
         let mut required = Vec::new();
         let mut default = Vec::new();
         let mut variadic = None;
+        let mut keyword = Vec::new();
+        let mut keyword_default = Vec::new();
+        let mut kwargs = None;
+        
+        // parameters after a variadic parameter or a bare "..." can only be passed by name
+        let mut keyword_only = false;
 
         loop {
             let next = self.peek()?;
@@ -1031,6 +1452,25 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
             ctx.push(ContextTag::FunParam);
             ctx.set_start(next);
             
+            // bare "..." separator
+            
+            if matches!(next.token, Token::Ellipsis) {
+                if keyword_only {
+                    return Err("only one variadic parameter or \"...\" is allowed before keyword-only parameters".into());
+                }
+                keyword_only = true;
+                ctx.set_end(&self.advance().unwrap());
+                
+                let next = self.advance()?;
+                ctx.set_end(&next);
+                if !matches!(next.token, Token::Comma) || matches!(self.peek()?.token, Token::CloseParen) {
+                    return Err("expected keyword-only parameters after \"...\"".into());
+                }
+                
+                ctx.pop_extend();
+                continue;
+            }
+            
             // mutability modifier
             
             let mode = match next.token {
@@ -1055,6 +1495,15 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
             
             let name = self.intern_str(name);
             
+            let is_duplicate = required.iter().chain(variadic.iter()).chain(keyword.iter()).chain(kwargs.iter())
+                .map(|param: &ParamDef| param.name)
+                .chain(default.iter().chain(keyword_default.iter()).map(|param: &DefaultDef| param.name))
+                .any(|other| other == name);
+            
+            if is_duplicate {
+                return Err("duplicate parameter name".into());
+            }
+            
             // possibly variadic
             
             let next = self.peek()?;
@@ -1063,6 +1512,9 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
                 ctx.set_end(&self.advance().unwrap());
             }
             
+            // possible type annotation
+            let annotation = self.try_parse_annotation(ctx)?;
+            
             // possible default value
             let next = self.peek()?;
             let default_value = match next.token {
@@ -1073,7 +1525,7 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
                 Token::OpAssign => {
                     ctx.set_end(&self.advance().unwrap());
                     
-                    Some(Box::new(self.parse_expr(ctx)?))
+                    Some(Box::new(self.parse_inner_expr_meta(ctx)?))
                 },
                 
                 _ => None,
@@ -1081,35 +1533,45 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
             
             // expect either a comma "," or the closing ")"
             let next = self.peek()?;
+            if !matches!(next.token, Token::Comma | Token::CloseParen) {
+                return Err("invalid parameter".into());
+            }
+            let is_last = matches!(next.token, Token::CloseParen);
 
             let mode = mode.unwrap_or(Access::ReadOnly);
-            match next.token {
-                // variadic parameter
-                Token::Comma if is_variadic => {
-                    return Err("a variadic parameter must appear last in the parameter list".into());
+            
+            if is_variadic && keyword_only {
+                // collects any keyword arguments that don't match another parameter
+                if !is_last {
+                    return Err("a parameter that collects keyword arguments must appear last in the parameter list".into());
                 }
+                if annotation.is_some() {
+                    return Err("a parameter that collects keyword arguments can't have a type annotation".into());
+                }
+                kwargs.replace(ParamDef { name, mode, annotation });
                 
-                Token::CloseParen if is_variadic => {
-                    debug_assert!(default_value.is_none());
-                    variadic.replace(ParamDef { name, mode });
-                },
+            } else if is_variadic {
+                variadic.replace(ParamDef { name, mode, annotation });
+                keyword_only = true;
                 
-                // normal parameter
-                Token::Comma | Token::CloseParen if !is_variadic => {
-                    if let Some(default_expr) = default_value {
-                        default.push(DefaultDef { name, mode, default: default_expr });
-                    } else {
-                        if !default.is_empty() {
-                            return Err("cannot have a non-default parameter after a default parameter".into());
-                        }
-                        required.push(ParamDef { name, mode });
-                    }
-                },
+            } else if keyword_only {
+                if let Some(default_expr) = default_value {
+                    keyword_default.push(DefaultDef { name, mode, annotation, default: default_expr });
+                } else {
+                    keyword.push(ParamDef { name, mode, annotation });
+                }
                 
-                _ => return Err("invalid parameter".into()),
+            } else if let Some(default_expr) = default_value {
+                default.push(DefaultDef { name, mode, annotation, default: default_expr });
+            
+            } else {
+                if !default.is_empty() {
+                    return Err("cannot have a non-default parameter after a default parameter".into());
+                }
+                required.push(ParamDef { name, mode, annotation });
             }
             
-            if matches!(next.token, Token::Comma) {
+            if !is_last {
                 ctx.set_end(&self.advance().unwrap());
             }
             
@@ -1121,25 +1583,202 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
             required: required.into_boxed_slice(),
             default: default.into_boxed_slice(),
             variadic,
+            keyword: keyword.into_boxed_slice(),
+            keyword_default: keyword_default.into_boxed_slice(),
+            kwargs,
         };
         
         Ok(signature)
     }
     
     /*
-        Object Constructor syntax:
-        
-        object-constructor ::= "{" member-initializer ( "," member-initializer )* "}" ;
-        member-initializer ::= ( IDENTIFIER | "[" primary "]" ) ":" expression ;
-    
+        class-def ::= "class" ( IDENTIFIER )? ( "(" expression ")" )? ( class-member )* "end" ;
+        class-member ::= field-def | method-def ;
+        field-def ::= ( "let" | "var" ) IDENTIFIER ( "=" expression )? ;
+        method-def ::= "fun" IDENTIFIER "(" parameter-list ")" statement-list "end" ;
     */
-    fn parse_table_expr(&mut self, ctx: &mut ErrorContext) -> ParseResult<Expr> {
-        let items = self.parse_table_literal(ctx)?;
-        Ok(Expr::Table(items.into_boxed_slice()))
-    }
-    
-    fn parse_table_literal(&mut self, ctx: &mut ErrorContext) -> ParseResult<Vec<TableItem>> {
-        ctx.push(ContextTag::TableCtor);
+    fn parse_class_decl_expr(&mut self, ctx: &mut ErrorContext) -> ParseResult<Expr> {
+        let next = self.advance()?;
+        
+        ctx.push(ContextTag::ClassDefExpr);
+        ctx.set_start(&next);
+        debug_assert!(matches!(next.token, Token::Class));
+        
+        let mut name = None;
+        if let Token::Identifier(..) = self.peek()?.token {
+            let next = self.advance().unwrap();
+            ctx.set_end(&next);
+            if let Token::Identifier(ident) = next.token {
+                name.replace(self.intern_str(ident));
+            }
+        }
+        
+        // optional base class
+        let mut base = None;
+        if let Token::OpenParen = self.peek()?.token {
+            ctx.set_end(&self.advance().unwrap());
+            
+            base.replace(Box::new(self.parse_expr(ctx)?));
+            
+            let next = self.advance()?;
+            ctx.set_end(&next);
+            if !matches!(next.token, Token::CloseParen) {
+                return Err("expected closing \")\" after base class".into());
+            }
+        }
+        
+        let mut fields = Vec::new();
+        let mut methods = Vec::new();
+        
+        loop {
+            let next = self.peek()?;
+            match next.token {
+                Token::Semicolon => { self.advance().unwrap(); },
+                
+                Token::End => break,
+                
+                Token::Fun | Token::Var | Token::Let => {
+                    let is_method = matches!(next.token, Token::Fun);
+                    
+                    // each member gets its own context, so that an error in one member doesn't discard the class
+                    let mut member_ctx = ErrorContext::new(ContextTag::ClassMember);
+                    let result = 
+                        if is_method { self.parse_method_def(&mut member_ctx).map(|method| methods.push(method)) }
+                        else { self.parse_field_def(&mut member_ctx).map(|field| fields.push(field)) };
+                    
+                    match result {
+                        Ok(()) => ctx.frame_mut().extend(member_ctx.take()),
+                        Err(error) => self.synchronize_class_member(member_ctx, error)?,
+                    }
+                },
+                
+                _ => return Err("expected a field or method definition".into()),
+            }
+        }
+        
+        ctx.set_end(&self.advance().unwrap()); // consume "end"
+        ctx.pop_extend();
+        
+        let class_def = ClassDef {
+            name, base,
+            fields: fields.into_boxed_slice(),
+            methods: methods.into_boxed_slice(),
+        };
+        
+        // SYNTACTIC SUGAR: class Name ... end => let Name = class ... end
+        if let Some(name) = name {
+            let class_decl = Assignment {
+                action: MatchAction::DeclImmutable,
+                op: None,
+                lhs: Pattern::Identifier(name),
+                rhs: Expr::ClassDef(class_def),
+            };
+            
+            Ok(Expr::Assignment(Box::new(class_decl)))
+        
+        } else {
+            
+            Ok(Expr::ClassDef(class_def))
+        }
+    }
+    
+    // Report an error in a class member, then skip ahead to where the next member or the end of the class might be
+    fn synchronize_class_member(&mut self, ctx: ErrorContext, error: ParserError) -> ParseResult<()> {
+        if matches!(error.kind(), ErrorKind::EndofTokenStream) {
+            return Err(error);
+        }
+        
+        self.errors.push_back(Self::process_error(ctx, error));
+        
+        loop {
+            match self.peek() {
+                Err(error) if matches!(error.kind(), ErrorKind::EndofTokenStream) => break,
+                Err(..) => continue,  // peek will consume errors
+                
+                Ok(TokenMeta { token: Token::Fun | Token::Var | Token::Let | Token::End | Token::EOF, .. }) => break,
+                Ok(..) => { self.advance().unwrap(); },
+            }
+        }
+        Ok(())
+    }
+    
+    fn parse_field_def(&mut self, ctx: &mut ErrorContext) -> ParseResult<FieldDef> {
+        ctx.push(ContextTag::ClassMember);
+        
+        let next = self.advance().unwrap();
+        ctx.set_start(&next);
+        
+        let mode = match next.token {
+            Token::Var => Access::ReadWrite,
+            Token::Let => Access::ReadOnly,
+            _ => unreachable!(),
+        };
+        
+        let next = self.advance()?;
+        ctx.set_end(&next);
+        
+        let name = if let Token::Identifier(name) = next.token {
+            self.intern_str(name)
+        } else {
+            return Err("expected a field name".into());
+        };
+        
+        let mut default = None;
+        if let Token::OpAssign = self.peek()?.token {
+            ctx.set_end(&self.advance().unwrap());
+            default.replace(self.parse_expr(ctx)?);
+        }
+        
+        ctx.pop_extend();
+        Ok(FieldDef { name, mode, default })
+    }
+    
+    fn parse_method_def(&mut self, ctx: &mut ErrorContext) -> ParseResult<MethodDef> {
+        ctx.push(ContextTag::ClassMember);
+        
+        let next = self.advance().unwrap();
+        ctx.set_start(&next);
+        debug_assert!(matches!(next.token, Token::Fun));
+        
+        let next = self.advance()?;
+        ctx.set_end(&next);
+        
+        let name = if let Token::Identifier(name) = next.token {
+            self.intern_str(name)
+        } else {
+            return Err("expected a method name".into());
+        };
+        
+        let mut fundef = self.parse_function_def(ctx)?;
+        
+        // methods receive the instance they are bound to as an implicit first parameter
+        let receiver = ParamDef { name: self.intern_str("self"), mode: Access::ReadOnly, annotation: None };
+        let mut required = fundef.signature.required.into_vec();
+        required.insert(0, receiver);
+        
+        fundef.signature.required = required.into_boxed_slice();
+        fundef.signature.name.replace(name);
+        
+        let symbol = ctx.frame().as_debug_symbol().unwrap();
+        ctx.pop_extend();
+        
+        Ok(MethodDef { fundef, symbol })
+    }
+    
+    /*
+        Object Constructor syntax:
+        
+        object-constructor ::= "{" member-initializer ( "," member-initializer )* "}" ;
+        member-initializer ::= ( IDENTIFIER | "[" primary "]" ) ":" expression ;
+    
+    */
+    fn parse_table_expr(&mut self, ctx: &mut ErrorContext) -> ParseResult<Expr> {
+        let items = self.parse_table_literal(ctx)?;
+        Ok(Expr::Table(items.into_boxed_slice()))
+    }
+    
+    fn parse_table_literal(&mut self, ctx: &mut ErrorContext) -> ParseResult<Vec<TableItem>> {
+        ctx.push(ContextTag::TableCtor);
         
         let next = self.advance().unwrap();
         ctx.set_start(&next);
@@ -1155,6 +1794,8 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
             
             let field = self.parse_table_field(ctx)?;
             
+            let annotation = self.try_parse_annotation(ctx)?;
+            
             let next = self.advance()?;
             ctx.set_end(&next);
             if !matches!(next.token, Token::OpAssign) {
@@ -1171,7 +1812,7 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
                 ExprMeta::new(variant, symbol)
             };
             
-            items.push(TableItem { field, value: expr });
+            items.push(TableItem { field, annotation, value: expr });
             
             let next = self.peek()?;
             if matches!(next.token, Token::Comma) {
@@ -1190,6 +1831,321 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
         Ok(items)
     }
     
+    /*
+        List and dict literals:
+        
+        list-literal ::= "[" "]" | "[" expression ( "," expression )* ( "," )? "]" ;
+        dict-literal ::= "[" ":" "]" | "[" dict-item ( "," dict-item )* ( "," )? "]" ;
+        dict-item ::= expression ":" expression ;
+        
+        list-comprehension ::= "[" expression comprehension-clauses "]" ;
+        dict-comprehension ::= "[" dict-item comprehension-clauses "]" ;
+        
+        Whether the literal is a list or a dict is decided by the first item.
+    */
+    fn parse_collection_expr(&mut self, ctx: &mut ErrorContext) -> ParseResult<Expr> {
+        ctx.push(ContextTag::CollectionCtor);
+        
+        let next = self.advance().unwrap();
+        ctx.set_start(&next);
+        debug_assert!(matches!(next.token, Token::OpenSquare));
+        
+        let next = self.peek()?;
+        match next.token {
+            Token::CloseSquare => {
+                ctx.set_end(&self.advance().unwrap());
+                ctx.pop_extend();
+                return Ok(Expr::List(Box::new([])));
+            }
+            
+            Token::Colon => {
+                ctx.set_end(&self.advance().unwrap());
+                
+                let next = self.advance()?;
+                ctx.set_end(&next);
+                if !matches!(next.token, Token::CloseSquare) {
+                    return Err("expected closing \"]\" after \":\" in empty dict".into());
+                }
+                ctx.pop_extend();
+                return Ok(Expr::Dict(Box::new([])));
+            }
+            
+            _ => { }
+        }
+        
+        let first = self.parse_inner_expr_meta(ctx)?;
+        
+        let expr = if matches!(self.peek()?.token, Token::Colon) {
+            self.parse_dict_items(ctx, first)?
+        } else if matches!(self.peek()?.token, Token::For) {
+            self.parse_list_comprehension(ctx, first)?
+        } else {
+            self.parse_list_items(ctx, first)?
+        };
+        
+        let next = self.advance()?;
+        ctx.set_end(&next);
+        
+        if !matches!(next.token, Token::CloseSquare) {
+            return Err("expected closing \"]\"".into());
+        }
+        
+        ctx.pop_extend();
+        Ok(expr)
+    }
+    
+    fn parse_list_items(&mut self, ctx: &mut ErrorContext, first: ExprMeta) -> ParseResult<Expr> {
+        let mut items = vec![ first ];
+        
+        loop {
+            let next = self.peek()?;
+            if !matches!(next.token, Token::Comma) {
+                break;
+            }
+            ctx.set_end(&self.advance().unwrap());
+            
+            // allow trailing comma
+            if matches!(self.peek()?.token, Token::CloseSquare) {
+                break;
+            }
+            
+            items.push(self.parse_inner_expr_meta(ctx)?);
+        }
+        
+        Ok(Expr::List(items.into_boxed_slice()))
+    }
+    
+    fn parse_dict_items(&mut self, ctx: &mut ErrorContext, first_key: ExprMeta) -> ParseResult<Expr> {
+        let mut items = Vec::new();
+        let mut key = first_key;
+        
+        loop {
+            let next = self.advance()?;
+            ctx.set_end(&next);
+            if !matches!(next.token, Token::Colon) {
+                return Err("missing \":\" in dict item".into())
+            }
+            
+            let value = self.parse_inner_expr_meta(ctx)?;
+            
+            if items.is_empty() && matches!(self.peek()?.token, Token::For) {
+                return self.parse_dict_comprehension(ctx, DictItem { key, value });
+            }
+            
+            items.push(DictItem { key, value });
+            
+            let next = self.peek()?;
+            if !matches!(next.token, Token::Comma) {
+                break;
+            }
+            ctx.set_end(&self.advance().unwrap());
+            
+            // allow trailing comma
+            if matches!(self.peek()?.token, Token::CloseSquare) {
+                break;
+            }
+            
+            key = self.parse_inner_expr_meta(ctx)?;
+        }
+        
+        Ok(Expr::Dict(items.into_boxed_slice()))
+    }
+    
+    /*
+        Comprehensions:
+        
+        comprehension-clauses ::= for-clause ( for-clause | if-clause )* ;
+        for-clause ::= "for" lvalue-list "in" expression ;
+        if-clause ::= "if" expression ;
+        
+        Comprehensions are desugared into an anonymous function that is called immediately with
+        the iterable of the first "for" clause, so that they get their own scope and capture names
+        from the enclosing scope like any other closure. Generator expressions (and list and tuple 
+        comprehensions, which unpack one) compile to a generator function that yields each item.
+    */
+    fn parse_comprehension_clauses(&mut self, ctx: &mut ErrorContext) -> ParseResult<Vec<ComprehensionClause>> {
+        ctx.push(ContextTag::Comprehension);
+        ctx.set_start(self.peek()?);
+        
+        let mut clauses = Vec::new();
+        loop {
+            let next = self.peek()?;
+            match next.token {
+                Token::For => {
+                    ctx.set_end(&self.advance().unwrap());
+                    
+                    let pattern = self.parse_lvalue_list(ctx)?;
+                    
+                    let next = self.advance()?;
+                    ctx.set_end(&next);
+                    if !matches!(next.token, Token::In) {
+                        return Err("expected \"in\" after \"for\" in comprehension".into());
+                    }
+                    
+                    let iter = self.parse_inner_expr(ctx)?;
+                    clauses.push(ComprehensionClause::For(pattern, iter));
+                }
+                
+                Token::If => {
+                    ctx.set_end(&self.advance().unwrap());
+                    
+                    let condition = self.parse_inner_expr(ctx)?;
+                    clauses.push(ComprehensionClause::If(condition));
+                }
+                
+                _ => break,
+            }
+        }
+        
+        ctx.pop_extend();
+        Ok(clauses)
+    }
+    
+    fn parse_list_comprehension(&mut self, ctx: &mut ErrorContext, item: ExprMeta) -> ParseResult<Expr> {
+        let clauses = self.parse_comprehension_clauses(ctx)?;
+        let symbol = ctx.frame().as_debug_symbol().unwrap();
+        
+        let generator = self.build_generator_expr(clauses, item.take_variant(), symbol);
+        let unpack = Expr::Unpack(Some(Box::new(generator)));
+        Ok(Expr::List(Box::new([ ExprMeta::new(unpack, symbol) ])))
+    }
+    
+    fn parse_dict_comprehension(&mut self, ctx: &mut ErrorContext, item: DictItem) -> ParseResult<Expr> {
+        let clauses = self.parse_comprehension_clauses(ctx)?;
+        let symbol = ctx.frame().as_debug_symbol().unwrap();
+        
+        // let $dict = [:]; (clauses...) $dict[key] = value; $dict
+        let dict_name = self.intern_str("$dict");
+        
+        let decl = Assignment {
+            lhs: Pattern::Identifier(dict_name),
+            action: MatchAction::DeclImmutable,
+            op: None,
+            rhs: Expr::Dict(Box::new([])),
+        };
+        
+        let insert = Assignment {
+            lhs: Pattern::Index(Box::new(IndexPattern {
+                receiver: Primary::new(Atom::Identifier(dict_name), Vec::new()),
+                index: item.key,
+            })),
+            action: MatchAction::AssignLocal,
+            op: None,
+            rhs: item.value.take_variant(),
+        };
+        let insert = Stmt::Expression(Expr::Assignment(Box::new(insert)));
+        
+        let (iter, for_loop) = self.build_comprehension_loop(clauses, insert, symbol);
+        
+        let body = vec![
+            StmtMeta::new(Stmt::Expression(Expr::Assignment(Box::new(decl))), symbol),
+            StmtMeta::new(for_loop, symbol),
+            StmtMeta::new(Stmt::Expression(Expr::Atom(Atom::Identifier(dict_name))), symbol),
+        ];
+        
+        let fundef = self.build_comprehension_fun(body, false);
+        Ok(Self::build_immediate_call(fundef, iter, symbol))
+    }
+    
+    // generator expressions and tuple comprehensions
+    fn parse_group_comprehension(&mut self, ctx: &mut ErrorContext, item: Expr) -> ParseResult<Expr> {
+        let clauses = self.parse_comprehension_clauses(ctx)?;
+        let symbol = ctx.frame().as_debug_symbol().unwrap();
+        
+        let generator = self.build_generator_expr(clauses, item, symbol);
+        
+        // a trailing comma makes a tuple, just like it does for a tuple with a single item
+        if matches!(self.peek()?.token, Token::Comma) {
+            ctx.set_end(&self.advance().unwrap());
+            
+            let unpack = Expr::Unpack(Some(Box::new(generator)));
+            return Ok(Expr::Tuple(Box::new([ ExprMeta::new(unpack, symbol) ])));
+        }
+        
+        Ok(generator)
+    }
+    
+    fn build_generator_expr(&mut self, clauses: Vec<ComprehensionClause>, item: Expr, symbol: DebugSymbol) -> Expr {
+        let (iter, for_loop) = self.build_comprehension_loop(clauses, Stmt::Yield(Some(item)), symbol);
+        
+        let fundef = self.build_comprehension_fun(vec![ StmtMeta::new(for_loop, symbol) ], true);
+        Self::build_immediate_call(fundef, iter, symbol)
+    }
+    
+    // nests the innermost statement inside of the comprehension clauses, from the last clause to the first
+    // returns the iterable of the first "for" clause, which is replaced by the comprehension function's parameter
+    fn build_comprehension_loop(&mut self, clauses: Vec<ComprehensionClause>, inner: Stmt, symbol: DebugSymbol) -> (Expr, Stmt) {
+        let iter_name = self.intern_str("$iter");
+        
+        let mut clauses = clauses.into_iter();
+        let (pattern, iter) = match clauses.next() {
+            Some(ComprehensionClause::For(pattern, iter)) => (pattern, iter),
+            _ => unreachable!("comprehension must start with a \"for\" clause"),
+        };
+        
+        let mut stmt = inner;
+        for clause in clauses.rev() {
+            let body = StmtList::new(vec![ StmtMeta::new(stmt, symbol) ], None);
+            stmt = match clause {
+                ComprehensionClause::For(pattern, iter) => Stmt::ForLoop {
+                    label: None, pattern, iter, body,
+                },
+                
+                ComprehensionClause::If(condition) => {
+                    let branch = ConditionalBranch::new(condition, ExprBlock::from(body));
+                    Stmt::Expression(Expr::IfExpr {
+                        branches: Box::new([ branch ]),
+                        else_clause: None,
+                    })
+                }
+            };
+        }
+        
+        let for_loop = Stmt::ForLoop {
+            label: None,
+            pattern,
+            iter: Expr::Atom(Atom::Identifier(iter_name)),
+            body: StmtList::new(vec![ StmtMeta::new(stmt, symbol) ], None),
+        };
+        
+        (iter, for_loop)
+    }
+    
+    fn build_comprehension_fun(&mut self, body: Vec<StmtMeta>, generator: bool) -> FunctionDef {
+        let param = ParamDef {
+            name: self.intern_str("$iter"),
+            mode: Access::ReadOnly,
+            annotation: None,
+        };
+        
+        let signature = SignatureDef {
+            name: None,
+            required: Box::new([ param ]),
+            default: Box::new([]),
+            variadic: None,
+            keyword: Box::new([]),
+            keyword_default: Box::new([]),
+            kwargs: None,
+        };
+        
+        FunctionDef {
+            signature,
+            return_type: None,
+            body: Box::new(ExprBlock::from(StmtList::new(body, None))),
+            generator,
+        }
+    }
+    
+    fn build_immediate_call(fundef: FunctionDef, arg: Expr, symbol: DebugSymbol) -> Expr {
+        let callee = Atom::Group {
+            modifier: None,
+            inner: Box::new(Expr::FunctionDef(fundef)),
+            annotation: None,
+        };
+        let args = Box::new([ ExprMeta::new(arg, symbol) ]);
+        Expr::Primary(Primary::new(callee, vec![ AccessItem::Invoke(args, Box::new([])) ]))
+    }
+    
     fn parse_table_field(&mut self, ctx: &mut ErrorContext) -> ParseResult<TableField> {
         let next = self.peek()?;
         if let Token::OpenSquare = next.token {
@@ -1259,8 +2215,8 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
                 Token::OpenSquare => 
                     items.push(self.parse_index_access(ctx)?),
                                 
-                // invocation ::= "(" ")" | "(" argument ( "," argument )* ")" ; 
-                // argument ::= expression ( "..." )? ;  (* "..." is for argument unpacking syntax *)
+                // invocation ::= "(" ")" | "(" argument ( "," argument )* ( "," )? ")" ; 
+                // argument ::= expression ( "..." )? | IDENTIFIER "=" expression ;  (* "..." is for argument unpacking syntax *)
                 // invocations are not allowed to be on a separate line from the invocation receiver
                 Token::OpenParen if !next.newline => 
                     items.push(self.parse_invocation(ctx)?),
@@ -1339,49 +2295,90 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
         debug_assert!(matches!(next.token, Token::OpenParen));
         
         let mut args = Vec::new();
+        let mut kwargs = Vec::<KeywordArg>::new();
         
-        // check for empty argument list
-        if matches!(self.peek()?.token, Token::CloseParen) {
+        loop {
+            if matches!(self.peek()?.token, Token::CloseParen) {
+                break;
+            }
             
-            ctx.set_end(&self.advance().unwrap());
+            ctx.push(ContextTag::ExprMeta);
+            let assign = self.try_parse_assign_keyword(ctx)?;
+            let expr = self.parse_inner_expr(ctx)?;
             
-        } else {
-        
-            // we use the fact that argument lists parse very similarly to tuples
-            // parse a single ExprMeta. If the variant is a Tuple (not a group containing a tuple!),
-            // that tuple's contents becomes the argument list.
+            // a keyword argument looks like an assignment to a plain identifier
+            let is_keyword = assign.is_none()
+                && matches!(expr, Expr::Atom(Atom::Identifier(..))) 
+                && matches!(self.peek()?.token, Token::OpAssign);
             
-            let (expr, symbol) = self.parse_expr(ctx)?.take();
+            if let (true, Expr::Atom(Atom::Identifier(name))) = (is_keyword, &expr) {
+                let name = *name;
+                ctx.set_end(&self.advance().unwrap()); // consume "="
+                
+                if kwargs.iter().any(|kwarg| kwarg.name == name) {
+                    return Err("duplicate keyword argument".into());
+                }
+                
+                let value = self.parse_inner_expr_meta(ctx)?;
+                ctx.pop_extend();
+                
+                kwargs.push(KeywordArg { name, value });
             
-            if let Expr::Tuple(items) = expr {
-                args.extend(items.into_vec().into_iter());
             } else {
+                // other assignment expressions can still be used as arguments
+                let expr = self.parse_assignment_rhs(ctx, assign, expr, true)?;
+                let symbol = ctx.frame().as_debug_symbol().unwrap();
+                ctx.pop_extend();
+                
+                if !kwargs.is_empty() {
+                    return Err("positional arguments must come before keyword arguments".into());
+                }
+                
                 args.push(ExprMeta::new(expr, symbol));
             }
             
-            // check for close paren
-            let next = self.advance()?;
-            ctx.set_end(&next);
-            if !matches!(next.token, Token::CloseParen) {
-                return Err("expected closing \")\" after argument list".into());
+            // expect either a comma "," or the closing ")"
+            let next = self.peek()?;
+            match next.token {
+                Token::Comma => ctx.set_end(&self.advance().unwrap()),
+                Token::CloseParen => break,
+                _ => return Err("expected closing \")\" after argument list".into()),
             }
         }
         
-        let invocation = AccessItem::Invoke(args.into_boxed_slice());
+        ctx.set_end(&self.advance().unwrap()); // consume ")"
+        
+        let invocation = AccessItem::Invoke(args.into_boxed_slice(), kwargs.into_boxed_slice());
         
         ctx.pop_extend();
         Ok(invocation)
     }
     
-    // atom ::= LITERAL | IDENTIFIER | "(" expression ")" ;
+    // atom ::= LITERAL | IDENTIFIER | "(" expression ")" | list-literal | dict-literal ;
     fn parse_atom(&mut self, ctx: &mut ErrorContext) -> ParseResult<Atom> { 
         
         if let Token::OpenParen = self.peek()?.token {
             Ok(self.parse_group_expr(ctx)?)  // Groups
             
+        } else if let Token::OpenSquare = self.peek()?.token {
+            // list and dict literals can be indexed or have their methods called, so they are parsed like a group
+            let collection = self.parse_collection_expr(ctx)?;
+            Ok(Atom::Group {
+                modifier: None,
+                inner: Box::new(collection),
+                annotation: None,
+            })
+            
         } else { 
             ctx.push(ContextTag::Atom);
             
+            // leave tokens that close a block for the enclosing block, so that it can recover from the error
+            let next = self.peek()?;
+            if matches!(next.token, Token::End | Token::Elif | Token::Else | Token::Case | Token::Catch | Token::Finally) {
+                ctx.set_start(next);
+                return Err("expected an expression here".into());
+            }
+            
             let next = self.advance().unwrap();
             ctx.set_start(&next);
             
@@ -1391,6 +2388,9 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
                     Atom::Identifier(self.intern_str(name))
                 },
                 
+                Token::Self_ => Atom::Self_,
+                Token::Super => Atom::Super,
+                
                 // Literals
                 Token::Nil   => Atom::Nil,
                 Token::True  => Atom::BooleanLiteral(true),
@@ -1401,9 +2401,12 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
                 Token::StringLiteral(value)   => {
                     Atom::StringLiteral(self.intern_str(value))
                 },
+                Token::InterpolatedString(segments) => {
+                    self.parse_interpolated_string(segments)?
+                },
                 
                 // Error productions
-                Token::Class | Token::Fun | Token::If | Token::Var | Token::Let | Token::Begin | Token::Label(..) => {
+                Token::Class | Token::Fun | Token::If | Token::Match | Token::Try | Token::Var | Token::Let | Token::Begin | Token::Label(..) => {
                     let name = match next.token {
                         Token::Class => "class definitions",
                         Token::Fun => "function definitions",
@@ -1431,6 +2434,60 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
         }
     }
 
+    fn parse_interpolated_string(&mut self, segments: Box<[StringSegment]>) -> ParseResult<Atom> {
+        let mut parts = Vec::new();
+        
+        for segment in segments.into_vec() {
+            let part = match segment {
+                StringSegment::Literal(text) => StringPart::Literal(self.intern_str(text)),
+                
+                StringSegment::Interpolate(interp) => {
+                    let expr = Self::parse_interpolation(&mut *self.interner, interp.tokens)?;
+                    
+                    let spec = match interp.spec {
+                        Some(spec) => {
+                            spec.parse::<FormatSpec>()
+                                .map_err(|error| ParserError::from(ErrorKind::SyntaxError(error.to_string())))?;
+                            
+                            Some(self.intern_str(spec))
+                        },
+                        None => None,
+                    };
+                    
+                    StringPart::Expr { expr, spec }
+                },
+            };
+            parts.push(part);
+        }
+        
+        if parts.is_empty() {
+            parts.push(StringPart::Literal(self.intern_str("")));
+        }
+        
+        Ok(Atom::InterpolatedString(parts.into_boxed_slice()))
+    }
+    
+    // the tokens of an interpolated expression are parsed separately from the enclosing token stream
+    fn parse_interpolation(interner: &mut StringInterner, tokens: Vec<TokenMeta>) -> ParseResult<ExprMeta> {
+        let mut ctx = ErrorContext::new(ContextTag::Interpolation);
+        if let Some(first) = tokens.first() {
+            ctx.set_start(first);
+        }
+        
+        let mut parser = Parser::new(interner, tokens.into_iter().map(Ok));
+        let result = parser.parse_expr(&mut ctx)
+            .and_then(|expr| {
+                let next = parser.advance()?;
+                if !matches!(next.token, Token::EOF) {
+                    let error = ParserError::from("unexpected token after interpolated expression");
+                    return Err(error.with_symbol(next.symbol));
+                }
+                Ok(expr)
+            });
+        
+        result.map_err(|error| error.with_error_context(ctx))
+    }
+    
     fn parse_group_expr(&mut self, ctx: &mut ErrorContext) -> ParseResult<Atom> {
         ctx.push(ContextTag::Group);
         
@@ -1452,6 +2509,11 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
         // Parse inner expression
         let mut expr = self.parse_expr_variant(ctx)?;
         
+        // generator expression or tuple comprehension
+        if modifier.is_none() && matches!(self.peek()?.token, Token::For) {
+            expr = self.parse_group_comprehension(ctx, expr)?;
+        }
+        
         // if inner expression is an assignment, transfer our modifier to it
         match (&mut expr, modifier) {
             (Expr::Assignment(assign), Some(modifier)) => {
@@ -1460,6 +2522,9 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
             _ => { },
         }
         
+        // possible type annotation
+        let annotation = self.try_parse_annotation(ctx)?.map(Box::new);
+        
         // Consume and check closing paren
         let next = self.advance()?;
         ctx.set_end(&next);
@@ -1469,9 +2534,87 @@ impl<I> Parser<'_, I> where I: Iterator<Item=Result<TokenMeta, LexerError>> {
         
         ctx.pop_extend();
         Ok(Atom::Group {
-            modifier, inner: Box::new(expr),
+            modifier, annotation, inner: Box::new(expr),
         })
     }
+    
+    /*
+        Type annotation syntax:
+        
+        type-expression ::= type-term ( "|" type-term )* ;
+        type-term ::= "nil" | IDENTIFIER ( "." IDENTIFIER )* | "(" type-expression ")" ;
+    */
+    
+    fn try_parse_annotation(&mut self, ctx: &mut ErrorContext) -> ParseResult<Option<TypeExpr>> {
+        if !matches!(self.peek()?.token, Token::Colon) {
+            return Ok(None);
+        }
+        
+        ctx.set_end(&self.advance().unwrap()); // consume ":"
+        Ok(Some(self.parse_type_expr(ctx)?))
+    }
+    
+    fn parse_type_expr(&mut self, ctx: &mut ErrorContext) -> ParseResult<TypeExpr> {
+        ctx.push(ContextTag::TypeExpr);
+        ctx.set_start(self.peek()?);
+        
+        let mut items = vec![ self.parse_type_term(ctx)? ];
+        while matches!(self.peek()?.token, Token::OpOr) {
+            ctx.set_end(&self.advance().unwrap());
+            items.push(self.parse_type_term(ctx)?);
+        }
+        
+        ctx.pop_extend();
+        
+        if items.len() == 1 {
+            Ok(items.pop().unwrap())
+        } else {
+            Ok(TypeExpr::Union(items.into_boxed_slice()))
+        }
+    }
+    
+    fn parse_type_term(&mut self, ctx: &mut ErrorContext) -> ParseResult<TypeExpr> {
+        let next = self.advance()?;
+        ctx.set_end(&next);
+        
+        let name = match next.token {
+            Token::Nil => return Ok(TypeExpr::Nil),
+            
+            Token::OpenParen => {
+                let inner = self.parse_type_expr(ctx)?;
+                
+                let next = self.advance()?;
+                ctx.set_end(&next);
+                if !matches!(next.token, Token::CloseParen) {
+                    return Err("expected closing \")\"".into());
+                }
+                return Ok(inner);
+            }
+            
+            Token::Identifier(name) => name,
+            
+            _ => return Err("expected a type".into()),
+        };
+        
+        // builtin type names can't be followed by an attribute path
+        if let Some(builtin) = BuiltinType::from_name(&name) {
+            return Ok(TypeExpr::Builtin(builtin));
+        }
+        
+        let mut path = vec![ self.intern_str(name) ];
+        while matches!(self.peek()?.token, Token::OpAccess) {
+            ctx.set_end(&self.advance().unwrap());
+            
+            let next = self.advance()?;
+            ctx.set_end(&next);
+            match next.token {
+                Token::Identifier(name) => path.push(self.intern_str(name)),
+                _ => return Err("expected an attribute name after \".\"".into()),
+            }
+        }
+        
+        Ok(TypeExpr::Class(path.into_boxed_slice()))
+    }
 
     /* Pattern Parsing */
     fn try_parse_assign_keyword(&mut self, ctx: &mut ErrorContext) -> ParseResult<Option<MatchAction>> {
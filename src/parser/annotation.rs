@@ -0,0 +1,24 @@
+use crate::language::InternSymbol;
+use crate::runtime::types::BuiltinType;
+
+
+// Type Annotations
+
+#[derive(Debug, Clone)]
+pub enum TypeExpr {
+    Nil,
+    Builtin(BuiltinType),
+    Class(Box<[InternSymbol]>),  // name, followed by an attribute path, e.g. "module.Class"
+    Union(Box<[TypeExpr]>),
+}
+
+impl TypeExpr {
+    /// True if any value satisfies this type, in which case it doesn't need to be checked
+    pub fn is_any(&self) -> bool {
+        match self {
+            Self::Builtin(BuiltinType::Any) => true,
+            Self::Union(items) => items.iter().any(Self::is_any),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use crate::language::{InternSymbol, Access};
+use crate::parser::expr::ExprMeta;
+use crate::parser::fundefs::FunctionDef;
+use crate::debug::DebugSymbol;
+
+
+// Class Definitions
+#[derive(Debug, Clone)]
+pub struct ClassDef {
+    pub name: Option<InternSymbol>,
+    pub base: Option<Box<ExprMeta>>,
+    pub fields: Box<[FieldDef]>,
+    pub methods: Box<[MethodDef]>,
+}
+
+// fields are copied into each instance when the class is called
+#[derive(Debug, Clone)]
+pub struct FieldDef {
+    pub name: InternSymbol,
+    pub mode: Access,
+    pub default: Option<ExprMeta>,
+}
+
+// the first parameter of a method's signature is always "self"
+#[derive(Debug, Clone)]
+pub struct MethodDef {
+    pub fundef: FunctionDef,
+    pub symbol: DebugSymbol,
+}
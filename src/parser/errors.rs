@@ -39,8 +39,14 @@ pub enum ContextTag {
     Expr,
     BlockExpr,
     IfExpr,
+    MatchExpr,
+    TryExpr,
     FunDefExpr,
     FunParam,
+    LambdaExpr,
+    ClassDefExpr,
+    ClassMember,
+    ImportExpr,
     AssignmentExpr,
     BinaryOpExpr,
     UnaryOpExpr,
@@ -50,10 +56,14 @@ pub enum ContextTag {
     Invocation,
     TupleCtor,
     TableCtor,
+    CollectionCtor,
+    Comprehension,
     Atom,
+    Interpolation,
     Group,
     Pattern,
     Label,
+    TypeExpr,
 }
 
 impl From<ErrorKind> for ParserError {
@@ -118,7 +128,7 @@ impl ParserError {
             self.context.replace(context.frame().context());
         }
         if self.symbol.is_none() {
-            self.symbol.replace(context.take_debug_symbol());
+            self.symbol = context.take_debug_symbol();
         }
         self
     }
@@ -208,7 +218,8 @@ impl<'m> ErrorContext {
     pub fn set_start(&mut self, token: &TokenMeta) { self.frame_mut().set_start(token) }
     pub fn set_end(&mut self, token: &TokenMeta) { self.frame_mut().set_end(token) }
     
-    pub fn take_debug_symbol(mut self) -> DebugSymbol {
+    // the innermost symbol available, if the error happened before any tokens were seen there may not be one
+    pub fn take_debug_symbol(mut self) -> Option<DebugSymbol> {
         let mut symbol = self.frame().as_debug_symbol();
         while symbol.is_none() {
             if self.stack.len() <= 1 {
@@ -220,7 +231,7 @@ impl<'m> ErrorContext {
             symbol = self.frame().as_debug_symbol();
         }
         
-        symbol.expect("could not take debug symbol")
+        symbol
     }
 }
 
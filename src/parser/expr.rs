@@ -2,8 +2,10 @@ use crate::debug::DebugSymbol;
 use crate::language::{InternSymbol, Access};
 use crate::parser::operator::{BinaryOp, UnaryOp};
 use crate::parser::primary::{Atom, Primary};
-use crate::parser::pattern::Assignment;
+use crate::parser::pattern::{Pattern, Assignment};
 use crate::parser::fundefs::FunctionDef;
+use crate::parser::classdefs::ClassDef;
+use crate::parser::annotation::TypeExpr;
 use crate::parser::stmt::{StmtMeta, Stmt, Label, StmtList};
 
 // TODO replace Vecs with boxed slices
@@ -25,6 +27,10 @@ pub enum Expr {
     
     Table(Box<[TableItem]>),
     
+    List(Box<[ExprMeta]>),
+    
+    Dict(Box<[DictItem]>),
+    
     // ObjectCtor(Box<ObjectConstructor>),
     
     IfExpr {
@@ -32,15 +38,28 @@ pub enum Expr {
         else_clause: Option<Box<ExprBlock>>,
     },
     
+    Match {
+        subject: Box<Expr>,
+        cases: Box<[MatchCase]>,
+    },
+    
     Block {
         label: Option<Label>, 
         suite: Box<ExprBlock>,
     },
     
+    Try {
+        suite: Box<ExprBlock>,
+        catch: Option<Box<CatchClause>>,
+        finally: Option<Box<StmtList>>,
+    },
+    
     FunctionDef(FunctionDef),
     
-    // ClassDef
+    ClassDef(ClassDef),
     
+    Import(InternSymbol),  // dotted module path, e.g. "foo.bar"
+
 }
 
 // Tables
@@ -54,6 +73,15 @@ pub enum TableField {
 #[derive(Debug, Clone)]
 pub struct TableItem {
     pub field: TableField,
+    pub annotation: Option<TypeExpr>,
+    pub value: ExprMeta,
+}
+
+// Dicts
+
+#[derive(Debug, Clone)]
+pub struct DictItem {
+    pub key: ExprMeta,
     pub value: ExprMeta,
 }
 
@@ -106,6 +134,49 @@ impl ConditionalBranch {
 }
 
 
+// Pattern Matching
+
+#[derive(Debug, Clone)]
+pub struct MatchCase {
+    pattern: Pattern,
+    guard: Option<Expr>,
+    suite: ExprBlock,
+}
+
+impl MatchCase {
+    pub fn new(pattern: Pattern, guard: Option<Expr>, suite: ExprBlock) -> Self {
+        Self { pattern, guard, suite }
+    }
+    
+    pub fn pattern(&self) -> &Pattern { &self.pattern }
+    pub fn guard(&self) -> Option<&Expr> { self.guard.as_ref() }
+    pub fn suite(&self) -> &ExprBlock { &self.suite }
+    
+    /// True if this case will be taken for any value
+    pub fn is_irrefutable(&self) -> bool {
+        self.guard.is_none() && self.pattern.is_irrefutable()
+    }
+}
+
+
+// Error Handling
+
+#[derive(Debug, Clone)]
+pub struct CatchClause {
+    name: InternSymbol,
+    suite: ExprBlock,
+}
+
+impl CatchClause {
+    pub fn new(name: InternSymbol, suite: ExprBlock) -> Self {
+        Self { name, suite }
+    }
+    
+    pub fn name(&self) -> &InternSymbol { &self.name }
+    pub fn suite(&self) -> &ExprBlock { &self.suite }
+}
+
+
 /// An `Expr` plus a `DebugSymbol`
 #[derive(Debug, Clone)]
 pub struct ExprMeta {
@@ -1,12 +1,15 @@
 use crate::language::{InternSymbol, Access};
 use crate::parser::expr::{ExprMeta, ExprBlock};
+use crate::parser::annotation::TypeExpr;
 
 
 // Function Definitions
 #[derive(Debug, Clone)]
 pub struct FunctionDef {
     pub signature: SignatureDef,
+    pub return_type: Option<TypeExpr>,
     pub body: Box<ExprBlock>,
+    pub generator: bool,  // the body contains "yield", so calling the function creates a generator
 }
 
 #[derive(Debug, Clone)]
@@ -15,6 +18,9 @@ pub struct SignatureDef {
     pub required: Box<[ParamDef]>,
     pub default: Box<[DefaultDef]>,
     pub variadic: Option<ParamDef>,
+    pub keyword: Box<[ParamDef]>,
+    pub keyword_default: Box<[DefaultDef]>,
+    pub kwargs: Option<ParamDef>,
 }
 
 impl SignatureDef {
@@ -22,6 +28,9 @@ impl SignatureDef {
         self.required.len()
         + self.default.len()
         + usize::from(self.variadic.is_some())
+        + self.keyword.len()
+        + self.keyword_default.len()
+        + usize::from(self.kwargs.is_some())
     }
 }
 
@@ -29,11 +38,13 @@ impl SignatureDef {
 pub struct ParamDef {
     pub name: InternSymbol,
     pub mode: Access,
+    pub annotation: Option<TypeExpr>,
 }
 
 #[derive(Debug, Clone)]
 pub struct DefaultDef {
     pub name: InternSymbol,
     pub mode: Access,
+    pub annotation: Option<TypeExpr>,
     pub default: Box<ExprMeta>,
 }
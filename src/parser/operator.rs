@@ -13,66 +13,77 @@ pub enum UnaryOp {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BinaryOp {
     // precedence level 1
-    Mul, Div, Mod,
+    Exp,
     
     // precedence level 2
-    Add, Sub,
+    Mul, Div, Mod,
     
     // precedence level 3
-    LShift, RShift,
+    Add, Sub,
     
     // precedence level 4
-    BitAnd,
+    LShift, RShift,
     
     // precedence level 5
-    BitXor,
+    BitAnd,
     
     // precedence level 6
-    BitOr,
+    BitXor,
     
     // precedence level 7
-    LT, GT, LE, GE,
+    BitOr,
     
     // precedence level 8
-    EQ, NE,
+    LT, GT, LE, GE,
     
     // precedence level 9
-    And,
+    EQ, NE,
     
     // precedence level 10
+    And,
+    
+    // precedence level 11
     Or,
 }
 
 pub type Precedence = u8;
 pub const PRECEDENCE_END: Precedence = 0; // tightest binding
-pub const PRECEDENCE_START: Precedence = 10; // weakest binding
+pub const PRECEDENCE_START: Precedence = 11; // weakest binding
 
 impl BinaryOp {
     
     pub const fn precedence_level(&self) -> Precedence {
         match self {
-            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => 1,
+            BinaryOp::Exp => 1,
+            
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => 2,
             
-            BinaryOp::Add | BinaryOp::Sub => 2,
+            BinaryOp::Add | BinaryOp::Sub => 3,
             
-            BinaryOp::LShift | BinaryOp::RShift => 3,
+            BinaryOp::LShift | BinaryOp::RShift => 4,
             
-            BinaryOp::BitAnd => 4,
-            BinaryOp::BitXor => 5,
-            BinaryOp::BitOr => 6,
+            BinaryOp::BitAnd => 5,
+            BinaryOp::BitXor => 6,
+            BinaryOp::BitOr => 7,
             
-            BinaryOp::LT | BinaryOp::GT | BinaryOp::LE | BinaryOp::GE  => 7,
-            BinaryOp::EQ | BinaryOp::NE => 8,
+            BinaryOp::LT | BinaryOp::GT | BinaryOp::LE | BinaryOp::GE  => 8,
+            BinaryOp::EQ | BinaryOp::NE => 9,
             
-            BinaryOp::And => 9,
-            BinaryOp::Or => 10,
+            BinaryOp::And => 10,
+            BinaryOp::Or => 11,
         }
     }
+    
+    // all other binary operators are left associative
+    pub const fn is_right_assoc(&self) -> bool {
+        matches!(self, BinaryOp::Exp)
+    }
 }
 
 impl fmt::Display for BinaryOp {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         let symbol = match self {
+            BinaryOp::Exp    => "**",
             BinaryOp::Mul    => "*", 
             BinaryOp::Div    => "/", 
             BinaryOp::Mod    => "%",
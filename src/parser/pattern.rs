@@ -1,8 +1,9 @@
 
 use crate::language::InternSymbol;
 use crate::parser::primary::{Primary, AccessItem, Atom};
-use crate::parser::operator::BinaryOp;
-use crate::parser::expr::{Expr, ExprMeta};
+use crate::parser::operator::{UnaryOp, BinaryOp};
+use crate::parser::expr::{Expr, ExprMeta, TableField};
+use crate::parser::annotation::TypeExpr;
 
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -22,10 +23,21 @@ pub enum Pattern {
     Tuple(Box<[Pattern]>),
     Pack(Option<Box<Pattern>>),
     
+    // only found in the case patterns of a match expression
+    Wildcard,
+    Literal(Atom),
+    Object(Box<[FieldPattern]>),
+    Alternative(Box<[Pattern]>),
+    
     Modifier {
         modifier: MatchAction,
         pattern: Box<Pattern>,
     },
+    
+    Annotated {
+        pattern: Box<Pattern>,
+        annotation: Box<TypeExpr>,
+    },
 }
 
 // Pattern Data
@@ -42,6 +54,38 @@ pub struct IndexPattern {
     pub index: ExprMeta,
 }
 
+#[derive(Debug, Clone)]
+pub struct FieldPattern {
+    pub name: InternSymbol,
+    pub pattern: Pattern,
+}
+
+impl Pattern {
+    /// True if a case pattern will match any value
+    pub fn is_irrefutable(&self) -> bool {
+        match self {
+            Self::Wildcard | Self::Identifier(..) => true,
+            Self::Alternative(alternatives) => alternatives.iter().any(Self::is_irrefutable),
+            _ => false,
+        }
+    }
+    
+    /// Collect the names that a case pattern binds to
+    pub fn bindings(&self, names: &mut Vec<InternSymbol>) {
+        match self {
+            Self::Identifier(name) => names.push(*name),
+            Self::Pack(Some(pattern)) => pattern.bindings(names),
+            Self::Object(fields) => for field in fields.iter() {
+                field.pattern.bindings(names);
+            },
+            Self::Tuple(items) | Self::Alternative(items) => for item in items.iter() {
+                item.bindings(names);
+            },
+            _ => { },
+        }
+    }
+}
+
 // Assignments
 
 #[derive(Debug, Clone)]
@@ -69,9 +113,20 @@ impl TryFrom<Atom> for Pattern {
         match atom {
             Atom::Identifier(name) => Ok(Pattern::Identifier(name)),
             
-            Atom::Group { modifier, inner } => {
-                let pattern = (*inner).try_into()?;
-
+            Atom::Group { modifier, inner, annotation } => {
+                let mut pattern = (*inner).try_into()?;
+                
+                if let Some(annotation) = annotation {
+                    // the packed items of a "..." pattern have no single type to check
+                    if matches!(pattern, Pattern::Pack(..)) {
+                        return Err(IntoPatternError);
+                    }
+                    pattern = Self::Annotated {
+                        pattern: Box::new(pattern),
+                        annotation,
+                    };
+                }
+                
                 if let Some(modifier) = modifier {
                     Ok(Self::Modifier {
                         modifier,
@@ -135,3 +190,88 @@ impl TryFrom<Expr> for Pattern {
         }
     }
 }
+
+
+// Convert expressions into case patterns...
+
+/*
+    case-pattern ::= "_" | IDENTIFIER | literal | "-" numeric-literal 
+                   | case-pattern ( "|" case-pattern )+ 
+                   | case-pattern ( "," case-pattern )* | "(" case-pattern ")" 
+                   | case-pattern "..." | "..."
+                   | "{" IDENTIFIER "=" case-pattern ( "," IDENTIFIER "=" case-pattern )* "}" ;
+*/
+
+impl Pattern {
+    /// Convert the expression following "case" into a pattern. Names bind the matched value,
+    /// except for the wildcard name which matches anything without binding it.
+    pub fn try_from_case(expr: Expr, wildcard: InternSymbol) -> Result<Self, IntoPatternError> {
+        match expr {
+            Expr::Atom(Atom::Identifier(name)) if name == wildcard => Ok(Self::Wildcard),
+            Expr::Atom(Atom::Identifier(name)) => Ok(Self::Identifier(name)),
+            
+            Expr::Atom(Atom::Group { modifier: None, inner, annotation: None }) 
+                => Self::try_from_case(*inner, wildcard),
+            
+            Expr::Atom(atom @ (
+                Atom::Nil | Atom::EmptyTuple | Atom::BooleanLiteral(..) 
+                | Atom::IntegerLiteral(..) | Atom::FloatLiteral(..) | Atom::StringLiteral(..)
+            )) => Ok(Self::Literal(atom)),
+            
+            Expr::UnaryOp(UnaryOp::Neg, operand) => match *operand {
+                Expr::Atom(Atom::IntegerLiteral(value)) => value.checked_neg()
+                    .map(|value| Self::Literal(Atom::IntegerLiteral(value)))
+                    .ok_or(IntoPatternError),
+                
+                Expr::Atom(Atom::FloatLiteral(value)) => Ok(Self::Literal(Atom::FloatLiteral(-value))),
+                
+                _ => Err(IntoPatternError),
+            },
+            
+            Expr::BinaryOp(BinaryOp::BitOr, operands) => {
+                let (lhs, rhs) = *operands;
+                
+                let mut alternatives = Vec::new();
+                for operand in [lhs, rhs] {
+                    match Self::try_from_case(operand, wildcard)? {
+                        Self::Alternative(items) => alternatives.extend(items.into_vec()),
+                        pattern => alternatives.push(pattern),
+                    }
+                }
+                
+                Ok(Self::Alternative(alternatives.into_boxed_slice()))
+            },
+            
+            Expr::Unpack(Some(expr)) => {
+                let inner = Self::try_from_case(*expr, wildcard)?;
+                Ok(Self::Pack(Some(Box::new(inner))))
+            }
+            Expr::Unpack(None) => Ok(Self::Pack(None)),
+            
+            Expr::Tuple(items) => {
+                let items = items.into_vec().into_iter()
+                    .map(|expr| Self::try_from_case(expr.take_variant(), wildcard))
+                    .collect::<Result<Vec<Pattern>, IntoPatternError>>()?;
+                
+                Ok(Self::Tuple(items.into_boxed_slice()))
+            },
+            
+            Expr::Table(items) => {
+                let mut fields = Vec::new();
+                for item in items.into_vec().into_iter() {
+                    let name = match item.field {
+                        TableField::Attribute(_, name) if item.annotation.is_none() => name,
+                        _ => return Err(IntoPatternError),
+                    };
+                    
+                    let pattern = Self::try_from_case(item.value.take_variant(), wildcard)?;
+                    fields.push(FieldPattern { name, pattern });
+                }
+                
+                Ok(Self::Object(fields.into_boxed_slice()))
+            },
+            
+            _ => Err(IntoPatternError),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use crate::language::{IntType, FloatType, InternSymbol};
 use crate::parser::expr::{ExprMeta, Expr, TableItem};
 use crate::parser::pattern::MatchAction;
+use crate::parser::annotation::TypeExpr;
 
 
 // Primary Expressions
@@ -9,30 +10,48 @@ use crate::parser::pattern::MatchAction;
 pub enum Atom {
     Nil,
     EmptyTuple,
-    // Self_,
-    // Super,
+    Self_,
+    Super,
     
     Identifier(InternSymbol),
     BooleanLiteral(bool),
     IntegerLiteral(IntType),
     FloatLiteral(FloatType),
     StringLiteral(InternSymbol),
+    InterpolatedString(Box<[StringPart]>),
     
     Group {
         modifier: Option<MatchAction>,
         inner: Box<Expr>,
+        annotation: Option<Box<TypeExpr>>,
     }
 }
 
+#[derive(Debug, Clone)]
+pub enum StringPart {
+    Literal(InternSymbol),
+    Expr {
+        expr: ExprMeta,
+        spec: Option<InternSymbol>,  // a format spec, already validated by the parser
+    },
+}
+
 // These are the highest precedence operations in the language
 #[derive(Debug, Clone)]
 pub enum AccessItem {
     Attribute(InternSymbol),
     Index(ExprMeta),
-    Invoke(Box<[ExprMeta]>),
+    Invoke(Box<[ExprMeta]>, Box<[KeywordArg]>),
     InvokeTable(Box<[TableItem]>),
 }
 
+// an argument passed by name, e.g. `f(verbose = true)`
+#[derive(Debug, Clone)]
+pub struct KeywordArg {
+    pub name: InternSymbol,
+    pub value: ExprMeta,
+}
+
 #[derive(Debug, Clone)]
 pub struct Primary {
     atom: Atom,
@@ -36,6 +36,12 @@ pub enum Stmt {
     },
     
     Assert(Expr),
+    
+    Throw(Expr),
+    
+    Yield(Option<Expr>),
+    
+    Delete(Pattern),  // only names, attributes, items and tuples of these
 }
 
 
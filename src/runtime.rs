@@ -10,6 +10,7 @@ pub mod function;
 pub mod iter;
 pub mod module;
 pub mod errors;
+pub mod convert;
 
 mod tests;
 
@@ -19,6 +20,7 @@ pub use strings::STRING_TABLE;
 pub use variant::{Variant, VariantKey};
 pub use module::Module;
 pub use errors::{RuntimeError, ExecResult};
+pub use convert::{FromVariant, IntoVariant};
 
 // Default Hasher
 
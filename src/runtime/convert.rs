@@ -0,0 +1,134 @@
+//! Conversions between `Variant` and Rust types, used when exchanging values with a host application.
+
+use crate::language::{IntType, FloatType};
+use crate::runtime::Variant;
+use crate::runtime::types::{Type, List};
+use crate::runtime::strings::StringValue;
+use crate::runtime::errors::{ExecResult, RuntimeError};
+
+
+pub trait IntoVariant {
+    fn into_variant(self) -> Variant;
+}
+
+pub trait FromVariant: Sized {
+    fn from_variant(value: &Variant) -> ExecResult<Self>;
+}
+
+
+impl IntoVariant for Variant {
+    fn into_variant(self) -> Variant { self }
+}
+
+// There is no FromVariant for Variant. A host that keeps a value produced by a script holds an `engine::Handle` instead,
+// so that the value isn't collected.
+
+impl IntoVariant for () {
+    fn into_variant(self) -> Variant { Variant::Nil }
+}
+
+impl FromVariant for () {
+    fn from_variant(value: &Variant) -> ExecResult<Self> {
+        match value {
+            Variant::Nil => Ok(()),
+            _ => Err(RuntimeError::type_mismatch(Type::Nil, value)),
+        }
+    }
+}
+
+impl IntoVariant for bool {
+    fn into_variant(self) -> Variant { Variant::from(self) }
+}
+
+impl FromVariant for bool {
+    fn from_variant(value: &Variant) -> ExecResult<Self> {
+        match value {
+            Variant::BoolTrue => Ok(true),
+            Variant::BoolFalse => Ok(false),
+            _ => Err(RuntimeError::type_mismatch(Type::Boolean, value)),
+        }
+    }
+}
+
+impl IntoVariant for IntType {
+    fn into_variant(self) -> Variant { Variant::from(self) }
+}
+
+impl FromVariant for IntType {
+    fn from_variant(value: &Variant) -> ExecResult<Self> {
+        match value {
+            Variant::Integer(value) => Ok(*value),
+            _ => Err(RuntimeError::type_mismatch(Type::Integer, value)),
+        }
+    }
+}
+
+// integers are also accepted where a float is expected
+impl IntoVariant for FloatType {
+    fn into_variant(self) -> Variant { Variant::from(self) }
+}
+
+impl FromVariant for FloatType {
+    fn from_variant(value: &Variant) -> ExecResult<Self> {
+        match value {
+            Variant::Float(value) => Ok(*value),
+            Variant::Integer(value) => Ok(*value as FloatType),
+            _ => Err(RuntimeError::type_mismatch(Type::Float, value)),
+        }
+    }
+}
+
+impl IntoVariant for &str {
+    fn into_variant(self) -> Variant {
+        Variant::from(StringValue::new_maybe_interned(self))
+    }
+}
+
+impl IntoVariant for String {
+    fn into_variant(self) -> Variant {
+        Variant::from(StringValue::new_maybe_interned(self))
+    }
+}
+
+impl FromVariant for String {
+    fn from_variant(value: &Variant) -> ExecResult<Self> {
+        value.as_strval()
+            .map(|strval| strval.to_string())
+            .ok_or_else(|| RuntimeError::type_mismatch(Type::String, value))
+    }
+}
+
+// None is converted to nil
+impl<T> IntoVariant for Option<T> where T: IntoVariant {
+    fn into_variant(self) -> Variant {
+        self.map_or(Variant::Nil, T::into_variant)
+    }
+}
+
+impl<T> FromVariant for Option<T> where T: FromVariant {
+    fn from_variant(value: &Variant) -> ExecResult<Self> {
+        match value {
+            Variant::Nil => Ok(None),
+            value => T::from_variant(value).map(Some),
+        }
+    }
+}
+
+// Vecs are converted to lists, and can be produced from any iterable
+impl<T> IntoVariant for Vec<T> where T: IntoVariant {
+    fn into_variant(self) -> Variant {
+        let items = self.into_iter()
+            .map(T::into_variant)
+            .collect::<Vec<Variant>>();
+        
+        Variant::from(List::from(items))
+    }
+}
+
+impl<T> FromVariant for Vec<T> where T: FromVariant {
+    fn from_variant(value: &Variant) -> ExecResult<Self> {
+        value.iter_init()?.into_iter()
+            .map(|item| T::from_variant(&item?))
+            .collect()
+    }
+}
@@ -46,6 +46,8 @@ impl RuntimeError {
     
     pub fn kind(&self) -> &ErrorKind { &self.kind }
     
+    pub fn message(&self) -> &StringValue { &self.message }
+    
     pub fn traceback(&self) -> Traceback<'_> {
         Traceback::build(self.traceback.iter())
     }
@@ -72,6 +74,17 @@ impl RuntimeError {
     pub fn push_trace(mut self: Box<Self>, site: TraceSite) -> Box<Self> {
         self.traceback.push(site); self
     }
+    
+    /// Used when an error value is thrown again, so that the traceback starts from the new throw site
+    pub fn clear_trace(mut self: Box<Self>) -> Box<Self> {
+        self.traceback.clear(); self
+    }
+    
+    /// Remove the outermost sites, used when an error is passed back to code that will extend the traceback again
+    pub fn strip_trace(mut self: Box<Self>, count: usize) -> Box<Self> {
+        let len = self.traceback.len().saturating_sub(count);
+        self.traceback.truncate(len); self
+    }
 }
 
 #[allow(clippy::useless_format)]
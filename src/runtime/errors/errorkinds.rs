@@ -3,7 +3,7 @@
 use crate::utils;
 use crate::runtime::Variant;
 use crate::runtime::function::Signature;
-use crate::runtime::types::MethodTag;
+use crate::runtime::types::{Type, MethodTag};
 use crate::runtime::strings::{StringValue, StringSymbol, static_symbol};
 use crate::runtime::errors::RuntimeError;
 
@@ -16,6 +16,8 @@ pub enum ErrorKind {
     DivideByZero,
     NegativeShiftCount,
     NameNotDefined,
+    AttributeNotFound,
+    KeyNotFound,
     CantAssignImmutable,
     UnhashableValue,
     MissingArguments,
@@ -24,7 +26,13 @@ pub enum ErrorKind {
     AssertFailed,
     InvalidValue,
     UnpackError,
+    ImportError,
     Unspecified,
+    UserError,
+    IndexError,
+    TypeError,
+    DuplicateArgument,
+    UnknownArgument,
 }
 
 impl ErrorKind {
@@ -36,6 +44,8 @@ impl ErrorKind {
             Self::DivideByZero => static_symbol!("DivideByZeroError"),
             Self::NegativeShiftCount => static_symbol!("NegativeShiftCountError"),
             Self::NameNotDefined => static_symbol!("NameNotDefinedError"),
+            Self::AttributeNotFound => static_symbol!("AttributeNotFoundError"),
+            Self::KeyNotFound => static_symbol!("KeyNotFoundError"),
             Self::CantAssignImmutable => static_symbol!("CantAssignImmutableError"),
             Self::UnhashableValue => static_symbol!("UnhashableValueError"),
             Self::MissingArguments => static_symbol!("MissingArgumentsError"),
@@ -44,7 +54,13 @@ impl ErrorKind {
             Self::AssertFailed => static_symbol!("AssertFailedError"),
             Self::InvalidValue => static_symbol!("InvalidValueError"),
             Self::UnpackError => static_symbol!("UnpackError"),
+            Self::ImportError => static_symbol!("ImportError"),
             Self::Unspecified => static_symbol!("UnspecifiedError"),
+            Self::UserError => static_symbol!("UserError"),
+            Self::IndexError => static_symbol!("IndexError"),
+            Self::TypeError => static_symbol!("TypeError"),
+            Self::DuplicateArgument => static_symbol!("DuplicateArgumentError"),
+            Self::UnknownArgument => static_symbol!("UnknownArgumentError"),
         };
         name.into()
     }
@@ -106,6 +122,38 @@ impl RuntimeError {
         ))
     }
 
+    pub fn attribute_not_found(receiver: &Variant, name: StringSymbol) -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::AttributeNotFound,
+            StringValue::new_uninterned(format!(
+                "'{}' has no attribute \"{}\"", format_type(receiver), name
+            )),
+        ))
+    }
+    
+    pub fn key_not_found(key: &Variant) -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::KeyNotFound,
+            StringValue::new_uninterned(format!("key not found: {}", key.display_echo())),
+        ))
+    }
+    
+    pub fn index_out_of_range(index: &Variant, len: usize) -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::IndexError,
+            StringValue::new_uninterned(format!(
+                "index {} out of range for length {}", index.display_echo(), len
+            )),
+        ))
+    }
+    
+    pub fn pop_from_empty_list() -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::IndexError,
+            StringValue::from(static_symbol!("pop from empty list")),
+        ))
+    }
+    
     pub fn cant_assign_immutable(name: StringSymbol) -> Box<Self> {
         Box::new(Self::new(
             ErrorKind::CantAssignImmutable,
@@ -113,6 +161,13 @@ impl RuntimeError {
         ))
     }
 
+    pub fn cant_assign_immutable_attr(name: StringSymbol) -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::CantAssignImmutable,
+            StringValue::new_uninterned(format!("can't assign to immutable attribute \"{}\"", name)),
+        ))
+    }
+    
     pub fn unhashable_value(value: &Variant) -> Box<Self> {
         Box::new(Self::new(
             ErrorKind::UnhashableValue,
@@ -130,13 +185,8 @@ impl RuntimeError {
         ))
     }
 
-    pub fn missing_arguments(signature: &Signature, nargs: usize) -> Box<Self> {
-        let missing = signature.required().iter()
-            .skip(nargs)
-            .map(|param| *param.name())
-            .collect::<Vec<StringSymbol>>();
-        
-        let count = signature.min_arity() - nargs;
+    pub fn missing_arguments(signature: &Signature, missing: &[StringSymbol]) -> Box<Self> {
+        let count = missing.len();
         
         let message = format!(
             "{} missing {} required {}: {}",
@@ -144,7 +194,7 @@ impl RuntimeError {
             count, 
             if count == 1 { "argument" }
             else { "arguments" },
-            utils::fmt_join(", ", &missing),
+            utils::fmt_join(", ", missing),
         );
         
         Box::new(Self::new(
@@ -155,7 +205,7 @@ impl RuntimeError {
 
     pub fn too_many_arguments(signature: &Signature, nargs: usize) -> Box<Self> {
         let message = format!(
-            "{} takes {} arguments but {} were given", 
+            "{} takes {} arguments but {} were given",
             signature.fmt_name(), 
             signature.max_arity().unwrap(), 
             nargs,
@@ -167,6 +217,51 @@ impl RuntimeError {
         ))
     }
 
+    pub fn duplicate_argument(signature: &Signature, name: StringSymbol) -> Box<Self> {
+        let message = format!(
+            "{} got multiple values for argument \"{}\"",
+            signature.fmt_name(), name,
+        );
+        
+        Box::new(Self::new(
+            ErrorKind::DuplicateArgument,
+            StringValue::new_uninterned(message),
+        ))
+    }
+    
+    pub fn unknown_argument(signature: &Signature, name: StringSymbol) -> Box<Self> {
+        let message = format!(
+            "{} got an unexpected keyword argument \"{}\"",
+            signature.fmt_name(), name,
+        );
+        
+        Box::new(Self::new(
+            ErrorKind::UnknownArgument,
+            StringValue::new_uninterned(message),
+        ))
+    }
+    
+    pub fn no_init_arguments(class: &Variant, nargs: usize) -> Box<Self> {
+        let message = format!(
+            "{} takes no arguments but {} were given",
+            class.display_echo(), nargs,
+        );
+        
+        Box::new(Self::new(
+            ErrorKind::TooManyArguments,
+            StringValue::new_uninterned(message),
+        ))
+    }
+    
+    pub fn invalid_base_class(base: &Variant) -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::InvalidValue,
+            StringValue::new_uninterned(format!(
+                "can't inherit from '{}'", format_type(base)
+            )),
+        ))
+    }
+    
     pub fn metamethod_not_supported(receiver: &Variant, method: MethodTag) -> Box<Self> {
         let receiver = format_type(receiver);
         
@@ -176,6 +271,13 @@ impl RuntimeError {
             MethodTag::AsFloat => format!("can't interpret '{}' as float", receiver),
             MethodTag::Invoke => format!("type '{}' is not callable", receiver),
             
+            MethodTag::GetAttr | MethodTag::SetAttr
+                => format!("type '{}' does not have attributes", receiver),
+            MethodTag::GetItem | MethodTag::SetItem
+                => format!("type '{}' is not indexable", receiver),
+            MethodTag::DelAttr => format!("can't delete attributes of type '{}'", receiver),
+            MethodTag::DelItem => format!("can't delete items of type '{}'", receiver),
+            
             MethodTag::IterInit => format!("type '{}' is not iterable", receiver),
             MethodTag::IterNext | MethodTag::IterItem
                 => format!("type '{}' is not an iterator", receiver),
@@ -189,6 +291,29 @@ impl RuntimeError {
         ))
     }
 
+    pub fn module_not_found(path: StringSymbol) -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::ImportError,
+            StringValue::new_uninterned(format!("could not find module \"{}\"", path)),
+        ))
+    }
+    
+    pub fn circular_import(path: StringSymbol) -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::ImportError,
+            StringValue::new_uninterned(format!("circular import of module \"{}\"", path)),
+        ))
+    }
+    
+    pub fn module_build_failed(path: StringSymbol, message: impl AsRef<str>) -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::ImportError,
+            StringValue::new_uninterned(format!(
+                "could not build module \"{}\": {}", path, message.as_ref()
+            )),
+        ))
+    }
+    
     pub fn invalid_value(message: impl AsRef<str>) -> Box<Self> {
         Box::new(Self::new(
             ErrorKind::InvalidValue,
@@ -196,6 +321,65 @@ impl RuntimeError {
         ))
     }
 
+    pub fn type_mismatch(expected: Type, value: &Variant) -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::TypeError,
+            StringValue::new_uninterned(format!(
+                "expected '{}', got '{}'", expected, format_type(value)
+            )),
+        ))
+    }
+    
+    pub fn type_check_failed(expected: impl AsRef<str>, value: &Variant) -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::TypeError,
+            StringValue::new_uninterned(format!(
+                "expected type '{}', got '{}'", expected.as_ref(), format_type(value)
+            )),
+        ))
+    }
+    
+    pub fn throw_invalid_value(value: &Variant) -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::InvalidValue,
+            StringValue::new_uninterned(format!(
+                "can only throw error values, not '{}'", format_type(value)
+            )),
+        ))
+    }
+    
+    pub fn generator_running() -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::InvalidValue,
+            StringValue::new_uninterned("generator is already running"),
+        ))
+    }
+    
+    pub fn generator_not_resumable() -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::MethodNotSupported,
+            StringValue::new_uninterned("generators can't be iterated here, use a for loop or list() instead"),
+        ))
+    }
+    
+    pub fn coroutine_running() -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::InvalidValue,
+            StringValue::new_uninterned("coroutine is already running"),
+        ))
+    }
+    
+    pub fn coroutine_finished() -> Box<Self> {
+        Box::new(Self::new(
+            ErrorKind::InvalidValue,
+            StringValue::new_uninterned("cannot resume a finished coroutine"),
+        ))
+    }
+    
+    pub fn user_error(message: StringValue) -> Box<Self> {
+        Box::new(Self::new(ErrorKind::UserError, message))
+    }
+    
     pub fn other(message: impl AsRef<str>) -> Box<Self> {
         Box::new(Self::new(
             ErrorKind::Unspecified,
@@ -1,7 +1,12 @@
 use core::fmt;
 use core::cell::Cell;
+use core::pin::Pin;
+use core::future::Future;
+use core::task::{Context, Poll};
 use crate::codegen::{FunctionID, FunctionProto};
 use crate::runtime::Variant;
+use crate::runtime::strings::{StringSymbol, static_symbol};
+use crate::runtime::types::Marker;
 use crate::runtime::module::{Module, NamespaceEnv};
 use crate::runtime::vm::VirtualMachine;
 use crate::runtime::gc::{Gc, GcTrace};
@@ -9,7 +14,7 @@ use crate::runtime::errors::ExecResult;
 
 mod signature;
 
-pub use signature::{Signature, Parameter};
+pub use signature::{Signature, Parameter, BoundArgs};
 pub use crate::codegen::opcodes::UpvalueIndex;
 
 
@@ -23,16 +28,30 @@ pub enum Call {
     Native {
         func: Gc<NativeFunction>,
         nargs: usize,
+        kwargs: Vec<(StringSymbol, Variant)>,
     },
 }
 
+/// Placeholder for a parameter that did not receive an argument, until its default value is evaluated
+pub fn missing_arg() -> Variant {
+    Variant::Marker(missing_marker())
+}
+
+pub fn is_missing_arg(value: &Variant) -> bool {
+    matches!(value, Variant::Marker(marker) if *marker == missing_marker())
+}
+
+fn missing_marker() -> Marker {
+    Marker::new(static_symbol!("<missing argument>"))
+}
+
 pub trait Callable {
     fn signature(&self) -> &Signature;
-    fn raw_call(&self, args: &[Variant]) -> Call;
+    fn raw_call(&self, args: &[Variant], kwargs: &[(StringSymbol, Variant)]) -> Call;
 
-    fn checked_call(&self, args: &[Variant]) -> ExecResult<Call> {
-        self.signature().check_args(args)?;
-        Ok(self.raw_call(args))
+    fn checked_call(&self, args: &[Variant], kwargs: &[(StringSymbol, Variant)]) -> ExecResult<Call> {
+        self.signature().check_args(args, kwargs)?;
+        Ok(self.raw_call(args, kwargs))
     }
 
 }
@@ -65,7 +84,7 @@ impl Function {
 impl Callable for Function {
     fn signature(&self) -> &Signature { self.proto().signature() }
     
-    fn raw_call(&self, _args: &[Variant]) -> Call {
+    fn raw_call(&self, _args: &[Variant], _kwargs: &[(StringSymbol, Variant)]) -> Call {
         Call::Chunk {
             module: self.module,
             chunk_id: self.fun_id,
@@ -78,8 +97,8 @@ impl Callable for Gc<Function> {
         <Function as Callable>::signature(self)
     }
     
-    fn raw_call(&self, args: &[Variant]) -> Call {
-        <Function as Callable>::raw_call(self, args)
+    fn raw_call(&self, args: &[Variant], kwargs: &[(StringSymbol, Variant)]) -> Call {
+        <Function as Callable>::raw_call(self, args, kwargs)
     }
 }
 
@@ -132,24 +151,70 @@ impl Upvalue {
     pub fn close(&self, gc_cell: Gc<Cell<Variant>>) {
         self.value.set(Closure::Closed(gc_cell))
     }
+    
+    /// Used when a suspended generator is resumed and its locals are back on the stack
+    #[inline]
+    pub fn reopen(&self, index: usize) {
+        self.value.set(Closure::Open(index))
+    }
 }
 
 
 
 // Native Functions
 
-pub type NativeFn = fn(self_fun: &NativeFunction, vm: &mut VirtualMachine<'_>, args: &[Variant]) -> ExecResult<Variant>;
+pub type NativeFn = fn(self_fun: &NativeFunction, vm: &mut VirtualMachine<'_>, args: &[Variant], kwargs: &[(StringSymbol, Variant)]) -> ExecResult<Variant>;
+
+/// Native functions created at runtime by the host application.
+/// Any captured state is not traced by the GC, so it must not contain `Gc` values.
+pub type NativeClosure = Box<dyn Fn(&mut VirtualMachine<'_>, &[Variant]) -> ExecResult<Variant>>;
+
+/// The eventual result of an async native function. While it is pending the VM is paused, and the host
+/// is expected to poll it and then pass the result to `VirtualMachine::resume_pending()`.
+/// As with `NativeClosure`, the future must not hold on to `Gc` values.
+pub struct NativeFuture(Pin<Box<dyn Future<Output=ExecResult<Variant>>>>);
+
+impl NativeFuture {
+    pub fn new(future: impl Future<Output=ExecResult<Variant>> + 'static) -> Self {
+        Self(Box::pin(future))
+    }
+}
+
+impl Future for NativeFuture {
+    type Output = ExecResult<Variant>;
+    
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.0.as_mut().poll(cx)
+    }
+}
+
+impl fmt::Debug for NativeFuture {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("NativeFuture")
+    }
+}
+
+enum NativeBody {
+    Fn(NativeFn),
+    Closure(NativeClosure),
+}
 
 pub struct NativeFunction {
     signature: Signature,
     defaults: Option<Box<[Variant]>>,
     env: Gc<NamespaceEnv>,
-    func: NativeFn,
+    func: NativeBody,
 }
 
 impl NativeFunction {
     pub fn new(signature: Signature, defaults: Option<Box<[Variant]>>, env: Gc<NamespaceEnv>, func: NativeFn) -> Self {
-        Self { signature, defaults, env, func }
+        Self { signature, defaults, env, func: NativeBody::Fn(func) }
+    }
+    
+    /// Closures receive their arguments exactly as they were passed, default arguments are not supported.
+    /// Any keyword arguments are moved into the position of the parameter they name.
+    pub fn with_closure(signature: Signature, env: Gc<NamespaceEnv>, func: NativeClosure) -> Self {
+        Self { signature, defaults: None, env, func: NativeBody::Closure(func) }
     }
     
     pub fn signature(&self) -> &Signature { &self.signature }
@@ -164,19 +229,31 @@ impl NativeFunction {
     }
     
     /// actually execute a native function
-    pub fn exec_fun(&self, vm: &mut VirtualMachine<'_>, args: &[Variant]) -> ExecResult<Variant> {
-        self.signature().check_args(args)?;
-        (self.func)(self, vm, args)
+    pub fn exec_fun(&self, vm: &mut VirtualMachine<'_>, args: &[Variant], kwargs: &[(StringSymbol, Variant)]) -> ExecResult<Variant> {
+        self.signature().check_args(args, kwargs)?;
+        match &self.func {
+            NativeBody::Fn(func) => func(self, vm, args, kwargs),
+            NativeBody::Closure(func) if kwargs.is_empty() => func(vm, args),
+            NativeBody::Closure(func) => {
+                let mut argbuf = vec![Variant::Nil; self.signature.arg_len()];
+                let bound = self.signature.bind_args(args, kwargs, &[], &mut argbuf);
+                
+                let args = bound.args.iter().chain(bound.varargs.iter())
+                    .copied().collect::<Vec<Variant>>();
+                func(vm, &args)
+            }
+        }
     }
 }
 
 impl Callable for Gc<NativeFunction> {
     fn signature(&self) -> &Signature { &self.signature }
     
-    fn raw_call(&self, args: &[Variant]) -> Call {
+    fn raw_call(&self, args: &[Variant], kwargs: &[(StringSymbol, Variant)]) -> Call {
         Call::Native {
             func: *self,
             nargs: args.len(),
+            kwargs: kwargs.to_vec(),
         }
     }
 }
@@ -204,7 +281,10 @@ impl fmt::Debug for NativeFunction {
             .field("signature", &self.signature)
             .field("defaults", &self.defaults)
             .field("env", &self.env)
-            .field("func", &core::ptr::addr_of!(self.func))
+            .field("func", &match &self.func {
+                NativeBody::Fn(func) => *func as *const (),
+                NativeBody::Closure(func) => &**func as *const _ as *const (),
+            })
             .finish()
     }
 }
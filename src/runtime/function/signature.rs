@@ -12,22 +12,36 @@ pub struct Signature {
     required: Box<[Parameter]>,
     default: Box<[Parameter]>,
     variadic: Option<Parameter>,
+    keyword: Box<[Parameter]>,
+    keyword_default: Box<[Parameter]>,
+    kwargs: Option<Parameter>,
 }
 
 impl Signature {
     pub fn new(name: Option<impl Into<StringSymbol>>, required: Vec<Parameter>, default: Vec<Parameter>, variadic: Option<Parameter>) -> Self {
-        let name = name.map(|name| name.into());
-        
-        // build this once and cache the result, because it's expensive
-        let display = format_signature(name.as_ref(), &required, &default, variadic.as_ref());
-        
-        Self {
-            name,
-            display,
+        let mut signature = Self {
+            name: name.map(|name| name.into()),
+            display: String::new(),
             required: required.into_boxed_slice(),
             default: default.into_boxed_slice(),
             variadic,
-        }
+            keyword: Box::new([]),
+            keyword_default: Box::new([]),
+            kwargs: None,
+        };
+        
+        // build this once and cache the result, because it's expensive
+        signature.display = format_signature(&signature);
+        signature
+    }
+    
+    /// Add keyword-only parameters, and optionally a parameter that collects any unmatched keyword arguments
+    pub fn with_keywords(mut self, keyword: Vec<Parameter>, keyword_default: Vec<Parameter>, kwargs: Option<Parameter>) -> Self {
+        self.keyword = keyword.into_boxed_slice();
+        self.keyword_default = keyword_default.into_boxed_slice();
+        self.kwargs = kwargs;
+        self.display = format_signature(&self);
+        self
     }
     
     pub fn name(&self) -> Option<StringSymbol> { self.name }
@@ -59,6 +73,9 @@ impl Signature {
     pub fn required(&self) -> &[Parameter] { &self.required }
     pub fn default(&self) -> &[Parameter] { &self.default }
     pub fn variadic(&self) -> Option<&Parameter> { self.variadic.as_ref() }
+    pub fn keyword(&self) -> &[Parameter] { &self.keyword }
+    pub fn keyword_default(&self) -> &[Parameter] { &self.keyword_default }
+    pub fn kwargs(&self) -> Option<&Parameter> { self.kwargs.as_ref() }
     
     pub fn min_arity(&self) -> usize {
         self.required.len()
@@ -66,16 +83,44 @@ impl Signature {
     
     pub fn max_arity(&self) -> Option<usize> {
         if self.variadic().is_some() { None }
-        else { Some(self.required.len() + self.default.len()) }
+        else { Some(self.positional_len()) }
     }
     
-    pub fn check_args(&self, args: &[Variant]) -> ExecResult<()> {
-        if args.len() < self.required.len() {
-            return Err(RuntimeError::missing_arguments(self, args.len()))
+    pub fn check_args(&self, args: &[Variant], kwargs: &[(StringSymbol, Variant)]) -> ExecResult<()> {
+        self.check_call(args.len(), kwargs.iter().map(|(name, _)| name))
+    }
+    
+    /// Check that a call with `nargs` positional arguments and the given keyword argument names can be bound
+    pub fn check_call<'a>(&self, nargs: usize, names: impl Iterator<Item=&'a StringSymbol> + Clone) -> ExecResult<()> {
+        if matches!(self.max_arity(), Some(max_arity) if nargs > max_arity) {
+            return Err(RuntimeError::too_many_arguments(self, nargs))
+        }
+        
+        let positional = nargs.min(self.positional_len());
+        for (idx, name) in names.clone().enumerate() {
+            if names.clone().take(idx).any(|other| other == name) {
+                return Err(RuntimeError::duplicate_argument(self, *name))
+            }
+            
+            match self.keyword_slot(name) {
+                Some(slot) if slot < positional => 
+                    return Err(RuntimeError::duplicate_argument(self, *name)),
+                
+                None if self.kwargs.is_none() => 
+                    return Err(RuntimeError::unknown_argument(self, *name)),
+                
+                _ => { },
+            }
         }
         
-        if matches!(self.max_arity(), Some(max_arity) if args.len() > max_arity) {
-            return Err(RuntimeError::too_many_arguments(self, args.len()))
+        let missing = self.required.iter().skip(nargs)
+            .chain(self.keyword.iter())
+            .map(|param| *param.name())
+            .filter(|name| !names.clone().any(|other| other == name))
+            .collect::<Vec<StringSymbol>>();
+        
+        if !missing.is_empty() {
+            return Err(RuntimeError::missing_arguments(self, &missing))
         }
         
         Ok(())
@@ -85,46 +130,63 @@ impl Signature {
         self.required.len()
         + self.default.len()
         + usize::from(self.variadic.is_some())
+        + self.keyword.len()
+        + self.keyword_default.len()
+        + usize::from(self.kwargs.is_some())
+    }
+    
+    /// The number of parameters that can receive an argument by position, not counting the variadic parameter
+    pub fn positional_len(&self) -> usize {
+        self.required.len() + self.default.len()
     }
     
     /// Get the length of the argument buffer required by bind_args()
     pub fn arg_len(&self) -> usize {
-        self.required.len() + self.default.len()
+        self.positional_len() + self.keyword.len() + self.keyword_default.len()
+    }
+    
+    /// Find the position in the argument buffer of the parameter that receives a keyword argument.
+    /// The variadic and kwargs parameters can't be passed by name.
+    pub fn keyword_slot(&self, name: &StringSymbol) -> Option<usize> {
+        self.required.iter()
+            .chain(self.default.iter())
+            .chain(self.keyword.iter())
+            .chain(self.keyword_default.iter())
+            .position(|param| param.name() == name)
     }
     
     /// Helper for native functions. Prepares a complete argument buffer by cloning argument values,
-    /// while handling default and variadic arguments. Assumes check_args() has already succeeded.
-    pub fn bind_args<'a>(&self, args: &'a [Variant], defaults: &'a [Variant], argbuf: &'a mut [Variant]) -> BoundArgs<'a> {
-        debug_assert!(args.len() >= self.required.len());
+    /// while handling default, variadic and keyword arguments. Assumes check_args() has already succeeded.
+    pub fn bind_args<'a>(&self, args: &'a [Variant], kwargs: &[(StringSymbol, Variant)], defaults: &[Variant], argbuf: &'a mut [Variant]) -> BoundArgs<'a> {
         debug_assert!(argbuf.len() == self.arg_len());
-        debug_assert!(defaults.len() == self.default.len());
+        debug_assert!(defaults.len() == self.default.len() + self.keyword_default.len());
         
-        let mut arg_idx = 0;
-        for _ in self.required.iter() {
-            argbuf[arg_idx] = args[arg_idx];
-            arg_idx += 1;
-        }
+        let (defaults, keyword_defaults) = defaults.split_at(self.default.len());
+        argbuf[self.required.len()..self.positional_len()].copy_from_slice(defaults);
+        argbuf[self.positional_len() + self.keyword.len()..].copy_from_slice(keyword_defaults);
         
-        for (default_idx, _) in self.default.iter().enumerate() {
-            if arg_idx < args.len() {
-                argbuf[arg_idx] = args[arg_idx];
-            } else {
-                argbuf[arg_idx] = defaults[default_idx];
-            }
-            arg_idx += 1;
-        }
+        self.bind_partial(args, kwargs, argbuf)
+    }
+    
+    /// Like bind_args(), except that parameters which did not receive an argument are left untouched.
+    pub fn bind_partial<'a>(&self, args: &'a [Variant], kwargs: &[(StringSymbol, Variant)], argbuf: &'a mut [Variant]) -> BoundArgs<'a> {
+        debug_assert!(argbuf.len() == self.arg_len());
         
-        let varargs;
-        if arg_idx > args.len() {
-            varargs = &[] as &[Variant];
-        } else {
-            let (_, rest) = args.split_at(arg_idx);
-            varargs = rest;
+        let (args, varargs) = args.split_at(args.len().min(self.positional_len()));
+        argbuf[..args.len()].copy_from_slice(args);
+        
+        let mut unmatched = Vec::new();
+        for (name, value) in kwargs.iter() {
+            match self.keyword_slot(name) {
+                Some(slot) => argbuf[slot] = *value,
+                None => unmatched.push((*name, *value)),
+            }
         }
         
         BoundArgs {
             args: argbuf,
             varargs,
+            kwargs: unmatched,
         }
     }
 }
@@ -132,6 +194,7 @@ impl Signature {
 pub struct BoundArgs<'a> {
     pub args: &'a [Variant],
     pub varargs: &'a [Variant],
+    pub kwargs: Vec<(StringSymbol, Variant)>,
 }
 
 
@@ -157,29 +220,52 @@ impl fmt::Display for Signature {
     }
 }
 
-fn format_signature(name: Option<&StringSymbol>, required: &[Parameter], default: &[Parameter], variadic: Option<&Parameter>) -> String {
+fn format_signature(signature: &Signature) -> String {
     STRING_TABLE.with(|string_table| {
         let string_table = string_table.borrow();
         
-        let name = name
+        let name = signature.name.as_ref()
             .map(|name| string_table.resolve(name));
         
         let mut parameters = Vec::new();
         
-        let required_names = required.iter()
+        let required_names = signature.required.iter()
             .map(|param| string_table.resolve(&param.name).to_string());
         parameters.extend(required_names);
             
-        let default_names = default.iter()
+        let default_names = signature.default.iter()
             .map(|param| string_table.resolve(&param.name))
             .map(|name| format!("{} = ...", name));
         parameters.extend(default_names);
         
-        let variadic_name = variadic
+        let variadic_name = signature.variadic.as_ref()
             .map(|param| string_table.resolve(&param.name))
             .map(|name| format!("{}...", name));
         parameters.extend(variadic_name);
         
+        // a bare "..." separates keyword-only parameters when there is no variadic parameter
+        let has_keywords = !signature.keyword.is_empty() 
+            || !signature.keyword_default.is_empty() 
+            || signature.kwargs.is_some();
+        
+        if has_keywords && signature.variadic.is_none() {
+            parameters.push("...".to_string());
+        }
+        
+        let keyword_names = signature.keyword.iter()
+            .map(|param| string_table.resolve(&param.name).to_string());
+        parameters.extend(keyword_names);
+        
+        let keyword_default_names = signature.keyword_default.iter()
+            .map(|param| string_table.resolve(&param.name))
+            .map(|name| format!("{} = ...", name));
+        parameters.extend(keyword_default_names);
+        
+        let kwargs_name = signature.kwargs.as_ref()
+            .map(|param| string_table.resolve(&param.name))
+            .map(|name| format!("{}...", name));
+        parameters.extend(kwargs_name);
+        
         format!("fun {}({})", name.unwrap_or(""), parameters.join(", "))
     })
 }
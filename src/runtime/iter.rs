@@ -27,6 +27,7 @@ use crate::runtime::errors::{ExecResult};
     
 */
 
+#[derive(Clone)]
 pub struct IterState {
     iter: Variant,
     state: Variant,
@@ -7,7 +7,7 @@
 ///! resulting module to a name.
 
 use core::fmt;
-use core::cell::{RefCell, Ref, RefMut};
+use core::cell::{Cell, RefCell, Ref, RefMut};
 use core::hash::{Hash, Hasher, BuildHasher};
 use std::path::PathBuf;
 use once_cell::sync::Lazy;
@@ -20,16 +20,26 @@ use crate::runtime::errors::{ExecResult, RuntimeError};
 
 pub use crate::codegen::{ProgramData, Constant, Chunk, FunctionProto, ConstID, FunctionID};
 
+mod loader;
+
+pub use loader::{ModuleLoader, LoadModule, SOURCE_EXTENSION};
+
 
 #[derive(Debug, Clone)]
 pub struct Variable {
+    name: StringSymbol,
     access: Access,
     value: Variant,
 }
 
+/// Variables are stored in slots so that lookups can be cached by slot index.
+/// The shape changes whenever a slot could be assigned to a different name, which invalidates
+/// any cached slot indices.
 #[derive(Debug, Clone)]
 pub struct Namespace {
-    store: HashMap<StringSymbol, Variable>,
+    slots: Vec<Variable>,
+    index: HashMap<StringSymbol, usize>,
+    shape: usize,
 }
 
 impl Default for Namespace {
@@ -39,51 +49,82 @@ impl Default for Namespace {
 impl Namespace {
     pub fn new() -> Self {
         Self { 
-            store: HashMap::with_hasher(DefaultBuildHasher::default()),
+            slots: Vec::new(),
+            index: HashMap::with_hasher(DefaultBuildHasher::default()),
+            shape: 0,
         }
     }
     
     pub fn names(&self) -> impl Iterator<Item=&StringSymbol> {
-        self.store.keys()
+        self.slots.iter().map(|var| &var.name)
     }
     
     pub fn values(&self) -> impl Iterator<Item=&Variant> {
-        self.store.values().map(|var| &var.value)
+        self.slots.iter().map(|var| &var.value)
     }
     
     // if the variable already exists, it is overwritten
     pub fn create(&mut self, name: StringSymbol, access: Access, value: Variant) {
-        self.store.insert(name, Variable { access, value });
+        let variable = Variable { name, access, value };
+        if let Some(slot) = self.index.get(&name) {
+            self.slots[*slot] = variable;
+        } else {
+            // new slots are appended, so existing slot indices are still valid
+            self.index.insert(name, self.slots.len());
+            self.slots.push(variable);
+        }
     }
     
     pub fn delete(&mut self, name: &StringSymbol) -> ExecResult<()> {
-        if self.store.remove(name).is_none() {
-            return Err(RuntimeError::name_not_defined(*name))
+        let slot = self.index.remove(name)
+            .ok_or_else(|| RuntimeError::name_not_defined(*name))?;
+        
+        // the last variable is moved into the deleted slot
+        self.slots.swap_remove(slot);
+        if let Some(moved) = self.slots.get(slot) {
+            self.index.insert(moved.name, slot);
         }
+        self.shape = self.shape.wrapping_add(1);
         Ok(())
     }
     
     pub fn lookup<'a>(&'a self, name: &StringSymbol) -> ExecResult<&'a Variant> {
-        self.store.get(name)
-            .map(|var| &var.value)
-            .ok_or_else(|| RuntimeError::name_not_defined(*name))
+        let slot = self.resolve(name)?;
+        Ok(self.get_slot(slot))
     }
     
     pub fn lookup_mut<'a>(&'a mut self, name: &StringSymbol) -> ExecResult<&'a mut Variant> {
-        let variable = self.store.get_mut(name)
-            .ok_or_else(|| RuntimeError::name_not_defined(*name))?;
-            
-        if variable.access != Access::ReadWrite {
-            return Err(RuntimeError::cant_assign_immutable(*name));
-        }
-        
-        Ok(&mut variable.value)
+        let slot = self.resolve(name)?;
+        self.get_slot_mut(slot)
     }
     
     pub fn extend(&mut self, other: &Namespace) {
-        for (name, variable) in other.store.iter() {
-            self.create(*name, variable.access, variable.value)
+        for variable in other.slots.iter() {
+            self.create(variable.name, variable.access, variable.value)
+        }
+    }
+    
+    /// Slot indices that were resolved while the shape was the same are still valid.
+    #[inline]
+    pub fn shape(&self) -> usize { self.shape }
+    
+    pub fn resolve(&self, name: &StringSymbol) -> ExecResult<usize> {
+        self.index.get(name).copied()
+            .ok_or_else(|| RuntimeError::name_not_defined(*name))
+    }
+    
+    #[inline]
+    pub fn get_slot(&self, slot: usize) -> &Variant {
+        &self.slots[slot].value
+    }
+    
+    #[inline]
+    pub fn get_slot_mut(&mut self, slot: usize) -> ExecResult<&mut Variant> {
+        let variable = &mut self.slots[slot];
+        if variable.access != Access::ReadWrite {
+            return Err(RuntimeError::cant_assign_immutable(variable.name));
         }
+        Ok(&mut variable.value)
     }
 }
 
@@ -138,6 +179,14 @@ pub struct Module {
     source: Option<ModuleSource>,
     data: ProgramData,
     globals: Gc<NamespaceEnv>,
+    global_cache: Box<[Cell<Option<CachedSlot>>]>,  // indexed by the ConstID of the global's name
+}
+
+// the slot a global name resolved to, and the namespace shape at that time
+#[derive(Debug, Clone, Copy)]
+struct CachedSlot {
+    shape: usize,
+    slot: usize,
 }
 
 unsafe impl GcTrace for Module {
@@ -159,12 +208,17 @@ impl Module {
         
         let display = ident.to_string();
         
+        let global_cache = (0..data.consts_len())
+            .map(|_| Cell::new(None))
+            .collect();
+        
         let module = Self {
             ident,
             display,
             source,
             data,
             globals,
+            global_cache,
         };
         
         Gc::new(module)
@@ -200,6 +254,43 @@ impl Module {
     pub fn get_function(&self, fun_id: FunctionID) -> &FunctionProto {
         self.data.get_function(fun_id)
     }
+    
+    /// Load a global variable, given the constant that holds its name.
+    #[inline]
+    pub fn load_global(&self, cid: ConstID) -> ExecResult<Variant> {
+        let namespace = self.globals.borrow();
+        let slot = self.resolve_global(&namespace, cid)?;
+        Ok(*namespace.get_slot(slot))
+    }
+    
+    /// Assign to a global variable, given the constant that holds its name.
+    #[inline]
+    pub fn store_global(&self, cid: ConstID, value: Variant) -> ExecResult<()> {
+        let mut namespace = self.globals.borrow_mut();
+        let slot = self.resolve_global(&namespace, cid)?;
+        *namespace.get_slot_mut(slot)? = value;
+        Ok(())
+    }
+    
+    // only hashes the name if the namespace has changed shape since it was last resolved
+    #[inline]
+    fn resolve_global(&self, namespace: &Namespace, cid: ConstID) -> ExecResult<usize> {
+        let cache = &self.global_cache[usize::from(cid)];
+        if let Some(cached) = cache.get() {
+            if cached.shape == namespace.shape() {
+                return Ok(cached.slot);
+            }
+        }
+        
+        let name = match self.data.get_const(cid) {
+            Constant::String(idx) => *self.data.get_string(*idx),
+            _ => panic!("invalid operand"),
+        };
+        
+        let slot = namespace.resolve(&name)?;
+        cache.set(Some(CachedSlot { shape: namespace.shape(), slot }));
+        Ok(slot)
+    }
 }
 
 impl fmt::Display for Module {
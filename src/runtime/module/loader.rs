@@ -0,0 +1,166 @@
+//! Resolves import paths to source files, builds them, and caches the resulting modules.
+
+use std::path::PathBuf;
+use crate::source::ModuleSource;
+use crate::BuildOptions;
+use crate::codegen::{Program, OptLevel};
+use crate::runtime::HashMap;
+use crate::runtime::gc::Gc;
+use crate::runtime::strings::StringSymbol;
+use crate::runtime::module::{Module, ModuleIdent, NamespaceEnv};
+use crate::runtime::errors::{ExecResult, RuntimeError};
+
+
+/// File extension used when searching for module source files
+pub const SOURCE_EXTENSION: &str = "sph";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoadStatus {
+    Loading,  // the module's main chunk is still executing
+    Loaded,
+}
+
+#[derive(Debug)]
+struct ModuleEntry {
+    module: Gc<Module>,
+    status: LoadStatus,
+    main: Option<Box<[u8]>>,  // kept alive here so that the VM can execute it
+}
+
+/// The result of loading a module
+pub enum LoadModule<'a> {
+    Cached(Gc<Module>),
+    Execute(Gc<Module>, &'a [u8]),  // a newly built module and its main chunk
+}
+
+/// Each VM has its own loader, so modules are only executed once per VM.
+/// Modules are cached by their canonicalized source path.
+#[derive(Debug, Default)]
+pub struct ModuleLoader {
+    search_paths: Vec<PathBuf>,
+    prelude: Option<Gc<NamespaceEnv>>,
+    cache: HashMap<PathBuf, ModuleEntry>,
+    opt_level: OptLevel,
+}
+
+impl ModuleLoader {
+    pub fn new() -> Self { Self::default() }
+    
+    pub fn search_paths(&self) -> &[PathBuf] { &self.search_paths }
+    
+    /// Directories that are searched (in order) after the directory of the importing module
+    pub fn add_search_path(&mut self, path: impl Into<PathBuf>) {
+        self.search_paths.push(path.into())
+    }
+    
+    /// The optimization level used to build imported modules
+    pub fn set_opt_level(&mut self, opt_level: OptLevel) {
+        self.opt_level = opt_level;
+    }
+    
+    /// Names that are copied into the globals of every newly loaded module
+    pub fn set_prelude(&mut self, prelude: Gc<NamespaceEnv>) {
+        self.prelude.replace(prelude);
+    }
+    
+    /// Register a module that is already executing, so that attempts to import it are detected as circular
+    pub fn insert_running(&mut self, module: Gc<Module>) {
+        if let ModuleIdent::SourcePath(path) = module.ident() {
+            let entry = ModuleEntry {
+                module,
+                status: LoadStatus::Loading,
+                main: None,
+            };
+            self.cache.insert(path.clone(), entry);
+        }
+    }
+    
+    /// Should be called after a module's main chunk has finished executing
+    pub fn mark_loaded(&mut self, module: Gc<Module>) {
+        if let ModuleIdent::SourcePath(path) = module.ident() {
+            if let Some(entry) = self.cache.get_mut(path) {
+                entry.status = LoadStatus::Loaded;
+            }
+        }
+    }
+    
+    /// Should be called if a module's main chunk fails to finish executing, so that it can be imported again
+    pub fn remove_loading(&mut self, module: Gc<Module>) {
+        if let ModuleIdent::SourcePath(path) = module.ident() {
+            let is_loading = matches!(self.cache.get(path), Some(entry) if entry.status == LoadStatus::Loading);
+            if is_loading {
+                self.cache.remove(path);
+            }
+        }
+    }
+    
+    pub fn iter_modules(&self) -> impl Iterator<Item=&Gc<Module>> {
+        self.cache.values().map(|entry| &entry.module)
+    }
+    
+    pub fn trace(&self) {
+        if let Some(prelude) = self.prelude {
+            prelude.mark_trace();
+        }
+        for module in self.iter_modules() {
+            module.mark_trace();
+        }
+    }
+    
+    /// Convert a dotted module path (e.g. "foo.bar") into the path of a source file.
+    /// The directory of the importing module is searched first, then each of the search paths.
+    /// Modules that were not loaded from a file import relative to the current working directory.
+    pub fn resolve(&self, import_path: &str, importer: Option<&ModuleSource>) -> Option<PathBuf> {
+        let mut relative = import_path.split('.').collect::<PathBuf>();
+        relative.set_extension(SOURCE_EXTENSION);
+        
+        let importer_dir = match importer {
+            Some(ModuleSource::File(path)) => path.parent().map(PathBuf::from),
+            _ => std::env::current_dir().ok(),
+        };
+        
+        importer_dir.iter().map(PathBuf::as_path)
+            .chain(self.search_paths.iter().map(PathBuf::as_path))
+            .map(|dir| dir.join(&relative))
+            .find(|path| path.is_file())
+            .and_then(|path| path.canonicalize().ok())
+    }
+    
+    pub fn load_module(&mut self, import_path: StringSymbol, importer: Option<&ModuleSource>) -> ExecResult<LoadModule<'_>> {
+        let path = self.resolve(&import_path.to_string(), importer)
+            .ok_or_else(|| RuntimeError::module_not_found(import_path))?;
+        
+        if let Some(entry) = self.cache.get(&path) {
+            return match entry.status {
+                LoadStatus::Loaded => Ok(LoadModule::Cached(entry.module)),
+                LoadStatus::Loading => Err(RuntimeError::circular_import(import_path)),
+            }
+        }
+        
+        let source = ModuleSource::File(path.clone());
+        let options = BuildOptions {
+            opt_level: self.opt_level,
+            ..BuildOptions::default()
+        };
+        
+        let (build, _) = crate::build_module_with(&source, options)
+            .map_err(|errors| RuntimeError::module_build_failed(import_path, crate::format_build_errors(&errors)))?;
+        
+        let program = Program::load(build.program);
+        
+        let globals = NamespaceEnv::new();
+        if let Some(prelude) = self.prelude {
+            globals.borrow_mut().extend(&prelude.borrow());
+        }
+        
+        let module = Module::with_env(Some(source), program.data, globals);
+        let entry = ModuleEntry {
+            module,
+            status: LoadStatus::Loading,
+            main: Some(program.main),
+        };
+        
+        let entry = self.cache.entry(path).or_insert(entry);
+        Ok(LoadModule::Execute(module, entry.main.as_ref().unwrap()))
+    }
+}
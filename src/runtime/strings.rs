@@ -6,6 +6,7 @@ use crate::runtime::errors::ExecResult;
 
 pub mod intern;
 pub mod buffer;
+pub mod format;
 
 pub use intern::{StringSymbol, StringInterner, static_symbol, STRING_TABLE};
 pub use buffer::StrBuffer;
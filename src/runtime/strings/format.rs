@@ -0,0 +1,224 @@
+//! Format specs for interpolated strings, e.g. the `08.3` in `$"{x:08.3}"`.
+//!
+//! The grammar is `[[fill]align][+][0][width][.precision][radix]` where align is one of `<` `>` `^`
+//! and radix is one of `b` `o` `x` `X`.
+
+use core::fmt;
+use core::str::FromStr;
+use std::error::Error;
+use crate::language::IntType;
+use crate::runtime::Variant;
+use crate::runtime::types::Type;
+use crate::runtime::strings::StringValue;
+use crate::runtime::errors::{ExecResult, RuntimeError};
+
+
+// keep a typo in a spec from allocating huge strings
+const MAX_WIDTH: usize = u16::MAX as usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    Right,
+    Center,
+}
+
+impl Alignment {
+    fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            '<' => Some(Self::Left),
+            '>' => Some(Self::Right),
+            '^' => Some(Self::Center),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Radix {
+    Binary,
+    Octal,
+    LowerHex,
+    UpperHex,
+}
+
+impl Radix {
+    fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            'b' => Some(Self::Binary),
+            'o' => Some(Self::Octal),
+            'x' => Some(Self::LowerHex),
+            'X' => Some(Self::UpperHex),
+            _ => None,
+        }
+    }
+    
+    fn format(&self, value: IntType) -> String {
+        let value = value.unsigned_abs();
+        match self {
+            Self::Binary => format!("{:b}", value),
+            Self::Octal => format!("{:o}", value),
+            Self::LowerHex => format!("{:x}", value),
+            Self::UpperHex => format!("{:X}", value),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormatSpec {
+    fill: Option<char>,
+    align: Option<Alignment>,
+    sign: bool,
+    zero_pad: bool,
+    width: usize,
+    precision: Option<usize>,
+    radix: Option<Radix>,
+}
+
+impl FromStr for FormatSpec {
+    type Err = FormatSpecError;
+    
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let error = || FormatSpecError { spec: spec.to_string() };
+        
+        let mut result = Self::default();
+        let mut chars = spec.chars().peekable();
+        
+        // fill and alignment
+        let mut lookahead = spec.chars();
+        match (lookahead.next(), lookahead.next()) {
+            (Some(fill), Some(align)) if Alignment::from_char(align).is_some() => {
+                result.fill = Some(fill);
+                result.align = Alignment::from_char(align);
+                chars.nth(1);
+            }
+            (Some(align), _) if Alignment::from_char(align).is_some() => {
+                result.align = Alignment::from_char(align);
+                chars.next();
+            }
+            _ => { },
+        }
+        
+        result.sign = chars.next_if_eq(&'+').is_some();
+        result.zero_pad = chars.next_if_eq(&'0').is_some();
+        
+        if let Some(width) = parse_digits(&mut chars) {
+            result.width = width.ok_or_else(error)?;
+        }
+        
+        if chars.next_if_eq(&'.').is_some() {
+            let precision = parse_digits(&mut chars).flatten().ok_or_else(error)?;
+            result.precision = Some(precision);
+        }
+        
+        if let Some(ch) = chars.next() {
+            result.radix = Some(Radix::from_char(ch).ok_or_else(error)?);
+        }
+        
+        if chars.next().is_some() {
+            return Err(error());
+        }
+        
+        Ok(result)
+    }
+}
+
+// None if there are no digits, Some(None) if the number is too large
+fn parse_digits(chars: &mut core::iter::Peekable<core::str::Chars>) -> Option<Option<usize>> {
+    let mut value = None;
+    while let Some(digit) = chars.peek().and_then(|ch| ch.to_digit(10)) {
+        chars.next();
+        
+        let next = value.unwrap_or(Some(0))
+            .and_then(|value: usize| value.checked_mul(10))
+            .and_then(|value| value.checked_add(digit as usize))
+            .filter(|value| *value <= MAX_WIDTH);
+        
+        value = Some(next);
+    }
+    value
+}
+
+impl FormatSpec {
+    pub fn format(&self, value: &Variant) -> ExecResult<StringValue> {
+        let (negative, body, numeric) = match value {
+            Variant::Integer(value) => {
+                let body = match (self.radix, self.precision) {
+                    (Some(radix), _) => radix.format(*value),
+                    (None, Some(precision)) => format!("{}.{:0<precision$}", value.unsigned_abs(), ""),
+                    (None, None) => value.unsigned_abs().to_string(),
+                };
+                (*value < 0, body, true)
+            }
+            
+            _ if self.radix.is_some() => return Err(RuntimeError::type_mismatch(Type::Integer, value)),
+            
+            Variant::Float(value) => {
+                let body = match self.precision {
+                    Some(precision) => format!("{:.precision$}", value.abs()),
+                    None => Variant::from(value.abs()).fmt_str()?.to_string(),
+                };
+                (value.is_sign_negative() && !value.is_nan(), body, true)
+            }
+            
+            _ => {
+                let mut body = value.fmt_str()?.to_string();
+                if let Some(precision) = self.precision {
+                    if let Some((idx, _)) = body.char_indices().nth(precision) {
+                        body.truncate(idx);
+                    }
+                }
+                (false, body, false)
+            }
+        };
+        
+        let sign = match (negative, self.sign && numeric) {
+            (true, _) => "-",
+            (false, true) => "+",
+            (false, false) => "",
+        };
+        
+        let len = sign.chars().count() + body.chars().count();
+        let padding = self.width.saturating_sub(len);
+        
+        let mut output = String::with_capacity(body.len() + sign.len() + padding);
+        
+        // zero padding goes between the sign and the digits
+        if numeric && self.zero_pad && self.align.is_none() {
+            output.push_str(sign);
+            output.extend(core::iter::repeat_n('0', padding));
+            output.push_str(&body);
+            return Ok(StringValue::new_maybe_interned(output));
+        }
+        
+        let fill = self.fill.unwrap_or(if self.zero_pad { '0' } else { ' ' });
+        let align = self.align.unwrap_or(if numeric { Alignment::Right } else { Alignment::Left });
+        let (left, right) = match align {
+            Alignment::Left => (0, padding),
+            Alignment::Right => (padding, 0),
+            Alignment::Center => (padding / 2, padding - padding / 2),
+        };
+        
+        output.extend(core::iter::repeat_n(fill, left));
+        output.push_str(sign);
+        output.push_str(&body);
+        output.extend(core::iter::repeat_n(fill, right));
+        
+        Ok(StringValue::new_maybe_interned(output))
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct FormatSpecError {
+    spec: String,
+}
+
+impl Error for FormatSpecError { }
+
+impl fmt::Display for FormatSpecError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "invalid format spec \"{}\"", self.spec)
+    }
+}
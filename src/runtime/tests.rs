@@ -25,4 +25,17 @@ fn hash_intern_hasher_is_stable() {
     }
     
     
-}
\ No newline at end of file
+}
+
+#[test]
+fn format_spec_parsing() {
+    use crate::runtime::strings::format::FormatSpec;
+    
+    for spec in ["", "5", "<5", "*^10", "+08.3", ".2", "x", "#>8X", "0b"] {
+        assert!(spec.parse::<FormatSpec>().is_ok(), "failed to parse \"{}\"", spec);
+    }
+    
+    for spec in ["z", "5.", "8.3.2", "xx", "99999999"] {
+        assert!(spec.parse::<FormatSpec>().is_err(), "parsed invalid spec \"{}\"", spec);
+    }
+}
@@ -3,7 +3,7 @@ use crate::language::{IntType, FloatType};
 use crate::runtime::Variant;
 use crate::runtime::iter::IterState;
 use crate::runtime::function::Call;
-use crate::runtime::strings::{StringValue, static_symbol};
+use crate::runtime::strings::{StringValue, StringSymbol, static_symbol};
 use crate::runtime::errors::{ExecResult, RuntimeError};
 
 
@@ -14,13 +14,24 @@ mod boolean;
 mod numeric;
 mod string;
 mod tuple;
+mod list;
+mod dict;
+mod object;
+mod class;
 mod iterator;
 mod misc;
+mod recursion;
+mod annotation;
 
 pub use tuple::Tuple;
+pub use list::List;
+pub use dict::Dict;
+pub use object::{Object, Attribute};
+pub use class::{Class, BoundMethod};
 pub use misc::{Marker, UserData};
 pub use numeric::{int_from_str, float_from_str};
 pub use iterator::UserIterator;
+pub use annotation::BuiltinType;
 
 use misc::Nil;
 
@@ -37,10 +48,14 @@ pub enum Type {
     Float,
     String,
     Tuple,
+    List,
+    Dict,
     Function,
     Iterator,
+    Coroutine,
     Metatable,
     Object,
+    Module,
     Error,
     UserData,
 }
@@ -55,10 +70,14 @@ impl Type {
             Self::Float => static_symbol!("float"),
             Self::String => static_symbol!("string"),
             Self::Tuple => static_symbol!("tuple"),
+            Self::List => static_symbol!("list"),
+            Self::Dict => static_symbol!("dict"),
             Self::Function => static_symbol!("function"),
             Self::Iterator => static_symbol!("iterator"),
+            Self::Coroutine => static_symbol!("coroutine"),
             Self::Metatable => static_symbol!("metatable"),
             Self::Object => static_symbol!("object"),
+            Self::Module => static_symbol!("module"),
             Self::Error => static_symbol!("error"),
             Self::UserData => static_symbol!("userdata"),
         };
@@ -97,13 +116,19 @@ pub trait MetaObject {
     fn iter_next(&self, state: &Variant) -> Option<ExecResult<Variant>> { None }
     fn iter_get(&self, state: &Variant) -> Option<ExecResult<Variant>> { None }
     
+    // attributes
+    fn getattr(&self, name: &StringSymbol) -> Option<ExecResult<Variant>> { None }
+    fn setattr(&self, name: &StringSymbol, value: Variant) -> Option<ExecResult<()>> { None }
+    fn delattr(&self, name: &StringSymbol) -> Option<ExecResult<()>> { None }
+    
     // collections
     fn len(&self) -> Option<ExecResult<usize>> { None }
-    //fn getitem(&self, item: &Variant) -> Option<ExecResult<Variant>> { None }
-    //fn setitem(&self, item: &Variant) -> Option<ExecResult<Variant>> { None }
+    fn getitem(&self, key: &Variant) -> Option<ExecResult<Variant>> { None }
+    fn setitem(&self, key: &Variant, value: Variant) -> Option<ExecResult<()>> { None }
+    fn delitem(&self, key: &Variant) -> Option<ExecResult<()>> { None }
     
     // callable
-    fn invoke(&self, args: &[Variant]) -> Option<ExecResult<Call>> { None }
+    fn invoke(&self, args: &[Variant], kwargs: &[(StringSymbol, Variant)]) -> Option<ExecResult<Call>> { None }
     
     // unary operators
     fn op_neg(&self) -> Option<ExecResult<Variant>> { None }
@@ -111,6 +136,9 @@ pub trait MetaObject {
     fn op_inv(&self) -> Option<ExecResult<Variant>> { None }
 
     // arithmetic operators
+    fn op_pow(&self, rhs: &Variant) -> Option<ExecResult<Variant>> { None }
+    fn op_rpow(&self, lhs: &Variant) -> Option<ExecResult<Variant>> { None }
+    
     fn op_mul(&self, rhs: &Variant) -> Option<ExecResult<Variant>> { None }
     fn op_rmul(&self, lhs: &Variant) -> Option<ExecResult<Variant>> { None }
     
@@ -181,6 +209,36 @@ impl Variant {
         Ok(self.len()? == 0)
     }
     
+    pub fn getattr(&self, name: &StringSymbol) -> ExecResult<Variant> {
+        self.as_meta().getattr(name)
+            .ok_or_else(|| RuntimeError::metamethod_not_supported(self, MethodTag::GetAttr))?
+    }
+    
+    pub fn setattr(&self, name: &StringSymbol, value: Variant) -> ExecResult<()> {
+        self.as_meta().setattr(name, value)
+            .ok_or_else(|| RuntimeError::metamethod_not_supported(self, MethodTag::SetAttr))?
+    }
+    
+    pub fn delattr(&self, name: &StringSymbol) -> ExecResult<()> {
+        self.as_meta().delattr(name)
+            .ok_or_else(|| RuntimeError::metamethod_not_supported(self, MethodTag::DelAttr))?
+    }
+    
+    pub fn getitem(&self, key: &Variant) -> ExecResult<Variant> {
+        self.as_meta().getitem(key)
+            .ok_or_else(|| RuntimeError::metamethod_not_supported(self, MethodTag::GetItem))?
+    }
+    
+    pub fn setitem(&self, key: &Variant, value: Variant) -> ExecResult<()> {
+        self.as_meta().setitem(key, value)
+            .ok_or_else(|| RuntimeError::metamethod_not_supported(self, MethodTag::SetItem))?
+    }
+    
+    pub fn delitem(&self, key: &Variant) -> ExecResult<()> {
+        self.as_meta().delitem(key)
+            .ok_or_else(|| RuntimeError::metamethod_not_supported(self, MethodTag::DelItem))?
+    }
+    
     pub fn iter_init(&self) -> ExecResult<IterState> {
         self.as_meta().iter_init()
             .ok_or_else(|| RuntimeError::metamethod_not_supported(self, MethodTag::IterInit))?
@@ -196,8 +254,8 @@ impl Variant {
             .ok_or_else(|| RuntimeError::metamethod_not_supported(self, MethodTag::IterItem))?
     }
     
-    pub fn invoke(&self, args: &[Variant]) -> ExecResult<Call> {
-        self.as_meta().invoke(args)
+    pub fn invoke(&self, args: &[Variant], kwargs: &[(StringSymbol, Variant)]) -> ExecResult<Call> {
+        self.as_meta().invoke(args, kwargs)
             .ok_or_else(|| RuntimeError::metamethod_not_supported(self, MethodTag::Invoke))?
     }
     
@@ -210,7 +268,13 @@ impl Variant {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MethodTag {
     Invoke,
+    GetAttr,
+    SetAttr,
+    DelAttr,
     Len,
+    GetItem,
+    SetItem,
+    DelItem,
     IterInit,
     IterNext,
     IterItem,
@@ -231,8 +295,16 @@ impl MethodTag {
             Self::IterNext => "iter_next",
             Self::IterItem => "iter_get",
             
+            // attributes
+            Self::GetAttr => "getattr",
+            Self::SetAttr => "setattr",
+            Self::DelAttr => "delattr",
+            
             // sequences
             Self::Len => "len",
+            Self::GetItem => "getitem",
+            Self::SetItem => "setitem",
+            Self::DelItem => "delitem",
             
             // primitive coercion
             Self::AsBool => "bool",
@@ -0,0 +1,195 @@
+//! Runtime checks for type annotations.
+//!
+//! Type annotations are compiled into ordinary values: builtin types are represented by their name,
+//! classes by the class itself, `nil` by the nil value, and unions by a tuple of their alternatives.
+
+use core::fmt::Write;
+use crate::runtime::Variant;
+use crate::runtime::gc::Gc;
+use crate::runtime::types::{Type, Class};
+use crate::runtime::errors::{ExecResult, RuntimeError};
+
+
+/// The builtin types that can be named in a type annotation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuiltinType {
+    Any,
+    Bool,
+    Int,
+    Float,
+    String,
+    Tuple,
+    List,
+    Dict,
+    Function,
+    Iterator,
+    Coroutine,
+    Class,
+    Object,
+    Module,
+    Error,
+}
+
+impl BuiltinType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let builtin = match name {
+            "Any" => Self::Any,
+            "Bool" => Self::Bool,
+            "Int" => Self::Int,
+            "Float" => Self::Float,
+            "String" => Self::String,
+            "Tuple" => Self::Tuple,
+            "List" => Self::List,
+            "Dict" => Self::Dict,
+            "Function" => Self::Function,
+            "Iterator" => Self::Iterator,
+            "Coroutine" => Self::Coroutine,
+            "Class" => Self::Class,
+            "Object" => Self::Object,
+            "Module" => Self::Module,
+            "Error" => Self::Error,
+            _ => return None,
+        };
+        Some(builtin)
+    }
+    
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Any => "Any",
+            Self::Bool => "Bool",
+            Self::Int => "Int",
+            Self::Float => "Float",
+            Self::String => "String",
+            Self::Tuple => "Tuple",
+            Self::List => "List",
+            Self::Dict => "Dict",
+            Self::Function => "Function",
+            Self::Iterator => "Iterator",
+            Self::Coroutine => "Coroutine",
+            Self::Class => "Class",
+            Self::Object => "Object",
+            Self::Module => "Module",
+            Self::Error => "Error",
+        }
+    }
+    
+    /// The runtime type named by this builtin, if it names exactly one type
+    pub fn type_tag(&self) -> Option<Type> {
+        let tag = match self {
+            Self::Any | Self::Float => return None,
+            Self::Bool => Type::Boolean,
+            Self::Int => Type::Integer,
+            Self::String => Type::String,
+            Self::Tuple => Type::Tuple,
+            Self::List => Type::List,
+            Self::Dict => Type::Dict,
+            Self::Function => Type::Function,
+            Self::Iterator => Type::Iterator,
+            Self::Coroutine => Type::Coroutine,
+            Self::Class => Type::Metatable,
+            Self::Object => Type::Object,
+            Self::Module => Type::Module,
+            Self::Error => Type::Error,
+        };
+        Some(tag)
+    }
+    
+    /// True if values of the given runtime type satisfy this builtin
+    pub fn accepts(&self, tag: Type) -> bool {
+        match self {
+            Self::Any => true,
+            
+            // integers are also accepted where a float is expected
+            Self::Float => matches!(tag, Type::Float | Type::Integer),
+            
+            builtin => builtin.type_tag() == Some(tag),
+        }
+    }
+    
+    fn matches(&self, value: &Variant) -> bool {
+        self.accepts(value.type_tag())
+    }
+}
+
+
+impl Variant {
+    /// Check that a value satisfies a compiled type annotation
+    pub fn check_type(&self, annotation: &Variant) -> ExecResult<()> {
+        if self.is_instance(annotation)? {
+            return Ok(());
+        }
+        
+        let mut expected = String::new();
+        fmt_annotation(&mut expected, annotation)?;
+        Err(RuntimeError::type_check_failed(expected, self))
+    }
+    
+    fn is_instance(&self, annotation: &Variant) -> ExecResult<bool> {
+        match annotation {
+            Variant::Nil => Ok(matches!(self, Variant::Nil)),
+            
+            Variant::Class(class) => match self {
+                Variant::Object(obj) => Ok(obj.class().is_some_and(|base| is_subclass(base, class))),
+                _ => Ok(false),
+            },
+            
+            Variant::Tuple(items) => {
+                for item in items.items().iter() {
+                    if self.is_instance(item)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            
+            annotation => {
+                let builtin = annotation.as_strval()
+                    .and_then(|name| name.with_str(BuiltinType::from_name))
+                    .ok_or_else(|| RuntimeError::invalid_value("invalid type annotation"))?;
+                
+                Ok(builtin.matches(self))
+            }
+        }
+    }
+}
+
+fn is_subclass(mut class: Gc<Class>, base: &Gc<Class>) -> bool {
+    loop {
+        if Gc::ptr_eq(&class, base) {
+            return true;
+        }
+        match class.base() {
+            Some(next) => class = next,
+            None => return false,
+        }
+    }
+}
+
+fn fmt_annotation(buf: &mut String, annotation: &Variant) -> ExecResult<()> {
+    let result = match annotation {
+        Variant::Nil => buf.write_str("nil"),
+        
+        Variant::Class(class) => match class.name() {
+            Some(name) => write!(buf, "{}", name),
+            None => buf.write_str("<anonymous class>"),
+        },
+        
+        Variant::Tuple(items) => {
+            for (idx, item) in items.items().iter().enumerate() {
+                if idx > 0 {
+                    buf.push_str(" | ");
+                }
+                fmt_annotation(buf, item)?;
+            }
+            Ok(())
+        }
+        
+        annotation => {
+            let name = annotation.as_strval()
+                .unwrap_or_else(|| annotation.type_tag().name());
+            write!(buf, "{}", name)
+        }
+    };
+    
+    result.map_err(|err| RuntimeError::other(err.to_string()))
+}
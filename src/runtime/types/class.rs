@@ -0,0 +1,172 @@
+use core::cell::RefCell;
+use crate::language::Access;
+use crate::runtime::{Variant, HashMap, DefaultBuildHasher};
+use crate::runtime::gc::{Gc, GcTrace};
+use crate::runtime::strings::{StringValue, StringSymbol, static_symbol};
+use crate::runtime::types::{Type, MetaObject, Object};
+use crate::runtime::errors::{ExecResult, RuntimeError};
+
+
+/// The metatable shared by all instances of a class.
+/// Fields are copied into each new instance, while methods are looked up through the class (and its bases).
+/// Field defaults are evaluated once when the class is defined, so every instance starts with the same values.
+#[derive(Debug)]
+pub struct Class {
+    name: Option<StringSymbol>,
+    base: Option<Gc<Class>>,
+    fields: RefCell<HashMap<StringSymbol, (Access, Variant)>>,
+    methods: RefCell<HashMap<StringSymbol, Variant>>,
+}
+
+impl Class {
+    pub fn new(name: Option<StringSymbol>, base: Option<Gc<Class>>) -> Self {
+        Self {
+            name, base,
+            fields: RefCell::new(HashMap::with_hasher(DefaultBuildHasher::default())),
+            methods: RefCell::new(HashMap::with_hasher(DefaultBuildHasher::default())),
+        }
+    }
+    
+    pub fn name(&self) -> Option<StringSymbol> { self.name }
+    
+    pub fn base(&self) -> Option<Gc<Class>> { self.base }
+    
+    pub fn insert_field(&self, name: StringSymbol, access: Access, default: Variant) {
+        self.fields.borrow_mut().insert(name, (access, default));
+    }
+    
+    pub fn insert_method(&self, name: StringSymbol, method: Variant) {
+        self.methods.borrow_mut().insert(name, method);
+    }
+    
+    /// Search this class and then each of its bases for a method
+    pub fn lookup_method(&self, name: &StringSymbol) -> Option<Variant> {
+        if let Some(method) = self.methods.borrow().get(name) {
+            return Some(*method);
+        }
+        self.base.and_then(|base| base.lookup_method(name))
+    }
+}
+
+impl Gc<Class> {
+    /// Create a new instance with the fields of this class and all of its bases.
+    /// Does not call the initializer.
+    pub fn instantiate(&self) -> Object {
+        let instance = Object::with_class(*self);
+        self.init_fields(&instance);
+        instance
+    }
+    
+    fn init_fields(&self, instance: &Object) {
+        // fields from derived classes override those of the base
+        if let Some(base) = self.base {
+            base.init_fields(instance);
+        }
+        
+        for (name, (access, default)) in self.fields.borrow().iter() {
+            instance.insert_attr(*name, *access, *default);
+        }
+    }
+}
+
+unsafe impl GcTrace for Class {
+    fn trace(&self) {
+        if let Some(base) = self.base {
+            base.mark_trace();
+        }
+        
+        for (_, default) in self.fields.borrow().values() {
+            default.trace();
+        }
+        
+        for method in self.methods.borrow().values() {
+            method.trace();
+        }
+    }
+    
+    fn size_hint(&self) -> usize {
+        core::mem::size_of::<(StringSymbol, (Access, Variant))>() * self.fields.borrow().capacity()
+        + core::mem::size_of::<(StringSymbol, Variant)>() * self.methods.borrow().capacity()
+    }
+}
+
+impl MetaObject for Gc<Class> {
+    fn type_tag(&self) -> Type { Type::Metatable }
+    
+    fn getattr(&self, name: &StringSymbol) -> Option<ExecResult<Variant>> {
+        let result = self.lookup_method(name)
+            .ok_or_else(|| RuntimeError::attribute_not_found(&Variant::Class(*self), *name));
+        
+        Some(result)
+    }
+    
+    fn cmp_eq(&self, other: &Variant) -> Option<ExecResult<bool>> {
+        match other {
+            Variant::Class(other) => Some(Ok(Gc::ptr_eq(self, other))),
+            _ => Some(Ok(false)),
+        }
+    }
+    
+    fn fmt_repr(&self) -> ExecResult<StringValue> {
+        let result = match self.name {
+            Some(name) => format!("<class '{}'>", name),
+            None => format!("<class at {:#X}>", Gc::as_id(self)),
+        };
+        
+        Ok(StringValue::new_uninterned(result))
+    }
+}
+
+
+/// A method that has been retrieved from an instance.
+/// When called, the receiver is passed as the first argument.
+#[derive(Debug)]
+pub struct BoundMethod {
+    receiver: Variant,
+    method: Variant,
+}
+
+impl BoundMethod {
+    pub fn new(receiver: Variant, method: Variant) -> Self {
+        Self { receiver, method }
+    }
+    
+    pub fn receiver(&self) -> &Variant { &self.receiver }
+    
+    pub fn method(&self) -> &Variant { &self.method }
+}
+
+unsafe impl GcTrace for BoundMethod {
+    fn trace(&self) {
+        self.receiver.trace();
+        self.method.trace();
+    }
+}
+
+impl MetaObject for Gc<BoundMethod> {
+    fn type_tag(&self) -> Type { Type::Function }
+    
+    fn type_name(&self) -> ExecResult<StringValue> {
+        Ok(static_symbol!("method").into())
+    }
+    
+    fn cmp_eq(&self, other: &Variant) -> Option<ExecResult<bool>> {
+        match other {
+            Variant::BoundMethod(other) => {
+                let result = self.method.cmp_eq(&other.method).and_then(
+                    |eq| if eq { self.receiver.cmp_eq(&other.receiver) } else { Ok(false) }
+                );
+                Some(result)
+            },
+            _ => Some(Ok(false)),
+        }
+    }
+    
+    fn fmt_repr(&self) -> ExecResult<StringValue> {
+        let result = format!(
+            "<bound {} of {}>", self.method.fmt_repr()?, self.receiver.fmt_repr()?
+        );
+        
+        Ok(StringValue::new_uninterned(result))
+    }
+}
@@ -0,0 +1,236 @@
+use core::cell::RefCell;
+use core::fmt::Write;
+use crate::runtime::{Variant, VariantKey, HashMap, DefaultBuildHasher};
+use crate::runtime::gc::{Gc, GcTrace};
+use crate::runtime::function::NativeFunction;
+use crate::runtime::module::NamespaceEnv;
+use crate::runtime::strings::{StringValue, StringSymbol, static_symbol};
+use crate::runtime::iter::IterState;
+use crate::runtime::types::{Type, MetaObject, BoundMethod, List};
+use crate::runtime::types::recursion;
+use crate::runtime::errors::{ExecResult, RuntimeError};
+
+
+/// A mutable mapping from hashable keys to values
+#[derive(Debug)]
+pub struct Dict {
+    items: RefCell<HashMap<VariantKey, Variant>>,
+}
+
+impl Default for Dict {
+    fn default() -> Self { Self::new() }
+}
+
+impl Dict {
+    pub fn new() -> Self {
+        Self {
+            items: RefCell::new(HashMap::with_hasher(DefaultBuildHasher::default())),
+        }
+    }
+    
+    /// Create a new dict from an iterable that produces (key, value) pairs
+    pub fn from_iterable(iterable: &Variant) -> ExecResult<Self> {
+        let dict = Self::new();
+        
+        for item in iterable.iter_init()? {
+            let pair = item?.iter_init()?.into_iter()
+                .collect::<ExecResult<Vec<Variant>>>()?;
+            
+            match pair.as_slice() {
+                [key, value] => dict.insert(VariantKey::try_from(key)?, *value),
+                _ => return Err(RuntimeError::invalid_value("dict items must be (key, value) pairs")),
+            }
+        }
+        
+        Ok(dict)
+    }
+    
+    pub fn len(&self) -> usize { self.items.borrow().len() }
+    
+    pub fn is_empty(&self) -> bool { self.items.borrow().is_empty() }
+    
+    pub fn get(&self, key: &VariantKey) -> Option<Variant> {
+        self.items.borrow().get(key).copied()
+    }
+    
+    // if the key already exists, the value is overwritten
+    pub fn insert(&self, key: VariantKey, value: Variant) {
+        self.items.borrow_mut().insert(key, value);
+    }
+    
+    pub fn remove(&self, key: &VariantKey) -> Option<Variant> {
+        self.items.borrow_mut().remove(key)
+    }
+    
+    pub fn keys(&self) -> Vec<Variant> {
+        self.items.borrow().keys().map(|key| *key.as_variant()).collect()
+    }
+    
+    pub fn values(&self) -> Vec<Variant> {
+        self.items.borrow().values().copied().collect()
+    }
+    
+    pub fn items(&self) -> Vec<(Variant, Variant)> {
+        self.items.borrow().iter()
+            .map(|(key, value)| (*key.as_variant(), *value))
+            .collect()
+    }
+    
+    fn eq(&self, other: &Self) -> ExecResult<bool> {
+        if self.len() != other.len() {
+            return Ok(false);
+        }
+        
+        for (key, value) in self.items() {
+            let other_value = match other.get(&VariantKey::try_from(key)?) {
+                Some(other_value) => other_value,
+                None => return Ok(false),
+            };
+            
+            if !value.cmp_eq(&other_value)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+unsafe impl GcTrace for Dict {
+    fn trace(&self) {
+        for (key, value) in self.items.borrow().iter() {
+            key.as_variant().trace();
+            value.trace();
+        }
+    }
+    
+    fn size_hint(&self) -> usize {
+        core::mem::size_of::<(VariantKey, Variant)>() * self.items.borrow().capacity()
+    }
+}
+
+
+// Methods
+
+fn into_dict(receiver: &Variant) -> ExecResult<Gc<Dict>> {
+    match receiver {
+        Variant::Dict(dict) => Ok(*dict),
+        _ => Err(RuntimeError::invalid_value("method receiver must be a dict")),
+    }
+}
+
+// method functions are created when they are accessed, and bound to the receiving dict
+fn lookup_method(name: &StringSymbol) -> Option<NativeFunction> {
+    let method = if *name == static_symbol!("keys") {
+        native_function!(keys, NamespaceEnv::new(), params(receiver) => {
+            let keys = into_dict(receiver)?.keys();
+            Ok(Variant::from(List::from(keys)))
+        })
+    } else if *name == static_symbol!("values") {
+        native_function!(values, NamespaceEnv::new(), params(receiver) => {
+            let values = into_dict(receiver)?.values();
+            Ok(Variant::from(List::from(values)))
+        })
+    } else if *name == static_symbol!("items") {
+        native_function!(items, NamespaceEnv::new(), params(receiver) => {
+            let items = into_dict(receiver)?.items().into_iter()
+                .map(|(key, value)| Variant::from(vec![key, value].into_boxed_slice()))
+                .collect::<Vec<Variant>>();
+            
+            Ok(Variant::from(List::from(items)))
+        })
+    } else if *name == static_symbol!("pop") {
+        native_function!(pop, NamespaceEnv::new(), params(receiver, key) => {
+            let key = VariantKey::try_from(key)?;
+            into_dict(receiver)?.remove(&key)
+                .ok_or_else(|| RuntimeError::key_not_found(key.as_variant()))
+        })
+    } else {
+        return None
+    };
+    
+    Some(method)
+}
+
+
+impl MetaObject for Gc<Dict> {
+    fn type_tag(&self) -> Type { Type::Dict }
+    
+    fn len(&self) -> Option<ExecResult<usize>> {
+        Some(Ok(Dict::len(self)))
+    }
+    
+    fn getitem(&self, key: &Variant) -> Option<ExecResult<Variant>> {
+        let result = VariantKey::try_from(key).and_then(
+            |key| self.get(&key).ok_or_else(|| RuntimeError::key_not_found(key.as_variant()))
+        );
+        
+        Some(result)
+    }
+    
+    fn setitem(&self, key: &Variant, value: Variant) -> Option<ExecResult<()>> {
+        let result = VariantKey::try_from(key)
+            .map(|key| self.insert(key, value));
+        
+        Some(result)
+    }
+    
+    fn delitem(&self, key: &Variant) -> Option<ExecResult<()>> {
+        let result = VariantKey::try_from(key).and_then(
+            |key| self.remove(&key).map(|_| ()).ok_or_else(|| RuntimeError::key_not_found(key.as_variant()))
+        );
+        
+        Some(result)
+    }
+    
+    fn getattr(&self, name: &StringSymbol) -> Option<ExecResult<Variant>> {
+        let receiver = Variant::Dict(*self);
+        let result = lookup_method(name)
+            .map(|method| Variant::from(BoundMethod::new(receiver, Variant::from(method))))
+            .ok_or_else(|| RuntimeError::attribute_not_found(&receiver, *name));
+        
+        Some(result)
+    }
+    
+    // iterates over a snapshot of the keys, so the dict may be modified while it is being iterated
+    fn iter_init(&self) -> Option<ExecResult<IterState>> {
+        let keys = Variant::from(self.keys().into_boxed_slice());
+        Some(keys.iter_init())
+    }
+    
+    fn cmp_eq(&self, other: &Variant) -> Option<ExecResult<bool>> {
+        match other {
+            Variant::Dict(other) if Gc::ptr_eq(self, other) => Some(Ok(true)),
+            // if these dicts are already being compared further up, any difference will be found there
+            Variant::Dict(other) => match recursion::visit_eq(Gc::as_id(self), Gc::as_id(other)) {
+                Some(_visit) => Some(self.eq(other)),
+                None => Some(Ok(true)),
+            },
+            _ => Some(Ok(false)),
+        }
+    }
+    
+    fn fmt_repr(&self) -> ExecResult<StringValue> {
+        let items = self.items();
+        if items.is_empty() {
+            return Ok(StringValue::from(static_symbol!("[:]")));
+        }
+        
+        let _visit = match recursion::visit_repr(Gc::as_id(self)) {
+            Some(visit) => visit,
+            None => return Ok(StringValue::from(static_symbol!("[...]"))),
+        };
+        
+        let mut buf = String::from("[");
+        
+        for (idx, (key, value)) in items.iter().enumerate() {
+            if idx > 0 {
+                buf.push_str(", ");
+            }
+            write!(&mut buf, "{}: {}", key.fmt_repr()?, value.fmt_repr()?)
+                .map_err(|err| RuntimeError::other(err.to_string()))?;
+        }
+        buf.push(']');
+        
+        Ok(StringValue::new_maybe_interned(buf))
+    }
+}
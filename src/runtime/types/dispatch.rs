@@ -4,9 +4,11 @@ use crate::language::{IntType, FloatType};
 use crate::runtime::Variant;
 use crate::runtime::gc::Gc;
 use crate::runtime::function::{Call, Function, NativeFunction};
-use crate::runtime::strings::StringValue;
+use crate::runtime::module::Module;
+use crate::runtime::strings::{StringValue, StringSymbol};
 use crate::runtime::iter::IterState;
-use crate::runtime::types::{Type, MetaObject, Tuple, UserData, Nil, Marker, UserIterator};
+use crate::runtime::vm::{Generator, Coroutine};
+use crate::runtime::types::{Type, MetaObject, Tuple, List, Dict, Object, Class, BoundMethod, UserData, Nil, Marker, UserIterator};
 use crate::runtime::errors::{ExecResult, RuntimeError};
 
 
@@ -46,13 +48,22 @@ macro_rules! static_dispatch {
                 Variant::GCStr(gc_str) => <StringValue as MetaObject>::$name(&(*gc_str).into(), $( $arg ),* ),
                 
                 Variant::Tuple(tuple) => <Tuple as MetaObject>::$name(tuple, $( $arg ),* ),
+                Variant::List(list) => <Gc<List> as MetaObject>::$name(list, $( $arg ),* ),
+                Variant::Dict(dict) => <Gc<Dict> as MetaObject>::$name(dict, $( $arg ),* ),
+                Variant::Object(obj) => <Gc<Object> as MetaObject>::$name(obj, $( $arg ),* ),
+                Variant::Class(class) => <Gc<Class> as MetaObject>::$name(class, $( $arg ),* ),
                 
                 Variant::Function(fun) => <Gc<Function> as MetaObject>::$name(fun, $( $arg ),* ),
                 Variant::NativeFunction(fun) => <Gc<NativeFunction> as MetaObject>::$name(fun, $( $arg ),* ),
+                Variant::BoundMethod(method) => <Gc<BoundMethod> as MetaObject>::$name(method, $( $arg ),* ),
+                
+                Variant::Module(module) => <Gc<Module> as MetaObject>::$name(module, $( $arg ),* ),
                 
                 Variant::Error(error) => <Gc<RuntimeError> as MetaObject>::$name(error, $( $arg ),* ),
                 
                 Variant::Iterator(iter) => <Gc<dyn UserIterator> as MetaObject>::$name(iter, $( $arg ),* ),
+                Variant::Generator(generator) => <Gc<Generator> as MetaObject>::$name(generator, $( $arg ),* ),
+                Variant::Coroutine(coroutine) => <Gc<Coroutine> as MetaObject>::$name(coroutine, $( $arg ),* ),
                 
                 Variant::UserData(data) => <(dyn UserData + 'static) as MetaObject>::$name(&**data, $( $arg ),* ),
             }
@@ -77,11 +88,19 @@ impl MetaObject for MetaDispatch<'_> {
     static_dispatch!{ fn iter_get(state: &Variant) -> Option<ExecResult<Variant>> }
     static_dispatch!{ fn iter_next(state: &Variant) -> Option<ExecResult<Variant>> }
     
+    // attributes
+    static_dispatch!{ fn getattr(name: &StringSymbol) -> Option<ExecResult<Variant>> }
+    static_dispatch!{ fn setattr(name: &StringSymbol, value: Variant) -> Option<ExecResult<()>> }
+    static_dispatch!{ fn delattr(name: &StringSymbol) -> Option<ExecResult<()>> }
+    
     // collections
     static_dispatch!{ fn len() -> Option<ExecResult<usize>> }
+    static_dispatch!{ fn getitem(key: &Variant) -> Option<ExecResult<Variant>> }
+    static_dispatch!{ fn setitem(key: &Variant, value: Variant) -> Option<ExecResult<()>> }
+    static_dispatch!{ fn delitem(key: &Variant) -> Option<ExecResult<()>> }
     
     // callable
-    static_dispatch!{ fn invoke(args: &[Variant]) -> Option<ExecResult<Call>> }
+    static_dispatch!{ fn invoke(args: &[Variant], kwargs: &[(StringSymbol, Variant)]) -> Option<ExecResult<Call>> }
     
     // unary operators
     static_dispatch!{ fn op_neg() -> Option<ExecResult<Variant>> }
@@ -89,6 +108,9 @@ impl MetaObject for MetaDispatch<'_> {
     static_dispatch!{ fn op_inv() -> Option<ExecResult<Variant>> }
 
     // arithmetic operators
+    static_dispatch!{ fn op_pow(rhs: &Variant) -> Option<ExecResult<Variant>> }
+    static_dispatch!{ fn op_rpow(lhs: &Variant) -> Option<ExecResult<Variant>> }
+    
     static_dispatch!{ fn op_mul(rhs: &Variant) -> Option<ExecResult<Variant>> }
     static_dispatch!{ fn op_rmul(lhs: &Variant) -> Option<ExecResult<Variant>> }
     
@@ -3,13 +3,20 @@ use crate::runtime::gc::{Gc, GcTrace};
 use crate::runtime::strings::StringValue;
 use crate::runtime::types::{Type, MetaObject};
 use crate::runtime::iter::IterState;
-use crate::runtime::errors::{ExecResult};
+use crate::runtime::vm::{VirtualMachine, Generator};
+use crate::runtime::errors::{ExecResult, RuntimeError};
 
 
 /// Similar use case as UserData but a bit more limited in scope
 pub trait UserIterator: GcTrace {
     fn next_state(&self, state: Option<&Variant>) -> ExecResult<Variant>;
     fn get_item(&self, state: &Variant) -> ExecResult<Variant>;
+    
+    /// Used when the iterator is advanced by the VM. Iterators that wrap other iterators should 
+    /// override this to advance them using the VM as well, so that they can contain generators.
+    fn next_state_vm(&self, _vm: &mut VirtualMachine<'_>, state: Option<&Variant>) -> ExecResult<Variant> {
+        self.next_state(state)
+    }
 }
 
 // unlike UserData, the MetaObject impl for UserIterator is not customizable
@@ -49,3 +56,36 @@ impl MetaObject for Gc<dyn UserIterator> {
         Some(Ok(IterState::new(iter, state)))
     }
 }
+
+
+// Generators can only be resumed by the VM, see `VirtualMachine::iter_init()`
+impl MetaObject for Gc<Generator> {
+    fn type_tag(&self) -> Type { Type::Iterator }
+    
+    fn fmt_repr(&self) -> ExecResult<StringValue> {
+        let result = format!(
+            "<generator from {} at {:#X}>", self.signature().fmt_name(), Gc::as_id(self)
+        );
+        
+        Ok(StringValue::new_uninterned(result))
+    }
+    
+    fn cmp_eq(&self, other: &Variant) -> Option<ExecResult<bool>> {
+        match other {
+            Variant::Generator(other) => Some(Ok(Gc::ptr_eq(self, other))),
+            _ => Some(Ok(false)),
+        }
+    }
+    
+    fn iter_get(&self, _state: &Variant) -> Option<ExecResult<Variant>> {
+        Some(Ok(self.value()))
+    }
+    
+    fn iter_next(&self, _state: &Variant) -> Option<ExecResult<Variant>> {
+        Some(Err(RuntimeError::generator_not_resumable()))
+    }
+    
+    fn iter_init(&self) -> Option<ExecResult<IterState>> {
+        Some(Err(RuntimeError::generator_not_resumable()))
+    }
+}
@@ -0,0 +1,273 @@
+use core::cell::RefCell;
+use core::fmt::Write;
+use crate::language::IntType;
+use crate::runtime::Variant;
+use crate::runtime::gc::{Gc, GcTrace};
+use crate::runtime::function::NativeFunction;
+use crate::runtime::module::NamespaceEnv;
+use crate::runtime::strings::{StringValue, StringSymbol, static_symbol};
+use crate::runtime::iter::IterState;
+use crate::runtime::types::{Type, MetaObject, UserIterator, BoundMethod};
+use crate::runtime::types::recursion;
+use crate::runtime::errors::{ExecResult, RuntimeError};
+
+
+/// A mutable sequence of values
+#[derive(Debug, Default)]
+pub struct List {
+    items: RefCell<Vec<Variant>>,
+}
+
+impl From<Vec<Variant>> for List {
+    fn from(items: Vec<Variant>) -> Self {
+        Self { items: RefCell::new(items) }
+    }
+}
+
+impl List {
+    pub fn new() -> Self { Self::default() }
+    
+    /// Collect the values produced by an iterable into a new list
+    pub fn from_iterable(iterable: &Variant) -> ExecResult<Self> {
+        let items = iterable.iter_init()?.into_iter()
+            .collect::<ExecResult<Vec<Variant>>>()?;
+        
+        Ok(Self::from(items))
+    }
+    
+    pub fn len(&self) -> usize { self.items.borrow().len() }
+    
+    pub fn is_empty(&self) -> bool { self.items.borrow().is_empty() }
+    
+    pub fn to_vec(&self) -> Vec<Variant> { self.items.borrow().clone() }
+    
+    pub fn get(&self, index: usize) -> Option<Variant> {
+        self.items.borrow().get(index).copied()
+    }
+    
+    pub fn push(&self, value: Variant) {
+        self.items.borrow_mut().push(value)
+    }
+    
+    // negative indices count back from the end of the list
+    fn resolve_index(&self, index: &Variant) -> ExecResult<usize> {
+        let len = self.len();
+        self.normalize_index(index, len)
+            .filter(|idx| *idx < len)
+            .ok_or_else(|| RuntimeError::index_out_of_range(index, len))
+    }
+    
+    fn normalize_index(&self, index: &Variant, len: usize) -> Option<usize> {
+        let index = index.as_int().ok()?;
+        if index.is_negative() {
+            let offset = usize::try_from(index.checked_neg()?).ok()?;
+            len.checked_sub(offset)
+        } else {
+            usize::try_from(index).ok()
+        }
+    }
+    
+    fn get_item(&self, index: &Variant) -> ExecResult<Variant> {
+        let idx = self.resolve_index(index)?;
+        Ok(self.items.borrow()[idx])
+    }
+    
+    fn set_item(&self, index: &Variant, value: Variant) -> ExecResult<()> {
+        let idx = self.resolve_index(index)?;
+        self.items.borrow_mut()[idx] = value;
+        Ok(())
+    }
+    
+    fn insert(&self, index: &Variant, value: Variant) -> ExecResult<()> {
+        // inserting at the end of the list is allowed
+        let len = self.len();
+        let idx = self.normalize_index(index, len)
+            .filter(|idx| *idx <= len)
+            .ok_or_else(|| RuntimeError::index_out_of_range(index, len))?;
+        
+        self.items.borrow_mut().insert(idx, value);
+        Ok(())
+    }
+    
+    fn remove(&self, index: &Variant) -> ExecResult<Variant> {
+        let idx = self.resolve_index(index)?;
+        Ok(self.items.borrow_mut().remove(idx))
+    }
+}
+
+unsafe impl GcTrace for List {
+    fn trace(&self) {
+        for item in self.items.borrow().iter() {
+            item.trace();
+        }
+    }
+    
+    fn size_hint(&self) -> usize {
+        core::mem::size_of::<Variant>() * self.items.borrow().capacity()
+    }
+}
+
+
+// Methods
+
+fn into_list(receiver: &Variant) -> ExecResult<Gc<List>> {
+    match receiver {
+        Variant::List(list) => Ok(*list),
+        _ => Err(RuntimeError::invalid_value("method receiver must be a list")),
+    }
+}
+
+// method functions are created when they are accessed, and bound to the receiving list
+fn lookup_method(name: &StringSymbol) -> Option<NativeFunction> {
+    let method = if *name == static_symbol!("append") {
+        native_function!(append, NamespaceEnv::new(), params(receiver, value) => {
+            into_list(receiver)?.push(*value);
+            Ok(Variant::Nil)
+        })
+    } else if *name == static_symbol!("insert") {
+        native_function!(insert, NamespaceEnv::new(), params(receiver, index, value) => {
+            into_list(receiver)?.insert(index, *value)?;
+            Ok(Variant::Nil)
+        })
+    } else if *name == static_symbol!("pop") {
+        native_function!(pop, NamespaceEnv::new(), params(receiver), defaults(index = -1 as IntType) => {
+            let list = into_list(receiver)?;
+            if List::len(&list) == 0 {
+                return Err(RuntimeError::pop_from_empty_list());
+            }
+            list.remove(index)
+        })
+    } else {
+        return None
+    };
+    
+    Some(method)
+}
+
+
+impl MetaObject for Gc<List> {
+    fn type_tag(&self) -> Type { Type::List }
+    
+    fn len(&self) -> Option<ExecResult<usize>> {
+        Some(Ok(List::len(self)))
+    }
+    
+    fn getitem(&self, key: &Variant) -> Option<ExecResult<Variant>> {
+        Some(self.get_item(key))
+    }
+    
+    fn setitem(&self, key: &Variant, value: Variant) -> Option<ExecResult<()>> {
+        Some(self.set_item(key, value))
+    }
+    
+    fn delitem(&self, key: &Variant) -> Option<ExecResult<()>> {
+        Some(self.remove(key).map(|_| ()))
+    }
+    
+    fn getattr(&self, name: &StringSymbol) -> Option<ExecResult<Variant>> {
+        let receiver = Variant::List(*self);
+        let result = lookup_method(name)
+            .map(|method| Variant::from(BoundMethod::new(receiver, Variant::from(method))))
+            .ok_or_else(|| RuntimeError::attribute_not_found(&receiver, *name));
+        
+        Some(result)
+    }
+    
+    fn iter_init(&self) -> Option<ExecResult<IterState>> {
+        let iter: Box<dyn UserIterator> = Box::new(ListIter(*self));
+        let iter = Gc::from_box(iter);
+        iter.iter_init()
+    }
+    
+    fn cmp_eq(&self, other: &Variant) -> Option<ExecResult<bool>> {
+        let other = match other {
+            Variant::List(other) => other,
+            _ => return Some(Ok(false)),
+        };
+        
+        if Gc::ptr_eq(self, other) {
+            return Some(Ok(true));
+        }
+        
+        // if these lists are already being compared further up, any difference will be found there
+        let _visit = match recursion::visit_eq(Gc::as_id(self), Gc::as_id(other)) {
+            Some(visit) => visit,
+            None => return Some(Ok(true)),
+        };
+        
+        // copy the items so that comparisons can't observe the lists being borrowed
+        let (items, other_items) = (self.to_vec(), other.to_vec());
+        if items.len() != other_items.len() {
+            return Some(Ok(false));
+        }
+        
+        for (a, b) in items.iter().zip(other_items.iter()) {
+            match a.cmp_eq(b) {
+                Ok(true) => { },
+                result => return Some(result),
+            }
+        }
+        Some(Ok(true))
+    }
+    
+    fn fmt_repr(&self) -> ExecResult<StringValue> {
+        let _visit = match recursion::visit_repr(Gc::as_id(self)) {
+            Some(visit) => visit,
+            None => return Ok(StringValue::from(static_symbol!("[...]"))),
+        };
+        
+        let mut buf = String::from("[");
+        
+        for (idx, item) in self.to_vec().iter().enumerate() {
+            if idx > 0 {
+                buf.push_str(", ");
+            }
+            write!(&mut buf, "{}", item.fmt_repr()?)
+                .map_err(|err| RuntimeError::other(err.to_string()))?;
+        }
+        buf.push(']');
+        
+        Ok(StringValue::new_maybe_interned(buf))
+    }
+}
+
+
+// List Iterator
+
+// the list may be modified while it is being iterated, so bounds are checked on each step
+#[derive(Debug)]
+struct ListIter(Gc<List>);
+
+unsafe impl GcTrace for ListIter {
+    fn trace(&self) {
+        self.0.mark_trace()
+    }
+}
+
+impl UserIterator for ListIter {
+    fn get_item(&self, state: &Variant) -> ExecResult<Variant> {
+        let idx = usize::try_from(state.as_int()?)
+            .map_err(|_| RuntimeError::invalid_value("invalid state"))?;
+        
+        self.0.get(idx)
+            .ok_or_else(|| RuntimeError::index_out_of_range(state, List::len(&self.0)))
+    }
+    
+    fn next_state(&self, state: Option<&Variant>) -> ExecResult<Variant> {
+        let next = match state {
+            Some(state) => state.as_int()?
+                .checked_add(1)
+                .ok_or(RuntimeError::overflow_error())?,
+            
+            None => 0,
+        };
+        
+        let next_idx = usize::try_from(next)
+            .map_err(|_| RuntimeError::invalid_value("invalid state"))?;
+        
+        if next_idx >= List::len(&self.0) {
+            return Ok(Variant::Nil)
+        }
+        Ok(Variant::from(next))
+    }
+}
@@ -34,6 +34,7 @@ pub struct Metatable<T> {
     op_mul: Option<MethodBinary<T>>, // __mul
     op_div: Option<MethodBinary<T>>, // __div
     op_mod: Option<MethodBinary<T>>, // __mod
+    op_pow: Option<MethodBinary<T>>, // __pow
     op_and: Option<MethodBinary<T>>, // __and
     op_xor: Option<MethodBinary<T>>, // __xor
     op_or:  Option<MethodBinary<T>>, // __or
@@ -45,6 +46,7 @@ pub struct Metatable<T> {
     op_rmul: Option<MethodBinaryReflected<T>>, // __rmul
     op_rdiv: Option<MethodBinaryReflected<T>>, // __rdiv
     op_rmod: Option<MethodBinaryReflected<T>>, // __rmod
+    op_rpow: Option<MethodBinaryReflected<T>>, // __rpow
     op_rand: Option<MethodBinaryReflected<T>>, // __rand
     op_rxor: Option<MethodBinaryReflected<T>>, // __rxor
     op_ror:  Option<MethodBinaryReflected<T>>, // __ror
@@ -74,7 +76,7 @@ pub enum UnaryTag {
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum BinaryTag {
     Add, Sub,
-    Mul, Div, Mod,
+    Mul, Div, Mod, Pow,
     And, Xor, Or, 
     Shl, Shr,
 }
@@ -118,6 +120,7 @@ impl<T> Metatable<T> {
             BinaryTag::Mul => self.op_mul.as_ref(),
             BinaryTag::Div => self.op_div.as_ref(),
             BinaryTag::Mod => self.op_mod.as_ref(),
+            BinaryTag::Pow => self.op_pow.as_ref(),
             BinaryTag::And => self.op_and.as_ref(),
             BinaryTag::Xor => self.op_xor.as_ref(),
             BinaryTag::Or  => self.op_or.as_ref(),
@@ -133,6 +136,7 @@ impl<T> Metatable<T> {
             BinaryTag::Mul => self.op_mul.replace(method),
             BinaryTag::Div => self.op_div.replace(method),
             BinaryTag::Mod => self.op_mod.replace(method),
+            BinaryTag::Pow => self.op_pow.replace(method),
             BinaryTag::And => self.op_and.replace(method),
             BinaryTag::Xor => self.op_xor.replace(method),
             BinaryTag::Or  => self.op_or.replace(method),
@@ -148,6 +152,7 @@ impl<T> Metatable<T> {
             BinaryTag::Mul => self.op_mul.take(),
             BinaryTag::Div => self.op_div.take(),
             BinaryTag::Mod => self.op_mod.take(),
+            BinaryTag::Pow => self.op_pow.take(),
             BinaryTag::And => self.op_and.take(),
             BinaryTag::Xor => self.op_xor.take(),
             BinaryTag::Or  => self.op_or.take(),
@@ -164,6 +169,7 @@ impl<T> Metatable<T> {
             BinaryTag::Mul => self.op_rmul.as_ref(),
             BinaryTag::Div => self.op_rdiv.as_ref(),
             BinaryTag::Mod => self.op_rmod.as_ref(),
+            BinaryTag::Pow => self.op_rpow.as_ref(),
             BinaryTag::And => self.op_rand.as_ref(),
             BinaryTag::Xor => self.op_rxor.as_ref(),
             BinaryTag::Or  => self.op_ror.as_ref(),
@@ -179,6 +185,7 @@ impl<T> Metatable<T> {
             BinaryTag::Mul => self.op_rmul.replace(method),
             BinaryTag::Div => self.op_rdiv.replace(method),
             BinaryTag::Mod => self.op_rmod.replace(method),
+            BinaryTag::Pow => self.op_rpow.replace(method),
             BinaryTag::And => self.op_rand.replace(method),
             BinaryTag::Xor => self.op_rxor.replace(method),
             BinaryTag::Or  => self.op_ror.replace(method),
@@ -194,6 +201,7 @@ impl<T> Metatable<T> {
             BinaryTag::Mul => self.op_rmul.take(),
             BinaryTag::Div => self.op_rdiv.take(),
             BinaryTag::Mod => self.op_rmod.take(),
+            BinaryTag::Pow => self.op_rpow.take(),
             BinaryTag::And => self.op_rand.take(),
             BinaryTag::Xor => self.op_rxor.take(),
             BinaryTag::Or  => self.op_ror.take(),
@@ -246,6 +254,7 @@ impl<T> Default for Metatable<T> {
             op_mul: None,
             op_div: None,
             op_mod: None,
+            op_pow: None,
             
             op_and: None,
             op_xor: None,
@@ -259,6 +268,7 @@ impl<T> Default for Metatable<T> {
             op_rdiv: None,
             
             op_rmod: None,
+            op_rpow: None,
             op_rand: None,
             op_rxor: None,
             op_ror: None,
@@ -4,7 +4,9 @@ use crate::runtime::gc::{Gc, GcTrace};
 use crate::runtime::function::{Call, Callable};
 use crate::runtime::strings::{StringValue, StringSymbol, static_symbol};
 use crate::runtime::types::{Type, MetaObject};
-use crate::runtime::errors::{ExecResult, RuntimeError};
+use crate::runtime::module::Module;
+use crate::runtime::vm::Coroutine;
+use crate::runtime::errors::{ExecResult, ErrorKind, RuntimeError};
 
 
 pub struct Nil;
@@ -63,8 +65,8 @@ impl MetaObject for Marker {
 impl<F> MetaObject for Gc<F> where F: GcTrace, Gc<F>: Callable {
     fn type_tag(&self) -> Type { Type::Function }
     
-    fn invoke(&self, args: &[Variant]) -> Option<ExecResult<Call>> {
-        Some(self.checked_call(args))
+    fn invoke(&self, args: &[Variant], kwargs: &[(StringSymbol, Variant)]) -> Option<ExecResult<Call>> {
+        Some(self.checked_call(args, kwargs))
     }
     
     fn cmp_eq(&self, other: &Variant) -> Option<ExecResult<bool>> {
@@ -86,17 +88,103 @@ impl<F> MetaObject for Gc<F> where F: GcTrace, Gc<F>: Callable {
 }
 
 
+// Modules
+
+impl MetaObject for Gc<Module> {
+    fn type_tag(&self) -> Type { Type::Module }
+    
+    // the attributes of a module are its globals
+    fn getattr(&self, name: &StringSymbol) -> Option<ExecResult<Variant>> {
+        let globals = self.globals();
+        let result = globals.borrow().lookup(name).copied()
+            .map_err(|_| RuntimeError::attribute_not_found(&Variant::Module(*self), *name));
+        
+        Some(result)
+    }
+    
+    fn setattr(&self, name: &StringSymbol, value: Variant) -> Option<ExecResult<()>> {
+        let globals = self.globals();
+        let mut globals = globals.borrow_mut();
+        let result = match globals.lookup_mut(name) {
+            Ok(global) => {
+                *global = value;
+                Ok(())
+            },
+            Err(error) if matches!(error.kind(), ErrorKind::CantAssignImmutable) 
+                => Err(RuntimeError::cant_assign_immutable_attr(*name)),
+            Err(..) => Err(RuntimeError::attribute_not_found(&Variant::Module(*self), *name)),
+        };
+        
+        Some(result)
+    }
+    
+    fn cmp_eq(&self, other: &Variant) -> Option<ExecResult<bool>> {
+        match other {
+            Variant::Module(other) => Some(Ok(Gc::ptr_eq(self, other))),
+            _ => Some(Ok(false)),
+        }
+    }
+    
+    fn fmt_repr(&self) -> ExecResult<StringValue> {
+        let result = format!("<module {}>", **self);
+        Ok(StringValue::new_uninterned(result))
+    }
+}
+
+
 // Errors
 
 impl MetaObject for Gc<RuntimeError> {
     fn type_tag(&self) -> Type { Type::Error }
     
+    fn getattr(&self, name: &StringSymbol) -> Option<ExecResult<Variant>> {
+        let value = if *name == static_symbol!("kind") {
+            Variant::from(self.kind().name())
+        } else if *name == static_symbol!("message") {
+            Variant::from(*self.message())
+        } else if *name == static_symbol!("traceback") {
+            let traceback = self.traceback().to_string();
+            Variant::from(StringValue::new_uninterned(traceback))
+        } else {
+            return Some(Err(RuntimeError::attribute_not_found(&Variant::Error(*self), *name)))
+        };
+        
+        Some(Ok(value))
+    }
+    
     fn fmt_repr(&self) -> ExecResult<StringValue> {
         Ok(self.kind().name())
     }
 }
 
 
+// Coroutines
+
+impl MetaObject for Gc<Coroutine> {
+    fn type_tag(&self) -> Type { Type::Coroutine }
+    
+    fn getattr(&self, name: &StringSymbol) -> Option<ExecResult<Variant>> {
+        if *name == static_symbol!("status") {
+            let status = StringSymbol::from(self.status().name());
+            return Some(Ok(Variant::from(status)));
+        }
+        Some(Err(RuntimeError::attribute_not_found(&Variant::Coroutine(*self), *name)))
+    }
+    
+    fn cmp_eq(&self, other: &Variant) -> Option<ExecResult<bool>> {
+        match other {
+            Variant::Coroutine(other) => Some(Ok(Gc::ptr_eq(self, other))),
+            _ => Some(Ok(false)),
+        }
+    }
+    
+    fn fmt_repr(&self) -> ExecResult<StringValue> {
+        let result = format!("<coroutine at {:#X}>", Gc::as_id(self));
+        Ok(StringValue::new_uninterned(result))
+    }
+}
+
+
 /// Trait for custom data
 pub trait UserData: Any + GcTrace + MetaObject {
     fn type_tag(&self) -> Type { Type::UserData }
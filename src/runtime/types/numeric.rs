@@ -27,6 +27,27 @@ pub fn int_from_str(s: &str, radix: IntType) -> ExecResult<IntType> {
     Ok(value)
 }
 
+// negative exponents produce a float, since the result is not generally an integer
+fn int_pow(base: IntType, exp: IntType) -> ExecResult<Variant> {
+    if exp.is_negative() {
+        return Ok(Variant::from((base as FloatType).powf(exp as FloatType)));
+    }
+    
+    let result = match u32::try_from(exp) {
+        Ok(exp) => base.checked_pow(exp),
+        
+        // only a few bases can be raised to such a large power without overflowing
+        Err(..) => match base {
+            0 | 1 => Some(base),
+            -1 => Some(if exp % 2 == 0 { 1 } else { -1 }),
+            _ => None,
+        },
+    };
+    
+    result.map(Variant::Integer)
+        .ok_or_else(RuntimeError::overflow_error)
+}
+
 impl MetaObject for IntType {
     fn type_tag(&self) -> Type { Type::Integer }
     
@@ -34,10 +55,20 @@ impl MetaObject for IntType {
     fn as_int(&self) -> Option<ExecResult<IntType>> { Some(Ok(*self)) }
     fn as_float(&self) -> Option<ExecResult<FloatType>> { Some(Ok(*self as FloatType)) }
     
-    fn op_neg(&self) -> Option<ExecResult<Variant>> { Some(Ok(Variant::from(-(*self)))) }
+    fn op_neg(&self) -> Option<ExecResult<Variant>> {
+        Some(self.checked_neg().map(Variant::Integer).ok_or_else(RuntimeError::overflow_error))
+    }
     fn op_pos(&self) -> Option<ExecResult<Variant>> { Some(Ok(Variant::from(*self))) }
     fn op_inv(&self) -> Option<ExecResult<Variant>> { Some(Ok(Variant::from(!(*self)))) }
     
+    fn op_pow(&self, rhs: &Variant) -> Option<ExecResult<Variant>> {
+        rhs.as_meta().as_int().map(|rhs| int_pow(*self, rhs?))
+    }
+    
+    fn op_rpow(&self, lhs: &Variant) -> Option<ExecResult<Variant>> {
+        lhs.as_meta().as_int().map(|lhs| int_pow(lhs?, *self))
+    }
+    
     fn op_mul(&self, rhs: &Variant) -> Option<ExecResult<Variant>> {
         match rhs {
             Variant::Integer(rhs) => Some(checked_int_math!(checked_mul, *self, *rhs)),
@@ -264,6 +295,14 @@ impl MetaObject for FloatType {
     fn op_neg(&self) -> Option<ExecResult<Variant>> { Some(Ok(Variant::from(-(*self)))) }
     fn op_pos(&self) -> Option<ExecResult<Variant>> { Some(Ok(Variant::from(*self))) }
     
+    fn op_pow(&self, rhs: &Variant) -> Option<ExecResult<Variant>> {
+        rhs.as_meta().as_float().map(|rhs| Ok(Variant::from(self.powf(rhs?))))
+    }
+    
+    fn op_rpow(&self, lhs: &Variant) -> Option<ExecResult<Variant>> {
+        lhs.as_meta().as_float().map(|lhs| Ok(Variant::from(lhs?.powf(*self))))
+    }
+    
     fn op_mul(&self, rhs: &Variant) -> Option<ExecResult<Variant>> {
         rhs.as_meta().as_float().map(|rhs| Ok(Variant::from(*self * rhs?)))
     }
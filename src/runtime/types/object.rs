@@ -0,0 +1,200 @@
+use core::cell::{Cell, RefCell};
+use crate::language::Access;
+use crate::runtime::{Variant, VariantKey, HashMap, DefaultBuildHasher};
+use crate::runtime::gc::{Gc, GcTrace};
+use crate::runtime::strings::{StringValue, StringSymbol};
+use crate::runtime::types::{Type, MetaObject, Class, BoundMethod};
+use crate::runtime::errors::{ExecResult, RuntimeError};
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct Attribute {
+    access: Access,
+    value: Variant,
+}
+
+impl Attribute {
+    pub fn access(&self) -> Access { self.access }
+    pub fn value(&self) -> &Variant { &self.value }
+}
+
+
+/// General purpose container produced by table constructors and class instantiation.
+/// Named attributes and indexed items are stored separately.
+#[derive(Debug)]
+pub struct Object {
+    class: Option<Gc<Class>>,
+    attributes: RefCell<HashMap<StringSymbol, Attribute>>,
+    items: RefCell<HashMap<VariantKey, Variant>>,
+    initializing: Cell<bool>,  // immutable attributes can be assigned until the initializer returns
+}
+
+impl Default for Object {
+    fn default() -> Self { Self::new() }
+}
+
+impl Object {
+    pub fn new() -> Self {
+        Self::new_with(None)
+    }
+    
+    pub fn with_class(class: Gc<Class>) -> Self {
+        Self::new_with(Some(class))
+    }
+    
+    fn new_with(class: Option<Gc<Class>>) -> Self {
+        Self {
+            class,
+            attributes: RefCell::new(HashMap::with_hasher(DefaultBuildHasher::default())),
+            items: RefCell::new(HashMap::with_hasher(DefaultBuildHasher::default())),
+            initializing: Cell::new(false),
+        }
+    }
+    
+    pub fn class(&self) -> Option<Gc<Class>> { self.class }
+    
+    pub fn is_initializing(&self) -> bool { self.initializing.get() }
+    
+    pub fn set_initializing(&self, initializing: bool) { self.initializing.set(initializing) }
+    
+    pub fn attr_names(&self) -> Vec<StringSymbol> {
+        self.attributes.borrow().keys().copied().collect()
+    }
+    
+    pub fn get_attr(&self, name: &StringSymbol) -> Option<Attribute> {
+        self.attributes.borrow().get(name).copied()
+    }
+    
+    // if the attribute already exists, it is overwritten
+    pub fn insert_attr(&self, name: StringSymbol, access: Access, value: Variant) {
+        self.attributes.borrow_mut().insert(name, Attribute { access, value });
+    }
+    
+    pub fn get_item(&self, key: &VariantKey) -> Option<Variant> {
+        self.items.borrow().get(key).copied()
+    }
+    
+    pub fn insert_item(&self, key: VariantKey, value: Variant) {
+        self.items.borrow_mut().insert(key, value);
+    }
+    
+    pub fn remove_item(&self, key: &VariantKey) -> Option<Variant> {
+        self.items.borrow_mut().remove(key)
+    }
+}
+
+unsafe impl GcTrace for Object {
+    fn trace(&self) {
+        if let Some(class) = self.class {
+            class.mark_trace();
+        }
+        
+        for attr in self.attributes.borrow().values() {
+            attr.value.trace();
+        }
+        
+        for (key, value) in self.items.borrow().iter() {
+            key.as_variant().trace();
+            value.trace();
+        }
+    }
+    
+    fn size_hint(&self) -> usize {
+        core::mem::size_of::<(StringSymbol, Attribute)>() * self.attributes.borrow().capacity()
+        + core::mem::size_of::<(VariantKey, Variant)>() * self.items.borrow().capacity()
+    }
+}
+
+impl MetaObject for Gc<Object> {
+    fn type_tag(&self) -> Type { Type::Object }
+    
+    fn type_name(&self) -> ExecResult<StringValue> {
+        match self.class.and_then(|class| class.name()) {
+            Some(name) => Ok(name.into()),
+            None => Ok(self.type_tag().name()),
+        }
+    }
+    
+    fn getattr(&self, name: &StringSymbol) -> Option<ExecResult<Variant>> {
+        if let Some(attr) = self.get_attr(name) {
+            return Some(Ok(*attr.value()));
+        }
+        
+        // methods are bound to the instance they were retrieved from
+        let receiver = Variant::Object(*self);
+        let result = self.class
+            .and_then(|class| class.lookup_method(name))
+            .map(|method| Variant::from(BoundMethod::new(receiver, method)))
+            .ok_or_else(|| RuntimeError::attribute_not_found(&receiver, *name));
+        
+        Some(result)
+    }
+    
+    fn setattr(&self, name: &StringSymbol, value: Variant) -> Option<ExecResult<()>> {
+        let mut attributes = self.attributes.borrow_mut();
+        let result = match attributes.get_mut(name) {
+            None => Err(RuntimeError::attribute_not_found(&Variant::Object(*self), *name)),
+            Some(attr) if !attr.access.can_write() && !self.is_initializing() => 
+                Err(RuntimeError::cant_assign_immutable_attr(*name)),
+            Some(attr) => {
+                attr.value = value;
+                Ok(())
+            }
+        };
+        
+        Some(result)
+    }
+    
+    // only attributes stored on the instance can be deleted, not methods
+    fn delattr(&self, name: &StringSymbol) -> Option<ExecResult<()>> {
+        let mut attributes = self.attributes.borrow_mut();
+        let result = match attributes.get(name) {
+            None => Err(RuntimeError::attribute_not_found(&Variant::Object(*self), *name)),
+            Some(attr) if !attr.access.can_write() => Err(RuntimeError::cant_assign_immutable_attr(*name)),
+            Some(..) => {
+                attributes.remove(name);
+                Ok(())
+            }
+        };
+        
+        Some(result)
+    }
+    
+    fn getitem(&self, key: &Variant) -> Option<ExecResult<Variant>> {
+        let result = VariantKey::try_from(key).and_then(
+            |key| self.get_item(&key).ok_or_else(|| RuntimeError::key_not_found(key.as_variant()))
+        );
+        
+        Some(result)
+    }
+    
+    fn setitem(&self, key: &Variant, value: Variant) -> Option<ExecResult<()>> {
+        let result = VariantKey::try_from(key)
+            .map(|key| self.insert_item(key, value));
+        
+        Some(result)
+    }
+    
+    fn delitem(&self, key: &Variant) -> Option<ExecResult<()>> {
+        let result = VariantKey::try_from(key).and_then(
+            |key| self.remove_item(&key).map(|_| ()).ok_or_else(|| RuntimeError::key_not_found(key.as_variant()))
+        );
+        
+        Some(result)
+    }
+    
+    fn cmp_eq(&self, other: &Variant) -> Option<ExecResult<bool>> {
+        match other {
+            Variant::Object(other) => Some(Ok(Gc::ptr_eq(self, other))),
+            _ => Some(Ok(false)),
+        }
+    }
+    
+    fn fmt_repr(&self) -> ExecResult<StringValue> {
+        let result = format!(
+            "<{} at {:#X}>", self.type_name()?, Gc::as_id(self)
+        );
+        
+        Ok(StringValue::new_uninterned(result))
+    }
+}
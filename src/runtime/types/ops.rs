@@ -72,6 +72,11 @@ impl Variant {
     
     // Arithmetic
     
+    #[inline(always)]
+    pub fn apply_pow(&self, rhs: &Variant) -> ExecResult<Variant> {
+        meta_eval_binary!(self, rhs, op_pow, op_rpow)
+    }
+    
     #[inline(always)]
     pub fn apply_mul(&self, rhs: &Variant) -> ExecResult<Variant> {
         meta_eval_binary!(self, rhs, op_mul, op_rmul)
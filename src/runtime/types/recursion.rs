@@ -0,0 +1,47 @@
+//! Cycle detection for operations that recurse into the contents of containers.
+//!
+//! Lists and dicts can contain themselves, so formatting or comparing them must keep track of the containers
+//! that are already being visited further up the call stack.
+
+use core::cell::RefCell;
+use std::thread::LocalKey;
+
+
+thread_local! {
+    static REPR_VISITING: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+    static EQ_VISITING: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Marks a container as being visited until it is dropped.
+pub struct Visit<K: 'static> {
+    visiting: &'static LocalKey<RefCell<Vec<K>>>,
+}
+
+impl<K: PartialEq> Visit<K> {
+    fn enter(visiting: &'static LocalKey<RefCell<Vec<K>>>, key: K) -> Option<Self> {
+        visiting.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            if stack.contains(&key) {
+                return None;
+            }
+            stack.push(key);
+            Some(Self { visiting })
+        })
+    }
+}
+
+impl<K> Drop for Visit<K> {
+    fn drop(&mut self) {
+        self.visiting.with(|stack| stack.borrow_mut().pop());
+    }
+}
+
+/// Returns `None` if the container with the given id is already being formatted.
+pub fn visit_repr(id: usize) -> Option<Visit<usize>> {
+    Visit::enter(&REPR_VISITING, id)
+}
+
+/// Returns `None` if the same two containers are already being compared.
+pub fn visit_eq(id: usize, other_id: usize) -> Option<Visit<(usize, usize)>> {
+    Visit::enter(&EQ_VISITING, (id, other_id))
+}
@@ -2,8 +2,10 @@ use core::fmt;
 use core::hash::{Hash, Hasher};
 use static_assertions::const_assert_eq;
 use crate::language::{IntType, FloatType};
-use crate::runtime::types::{Tuple, UserData, UserIterator, Marker};
+use crate::runtime::types::{Tuple, List, Dict, Object, Class, BoundMethod, UserData, UserIterator, Marker};
 use crate::runtime::function::{Function, NativeFunction};
+use crate::runtime::module::Module;
+use crate::runtime::vm::{Generator, Coroutine};
 use crate::runtime::strings::{StringValue, StringSymbol, InlineStr};
 use crate::runtime::gc::{Gc, GcTrace};
 use crate::runtime::errors::{ExecResult, RuntimeError};
@@ -34,10 +36,19 @@ pub enum Variant {
     GCStr(Gc<str>),
     
     Tuple(Tuple),
+    List(Gc<List>),
+    Dict(Gc<Dict>),
+    Object(Gc<Object>),
+    Class(Gc<Class>),
     Function(Gc<Function>),
     NativeFunction(Gc<NativeFunction>),
+    BoundMethod(Gc<BoundMethod>),
+    
+    Module(Gc<Module>),
     
     Iterator(Gc<dyn UserIterator>),
+    Generator(Gc<Generator>),
+    Coroutine(Gc<Coroutine>),
     
     Error(Gc<RuntimeError>),
     
@@ -48,10 +59,20 @@ unsafe impl GcTrace for Variant {
     #[inline]
     fn trace(&self) {
         match self {
+            Self::GCStr(gc_str) => gc_str.mark_trace(),
             Self::Tuple(tuple) => tuple.trace(),
+            Self::List(list) => list.mark_trace(),
+            Self::Dict(dict) => dict.mark_trace(),
+            Self::Object(obj) => obj.mark_trace(),
+            Self::Class(class) => class.mark_trace(),
             Self::Function(fun) => fun.mark_trace(),
             Self::NativeFunction(fun) => fun.mark_trace(),
+            Self::BoundMethod(method) => method.mark_trace(),
+            Self::Module(module) => module.mark_trace(),
             Self::Iterator(iter) => iter.mark_trace(),
+            Self::Generator(generator) => generator.mark_trace(),
+            Self::Coroutine(coroutine) => coroutine.mark_trace(),
+            Self::Error(error) => error.mark_trace(),
             Self::UserData(data) => data.mark_trace(),
             _ => { },
         };
@@ -139,6 +160,36 @@ impl From<Box<[Variant]>> for Variant {
     }
 }
 
+impl From<List> for Variant {
+    fn from(list: List) -> Self {
+        Self::List(Gc::new(list))
+    }
+}
+
+impl From<Dict> for Variant {
+    fn from(dict: Dict) -> Self {
+        Self::Dict(Gc::new(dict))
+    }
+}
+
+impl From<Object> for Variant {
+    fn from(obj: Object) -> Self {
+        Self::Object(Gc::new(obj))
+    }
+}
+
+impl From<Class> for Variant {
+    fn from(class: Class) -> Self {
+        Self::Class(Gc::new(class))
+    }
+}
+
+impl From<BoundMethod> for Variant {
+    fn from(method: BoundMethod) -> Self {
+        Self::BoundMethod(Gc::new(method))
+    }
+}
+
 impl From<Function> for Variant {
     fn from(func: Function) -> Self {
         Self::Function(Gc::new(func))
@@ -172,8 +223,11 @@ impl Variant {
             
             Self::Integer(value) => (discr, value).hash(state),
             
+            Self::Object(obj) => (discr, obj).hash(state),
+            Self::Class(class) => (discr, class).hash(state),
             Self::Function(fun) => (discr, fun).hash(state),
             Self::NativeFunction(fun) => (discr, fun).hash(state),
+            Self::Module(module) => (discr, module).hash(state),
             Self::Tuple(items) => {
                 discr.hash(state); // also prevent prefix collisions
                 for item in items.as_ref().iter() {
@@ -193,31 +247,46 @@ impl Variant {
 
 
 /// Wrapper for variant that dynamically ensures hashability
-#[derive(Clone)]
-pub struct VariantKey<'a>(&'a Variant);
+#[derive(Debug, Clone, Copy)]
+pub struct VariantKey(Variant);
+
+impl VariantKey {
+    pub fn as_variant(&self) -> &Variant { &self.0 }
+}
 
-impl<'a> TryFrom<&'a Variant> for VariantKey<'a> {
+impl TryFrom<Variant> for VariantKey {
     type Error = Box<RuntimeError>;
-    fn try_from(value: &'a Variant) -> ExecResult<Self> {
+    fn try_from(value: Variant) -> ExecResult<Self> {
         if !value.can_hash() {
-            return Err(RuntimeError::unhashable_value(value));
+            return Err(RuntimeError::unhashable_value(&value));
         }
         Ok(Self(value))
     }
 }
 
-impl Hash for VariantKey<'_> {
+impl TryFrom<&Variant> for VariantKey {
+    type Error = Box<RuntimeError>;
+    fn try_from(value: &Variant) -> ExecResult<Self> {
+        Self::try_from(*value)
+    }
+}
+
+impl From<VariantKey> for Variant {
+    fn from(key: VariantKey) -> Self { key.0 }
+}
+
+impl Hash for VariantKey {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.0.try_hash(state).unwrap()
     }
 }
 
-impl<'s> PartialEq for VariantKey<'_> {
+impl PartialEq for VariantKey {
     fn eq(&self, other: &VariantKey) -> bool {
-        self.0.cmp_eq(other.0).unwrap_or(false)
+        self.0.cmp_eq(&other.0).unwrap_or(false)
     }
 }
-impl Eq for VariantKey<'_> { }
+impl Eq for VariantKey { }
 
 impl fmt::Display for Variant {
     fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
@@ -249,11 +318,19 @@ impl fmt::Debug for Variant {
             Self::InlineStr(value) => debug_tuple!(fmt, "InlineStr", &value.to_string()),
             Self::GCStr(gc_str) => debug_tuple!(fmt, "GCStr", &gc_str.to_string()),
             Self::Tuple(tuple) => debug_tuple!(fmt, "Tuple", tuple),
+            Self::List(list) => debug_tuple!(fmt, "List", &Gc::as_id(list)),
+            Self::Dict(dict) => debug_tuple!(fmt, "Dict", &Gc::as_id(dict)),
+            Self::Object(obj) => debug_tuple!(fmt, "Object", &Gc::as_id(obj)),
+            Self::Class(class) => debug_tuple!(fmt, "Class", &Gc::as_id(class)),
             Self::Function(fun)
                 => debug_tuple!(fmt, "Function", &fun.signature().fmt_signature().to_string()),
             Self::NativeFunction(fun) 
                 => debug_tuple!(fmt, "NativeFunction", &fun.signature().fmt_signature().to_string()),
+            Self::BoundMethod(method) => debug_tuple!(fmt, "BoundMethod", &Gc::as_id(method)),
+            Self::Module(module) => debug_tuple!(fmt, "Module", &module.to_string()),
             Self::Iterator(iter) => debug_tuple!(fmt, "Iterator", iter),
+            Self::Generator(generator) => debug_tuple!(fmt, "Generator", &Gc::as_id(generator)),
+            Self::Coroutine(coroutine) => debug_tuple!(fmt, "Coroutine", &Gc::as_id(coroutine)),
             Self::Error(error) => write!(fmt, "{:?}", &**error),
             Self::UserData(data) => debug_tuple!(fmt, "UserData", data),
         }
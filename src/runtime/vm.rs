@@ -1,17 +1,29 @@
 use core::cell::Cell;
 use core::ops::Deref;
+use crate::language::IntType;
+use crate::codegen::OpCode;
 use crate::runtime::{Variant, HashMap};
 use crate::runtime::gc::{Gc, GcWeak, GcTrace, gc_collect};
-use crate::runtime::function::{Call, Function, Upvalue, UpvalueIndex, Closure};
-use crate::runtime::module::Module;
-use crate::runtime::errors::ExecResult;
+use crate::runtime::function::{Call, Function, Upvalue, UpvalueIndex, Closure, NativeFuture};
+use crate::runtime::strings::StringSymbol;
+use crate::runtime::module::{Module, ModuleLoader, LoadModule, NamespaceEnv, Chunk};
+use crate::runtime::types::Object;
+use crate::runtime::iter::IterState;
+use crate::runtime::errors::{ExecResult, RuntimeError};
 use crate::debug::traceback::TraceSite;
 use crate::debug::snapshot::{VMSnapshot, VMFrameSnapshot};
 
 mod callframe;
 mod instruction;
+mod generator;
+mod coroutine;
 
-use callframe::VMCallFrame;
+use callframe::{VMCallFrame, Resumable, SuspendedFrame, SuspendedCalls};
+use generator::GeneratorState;
+use coroutine::CoroutineState;
+
+pub use generator::Generator;
+pub use coroutine::{Coroutine, CoroutineStatus};
 
 
 // Helpers
@@ -22,13 +34,40 @@ struct CallInfo {
     local_frame: usize,
     call: Call,
     site: TraceSite,
+    construct: Option<Gc<Object>>,  // if set, the call is an initializer and will return this object
+}
+
+// data used to set up an import
+struct ImportInfo {
+    path: StringSymbol,
+    site: TraceSite,
 }
 
 enum Control {
     Next,            // keep executing
     Call(CallInfo),  // setup a call
+    TailCall(Box<CallInfo>),  // replace the current call with a new one, boxed so that Control doesn't grow
+    Import(ImportInfo),  // load a module, executing it if it is not already loaded
     Return(Variant), // return from call
     Exit(Variant),   // stop execution
+    Reraise(Box<RuntimeError>),  // continue unwinding an error that was intercepted by a finally clause
+    Generator,       // suspend the current call into a new generator and return it
+    Yield(Variant),  // suspend the current generator and produce a value
+    Iterate(IterOp, TraceSite),  // iteration is handled by the VM, since it may need to resume a generator
+}
+
+/// Produced by `VirtualMachine::run_until_pending()`
+#[derive(Debug)]
+pub enum ExecStatus {
+    Finished(Variant),      // the main chunk has finished with this value
+    Pending(NativeFuture),  // an async native function is waiting for this future, see `resume_pending()`
+}
+
+#[derive(Debug, Clone, Copy)]
+enum IterOp {
+    Init,    // [ iterable ] => [ iter state ]
+    Next,    // [ iter state ] => [ iter next_state ]
+    Unpack,  // [ iter state ] => [ value[0] ... value[N] N ]
 }
 
 
@@ -99,6 +138,14 @@ impl UpvalueWeakRef {
 // struct UpvalueWeakRef
 
 
+// The most instructions that are executed without returning to the VM. Garbage is only collected in between, 
+// so this bounds how much can be allocated by a loop that doesn't call or return.
+const RUN_BUDGET: usize = 256;
+
+// Main chunk used to call a value that was placed on the stack by the host application
+static HOST_CALL_CHUNK: [u8; 2] = [ OpCode::Call as u8, OpCode::Exit as u8 ];
+
+
 // Stack-based Virtual Machine
 #[derive(Debug)]
 pub struct VirtualMachine<'c> {
@@ -109,11 +156,27 @@ pub struct VirtualMachine<'c> {
     locals: ValueStack,
     stack: ValueStack,
     upvalues: OpenUpvalues,
+    modules: ModuleLoader,
+    
+    running: Vec<Resumable>,  // generators and coroutines that are being resumed, innermost last
+    suspending: Option<Variant>,  // set by suspend(), the coroutine is suspended once the native call returns
+    roots: Vec<Variant>,  // values held by the host that must survive collection
+    
+    async_run: bool,  // set if the host can wait for async native functions
+    pending: Option<(NativeFuture, TraceSite)>,  // set by await_future(), handed to the host once the native call returns
+    awaiting: Option<TraceSite>,  // the call site of the async native function that the VM is paused in
 }
 
 impl<'c> VirtualMachine<'c> {
     /// Create a new VM with the specified root module and an empty main chunk
     pub fn new(main_module: Gc<Module>, main_chunk: &'c [u8]) -> Self {
+        // imported modules start with whatever the root module's globals contained before execution
+        let prelude = NamespaceEnv::from(main_module.globals().borrow().clone());
+        
+        let mut modules = ModuleLoader::new();
+        modules.set_prelude(Gc::new(prelude));
+        modules.insert_running(main_module);
+        
         Self {
             traceback: Vec::new(),
             calls: Vec::new(),
@@ -121,39 +184,178 @@ impl<'c> VirtualMachine<'c> {
             stack: ValueStack::new(),
             frame: VMCallFrame::main_chunk(main_module, main_chunk),
             upvalues: OpenUpvalues::new(),
+            modules,
+            running: Vec::new(),
+            suspending: None,
+            roots: Vec::new(),
+            async_run: false,
+            pending: None,
+            awaiting: None,
         }
     }
     
     pub fn frame(&self) -> &VMCallFrame<'_> { &self.frame }
     
+    /// Create a new VM that calls a value with the given arguments and then exits with the result.
+    /// The module is used as the calling frame, e.g. for tracebacks and resolving imports.
+    pub fn new_call(module: Gc<Module>, callee: Variant, args: &[Variant]) -> VirtualMachine<'static> {
+        let mut vm = VirtualMachine::new(module, &HOST_CALL_CHUNK);
+        
+        vm.stack.push(callee);
+        for arg in args.iter() {
+            vm.stack.push(*arg);
+        }
+        
+        let nargs = IntType::try_from(args.len()).expect("too many arguments");
+        vm.stack.push(Variant::from(nargs));
+        vm
+    }
+    
+    /// Used to configure the search path and prelude for imported modules
+    pub fn loader_mut(&mut self) -> &mut ModuleLoader { &mut self.modules }
+    
+    /// Keep a value alive while this VM runs, for values that the host holds on to between runs
+    pub fn add_root(&mut self, value: Variant) {
+        self.roots.push(value)
+    }
+    
     // the return value is mostly of interest to the REPL
     pub fn run(mut self) -> ExecResult<Variant> {
         loop {
-            if let Control::Exit(value) = self.exec_next()? {
+            if let Control::Exit(value) = self.exec_run()? {
                 return Ok(value)
             }
         }
     }
     
+    /// Run until the VM finishes or an async native function is waiting for a pending result. 
+    /// The VM can be run again after the result is passed to `resume_pending()`.
+    pub fn run_until_pending(&mut self) -> ExecResult<ExecStatus> {
+        self.async_run = true;
+        loop {
+            let control = self.exec_run()?;
+            if let Some((future, site)) = self.pending.take() {
+                self.awaiting = Some(site);
+                return Ok(ExecStatus::Pending(future));
+            }
+            if let Control::Exit(value) = control {
+                return Ok(ExecStatus::Finished(value));
+            }
+        }
+    }
+    
+    /// Run the VM, awaiting the pending results of any async native functions that it calls
+    pub async fn run_async(mut self) -> ExecResult<Variant> {
+        loop {
+            match self.run_until_pending()? {
+                ExecStatus::Finished(value) => return Ok(value),
+                ExecStatus::Pending(future) => {
+                    let result = future.await;
+                    self.resume_pending(result)?;
+                }
+            }
+        }
+    }
+    
+    /// Continue after `run_until_pending()` produced a pending result. The result becomes the return value of the
+    /// async native function, or if it is an error then it is raised wherever the function was called.
+    pub fn resume_pending(&mut self, result: ExecResult<Variant>) -> ExecResult<()> {
+        let site = self.awaiting.take().expect("no pending result to resume");
+        match result {
+            Ok(value) => {
+                self.stack.replace(value);
+                Ok(())
+            },
+            Err(error) => {
+                let error = error
+                    .push_trace(site)
+                    .extend_trace(self.traceback.iter().rev().cloned());
+                self.unwind(error).map(|_| ())
+            },
+        }
+    }
+    
+    /// Used by async native functions to pause the VM until a future is ready. The value returned by 
+    /// the native function is replaced with the result of the future when the VM is resumed.
+    pub fn await_future(&mut self, future: NativeFuture) -> ExecResult<()> {
+        if !self.async_run {
+            return Err(RuntimeError::invalid_value("async native functions can only be called by an async host"));
+        }
+        
+        // generators and coroutines are run by native code that can't be paused
+        if !self.running.is_empty() {
+            return Err(RuntimeError::invalid_value("can't wait for an async native function inside of a generator or coroutine"));
+        }
+        
+        let site = self.traceback.last().cloned().expect("await outside of a native call");
+        self.pending = Some((future, site));
+        Ok(())
+    }
+    
     pub fn run_steps(self) -> impl Iterator<Item=ExecResult<VMSnapshot>> + 'c {
         VMStepper::from(self)
     }
     
+    // execute a single instruction
     #[inline]
     fn exec_next(&mut self) -> ExecResult<Control> {
-        let control = self.frame.exec_next(&mut self.stack, &mut self.locals, &mut self.upvalues)
+        self.exec_step(1).or_else(|error| self.unwind(error))
+    }
+    
+    // execute instructions until one of them needs the VM
+    #[inline]
+    fn exec_run(&mut self) -> ExecResult<Control> {
+        self.exec_step(RUN_BUDGET).or_else(|error| self.unwind(error))
+    }
+    
+    #[inline]
+    fn exec_step(&mut self, budget: usize) -> ExecResult<Control> {
+        let control = self.frame.exec_run(budget, &mut self.stack, &mut self.locals, &mut self.upvalues)
             .map_err(|error| error.extend_trace(self.traceback.iter().rev().cloned()))?;
         
+        // reraised errors already have a traceback
+        if let Control::Reraise(error) = control {
+            return Err(error);
+        }
+        
         match &control {
+            // only main chunks exit, so if there are calls then we must be finishing an imported module
+            Control::Exit(value) if !self.calls.is_empty() => {
+                self.return_call(*value);
+                self.upvalues.prune_invalid();
+                gc_collect(self);
+                
+                // the VM should only exit once the main module is finished
+                return Ok(Control::Next)
+            },
             Control::Exit(..) => return Ok(control),
             
             Control::Return(value) if self.calls.is_empty() =>
                 return Ok(Control::Exit(*value)),
             
             Control::Return(value) => self.return_call(*value),
-            Control::Call(info) => self.setup_call(info)?,
+            Control::Call(info) => {
+                self.setup_call(info)?;
+                if let Some(value) = self.suspending.take() {
+                    self.suspend_coroutine(value);
+                }
+            },
+            Control::TailCall(info) => self.setup_tail_call(info),
+            Control::Generator => self.create_generator(),
+            Control::Yield(value) => self.yield_generator(*value),
+            Control::Iterate(op, site) => self.exec_iter(*op)
+                .map_err(|error| error
+                    .push_trace(*site)
+                    .extend_trace(self.traceback.iter().rev().cloned())
+                )?,
+            Control::Import(info) => self.setup_import(info)
+                .map_err(|error| error
+                    .push_trace(info.site)
+                    .extend_trace(self.traceback.iter().rev().cloned())
+                )?,
             
             Control::Next => { }
+            Control::Reraise(..) => unreachable!(),
         }
         
         self.upvalues.prune_invalid();
@@ -163,14 +365,31 @@ impl<'c> VirtualMachine<'c> {
     }
     
     fn setup_call(&mut self, callinfo: &CallInfo) -> ExecResult<()> {
-        self.traceback.push(callinfo.site.clone());
+        self.traceback.push(callinfo.site);
         
-        match callinfo.call {
-            Call::Native { func, nargs } => {
-                let args = self.stack.peek_many(nargs)
+        match &callinfo.call {
+            Call::Native { func, nargs, kwargs } => {
+                let args = self.stack.peek_many(*nargs)
                     .iter().copied().collect::<Vec<Variant>>();
                 
-                let retval = func.exec_fun(self, &args)?;
+                let result = func.exec_fun(self, &args, kwargs);
+                if let Some(instance) = callinfo.construct {
+                    instance.set_initializing(false);
+                }
+                
+                let retval = match result {
+                    Ok(retval) => retval,
+                    Err(error) => {
+                        // a native function that fails can't also suspend
+                        self.suspending = None;
+                        self.pending = None;
+                        
+                        let error = error.extend_trace(self.traceback.iter().rev().cloned());
+                        self.traceback.pop();
+                        return Err(error);
+                    }
+                };
+                let retval = callinfo.construct.map_or(retval, Variant::Object);
                 self.stack.truncate(callinfo.stack_frame);
                 self.locals.truncate(callinfo.local_frame);
                 self.stack.push(retval);
@@ -179,8 +398,9 @@ impl<'c> VirtualMachine<'c> {
             
             Call::Chunk { module, chunk_id } => {
                 let mut frame = VMCallFrame::call_frame(
-                    module, chunk_id, callinfo.stack_frame, callinfo.local_frame
+                    *module, *chunk_id, callinfo.stack_frame, callinfo.local_frame
                 );
+                frame.result = callinfo.construct.map(Variant::Object);
                 core::mem::swap(&mut self.frame, &mut frame);
                 self.calls.push(frame);
                 
@@ -194,13 +414,86 @@ impl<'c> VirtualMachine<'c> {
         Ok(())
     }
     
+    // [ ...call frame... callee arg[0] ... arg[n] ] => [ callee arg[0] ... arg[n] ]
+    fn setup_tail_call(&mut self, callinfo: &CallInfo) {
+        let (module, chunk_id) = match callinfo.call {
+            Call::Chunk { module, chunk_id } => (module, chunk_id),
+            Call::Native { .. } => unreachable!("native functions can't replace a call frame"),
+        };
+        
+        let stack_idx = self.frame.stack_frame();
+        let local_idx = self.frame.local_frame();
+        
+        // the replaced frame's locals are about to be overwritten
+        self.upvalues.close_from(local_idx, &self.locals);
+        
+        self.stack.discard_at(stack_idx, callinfo.stack_frame - stack_idx);
+        self.locals.discard_at(local_idx, callinfo.local_frame - local_idx);
+        self.frame = VMCallFrame::call_frame(module, chunk_id, stack_idx, local_idx);
+        
+        // keep the call site of the first frame that was replaced, and count the rest
+        let site = self.traceback.last_mut().expect("tail call outside of a function");
+        match site {
+            TraceSite::TailCall { elided, .. } => *elided += 1,
+            _ => *site = TraceSite::TailCall { site: Gc::new(*site), elided: 1 },
+        }
+        
+        log::debug!(
+            "Setup tail call: {{ stack: {}, locals: {} }}", 
+            self.frame.stack_frame(), self.frame.local_frame()
+        );
+    }
+    
+    fn setup_import(&mut self, import: &ImportInfo) -> ExecResult<()> {
+        let importer = self.frame.module();
+        
+        match self.modules.load_module(import.path, importer.source())? {
+            LoadModule::Cached(module) => self.stack.push(Variant::Module(module)),
+            
+            LoadModule::Execute(module, chunk) => {
+                // SAFETY: The main chunk is owned by the module cache, which lives as long as this VM does.
+                // Entries are only evicted if their main chunk fails, after the import frame has been unwound.
+                let chunk: *const [u8] = chunk;
+                let chunk = unsafe { chunk.as_ref::<'c>().unwrap() };
+                
+                let mut frame = VMCallFrame::import_frame(
+                    module, chunk, self.stack.len(), self.locals.len()
+                );
+                frame.result = Some(Variant::Module(module));
+                core::mem::swap(&mut self.frame, &mut frame);
+                self.calls.push(frame);
+                self.traceback.push(import.site);
+                
+                log::debug!("Setup import: {}", *module);
+            },
+        }
+        
+        Ok(())
+    }
+    
     fn return_call(&mut self, retval: Variant) {
         let stack_idx = self.frame.stack_frame();
         let local_idx = self.frame.local_frame();
         
+        // closures created by the returning frame may outlive its locals
+        self.upvalues.close_from(local_idx, &self.locals);
+        
         let mut frame = self.calls.pop().expect("empty call stack");
         core::mem::swap(&mut self.frame, &mut frame);
         
+        if let Some(resumed) = frame.resumed {
+            resumed.finish();
+        }
+        
+        // initializers always return the object being constructed, imports return the module
+        if let Some(Variant::Object(instance)) = frame.result {
+            instance.set_initializing(false);
+        }
+        let retval = frame.result.unwrap_or(retval);
+        if frame.chunk_id == Chunk::Main {
+            self.modules.mark_loaded(frame.module);
+        }
+        
         self.stack.truncate(stack_idx);
         self.locals.truncate(local_idx);
         self.stack.push(retval);
@@ -211,6 +504,413 @@ impl<'c> VirtualMachine<'c> {
             self.frame.stack_frame(), self.frame.local_frame()
         );
     }
+    
+    // Search for an error handler, discarding call frames until one is found.
+    // If no handler is found the error is returned and the VM cannot continue.
+    fn unwind(&mut self, error: Box<RuntimeError>) -> ExecResult<Control> {
+        loop {
+            if let Some(handler) = self.frame.handlers.pop() {
+                self.upvalues.close_from(handler.locals_len, &self.locals);
+                self.stack.truncate(handler.stack_len);
+                self.locals.truncate(handler.locals_len);
+                self.stack.push(Variant::Error(Gc::new(*error)));
+                self.frame.pc = handler.pc;
+                
+                log::debug!("Caught error: {{ stack: {}, locals: {} }}", self.stack.len(), self.locals.len());
+                return Ok(Control::Next);
+            }
+            
+            if self.calls.is_empty() {
+                return Err(error);
+            }
+            
+            // errors that escape a generator or coroutine are raised wherever it was resumed
+            let resumed = self.frame.resumed.is_some();
+            self.unwind_call();
+            if resumed {
+                return Err(error);
+            }
+        }
+    }
+    
+    fn unwind_call(&mut self) {
+        let stack_idx = self.frame.stack_frame();
+        let local_idx = self.frame.local_frame();
+        
+        self.upvalues.close_from(local_idx, &self.locals);
+        
+        let mut frame = self.calls.pop().expect("empty call stack");
+        core::mem::swap(&mut self.frame, &mut frame);
+        
+        // a module that failed to load should not remain in the cache
+        if frame.chunk_id == Chunk::Main {
+            self.modules.remove_loading(frame.module);
+        }
+        
+        // an initializer that failed may have already let the object escape
+        if let Some(Variant::Object(instance)) = frame.result {
+            instance.set_initializing(false);
+        }
+        
+        if let Some(resumed) = frame.resumed {
+            resumed.finish();
+        }
+        
+        self.stack.truncate(stack_idx);
+        self.locals.truncate(local_idx);
+        self.traceback.pop();
+    }
+}
+
+// Generators and coroutines
+impl VirtualMachine<'_> {
+    fn create_generator(&mut self) {
+        let fun_id = match self.frame.chunk_id {
+            Chunk::Function(fun_id) => fun_id,
+            Chunk::Main => panic!("generator created outside of a function"),
+        };
+        
+        let module = self.frame.module();
+        let calls = self.suspend_calls(self.calls.len());
+        let generator = Gc::new(Generator::new(module, fun_id, calls));
+        self.stack.push(Variant::Generator(generator));
+    }
+    
+    fn yield_generator(&mut self, value: Variant) {
+        let generator = match self.frame.resumed.take() {
+            Some(Resumable::Generator(generator)) => generator,
+            _ => panic!("yield outside of a generator"),
+        };
+        
+        let calls = self.suspend_calls(self.calls.len());
+        generator.suspend(calls, value);
+        self.stack.push(value);
+    }
+    
+    /// Run a generator until it yields a value or returns. Produces `None` if the generator has finished.
+    pub fn resume_generator(&mut self, generator: Gc<Generator>) -> ExecResult<Option<Variant>> {
+        let calls = match generator.take_state() {
+            GeneratorState::Suspended(calls) => calls,
+            GeneratorState::Finished => return Ok(None),
+            GeneratorState::Running => return Err(RuntimeError::generator_running()),
+        };
+        
+        // keep the generator rooted while it runs, it could finish and be dropped by everything else
+        let base = self.stack.len();
+        self.stack.push(Variant::Generator(generator));
+        
+        let depth = self.calls.len() + 1;
+        self.resume_calls(*calls, Resumable::Generator(generator));
+        let value = self.run_resumed(base, depth, Resumable::Generator(generator))?;
+        
+        if generator.is_finished() {
+            Ok(None)
+        } else {
+            Ok(Some(value))
+        }
+    }
+    
+    /// Run a coroutine until it is suspended or returns. The first time a coroutine is resumed the 
+    /// arguments are passed to its function, after that they are returned by the call to `suspend()`.
+    pub fn resume_coroutine(&mut self, coroutine: Gc<Coroutine>, args: &[Variant]) -> ExecResult<Variant> {
+        let resumed = Resumable::Coroutine(coroutine);
+        let base = self.stack.len();
+        let locals_base = self.locals.len();
+        let depth = self.calls.len() + 1;
+        
+        // keep the coroutine rooted while it runs, it could finish and be dropped by everything else
+        self.stack.push(Variant::Coroutine(coroutine));
+        
+        match coroutine.take_state() {
+            CoroutineState::Start(callee) => {
+                self.stack.push(callee);
+                self.stack.extend(args);
+                
+                // a native callee runs immediately, and can't suspend without a call frame
+                self.running.push(resumed);
+                let result = self.setup_resumed_call(args.len());
+                self.running.pop();
+                
+                if let Err(error) = result {
+                    coroutine.finish();
+                    self.stack.truncate(base);
+                    self.locals.truncate(locals_base);
+                    return Err(error);
+                }
+                
+                if self.calls.len() < depth {
+                    coroutine.finish();
+                    let value = self.stack.pop();
+                    self.stack.truncate(base);
+                    return Ok(value);
+                }
+                self.frame.resumed = Some(resumed);
+            },
+            
+            CoroutineState::Suspended(calls) => {
+                self.resume_calls(*calls, resumed);
+                
+                // the call to suspend() returns the resume arguments
+                self.stack.replace(pack_values(args));
+            },
+            
+            CoroutineState::Running => {
+                self.stack.truncate(base);
+                return Err(RuntimeError::coroutine_running());
+            },
+            
+            CoroutineState::Finished => {
+                self.stack.truncate(base);
+                return Err(RuntimeError::coroutine_finished());
+            },
+        }
+        
+        self.run_resumed(base, depth, resumed)
+    }
+    
+    // [ callee arg[0] ... arg[n] ] => [ ...call frame... ]
+    fn setup_resumed_call(&mut self, nargs: usize) -> ExecResult<()> {
+        let control = self.frame.call(self.frame.pc, nargs, Vec::new(), &mut self.stack, &mut self.locals)?;
+        if let Control::Call(mut info) = control {
+            // placeholder for the site that resumed the coroutine, see run_resumed()
+            info.site = TraceSite::Native;
+            self.setup_call(&info)
+                .map_err(|error| error.strip_trace(self.traceback.len() + 1))?;
+        }
+        Ok(())
+    }
+    
+    /// Suspend the running coroutine from inside of a native function, passing values to whoever resumed it
+    /// in the same way that `resume_coroutine()` passes arguments. The coroutine is suspended as soon as the 
+    /// native function returns.
+    pub fn suspend(&mut self, values: &[Variant]) -> ExecResult<()> {
+        let coroutine = match self.running.last() {
+            Some(Resumable::Coroutine(coroutine)) => *coroutine,
+            Some(Resumable::Generator(..)) => 
+                return Err(RuntimeError::invalid_value("can't suspend a coroutine from inside of a generator")),
+            None => 
+                return Err(RuntimeError::invalid_value("can't suspend outside of a coroutine")),
+        };
+        
+        let resumed = Resumable::Coroutine(coroutine);
+        let index = self.find_resumed(&resumed)
+            .ok_or_else(|| RuntimeError::invalid_value("can't suspend a coroutine from a native function"))?;
+        
+        let importing = self.calls[index..].iter().chain(core::iter::once(&self.frame))
+            .any(|frame| frame.chunk_id == Chunk::Main);
+        if importing {
+            return Err(RuntimeError::invalid_value("can't suspend a coroutine while a module is being imported"));
+        }
+        
+        self.suspending = Some(pack_values(values));
+        Ok(())
+    }
+    
+    fn suspend_coroutine(&mut self, value: Variant) {
+        let resumed = *self.running.last().expect("suspend outside of a coroutine");
+        let index = self.find_resumed(&resumed).expect("suspend outside of a coroutine");
+        
+        let calls = self.suspend_calls(index);
+        if let Resumable::Coroutine(coroutine) = resumed {
+            coroutine.suspend(calls);
+        }
+        self.stack.push(value);
+    }
+    
+    // the position of the first frame of a running generator or coroutine, the active frame is at calls.len()
+    fn find_resumed(&self, resumed: &Resumable) -> Option<usize> {
+        self.calls.iter().chain(core::iter::once(&self.frame))
+            .position(|frame| frame.resumed.is_some_and(|other| other.ptr_eq(resumed)))
+    }
+    
+    // Remove the frame at index and every frame above it from the call stack, along with their part of 
+    // the stacks, so that they can be restored later at a different position. The active frame is at calls.len().
+    fn suspend_calls(&mut self, index: usize) -> SuspendedCalls {
+        let (stack_idx, local_idx) = match self.calls.get(index) {
+            Some(frame) => (frame.stack_frame(), frame.local_frame()),
+            None => (self.frame.stack_frame(), self.frame.local_frame()),
+        };
+        
+        let count = self.calls.len() + 1 - index;
+        let mut frames = Vec::new();
+        while self.calls.len() >= index {
+            let caller = self.calls.pop().expect("empty call stack");
+            let frame = core::mem::replace(&mut self.frame, caller);
+            frames.push(SuspendedFrame::from_frame(frame, stack_idx, local_idx));
+        }
+        frames.reverse();
+        
+        // the call sites of every frame after the first, the first frame's call site belongs to whoever resumed it
+        let traceback = self.traceback.split_off(self.traceback.len() - count)
+            .into_iter().skip(1).collect();
+        
+        let calls = SuspendedCalls {
+            frames,
+            traceback,
+            locals: self.locals.peek_many(self.locals.len() - local_idx).into(),
+            stack: self.stack.peek_many(self.stack.len() - stack_idx).into(),
+            upvalues: self.upvalues.suspend_from(local_idx, &self.locals),
+        };
+        
+        self.stack.truncate(stack_idx);
+        self.locals.truncate(local_idx);
+        calls
+    }
+    
+    // Restore suspended frames on top of the call stack. 
+    fn resume_calls(&mut self, calls: SuspendedCalls, resumed: Resumable) {
+        let stack_idx = self.stack.len();
+        let local_idx = self.locals.len();
+        self.stack.extend(&calls.stack);
+        self.locals.extend(&calls.locals);
+        self.upvalues.reopen_at(local_idx, calls.upvalues, &mut self.locals);
+        
+        // placeholder for the site that resumed the frames, see run_resumed()
+        self.traceback.push(TraceSite::Native);
+        self.traceback.extend(calls.traceback);
+        
+        for (idx, suspended) in calls.frames.into_iter().enumerate() {
+            let mut frame = suspended.into_frame(stack_idx, local_idx);
+            if idx == 0 {
+                frame.resumed = Some(resumed);
+            }
+            
+            core::mem::swap(&mut self.frame, &mut frame);
+            self.calls.push(frame);
+        }
+    }
+    
+    // Run until the first resumed frame is no longer on the call stack, then produce the value it left behind
+    fn run_resumed(&mut self, base: usize, depth: usize, resumed: Resumable) -> ExecResult<Variant> {
+        self.running.push(resumed);
+        while self.calls.len() >= depth {
+            if let Err(error) = self.exec_run() {
+                self.running.pop();
+                self.stack.truncate(base);
+                
+                // whoever resumed the frames will add the rest of the traceback, including its own call site
+                return Err(error.strip_trace(self.traceback.len() + 1));
+            }
+        }
+        
+        self.running.pop();
+        let value = self.stack.pop();
+        self.stack.truncate(base);
+        Ok(value)
+    }
+}
+
+// zero values are nil, one value is itself, and more values are a tuple
+fn pack_values(values: &[Variant]) -> Variant {
+    match values {
+        [] => Variant::Nil,
+        [value] => *value,
+        values => Variant::from(values.to_vec().into_boxed_slice()),
+    }
+}
+
+// Iteration
+impl VirtualMachine<'_> {
+    /// Like `Variant::iter_init()`, except that generators are resumed by this VM
+    pub fn iter_init(&mut self, iterable: &Variant) -> ExecResult<IterState> {
+        match iterable {
+            Variant::Generator(generator) => {
+                let state = self.resume_generator(*generator)?.is_some();
+                Ok(IterState::new(*iterable, Variant::from(state)))
+            },
+            
+            Variant::Iterator(iter) => {
+                let state = iter.next_state_vm(self, None)?;
+                Ok(IterState::new(*iterable, state))
+            },
+            
+            _ => iterable.iter_init(),
+        }
+    }
+    
+    /// Like `IterState::next_state()`, except that generators are resumed by this VM
+    pub fn iter_next(&mut self, iter: &IterState) -> ExecResult<Variant> {
+        match iter.get_iter() {
+            Variant::Generator(generator) => {
+                let state = self.resume_generator(*generator)?.is_some();
+                Ok(Variant::from(state))
+            },
+            
+            Variant::Iterator(user_iter) => user_iter.next_state_vm(self, Some(iter.get_state())),
+            
+            _ => iter.next_state(),
+        }
+    }
+    
+    /// Collect all of the values produced by an iterable, resuming generators if needed
+    pub fn iter_collect(&mut self, iterable: &Variant) -> ExecResult<Vec<Variant>> {
+        // the iterator and the values collected so far are kept on the stack, in case a generator runs the GC
+        let base = self.stack.len();
+        let result = self.iter_init(iterable).and_then(|iter| {
+            self.stack.push(*iter.get_iter());
+            self.stack.push(*iter.get_state());
+            self.iter_unpack()
+        });
+        
+        match result {
+            Ok(count) => Ok(self.stack.pop_many(count)),
+            Err(error) => {
+                self.stack.truncate(base);
+                Err(error)
+            },
+        }
+    }
+    
+    fn exec_iter(&mut self, op: IterOp) -> ExecResult<()> {
+        match op {
+            IterOp::Init => {
+                let iterable = *self.stack.peek();
+                let iter = self.iter_init(&iterable)?;
+                self.stack.replace(*iter.get_iter());
+                self.stack.push(*iter.get_state());
+            },
+            
+            IterOp::Next => {
+                let iter = self.peek_iter(self.stack.len() - 2);
+                let next_state = self.iter_next(&iter)?;
+                self.stack.replace(next_state);
+            },
+            
+            IterOp::Unpack => {
+                let count = self.iter_unpack()?;
+                let count = IntType::try_from(count)
+                    .map_err(|_| RuntimeError::overflow_error())?;
+                self.stack.push(Variant::from(count));
+            },
+        }
+        Ok(())
+    }
+    
+    // [ iter state ] => [ value[0] ... value[N] ], returns N
+    fn iter_unpack(&mut self) -> ExecResult<usize> {
+        let base = self.stack.len() - 2;
+        
+        let mut count = 0;
+        loop {
+            let iter = self.peek_iter(base);
+            if !iter.has_value()? {
+                break;
+            }
+            self.stack.push(iter.get_value()?);
+            count += 1;
+            
+            let next_state = self.iter_next(&iter)?;
+            self.stack.replace_at(base + 1, next_state);
+        }
+        
+        self.stack.discard_at(base, 2);
+        Ok(count)
+    }
+    
+    #[inline]
+    fn peek_iter(&self, index: usize) -> IterState {
+        IterState::new(*self.stack.peek_at(index), *self.stack.peek_at(index + 1))
+    }
 }
 
 // trace through all Gc roots
@@ -235,6 +935,17 @@ unsafe impl GcTrace for VirtualMachine<'_> {
         for upval_ref in self.upvalues.iter_refs() {
             upval_ref.mark_trace();
         }
+        
+        // loaded modules
+        self.modules.trace();
+        
+        for resumed in self.running.iter() {
+            resumed.mark_trace();
+        }
+        if let Some(value) = self.suspending {
+            value.trace();
+        }
+        self.roots.iter().for_each(Variant::trace);
     }
 }
 
@@ -420,6 +1131,54 @@ impl OpenUpvalues {
             .or_insert_with(|| vec![ weak_ref ]);
     }
     
+    /// Close all upvalues that refer to locals at or above the given index
+    fn close_from(&mut self, start: usize, locals: &ValueStack) {
+        let indices = self.upvalues.keys().copied()
+            .filter(|index| *index >= start && *index < locals.len())
+            .collect::<Vec<usize>>();
+        
+        for index in indices.into_iter() {
+            self.close_upvalues(index, *locals.peek_at(index));
+        }
+    }
+    
+    /// Close all upvalues for a frame that is being suspended, returning them so that they can be reopened later
+    fn suspend_from(&mut self, start: usize, locals: &ValueStack) -> Vec<(usize, Vec<UpvalueWeakRef>)> {
+        let indices = self.upvalues.keys().copied()
+            .filter(|index| *index >= start && *index < locals.len())
+            .collect::<Vec<usize>>();
+        
+        let mut suspended = Vec::new();
+        for index in indices.into_iter() {
+            if let Some(refs) = self.upvalues.get(&index) {
+                suspended.push((index - start, refs.clone()));
+            }
+            self.close_upvalues(index, *locals.peek_at(index));
+        }
+        suspended
+    }
+    
+    /// Reopen upvalues that were closed by `suspend_from()`, relative to a new start index
+    fn reopen_at(&mut self, start: usize, suspended: Vec<(usize, Vec<UpvalueWeakRef>)>, locals: &mut ValueStack) {
+        for (offset, refs) in suspended.into_iter() {
+            let index = start + offset;
+            
+            // closures may have assigned to the variable while the frame was suspended
+            if let Some(upvalue) = refs.iter().find_map(UpvalueWeakRef::try_deref) {
+                locals.replace_at(index, locals.get_closure(&upvalue.closure()));
+            }
+            
+            for weak_ref in refs.iter() {
+                if let Some(upvalue) = weak_ref.try_deref() {
+                    upvalue.reopen(index)
+                }
+            }
+            self.upvalues.entry(index)
+                .or_default()
+                .extend(refs);
+        }
+    }
+    
     fn close_upvalues(&mut self, index: usize, value: Variant) {
         if let Some(upvalues) = self.upvalues.remove(&index) {
             let gc_cell = Gc::new(Cell::new(value));
@@ -1,7 +1,10 @@
 use crate::codegen::OpCode;
 use crate::debug::snapshot::VMFrameSnapshot;
+use crate::debug::traceback::TraceSite;
 use crate::runtime::gc::{Gc, GcTrace};
+use crate::runtime::Variant;
 use crate::runtime::module::{Module, Chunk, FunctionID};
+use crate::runtime::vm::{Generator, Coroutine, UpvalueWeakRef};
 
 
 #[derive(Debug)]
@@ -12,11 +15,28 @@ pub struct VMCallFrame<'c> {
     pub(super) stack_idx: usize,   // start index for this frame in the value stack
     pub(super) local_idx: usize,   // start index for this frame in the locals stack
     pub(super) pc: usize,
+    pub(super) result: Option<Variant>,  // if set, replaces the value returned by this frame
+    pub(super) handlers: Vec<ErrorHandler>,  // active try blocks, innermost last
+    pub(super) resumed: Option<Resumable>,  // set if this is the first frame of a running generator or coroutine
+}
+
+/// Where to resume execution if an error occurs inside of a try block
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorHandler {
+    pub(super) pc: usize,
+    pub(super) stack_len: usize,   // the value and locals stacks are restored to these lengths
+    pub(super) locals_len: usize,
 }
 
 unsafe impl GcTrace for VMCallFrame<'_> {
     fn trace(&self) {
         self.module.mark_trace();
+        if let Some(result) = self.result {
+            result.trace();
+        }
+        if let Some(resumed) = self.resumed {
+            resumed.mark_trace();
+        }
     }
 }
 
@@ -38,17 +58,28 @@ impl<'c> VMCallFrame<'c> {
             stack_idx,
             local_idx,
             pc: 0,
+            result: None,
+            handlers: Vec::new(),
+            resumed: None,
         }
     }
     
     pub fn main_chunk(module: Gc<Module>, chunk: &'c [u8]) -> Self {
+        Self::import_frame(module, chunk, 0, 0)
+    }
+    
+    // the main chunk of an imported module, which produces the module itself when finished
+    pub fn import_frame(module: Gc<Module>, chunk: &'c [u8], stack_idx: usize, local_idx: usize) -> Self {
         Self {
             module,
             chunk,
             chunk_id: Chunk::Main,
-            stack_idx: 0,
-            local_idx: 0,
+            stack_idx,
+            local_idx,
             pc: 0,
+            result: None,
+            handlers: Vec::new(),
+            resumed: None,
         }
     }
     
@@ -64,6 +95,126 @@ impl<'c> VMCallFrame<'c> {
 }
 
 
+// Suspended frames
+
+/// Something that owns a suspended call stack and runs it when resumed
+#[derive(Debug, Clone, Copy)]
+pub enum Resumable {
+    Generator(Gc<Generator>),
+    Coroutine(Gc<Coroutine>),
+}
+
+impl Resumable {
+    pub(super) fn mark_trace(&self) {
+        match self {
+            Self::Generator(generator) => generator.mark_trace(),
+            Self::Coroutine(coroutine) => coroutine.mark_trace(),
+        }
+    }
+    
+    // called when the first frame returns or is unwound by an error
+    pub(super) fn finish(&self) {
+        match self {
+            Self::Generator(generator) => generator.finish(),
+            Self::Coroutine(coroutine) => coroutine.finish(),
+        }
+    }
+    
+    pub(super) fn ptr_eq(&self, other: &Resumable) -> bool {
+        match (self, other) {
+            (Self::Generator(a), Self::Generator(b)) => Gc::ptr_eq(a, b),
+            (Self::Coroutine(a), Self::Coroutine(b)) => Gc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+/// A call frame that was removed from the call stack. Indices are relative to the first suspended frame.
+#[derive(Debug)]
+pub(super) struct SuspendedFrame {
+    pub(super) module: Gc<Module>,
+    pub(super) fun_id: FunctionID,
+    pub(super) stack_idx: usize,
+    pub(super) local_idx: usize,
+    pub(super) pc: usize,
+    pub(super) result: Option<Variant>,
+    pub(super) handlers: Vec<ErrorHandler>,
+}
+
+impl SuspendedFrame {
+    pub(super) fn from_frame(frame: VMCallFrame, stack_idx: usize, local_idx: usize) -> Self {
+        let fun_id = match frame.chunk_id {
+            Chunk::Function(fun_id) => fun_id,
+            Chunk::Main => panic!("can't suspend a main chunk"),
+        };
+        
+        let handlers = frame.handlers.into_iter()
+            .map(|handler| ErrorHandler {
+                pc: handler.pc,
+                stack_len: handler.stack_len - stack_idx,
+                locals_len: handler.locals_len - local_idx,
+            })
+            .collect();
+        
+        Self {
+            module: frame.module,
+            fun_id,
+            stack_idx: frame.stack_idx - stack_idx,
+            local_idx: frame.local_idx - local_idx,
+            pc: frame.pc,
+            result: frame.result,
+            handlers,
+        }
+    }
+    
+    pub(super) fn into_frame<'c>(self, stack_idx: usize, local_idx: usize) -> VMCallFrame<'c> {
+        let mut frame = VMCallFrame::call_frame(
+            self.module, self.fun_id, self.stack_idx + stack_idx, self.local_idx + local_idx
+        );
+        frame.pc = self.pc;
+        frame.result = self.result;
+        frame.handlers = self.handlers.into_iter()
+            .map(|handler| ErrorHandler {
+                pc: handler.pc,
+                stack_len: handler.stack_len + stack_idx,
+                locals_len: handler.locals_len + local_idx,
+            })
+            .collect();
+        frame
+    }
+}
+
+/// Everything needed to restore one or more call frames at a different position in the VM's stacks
+#[derive(Debug)]
+pub(super) struct SuspendedCalls {
+    pub(super) frames: Vec<SuspendedFrame>,  // outermost first
+    pub(super) traceback: Vec<TraceSite>,    // call sites for each frame after the first
+    pub(super) locals: Box<[Variant]>,
+    pub(super) stack: Box<[Variant]>,
+    pub(super) upvalues: Vec<(usize, Vec<UpvalueWeakRef>)>,  // closed while suspended, reopened on resume
+}
+
+unsafe impl GcTrace for SuspendedCalls {
+    fn trace(&self) {
+        for frame in self.frames.iter() {
+            frame.module.mark_trace();
+            if let Some(result) = frame.result {
+                result.trace();
+            }
+        }
+        for site in self.traceback.iter() {
+            site.trace();
+        }
+        
+        self.locals.trace();
+        self.stack.trace();
+        for upval_ref in self.upvalues.iter().flat_map(|(_, refs)| refs.iter()) {
+            upval_ref.mark_trace();
+        }
+    }
+}
+
+
 // debugging
 
 impl From<&VMCallFrame<'_>> for VMFrameSnapshot {
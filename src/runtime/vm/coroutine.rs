@@ -0,0 +1,98 @@
+//! Coroutines are created from any callable value by the `coroutine()` builtin.
+//!
+//! Unlike a generator, a coroutine can be suspended from inside of any script function that it calls.
+//! When that happens every call frame between the coroutine's function and the call to `suspend()` is
+//! saved, and they are all restored when the coroutine is resumed again.
+
+use core::fmt;
+use core::cell::RefCell;
+use crate::runtime::Variant;
+use crate::runtime::gc::GcTrace;
+use crate::runtime::vm::callframe::SuspendedCalls;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoroutineStatus {
+    Suspended,  // either not started yet, or waiting to be resumed
+    Running,
+    Finished,
+}
+
+impl CoroutineStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Suspended => "suspended",
+            Self::Running => "running",
+            Self::Finished => "finished",
+        }
+    }
+}
+
+impl fmt::Display for CoroutineStatus {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(self.name())
+    }
+}
+
+
+#[derive(Debug)]
+pub struct Coroutine {
+    state: RefCell<CoroutineState>,
+}
+
+#[derive(Debug)]
+pub(super) enum CoroutineState {
+    Start(Variant),  // the callee, which receives the arguments of the first resume
+    Suspended(Box<SuspendedCalls>),
+    Running,
+    Finished,
+}
+
+unsafe impl GcTrace for Coroutine {
+    fn trace(&self) {
+        match &*self.state.borrow() {
+            CoroutineState::Start(callee) => callee.trace(),
+            CoroutineState::Suspended(calls) => calls.trace(),
+            _ => { },
+        }
+    }
+}
+
+impl Coroutine {
+    pub fn new(callee: Variant) -> Self {
+        Self {
+            state: RefCell::new(CoroutineState::Start(callee)),
+        }
+    }
+    
+    pub fn status(&self) -> CoroutineStatus {
+        match *self.state.borrow() {
+            CoroutineState::Start(..) | CoroutineState::Suspended(..) => CoroutineStatus::Suspended,
+            CoroutineState::Running => CoroutineStatus::Running,
+            CoroutineState::Finished => CoroutineStatus::Finished,
+        }
+    }
+    
+    pub fn is_finished(&self) -> bool {
+        self.status() == CoroutineStatus::Finished
+    }
+    
+    // take the callee or the suspended calls so that they can be resumed
+    pub(super) fn take_state(&self) -> CoroutineState {
+        let mut state = self.state.borrow_mut();
+        match &*state {
+            CoroutineState::Start(..) | CoroutineState::Suspended(..)
+                => core::mem::replace(&mut *state, CoroutineState::Running),
+            CoroutineState::Running => CoroutineState::Running,
+            CoroutineState::Finished => CoroutineState::Finished,
+        }
+    }
+    
+    pub(super) fn suspend(&self, calls: SuspendedCalls) {
+        self.state.replace(CoroutineState::Suspended(Box::new(calls)));
+    }
+    
+    pub(super) fn finish(&self) {
+        self.state.replace(CoroutineState::Finished);
+    }
+}
@@ -0,0 +1,81 @@
+//! Generators are created by calling a function whose body contains `yield`.
+//!
+//! A generator owns the state of a suspended call frame: its slice of the locals and value stacks,
+//! its active error handlers and any of its locals that were captured by closures. When the generator is
+//! resumed, this state is restored on top of the VM's stacks and the frame runs until it yields again or returns.
+//! Unlike a coroutine, a generator can only suspend from its own frame and not from functions that it calls.
+
+use core::cell::{Cell, RefCell};
+use crate::runtime::Variant;
+use crate::runtime::gc::{Gc, GcTrace};
+use crate::runtime::module::{Module, FunctionID};
+use crate::runtime::function::Signature;
+use crate::runtime::vm::callframe::SuspendedCalls;
+
+
+#[derive(Debug)]
+pub struct Generator {
+    module: Gc<Module>,
+    fun_id: FunctionID,
+    state: RefCell<GeneratorState>,
+    value: Cell<Variant>,  // the most recently yielded value
+}
+
+#[derive(Debug)]
+pub(super) enum GeneratorState {
+    Suspended(Box<SuspendedCalls>),
+    Running,
+    Finished,
+}
+
+unsafe impl GcTrace for Generator {
+    fn trace(&self) {
+        self.module.mark_trace();
+        self.value.get().trace();
+        
+        if let GeneratorState::Suspended(calls) = &*self.state.borrow() {
+            calls.trace();
+        }
+    }
+}
+
+impl Generator {
+    pub(super) fn new(module: Gc<Module>, fun_id: FunctionID, calls: SuspendedCalls) -> Self {
+        Self {
+            module, fun_id,
+            state: RefCell::new(GeneratorState::Suspended(Box::new(calls))),
+            value: Cell::new(Variant::Nil),
+        }
+    }
+    
+    pub fn signature(&self) -> &Signature {
+        self.module.data().get_function(self.fun_id).signature()
+    }
+    
+    pub fn is_finished(&self) -> bool {
+        matches!(*self.state.borrow(), GeneratorState::Finished)
+    }
+    
+    /// The value produced by the last time the generator was resumed
+    pub fn value(&self) -> Variant { self.value.get() }
+    
+    // take the suspended calls so that they can be resumed
+    pub(super) fn take_state(&self) -> GeneratorState {
+        let mut state = self.state.borrow_mut();
+        match &*state {
+            GeneratorState::Suspended(..) => core::mem::replace(&mut *state, GeneratorState::Running),
+            GeneratorState::Running => GeneratorState::Running,
+            GeneratorState::Finished => GeneratorState::Finished,
+        }
+    }
+    
+    pub(super) fn suspend(&self, calls: SuspendedCalls, value: Variant) {
+        self.state.replace(GeneratorState::Suspended(Box::new(calls)));
+        self.value.set(value);
+    }
+    
+    pub(super) fn finish(&self) {
+        self.state.replace(GeneratorState::Finished);
+        self.value.set(Variant::Nil);
+    }
+}
@@ -1,14 +1,17 @@
 use crate::language::{IntType, Access};
 use crate::codegen::{OpCode, LocalIndex, UpvalueTarget};
 use crate::debug::traceback::TraceSite;
-use crate::runtime::Variant;
+use crate::runtime::{Variant, VariantKey};
 use crate::runtime::gc::Gc;
-use crate::runtime::function::{Function, Upvalue, UpvalueIndex};
-use crate::runtime::strings::StringSymbol;
+use crate::runtime::function::{self, Function, Signature, Call, Upvalue, UpvalueIndex};
+use crate::runtime::types::{Object, List, Dict, Class, BoundMethod};
+use crate::runtime::strings::{StringValue, StringSymbol, static_symbol};
+use crate::runtime::strings::format::FormatSpec;
 use crate::runtime::module::{ConstID, FunctionID, FunctionProto};
+use crate::runtime::errors::{ExecResult, ErrorKind, RuntimeError};
+use crate::runtime::vm::{ValueStack, OpenUpvalues, CallInfo, ImportInfo, Control, IterOp, VMCallFrame};
 use crate::runtime::iter::IterState;
-use crate::runtime::errors::{ExecResult, RuntimeError};
-use crate::runtime::vm::{ValueStack, OpenUpvalues, CallInfo, Control, VMCallFrame};
+use crate::runtime::vm::callframe::ErrorHandler;
 
 
 // Operand casts
@@ -38,6 +41,41 @@ fn into_function(value: Variant) -> Gc<Function> {
     }
 }
 
+#[inline]
+fn into_object(value: Variant) -> Gc<Object> {
+    match value {
+        Variant::Object(obj) => obj,
+        _ => panic!("invalid operand")
+    }
+}
+
+#[inline]
+fn into_class(value: Variant) -> Gc<Class> {
+    match value {
+        Variant::Class(class) => class,
+        _ => panic!("invalid operand")
+    }
+}
+
+// only tuples and lists are matched by sequence patterns
+#[inline]
+fn sequence_items(value: &Variant) -> Option<Vec<Variant>> {
+    match value {
+        Variant::Tuple(tuple) => Some(tuple.items().to_vec()),
+        Variant::List(list) => Some(list.to_vec()),
+        _ => None,
+    }
+}
+
+#[inline]
+fn make_tuple(items: Vec<Variant>) -> Variant {
+    if items.is_empty() {
+        Variant::Tuple(Default::default())
+    } else {
+        Variant::from(items.into_boxed_slice())
+    }
+}
+
 
 // Helper macros
 macro_rules! read_le_bytes {
@@ -66,13 +104,115 @@ macro_rules! eval_binary_op {
     };
 }
 
-macro_rules! eval_cmp {
-    ( $stack:expr, $cmp_method:tt ) => {
+// evaluate a binary operator given by the opcode of its generic instruction
+fn eval_binary(op: u8, lhs: &Variant, rhs: &Variant) -> ExecResult<Variant> {
+    let result = match OpCode::from_byte(op) {
+        Some(OpCode::And) => lhs.apply_and(rhs)?,
+        Some(OpCode::Xor) => lhs.apply_xor(rhs)?,
+        Some(OpCode::Or)  => lhs.apply_or(rhs)?,
+        Some(OpCode::Shl) => lhs.apply_shl(rhs)?,
+        Some(OpCode::Shr) => lhs.apply_shr(rhs)?,
+        Some(OpCode::Add) => lhs.apply_add(rhs)?,
+        Some(OpCode::Sub) => lhs.apply_sub(rhs)?,
+        Some(OpCode::Mul) => lhs.apply_mul(rhs)?,
+        Some(OpCode::Div) => lhs.apply_div(rhs)?,
+        Some(OpCode::Mod) => lhs.apply_mod(rhs)?,
+        Some(OpCode::Exp) => lhs.apply_pow(rhs)?,
+        
+        Some(OpCode::EQ) => Variant::from(lhs.cmp_eq(rhs)?),
+        Some(OpCode::NE) => Variant::from(lhs.cmp_ne(rhs)?),
+        Some(OpCode::LT) => Variant::from(lhs.cmp_lt(rhs)?),
+        Some(OpCode::LE) => Variant::from(lhs.cmp_le(rhs)?),
+        Some(OpCode::GE) => Variant::from(lhs.cmp_ge(rhs)?),
+        Some(OpCode::GT) => Variant::from(lhs.cmp_gt(rhs)?),
+        
+        _ => panic!("invalid operand"),
+    };
+    Ok(result)
+}
+
+// Operators with an integer fast path, which returns None if the result overflows
+// so that the generic operator can produce the error.
+
+#[inline(always)]
+fn int_add(lhs: IntType, rhs: IntType) -> Option<Variant> { lhs.checked_add(rhs).map(Variant::Integer) }
+#[inline(always)]
+fn int_sub(lhs: IntType, rhs: IntType) -> Option<Variant> { lhs.checked_sub(rhs).map(Variant::Integer) }
+#[inline(always)]
+fn int_mul(lhs: IntType, rhs: IntType) -> Option<Variant> { lhs.checked_mul(rhs).map(Variant::Integer) }
+#[inline(always)]
+fn int_eq(lhs: IntType, rhs: IntType) -> Option<Variant> { Some(Variant::from(lhs == rhs)) }
+#[inline(always)]
+fn int_ne(lhs: IntType, rhs: IntType) -> Option<Variant> { Some(Variant::from(lhs != rhs)) }
+#[inline(always)]
+fn int_lt(lhs: IntType, rhs: IntType) -> Option<Variant> { Some(Variant::from(lhs < rhs)) }
+#[inline(always)]
+fn int_le(lhs: IntType, rhs: IntType) -> Option<Variant> { Some(Variant::from(lhs <= rhs)) }
+#[inline(always)]
+fn int_ge(lhs: IntType, rhs: IntType) -> Option<Variant> { Some(Variant::from(lhs >= rhs)) }
+#[inline(always)]
+fn int_gt(lhs: IntType, rhs: IntType) -> Option<Variant> { Some(Variant::from(lhs > rhs)) }
+
+fn cmp_eq(lhs: &Variant, rhs: &Variant) -> ExecResult<Variant> { lhs.cmp_eq(rhs).map(Variant::from) }
+fn cmp_ne(lhs: &Variant, rhs: &Variant) -> ExecResult<Variant> { lhs.cmp_ne(rhs).map(Variant::from) }
+fn cmp_lt(lhs: &Variant, rhs: &Variant) -> ExecResult<Variant> { lhs.cmp_lt(rhs).map(Variant::from) }
+fn cmp_le(lhs: &Variant, rhs: &Variant) -> ExecResult<Variant> { lhs.cmp_le(rhs).map(Variant::from) }
+fn cmp_ge(lhs: &Variant, rhs: &Variant) -> ExecResult<Variant> { lhs.cmp_ge(rhs).map(Variant::from) }
+fn cmp_gt(lhs: &Variant, rhs: &Variant) -> ExecResult<Variant> { lhs.cmp_gt(rhs).map(Variant::from) }
+
+// the operator operand of a register instruction is the opcode of its generic instruction
+const OP_ADD: u8 = OpCode::Add as u8;
+const OP_SUB: u8 = OpCode::Sub as u8;
+const OP_MUL: u8 = OpCode::Mul as u8;
+const OP_EQ:  u8 = OpCode::EQ as u8;
+const OP_NE:  u8 = OpCode::NE as u8;
+const OP_LT:  u8 = OpCode::LT as u8;
+const OP_LE:  u8 = OpCode::LE as u8;
+const OP_GE:  u8 = OpCode::GE as u8;
+const OP_GT:  u8 = OpCode::GT as u8;
+
+// evaluate a binary operator, trying the integer fast path before the generic operator.
+// Always inlined with a constant operator, so each instruction only keeps the code it needs.
+#[inline(always)]
+fn eval_int_path(
+    lhs: &Variant, rhs: &Variant,
+    int_op: impl FnOnce(IntType, IntType) -> Option<Variant>,
+    generic_op: impl FnOnce(&Variant, &Variant) -> ExecResult<Variant>,
+) -> ExecResult<Variant> {
+    if let (Variant::Integer(lhs), Variant::Integer(rhs)) = (lhs, rhs) {
+        if let Some(result) = int_op(*lhs, *rhs) {
+            return Ok(result);
+        }
+    }
+    generic_op(lhs, rhs)
+}
+
+// [ lhs rhs ] => [ result ], for operators with an integer fast path
+macro_rules! eval_int_path_op {
+    ( $stack:expr, $int_op:expr, $generic_op:expr ) => {
         {
             let rhs = $stack.pop();
-            let lhs = $stack.peek();
-            let result = lhs.$cmp_method(&rhs)?;
-            $stack.replace(Variant::from(result));
+            let result = eval_int_path($stack.peek(), &rhs, $int_op, $generic_op)?;
+            $stack.replace(result);
+        }
+    };
+}
+
+// The operator operand of a register instruction is matched once, and each operator with an
+// integer fast path gets its own copy of the instruction. Other operators go through the generic path.
+macro_rules! eval_register_op {
+    ( $op:expr, $lhs:expr, $rhs:expr ) => {
+        match $op {
+            OP_ADD => eval_int_path($lhs, $rhs, int_add, Variant::apply_add),
+            OP_SUB => eval_int_path($lhs, $rhs, int_sub, Variant::apply_sub),
+            OP_MUL => eval_int_path($lhs, $rhs, int_mul, Variant::apply_mul),
+            OP_EQ  => eval_int_path($lhs, $rhs, int_eq, cmp_eq),
+            OP_NE  => eval_int_path($lhs, $rhs, int_ne, cmp_ne),
+            OP_LT  => eval_int_path($lhs, $rhs, int_lt, cmp_lt),
+            OP_LE  => eval_int_path($lhs, $rhs, int_le, cmp_le),
+            OP_GE  => eval_int_path($lhs, $rhs, int_ge, cmp_ge),
+            OP_GT  => eval_int_path($lhs, $rhs, int_gt, cmp_gt),
+            op => eval_binary(op, $lhs, $rhs),
         }
     };
 }
@@ -113,15 +253,142 @@ impl<'c> VMCallFrame<'c> {
         }
     }
 
+    // [ callee arg[0] ... arg[n] ] => [ callee ... ]
+    pub(super) fn call(&self, current_offset: usize, mut nargs: usize, kwargs: Vec<(StringSymbol, Variant)>, stack: &mut ValueStack, locals: &mut ValueStack) -> ExecResult<Control> {
+        // identify the start of the call frame
+        let call_len = 1 + nargs;
+        let stack_frame = stack.len() - call_len;
+        let local_frame = locals.len();
+        
+        // bound methods and initializers get the receiver as their first argument
+        let mut construct = None;
+        let receiver = match *stack.peek_at(stack_frame) {
+            Variant::BoundMethod(method) => {
+                stack.replace_at(stack_frame, *method.method());
+                Some(*method.receiver())
+            }
+            
+            Variant::Class(class) => {
+                let instance = Gc::new(class.instantiate());
+                
+                if let Some(init) = class.lookup_method(&static_symbol!("init")) {
+                    stack.replace_at(stack_frame, init);
+                    instance.set_initializing(true);
+                    construct = Some(instance);
+                    Some(Variant::Object(instance))
+                } else if nargs + kwargs.len() > 0 {
+                    return Err(RuntimeError::no_init_arguments(&Variant::Class(class), nargs + kwargs.len()));
+                } else {
+                    stack.replace(Variant::Object(instance));
+                    return Ok(Control::Next);
+                }
+            }
+            
+            _ => None,
+        };
+        
+        if let Some(receiver) = receiver {
+            stack.insert(stack_frame + 1, receiver);
+            nargs += 1;
+        }
+        
+        let nargs_value = IntType::try_from(nargs)
+            .map_err(|_| RuntimeError::overflow_error())?;
+        
+        let callee = *stack.peek_at(stack_frame);
+        locals.push(callee);
+        locals.push(Variant::from(nargs_value));
+        
+        let args = stack.peek_many(nargs);
+        let call = callee.invoke(args, &kwargs)?;
+        
+        if let Call::Chunk { .. } = call {
+            let function = into_function(callee);
+            Self::bind_args(function.signature(), nargs, &kwargs, stack)?;
+        }
+        
+        let call = CallInfo {
+            stack_frame,
+            local_frame,
+            call,
+            site: self.get_trace(current_offset),
+            construct,
+        };
+        Ok(Control::Call(call))
+    }
+    
+    // Whether a call can replace this frame instead of being pushed on top of it. Otherwise the call is made 
+    // normally and the RETURN that follows the tail call takes care of the value.
+    fn can_replace(&self, info: &CallInfo) -> bool {
+        matches!(info.call, Call::Chunk { .. })
+            && info.construct.is_none()
+            && self.result.is_none()
+            && self.resumed.is_none()
+            && self.handlers.is_empty()
+    }
+    
+    // arrange the arguments to a compiled function in the order that its parameters were declared,
+    // so that InsertArgs can move them into locals. Parameters with a default value that did not 
+    // receive an argument are left missing, the function preamble will evaluate the default.
+    fn bind_args(signature: &Signature, nargs: usize, kwargs: &[(StringSymbol, Variant)], stack: &mut ValueStack) -> ExecResult<()> {
+        let positional_only = signature.arg_len() == signature.positional_len() && signature.kwargs().is_none();
+        
+        // fast path, the positional arguments are already in place
+        if kwargs.is_empty() && positional_only {
+            let positional = signature.positional_len();
+            for _ in nargs..positional {
+                stack.push(function::missing_arg());
+            }
+            
+            if signature.variadic().is_some() {
+                let varargs = stack.pop_many(nargs.saturating_sub(positional));
+                stack.push(make_tuple(varargs));
+            }
+            return Ok(());
+        }
+        
+        let args = stack.pop_many(nargs);
+        let mut argbuf = vec![function::missing_arg(); signature.arg_len()];
+        let bound = signature.bind_partial(&args, kwargs, &mut argbuf);
+        
+        let (positional, keyword) = bound.args.split_at(signature.positional_len());
+        stack.extend(positional);
+        if signature.variadic().is_some() {
+            stack.push(make_tuple(bound.varargs.to_vec()));
+        }
+        stack.extend(keyword);
+        if signature.kwargs().is_some() {
+            let dict = Dict::new();
+            for (name, value) in bound.kwargs.iter() {
+                dict.insert(VariantKey::try_from(Variant::from(*name))?, *value);
+            }
+            stack.push(Variant::from(dict));
+        }
+        
+        Ok(())
+    }
+    
+    // execute up to `budget` instructions, stopping early at the first one that needs the VM
     #[inline]
-    pub(super) fn exec_next(&mut self, stack: &mut ValueStack, locals: &mut ValueStack, upvalues: &mut OpenUpvalues) -> ExecResult<Control> {
+    pub(super) fn exec_run(&mut self, budget: usize, stack: &mut ValueStack, locals: &mut ValueStack, upvalues: &mut OpenUpvalues) -> ExecResult<Control> {
+        for _ in 0..budget {
+            let control = self.exec_next(stack, locals, upvalues)?;
+            if !matches!(control, Control::Next) {
+                return Ok(control);
+            }
+        }
+        Ok(Control::Next)
+    }
+    
+    #[inline(always)]
+    fn exec_next(&mut self, stack: &mut ValueStack, locals: &mut ValueStack, upvalues: &mut OpenUpvalues) -> ExecResult<Control> {
         let op_byte = self.chunk.get(self.pc).expect("pc out of bounds");
-        let opcode = OpCode::from_byte(*op_byte)
+        let (opcode, instr_len) = OpCode::decode(*op_byte)
             .unwrap_or_else(|| panic!("invalid instruction: {:x}", op_byte));
         
-        let data_slice = (self.pc + 1) .. (self.pc + opcode.instr_len());
+        let data_slice = (self.pc + 1) .. (self.pc + instr_len);
         let current_offset = self.pc;
-        self.pc += opcode.instr_len(); // pc points to next instruction
+        self.pc += instr_len; // pc points to next instruction
         
         let data = self.chunk.get(data_slice).expect("truncated instruction");
         
@@ -149,14 +416,23 @@ impl<'c> VMCallFrame<'c> {
         Function::new(proto.fun_id(), self.module, upvalues)
     }
     
+    fn push_handler(&mut self, offset: isize, stack: &ValueStack, locals: &ValueStack) {
+        let handler = ErrorHandler {
+            pc: self.offset_pc(offset).expect("pc overflow/underflow"),
+            stack_len: stack.len(),
+            locals_len: locals.len(),
+        };
+        self.handlers.push(handler);
+    }
+    
     // TODO create a temporary struct for all of these values that can't be stored in the VMCallFrame
-    #[inline]
+    #[inline(always)]
     fn exec_instruction(&mut self, current_offset: usize, opcode: OpCode, data: &[u8], stack: &mut ValueStack, locals: &mut ValueStack, upvalues: &mut OpenUpvalues) -> ExecResult<Control> {
         match opcode {
             OpCode::Nop => { },
             
             OpCode::Exit => {
-                if stack.is_empty() {
+                if stack.len() <= self.stack_idx {
                     return Ok(Control::Exit(Variant::Nil))
                 }
                 let value = stack.pop();
@@ -168,6 +444,21 @@ impl<'c> VMCallFrame<'c> {
                 return Ok(Control::Return(value))
             },
             
+            OpCode::Generator => return Ok(Control::Generator),
+            
+            OpCode::Yield => {
+                let value = stack.pop();
+                return Ok(Control::Yield(value))
+            },
+            
+            OpCode::Import => {
+                let import = ImportInfo {
+                    path: into_name(stack.pop()),
+                    site: self.get_trace(current_offset),
+                };
+                return Ok(Control::Import(import))
+            },
+            
             OpCode::Error => {
                 let value = stack.pop();
                 if let Variant::Error(error) = value {
@@ -176,28 +467,63 @@ impl<'c> VMCallFrame<'c> {
                 panic!("invalid operand")
             },
             
+            OpCode::Throw => {
+                let value = stack.pop();
+                if let Variant::Error(error) = value {
+                    return Err(Box::new((*error).clone()).clear_trace());
+                }
+                return Err(RuntimeError::throw_invalid_value(&value));
+            },
+            
+            OpCode::Reraise => {
+                let value = stack.pop();
+                if let Variant::Error(error) = value {
+                    return Ok(Control::Reraise(Box::new((*error).clone())));
+                }
+                panic!("invalid operand")
+            },
+            
+            OpCode::TryBegin => {
+                let offset = isize::from(read_le_bytes!(i16, data));
+                self.push_handler(offset, stack, locals);
+            },
+            OpCode::LongTryBegin => {
+                let offset = isize::try_from(read_le_bytes!(i32, data)).unwrap();
+                self.push_handler(offset, stack, locals);
+            },
+            
+            OpCode::TryEnd => {
+                self.handlers.pop().expect("no active error handler");
+            },
+            
+            OpCode::AssertType => {
+                let annotation = stack.pop();
+                stack.peek().check_type(&annotation)?;
+            },
+            
             OpCode::Call => {
-                // read nargs and identify the start of the call frame
-                let nargs_value = stack.pop();
-                let nargs = into_usize(nargs_value);
-
-                let call_len = 1 + nargs;
-                let stack_frame = stack.len() - call_len;
-                let local_frame = locals.len();
-                
-                let callee = stack.peek_at(stack_frame);
-                locals.push(*callee);
-                locals.push(nargs_value);
-                
-                let args = stack.peek_many(nargs);
-                
-                let call = CallInfo {
-                    stack_frame,
-                    local_frame,
-                    call: callee.invoke(args)?,
-                    site: self.get_trace(current_offset),
+                let nargs = into_usize(stack.pop());
+                return self.call(current_offset, nargs, Vec::new(), stack, locals);
+            },
+            
+            OpCode::TailCall => {
+                let nargs = into_usize(stack.pop());
+                return match self.call(current_offset, nargs, Vec::new(), stack, locals)? {
+                    Control::Call(info) if self.can_replace(&info) => Ok(Control::TailCall(Box::new(info))),
+                    control => Ok(control),
                 };
-                return Ok(Control::Call(call))
+            },
+            
+            OpCode::CallKeywords => {
+                let count = usize::from(data[0]);
+                let kwargs = stack.peek_many(2 * count)
+                    .chunks_exact(2)
+                    .map(|item| (into_name(item[0]), item[1]))
+                    .collect::<Vec<(StringSymbol, Variant)>>();
+                stack.discard(2 * count);
+                
+                let nargs = into_usize(stack.pop());
+                return self.call(current_offset, nargs, kwargs, stack, locals);
             },
             
             OpCode::InsertArgs => {
@@ -207,6 +533,11 @@ impl<'c> VMCallFrame<'c> {
                 stack.discard(nargs);
             }
             
+            OpCode::ArgMissing => {
+                let missing = function::is_missing_arg(stack.peek());
+                stack.replace(Variant::from(missing));
+            }
+            
             OpCode::Pop => { 
                 stack.pop(); 
             },
@@ -222,31 +553,171 @@ impl<'c> VMCallFrame<'c> {
                 stack.push(*stack.peek());
             }
             
-            OpCode::IterInit => {
-                let iter = stack.peek().iter_init()?;
-                stack.replace(*iter.get_iter());
-                stack.push(*iter.get_state());
+            // iteration can resume generators, which needs the whole VM
+            OpCode::IterInit => return Ok(Control::Iterate(IterOp::Init, self.get_trace(current_offset))),
+            OpCode::IterNext => return Ok(Control::Iterate(IterOp::Next, self.get_trace(current_offset))),
+            OpCode::IterUnpack => return Ok(Control::Iterate(IterOp::Unpack, self.get_trace(current_offset))),
+            
+            // reading the current value never resumes a generator, it was produced when the state was advanced
+            OpCode::IterGet => {
+                let iter = IterState::new(*stack.peek_at(stack.len() - 2), *stack.peek());
+                stack.push(iter.get_value()?);
             }
             
-            OpCode::IterNext => {
-                let state = stack.pop();
-                let value = stack.peek().iter_get(&state)?;
-                let next_state = stack.peek().iter_next(&state)?;
-                stack.push(next_state);
-                stack.push(value);
+            OpCode::Object => {
+                stack.push(Variant::from(Object::new()));
+            }
+            OpCode::InsertAttr => {
+                let name = into_name(stack.pop());
+                let value = stack.pop();
+                into_object(*stack.peek()).insert_attr(name, Access::ReadOnly, value);
+            }
+            OpCode::InsertAttrMut => {
+                let name = into_name(stack.pop());
+                let value = stack.pop();
+                into_object(*stack.peek()).insert_attr(name, Access::ReadWrite, value);
+            }
+            OpCode::InsertItem => {
+                let value = stack.pop();
+                let key = VariantKey::try_from(stack.pop())?;
+                match stack.peek() {
+                    Variant::Object(obj) => obj.insert_item(key, value),
+                    Variant::Dict(dict) => dict.insert(key, value),
+                    _ => panic!("invalid operand"),
+                }
+            }
+            OpCode::Dict => {
+                stack.push(Variant::from(Dict::new()));
+            }
+            
+            OpCode::Class => {
+                let name = match stack.pop() {
+                    Variant::Nil => None,
+                    name => Some(into_name(name)),
+                };
+                let base = match *stack.peek() {
+                    Variant::Nil => None,
+                    Variant::Class(base) => Some(base),
+                    base => return Err(RuntimeError::invalid_base_class(&base)),
+                };
+                stack.replace(Variant::from(Class::new(name, base)));
+            }
+            OpCode::InsertField => {
+                let name = into_name(stack.pop());
+                let value = stack.pop();
+                into_class(*stack.peek()).insert_field(name, Access::ReadOnly, value);
+            }
+            OpCode::InsertFieldMut => {
+                let name = into_name(stack.pop());
+                let value = stack.pop();
+                into_class(*stack.peek()).insert_field(name, Access::ReadWrite, value);
+            }
+            OpCode::InsertMethod => {
+                let name = into_name(stack.pop());
+                let method = stack.pop();
+                into_class(*stack.peek()).insert_method(name, method);
+            }
+            
+            OpCode::LoadAttr => {
+                let name = into_name(stack.pop());
+                let value = stack.peek().getattr(&name)?;
+                stack.replace(value);
+            }
+            OpCode::StoreAttr => {
+                let name = into_name(stack.pop());
+                let receiver = stack.pop();
+                receiver.setattr(&name, *stack.peek())?;
+            }
+            OpCode::LoadItem => {
+                let key = stack.pop();
+                let value = stack.peek().getitem(&key)?;
+                stack.replace(value);
+            }
+            OpCode::StoreItem => {
+                let key = stack.pop();
+                let receiver = stack.pop();
+                receiver.setitem(&key, *stack.peek())?;
+            }
+            OpCode::DropAttr => {
+                let name = into_name(stack.pop());
+                stack.pop().delattr(&name)?;
+            }
+            OpCode::DropItem => {
+                let key = stack.pop();
+                stack.pop().delitem(&key)?;
+            }
+            OpCode::LoadSuper => {
+                let name = into_name(stack.pop());
+                let base = stack.pop();
+                let method = match base {
+                    Variant::Class(base) => base.lookup_method(&name),
+                    _ => None,  // the class has no base
+                };
+                let method = method.ok_or_else(|| RuntimeError::attribute_not_found(&base, name))?;
+                
+                let receiver = *stack.peek();
+                stack.replace(Variant::from(BoundMethod::new(receiver, method)));
+            }
+            
+            OpCode::MatchSeq => {
+                let len = usize::from(data[0]);
+                match sequence_items(&stack.pop()) {
+                    Some(items) if items.len() == len => {
+                        stack.extend(&items);
+                        stack.push(Variant::from(true));
+                    }
+                    _ => stack.push(Variant::from(false)),
+                }
+            }
+            OpCode::MatchSeqPack => {
+                let (pre_len, post_len) = (usize::from(data[0]), usize::from(data[1]));
+                match sequence_items(&stack.pop()) {
+                    Some(items) if items.len() >= pre_len + post_len => {
+                        let (pre_pack, rest) = items.split_at(pre_len);
+                        let (pack, post_pack) = rest.split_at(rest.len() - post_len);
+                        stack.extend(pre_pack);
+                        stack.push(make_tuple(pack.to_vec()));
+                        stack.extend(post_pack);
+                        stack.push(Variant::from(true));
+                    }
+                    _ => stack.push(Variant::from(false)),
+                }
+            }
+            OpCode::MatchAttr => {
+                let name = into_name(stack.pop());
+                match stack.peek().getattr(&name) {
+                    Ok(value) => {
+                        stack.push(value);
+                        stack.push(Variant::from(true));
+                    }
+                    Err(error) if matches!(error.kind(), ErrorKind::AttributeNotFound | ErrorKind::MethodNotSupported) 
+                        => stack.push(Variant::from(false)),
+                    Err(error) => return Err(error),
+                }
             }
             
-            OpCode::IterUnpack => {
-                let state = stack.pop();
-                let iter = stack.pop();
+            OpCode::FmtStr => {
+                let value = stack.pop();
+                stack.push(Variant::from(value.fmt_str()?));
+            }
+            OpCode::FmtSpec => {
+                let spec = stack.pop().as_strval().expect("invalid operand");
+                let value = stack.pop();
+                
+                let spec = spec.with_str(|spec| spec.parse::<FormatSpec>())
+                    .map_err(|error| RuntimeError::invalid_value(error.to_string()))?;
                 
-                let mut count = IntType::from(0);
-                for value in IterState::new(iter, state) {
-                    stack.push(value?);
-                    count = count.checked_add(1)
-                        .ok_or_else(RuntimeError::overflow_error)?;
+                stack.push(Variant::from(spec.format(&value)?));
+            }
+            OpCode::Concat => {
+                let count = usize::from(data[0]);
+                
+                let mut buf = String::new();
+                for item in stack.pop_many(count).iter() {
+                    let strval = item.as_strval().expect("invalid operand");
+                    strval.with_str(|s| buf.push_str(s));
                 }
-                stack.push(count.into());
+                stack.push(Variant::from(StringValue::new_maybe_interned(buf)));
             }
             
             OpCode::LoadFunction => {
@@ -286,20 +757,24 @@ impl<'c> VMCallFrame<'c> {
                 self.module.globals().borrow_mut().create(name, Access::ReadWrite, value);
             },
             OpCode::StoreGlobal => {
-                let name = into_name(stack.pop());
-                let value = *stack.peek();
-                
-                let globals = self.module.globals();
-                let mut namespace = globals.borrow_mut();
-                let store = namespace.lookup_mut(&name)?;
-                *store = value;
+                let cid = ConstID::from(data[0]);
+                self.module.store_global(cid, *stack.peek())?;
+            },
+            OpCode::StoreGlobal16 => {
+                let cid = ConstID::from(read_le_bytes!(u16, data));
+                self.module.store_global(cid, *stack.peek())?;
             },
             OpCode::LoadGlobal => {
-                let value = {
-                    let name = into_name(*stack.peek());
-                    *self.module.globals().borrow().lookup(&name)?
-                };
-                stack.replace(value);
+                let cid = ConstID::from(data[0]);
+                stack.push(self.module.load_global(cid)?);
+            },
+            OpCode::LoadGlobal16 => {
+                let cid = ConstID::from(read_le_bytes!(u16, data));
+                stack.push(self.module.load_global(cid)?);
+            },
+            OpCode::DropGlobal => {
+                let name = into_name(stack.pop());
+                self.module.globals().borrow_mut().delete(&name)?;
             },
             
             OpCode::InsertLocal => {
@@ -363,6 +838,18 @@ impl<'c> VMCallFrame<'c> {
             OpCode::False => stack.push(Variant::BoolFalse),
             OpCode::Empty => stack.push(Variant::Tuple(Default::default())),
             
+            OpCode::List => {
+                let list_len = usize::from(data[0]);
+                
+                let items = stack.pop_many(list_len);
+                stack.push(Variant::from(List::from(items)));
+            },
+            OpCode::ListN => {
+                let list_len = into_usize(stack.pop());
+                
+                let items = stack.pop_many(list_len);
+                stack.push(Variant::from(List::from(items)));
+            },
             OpCode::Tuple => {
                 let tuple_len = usize::from(data[0]);
                 
@@ -372,12 +859,8 @@ impl<'c> VMCallFrame<'c> {
             OpCode::TupleN => {
                 let tuple_len = into_usize(stack.pop());
                 
-                if tuple_len > 0 {
-                    let items = stack.pop_many(tuple_len).into_boxed_slice();
-                    stack.push(Variant::from(items));
-                } else {
-                    stack.push(Variant::Tuple(Default::default()));
-                }
+                let items = stack.pop_many(tuple_len);
+                stack.push(make_tuple(items));
             },
             
             OpCode::UInt8 => {
@@ -403,18 +886,93 @@ impl<'c> VMCallFrame<'c> {
             OpCode::Or  => eval_binary_op!(stack, apply_or),
             OpCode::Shl => eval_binary_op!(stack, apply_shl),
             OpCode::Shr => eval_binary_op!(stack, apply_shr),
-            OpCode::Add => eval_binary_op!(stack, apply_add),
-            OpCode::Sub => eval_binary_op!(stack, apply_sub),
-            OpCode::Mul => eval_binary_op!(stack, apply_mul),
             OpCode::Div => eval_binary_op!(stack, apply_div),
             OpCode::Mod => eval_binary_op!(stack, apply_mod),
+            OpCode::Exp => eval_binary_op!(stack, apply_pow),
+            
+            OpCode::Add => eval_int_path_op!(stack, int_add, Variant::apply_add),
+            OpCode::Sub => eval_int_path_op!(stack, int_sub, Variant::apply_sub),
+            OpCode::Mul => eval_int_path_op!(stack, int_mul, Variant::apply_mul),
+            OpCode::EQ  => eval_int_path_op!(stack, int_eq, cmp_eq),
+            OpCode::NE  => eval_int_path_op!(stack, int_ne, cmp_ne),
+            OpCode::LT  => eval_int_path_op!(stack, int_lt, cmp_lt),
+            OpCode::LE  => eval_int_path_op!(stack, int_le, cmp_le),
+            OpCode::GE  => eval_int_path_op!(stack, int_ge, cmp_ge),
+            OpCode::GT  => eval_int_path_op!(stack, int_gt, cmp_gt),
+            
+            OpCode::BinaryRR => {
+                let lhs = locals.peek_at(self.frame_offset(LocalIndex::from(data[1])));
+                let rhs = locals.peek_at(self.frame_offset(LocalIndex::from(data[2])));
+                stack.push(eval_register_op!(data[0], lhs, rhs)?);
+            }
+            OpCode::BinaryRI => {
+                let lhs = locals.peek_at(self.frame_offset(LocalIndex::from(data[1])));
+                let rhs = Variant::Integer(IntType::from(i8::from_le_bytes([data[2]])));
+                stack.push(eval_register_op!(data[0], lhs, &rhs)?);
+            }
+            OpCode::BinarySR => {
+                let rhs = locals.peek_at(self.frame_offset(LocalIndex::from(data[1])));
+                let result = eval_register_op!(data[0], stack.peek(), rhs)?;
+                stack.replace(result);
+            }
+            OpCode::BinarySI => {
+                let rhs = Variant::Integer(IntType::from(i8::from_le_bytes([data[1]])));
+                let result = eval_register_op!(data[0], stack.peek(), &rhs)?;
+                stack.replace(result);
+            }
+            OpCode::BinaryRC => {
+                let lhs = locals.peek_at(self.frame_offset(LocalIndex::from(data[1])));
+                let rhs = self.module.get_const(ConstID::from(data[2]));
+                stack.push(eval_register_op!(data[0], lhs, &rhs)?);
+            }
+            OpCode::BinarySC => {
+                let rhs = self.module.get_const(ConstID::from(data[1]));
+                let result = eval_register_op!(data[0], stack.peek(), &rhs)?;
+                stack.replace(result);
+            }
             
-            OpCode::EQ => eval_cmp!(stack, cmp_eq),
-            OpCode::NE => eval_cmp!(stack, cmp_ne),
-            OpCode::LT => eval_cmp!(stack, cmp_lt),
-            OpCode::LE => eval_cmp!(stack, cmp_le),
-            OpCode::GE => eval_cmp!(stack, cmp_ge),
-            OpCode::GT => eval_cmp!(stack, cmp_gt),
+            OpCode::BinaryRRStore => {
+                let lhs = locals.peek_at(self.frame_offset(LocalIndex::from(data[1])));
+                let rhs = locals.peek_at(self.frame_offset(LocalIndex::from(data[2])));
+                let result = eval_register_op!(data[0], lhs, rhs)?;
+                locals.replace_at(self.frame_offset(LocalIndex::from(data[3])), result);
+            }
+            OpCode::BinaryRIStore => {
+                let lhs = locals.peek_at(self.frame_offset(LocalIndex::from(data[1])));
+                let rhs = Variant::Integer(IntType::from(i8::from_le_bytes([data[2]])));
+                let result = eval_register_op!(data[0], lhs, &rhs)?;
+                locals.replace_at(self.frame_offset(LocalIndex::from(data[3])), result);
+            }
+            OpCode::BinaryRCStore => {
+                let lhs = locals.peek_at(self.frame_offset(LocalIndex::from(data[1])));
+                let rhs = self.module.get_const(ConstID::from(data[2]));
+                let result = eval_register_op!(data[0], lhs, &rhs)?;
+                locals.replace_at(self.frame_offset(LocalIndex::from(data[3])), result);
+            }
+            OpCode::BinarySRStore => {
+                let lhs = stack.pop();
+                let rhs = locals.peek_at(self.frame_offset(LocalIndex::from(data[1])));
+                let result = eval_register_op!(data[0], &lhs, rhs)?;
+                locals.replace_at(self.frame_offset(LocalIndex::from(data[2])), result);
+            }
+            OpCode::BinarySIStore => {
+                let lhs = stack.pop();
+                let rhs = Variant::Integer(IntType::from(i8::from_le_bytes([data[1]])));
+                let result = eval_register_op!(data[0], &lhs, &rhs)?;
+                locals.replace_at(self.frame_offset(LocalIndex::from(data[2])), result);
+            }
+            OpCode::BinarySCStore => {
+                let lhs = stack.pop();
+                let rhs = self.module.get_const(ConstID::from(data[1]));
+                let result = eval_register_op!(data[0], &lhs, &rhs)?;
+                locals.replace_at(self.frame_offset(LocalIndex::from(data[2])), result);
+            }
+            OpCode::BinaryRSStore => {
+                let rhs = stack.pop();
+                let lhs = locals.peek_at(self.frame_offset(LocalIndex::from(data[1])));
+                let result = eval_register_op!(data[0], lhs, &rhs)?;
+                locals.replace_at(self.frame_offset(LocalIndex::from(data[2])), result);
+            }
             
             OpCode::Jump => {
                 let offset = isize::from(read_le_bytes!(i16, data));
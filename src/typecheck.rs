@@ -0,0 +1,888 @@
+//! Static type checking.
+//!
+//! An optional analysis pass that runs over the AST before it is compiled. Types are inferred
+//! from literals, type annotations and the builtins in the prelude, and only for names that
+//! can't be reassigned. Operators and calls are checked by trying them out on sample values
+//! using the runtime's own implementation, so anything the checker reports would also fail
+//! if that code were run. The results are only warnings, they never prevent compilation.
+
+use std::rc::Rc;
+use std::collections::{HashMap, HashSet};
+use crate::language::{InternSymbol, Access};
+use crate::parser::stmt::{StmtMeta, Stmt, StmtList, ControlFlow};
+use crate::parser::expr::{Expr, ExprMeta, ExprBlock, ConditionalBranch, MatchCase, CatchClause, TableItem, TableField};
+use crate::parser::primary::{Atom, Primary, AccessItem, StringPart};
+use crate::parser::pattern::{Pattern, MatchAction};
+use crate::parser::fundefs::{FunctionDef, SignatureDef};
+use crate::parser::classdefs::ClassDef;
+use crate::parser::annotation::TypeExpr;
+use crate::parser::operator::BinaryOp;
+use crate::runtime::Variant;
+use crate::runtime::types::Type;
+use crate::runtime::function::{Signature, Parameter};
+use crate::runtime::strings::{StringInterner, StringSymbol};
+use crate::debug::DebugSymbol;
+use crate::builtins;
+
+mod types;
+mod errors;
+mod tests;
+
+pub use types::{StaticType, FunctionType};
+pub use errors::TypeWarning;
+
+use types::Probe;
+
+
+type Scope = HashMap<InternSymbol, StaticType>;
+
+// tracks the returned values of the function currently being checked
+struct FunctionFrame {
+    return_type: Option<TypeExpr>,
+    returns: Option<StaticType>,
+}
+
+impl FunctionFrame {
+    fn add_return(&mut self, ty: StaticType) {
+        let returns = match self.returns.take() {
+            Some(returns) => returns.join(&ty),
+            None => ty,
+        };
+        self.returns.replace(returns);
+    }
+}
+
+
+pub struct TypeChecker<'s> {
+    strings: &'s StringInterner,
+    scopes: Vec<Scope>,
+    frames: Vec<FunctionFrame>,
+    symbols: Vec<DebugSymbol>,
+    
+    // globals that are declared more than once can't be relied on inside functions
+    unstable: HashSet<InternSymbol>,
+    
+    warnings: Vec<TypeWarning>,
+}
+
+impl<'s> TypeChecker<'s> {
+    pub fn new(strings: &'s StringInterner) -> Self {
+        Self {
+            strings,
+            scopes: Vec::new(),
+            frames: Vec::new(),
+            symbols: Vec::new(),
+            unstable: HashSet::new(),
+            warnings: Vec::new(),
+        }
+    }
+    
+    pub fn check_program(mut self, program: &[StmtMeta]) -> Vec<TypeWarning> {
+        // names declared at the top level shadow the builtins for the whole program
+        let mut globals = HashSet::new();
+        for stmt in program.iter() {
+            if let Stmt::Expression(Expr::Assignment(assign)) = stmt.variant() {
+                if matches!(assign.action, MatchAction::DeclImmutable | MatchAction::DeclMutable) {
+                    collect_names(&assign.lhs, &mut |name| if !globals.insert(name) {
+                        self.unstable.insert(name);
+                    });
+                }
+            }
+        }
+        
+        let mut prelude = self.create_prelude_scope();
+        prelude.retain(|name, _| !globals.contains(name));
+        
+        self.scopes.push(prelude);
+        self.scopes.push(Scope::new());
+        
+        for stmt in program.iter() {
+            self.check_stmt_with_symbol(stmt);
+        }
+        
+        self.warnings
+    }
+    
+    fn create_prelude_scope(&self) -> Scope {
+        let mut scope = Scope::new();
+        
+        let prelude = builtins::create_prelude();
+        let namespace = prelude.borrow();
+        for name in namespace.names() {
+            // only builtins that the program refers to are needed
+            let name_str = name.to_string();
+            let symbol = match self.strings.get(&name_str) {
+                Some(symbol) => symbol,
+                None => continue,
+            };
+            
+            let ty = match namespace.lookup(name) {
+                Ok(Variant::NativeFunction(func)) => StaticType::Function(Rc::new(FunctionType {
+                    signature: func.signature().clone(),
+                    params: Vec::new().into_boxed_slice(),
+                    variadic: None,
+                    returns: builtin_result_type(&name_str),
+                })),
+                Ok(value) => StaticType::Value(value.type_tag()),
+                Err(..) => continue,
+            };
+            
+            scope.insert(symbol, ty);
+        }
+        
+        scope
+    }
+    
+    fn warn(&mut self, message: impl ToString) {
+        let symbol = self.symbols.last().copied();
+        self.warnings.push(TypeWarning::new(message, symbol));
+    }
+    
+    fn resolve_str(&self, name: &InternSymbol) -> &str {
+        self.strings.resolve(*name).expect("invalid symbol")
+    }
+    
+    fn fmt_type_expr(&self, annotation: &TypeExpr) -> String {
+        match annotation {
+            TypeExpr::Nil => "nil".to_string(),
+            TypeExpr::Builtin(builtin) => builtin.name().to_string(),
+            TypeExpr::Class(path) => path.iter()
+                .map(|name| self.resolve_str(name))
+                .collect::<Vec<&str>>().join("."),
+            TypeExpr::Union(items) => items.iter()
+                .map(|item| self.fmt_type_expr(item))
+                .collect::<Vec<String>>().join(" | "),
+        }
+    }
+    
+    fn check_annotation(&mut self, ty: &StaticType, annotation: &TypeExpr) {
+        if !ty.admits(annotation) {
+            let message = format!(
+                "expected type '{}', got '{}'",
+                self.fmt_type_expr(annotation), ty.type_tag().unwrap(),
+            );
+            self.warn(message);
+        }
+    }
+}
+
+
+// Scopes and Names
+
+impl TypeChecker<'_> {
+    fn push_scope(&mut self) {
+        self.scopes.push(Scope::new());
+    }
+    
+    fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+    
+    fn is_global_scope(&self) -> bool {
+        self.scopes.len() == 2
+    }
+    
+    fn lookup_name(&self, name: &InternSymbol) -> StaticType {
+        self.scopes.iter().rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+            .unwrap_or(StaticType::Unknown)
+    }
+    
+    fn declare_name(&mut self, name: InternSymbol, ty: StaticType) {
+        let ty =
+            if self.is_global_scope() && self.unstable.contains(&name) { StaticType::Unknown }
+            else { ty };
+        
+        self.scopes.last_mut().unwrap().insert(name, ty);
+    }
+    
+    // once a name is deleted, it may be declared again with a different type
+    fn delete_name(&mut self, name: &InternSymbol) {
+        if let Some(scope) = self.scopes.iter_mut().rev().find(|scope| scope.contains_key(name)) {
+            scope.insert(*name, StaticType::Unknown);
+        }
+    }
+}
+
+
+// Statements
+
+impl TypeChecker<'_> {
+    fn check_stmt_with_symbol(&mut self, stmt: &StmtMeta) {
+        self.symbols.push(*stmt.debug_symbol());
+        self.check_stmt(stmt.variant());
+        self.symbols.pop();
+    }
+    
+    fn check_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Expression(expr) | Stmt::Assert(expr) | Stmt::Throw(expr) => {
+                self.check_expr(expr);
+            }
+            
+            Stmt::Yield(expr) => if let Some(expr) = expr {
+                self.check_expr(expr);
+            },
+            
+            Stmt::Loop { body, .. } => self.check_stmt_block(body),
+            
+            Stmt::WhileLoop { condition, body, .. } => {
+                self.check_expr(condition);
+                self.check_stmt_block(body);
+            }
+            
+            Stmt::ForLoop { pattern, iter, body, .. } => {
+                self.check_expr(iter);
+                
+                self.push_scope();
+                self.check_assignment(MatchAction::DeclMutable, pattern, StaticType::Unknown);
+                self.check_stmt_list(body);
+                self.pop_scope();
+            }
+            
+            Stmt::Delete(target) => self.check_delete(target),
+        }
+    }
+    
+    fn check_stmt_list(&mut self, stmt_list: &StmtList) {
+        for stmt in stmt_list.iter() {
+            self.check_stmt_with_symbol(stmt);
+        }
+        
+        if let Some(control) = stmt_list.end_control() {
+            self.check_control_flow(control);
+        }
+    }
+    
+    fn check_stmt_block(&mut self, stmt_list: &StmtList) {
+        self.push_scope();
+        self.check_stmt_list(stmt_list);
+        self.pop_scope();
+    }
+    
+    // the result is None if the block does not complete normally
+    fn check_expr_block(&mut self, suite: &ExprBlock) -> Option<StaticType> {
+        self.push_scope();
+        
+        let stmt_list = suite.stmt_list();
+        for stmt in stmt_list.iter() {
+            self.check_stmt_with_symbol(stmt);
+        }
+        
+        let result = match stmt_list.end_control() {
+            Some(control) => {
+                self.check_control_flow(control);
+                None
+            }
+            None => Some(match suite.result() {
+                Some(expr) => self.check_expr_with_symbol(expr),
+                None => StaticType::Value(Type::Nil),
+            }),
+        };
+        
+        self.pop_scope();
+        result
+    }
+    
+    fn check_control_flow(&mut self, control_flow: &ControlFlow) {
+        if let Some(symbol) = control_flow.debug_symbol() {
+            self.symbols.push(*symbol);
+        }
+        
+        match control_flow {
+            ControlFlow::Continue { .. } => { },
+            
+            ControlFlow::Break { expr, .. } => {
+                if let Some(expr) = expr {
+                    self.check_expr(expr);
+                }
+            }
+            
+            ControlFlow::Return { expr, .. } => {
+                let ty = expr.as_ref()
+                    .map_or(StaticType::Value(Type::Nil), |expr| self.check_expr(expr));
+                
+                self.check_return(ty);
+            }
+        }
+        
+        if control_flow.debug_symbol().is_some() {
+            self.symbols.pop();
+        }
+    }
+    
+    fn check_return(&mut self, ty: StaticType) {
+        let return_type = match self.frames.last_mut() {
+            Some(frame) => {
+                frame.add_return(ty.clone());
+                frame.return_type.clone()
+            }
+            None => return,
+        };
+        
+        if let Some(return_type) = return_type {
+            self.check_annotation(&ty, &return_type);
+        }
+    }
+    
+    fn check_delete(&mut self, target: &Pattern) {
+        match target {
+            Pattern::Identifier(name) => self.delete_name(name),
+            Pattern::Tuple(items) => for item in items.iter() {
+                self.check_delete(item);
+            },
+            _ => self.check_assign_target(target),
+        }
+    }
+}
+
+
+// Expressions
+
+impl TypeChecker<'_> {
+    fn check_expr_with_symbol(&mut self, expr: &ExprMeta) -> StaticType {
+        self.symbols.push(*expr.debug_symbol());
+        let ty = self.check_expr(expr.variant());
+        self.symbols.pop();
+        ty
+    }
+    
+    fn check_expr(&mut self, expr: &Expr) -> StaticType {
+        match expr {
+            Expr::Atom(atom) => self.check_atom(atom),
+            
+            Expr::Primary(primary) => self.check_primary(primary),
+            
+            Expr::UnaryOp(op, expr) => {
+                let operand = self.check_expr(expr);
+                let probe = types::probe_unary_op(*op, &operand);
+                self.probe_result(probe)
+            }
+            
+            Expr::BinaryOp(op, exprs) => {
+                let (lhs, rhs) = &**exprs;
+                let lhs = self.check_expr(lhs);
+                let rhs = self.check_expr(rhs);
+                
+                if matches!(op, BinaryOp::And | BinaryOp::Or) {
+                    return lhs.join(&rhs);
+                }
+                
+                let probe = types::probe_binary_op(*op, &lhs, &rhs);
+                self.probe_result(probe)
+            }
+            
+            Expr::Assignment(assign) => {
+                if assign.op.is_some() {
+                    // update-assignment requires a name that can be reassigned, which will have an unknown type
+                    self.check_assign_target(&assign.lhs);
+                    self.check_expr(&assign.rhs);
+                    return StaticType::Unknown;
+                }
+                
+                match (&assign.lhs, &assign.rhs) {
+                    // assigning a tuple literal to a tuple pattern assigns each item separately
+                    (Pattern::Tuple(targets), Expr::Tuple(items))
+                    if targets.len() == items.len() && !has_unpack(items) && !has_pack(targets) => {
+                        for (target, item) in targets.iter().zip(items.iter()) {
+                            let ty = self.check_expr_with_symbol(item);
+                            self.check_assignment(assign.action, target, ty);
+                        }
+                        StaticType::Value(Type::Tuple)
+                    }
+                    
+                    (lhs, rhs) => {
+                        let ty = self.check_expr(rhs);
+                        self.check_assignment(assign.action, lhs, ty.clone());
+                        ty
+                    }
+                }
+            }
+            
+            Expr::Unpack(expr) => {
+                if let Some(expr) = expr {
+                    self.check_expr(expr);
+                }
+                StaticType::Unknown
+            }
+            
+            Expr::Tuple(items) | Expr::List(items) => {
+                for item in items.iter() {
+                    self.check_expr_with_symbol(item);
+                }
+                
+                if matches!(expr, Expr::Tuple(..)) { StaticType::Value(Type::Tuple) }
+                else { StaticType::Value(Type::List) }
+            }
+            
+            Expr::Dict(items) => {
+                for item in items.iter() {
+                    self.check_expr_with_symbol(&item.key);
+                    self.check_expr_with_symbol(&item.value);
+                }
+                StaticType::Value(Type::Dict)
+            }
+            
+            Expr::Table(items) => {
+                self.check_table(items);
+                StaticType::Unknown
+            }
+            
+            Expr::IfExpr { branches, else_clause } => self.check_if_expression(branches, else_clause.as_deref()),
+            
+            Expr::Match { subject, cases } => self.check_match_expression(subject, cases),
+            
+            // the result of a block may also come from a "break" with a value
+            Expr::Block { suite, .. } => {
+                self.check_expr_block(suite);
+                StaticType::Unknown
+            }
+            
+            Expr::Try { suite, catch, finally } => {
+                self.check_expr_block(suite);
+                if let Some(catch) = catch {
+                    self.check_catch_clause(catch);
+                }
+                if let Some(finally) = finally {
+                    self.check_stmt_block(finally);
+                }
+                StaticType::Unknown
+            }
+            
+            Expr::FunctionDef(fundef) => StaticType::Function(self.check_function_def(fundef)),
+            
+            Expr::ClassDef(classdef) => {
+                self.check_class_def(classdef);
+                StaticType::Value(Type::Metatable)
+            }
+            
+            Expr::Import(..) => StaticType::Value(Type::Module),
+        }
+    }
+    
+    fn probe_result(&mut self, probe: Probe) -> StaticType {
+        match probe {
+            Probe::Supported(ty) => ty,
+            Probe::Unsupported(error) => {
+                self.warn(error.message());
+                StaticType::Unknown
+            }
+        }
+    }
+    
+    fn check_atom(&mut self, atom: &Atom) -> StaticType {
+        match atom {
+            Atom::Nil => StaticType::Value(Type::Nil),
+            Atom::EmptyTuple => StaticType::Value(Type::Tuple),
+            Atom::BooleanLiteral(..) => StaticType::Value(Type::Boolean),
+            Atom::IntegerLiteral(..) => StaticType::Value(Type::Integer),
+            Atom::FloatLiteral(..) => StaticType::Value(Type::Float),
+            Atom::StringLiteral(..) => StaticType::Value(Type::String),
+            
+            Atom::InterpolatedString(parts) => {
+                for part in parts.iter() {
+                    if let StringPart::Expr { expr, .. } = part {
+                        self.check_expr_with_symbol(expr);
+                    }
+                }
+                StaticType::Value(Type::String)
+            }
+            
+            Atom::Identifier(name) => self.lookup_name(name),
+            
+            Atom::Self_ | Atom::Super => StaticType::Unknown,
+            
+            Atom::Group { inner, annotation, .. } => {
+                let ty = match &**inner {
+                    Expr::Unpack(Some(iter)) => {
+                        self.check_expr(iter);
+                        StaticType::Value(Type::Tuple)
+                    }
+                    inner => self.check_expr(inner),
+                };
+                
+                match annotation {
+                    Some(annotation) => self.check_annotated(ty, annotation),
+                    None => ty,
+                }
+            }
+        }
+    }
+    
+    // the type of a value after it has been checked against an annotation
+    fn check_annotated(&mut self, ty: StaticType, annotation: &TypeExpr) -> StaticType {
+        self.check_annotation(&ty, annotation);
+        
+        match StaticType::from_annotation(annotation) {
+            StaticType::Unknown => ty,
+            narrowed => narrowed,
+        }
+    }
+    
+    fn check_primary(&mut self, primary: &Primary) -> StaticType {
+        let mut ty = self.check_atom(primary.atom());
+        
+        for item in primary.path().iter() {
+            ty = match item {
+                AccessItem::Attribute(..) => StaticType::Unknown,
+                
+                AccessItem::Index(index) => {
+                    self.check_expr_with_symbol(index);
+                    StaticType::Unknown
+                }
+                
+                AccessItem::Invoke(args, kwargs) => {
+                    let arg_types = args.iter()
+                        .map(|arg| self.check_expr_with_symbol(arg))
+                        .collect::<Vec<StaticType>>();
+                    
+                    let kwarg_types = kwargs.iter()
+                        .map(|kwarg| {
+                            let name = StringSymbol::intern(self.resolve_str(&kwarg.name));
+                            (name, self.check_expr_with_symbol(&kwarg.value))
+                        })
+                        .collect::<Vec<(StringSymbol, StaticType)>>();
+                    
+                    if has_unpack(args) {
+                        // the number of arguments is only known at runtime
+                        self.check_invoke(&ty, None, &kwarg_types)
+                    } else {
+                        self.check_invoke(&ty, Some(&arg_types), &kwarg_types)
+                    }
+                }
+                
+                AccessItem::InvokeTable(items) => {
+                    self.check_table(items);
+                    self.check_invoke(&ty, Some(&[StaticType::Unknown]), &[])
+                }
+            };
+        }
+        
+        ty
+    }
+    
+    fn check_invoke(&mut self, callee: &StaticType, args: Option<&[StaticType]>, kwargs: &[(StringSymbol, StaticType)]) -> StaticType {
+        let function = match callee {
+            StaticType::Function(function) => function,
+            callee => {
+                let probe = types::probe_invoke(callee);
+                return self.probe_result(probe);
+            }
+        };
+        
+        if let Some(args) = args {
+            let names = kwargs.iter().map(|(name, _)| name);
+            if let Err(error) = function.signature.check_call(args.len(), names) {
+                self.warn(error.message());
+            }
+            
+            for (position, arg) in args.iter().enumerate() {
+                if let Some(annotation) = function.param_annotation(position) {
+                    self.check_annotation(arg, annotation);
+                }
+            }
+        }
+        
+        for (name, arg) in kwargs.iter() {
+            if let Some(annotation) = function.keyword_annotation(name) {
+                self.check_annotation(arg, annotation);
+            }
+        }
+        
+        function.returns.clone()
+    }
+    
+    fn check_table(&mut self, items: &[TableItem]) {
+        for item in items.iter() {
+            if let TableField::Index(index) = &item.field {
+                self.check_expr_with_symbol(index);
+            }
+            
+            let ty = self.check_expr_with_symbol(&item.value);
+            if let Some(annotation) = &item.annotation {
+                self.check_annotation(&ty, annotation);
+            }
+        }
+    }
+    
+    fn check_if_expression(&mut self, branches: &[ConditionalBranch], else_clause: Option<&ExprBlock>) -> StaticType {
+        let mut result: Option<StaticType> = None;
+        let mut join_result = |ty: Option<StaticType>| if let Some(ty) = ty {
+            result = Some(match result.take() {
+                Some(result) => result.join(&ty),
+                None => ty,
+            });
+        };
+        
+        for branch in branches.iter() {
+            self.check_expr(branch.condition());
+            join_result(self.check_expr_block(branch.suite()));
+        }
+        
+        match else_clause {
+            Some(suite) => join_result(self.check_expr_block(suite)),
+            None => join_result(Some(StaticType::Value(Type::Nil))),
+        }
+        
+        result.unwrap_or(StaticType::Unknown)
+    }
+    
+    fn check_match_expression(&mut self, subject: &Expr, cases: &[MatchCase]) -> StaticType {
+        let subject = self.check_expr(subject);
+        
+        let mut result: Option<StaticType> = None;
+        let mut join_result = |ty: Option<StaticType>| if let Some(ty) = ty {
+            result = Some(match result.take() {
+                Some(result) => result.join(&ty),
+                None => ty,
+            });
+        };
+        
+        for case in cases.iter() {
+            self.push_scope();
+            self.check_case_pattern(case.pattern(), subject.clone());
+            if let Some(guard) = case.guard() {
+                self.check_expr(guard);
+            }
+            join_result(self.check_expr_block(case.suite()));
+            self.pop_scope();
+        }
+        
+        // the result is nil if no case matches
+        if !cases.iter().any(MatchCase::is_irrefutable) {
+            join_result(Some(StaticType::Value(Type::Nil)));
+        }
+        
+        result.unwrap_or(StaticType::Unknown)
+    }
+    
+    fn check_case_pattern(&mut self, pattern: &Pattern, ty: StaticType) {
+        match pattern {
+            Pattern::Identifier(name) => self.declare_name(*name, ty),
+            
+            Pattern::Tuple(items) => for item in items.iter() {
+                self.check_case_pattern(item, StaticType::Unknown);
+            },
+            
+            Pattern::Pack(Some(pattern)) => self.check_case_pattern(pattern, StaticType::Value(Type::Tuple)),
+            
+            Pattern::Object(fields) => for field in fields.iter() {
+                self.check_case_pattern(&field.pattern, StaticType::Unknown);
+            },
+            
+            // alternatives can't bind names
+            _ => { },
+        }
+    }
+    
+    fn check_catch_clause(&mut self, catch: &CatchClause) {
+        self.push_scope();
+        self.declare_name(*catch.name(), StaticType::Unknown);
+        self.check_expr_block(catch.suite());
+        self.pop_scope();
+    }
+}
+
+
+// Assignment
+
+impl TypeChecker<'_> {
+    fn check_assignment(&mut self, action: MatchAction, lhs: &Pattern, ty: StaticType) {
+        match lhs {
+            Pattern::Identifier(name) => match action {
+                MatchAction::DeclImmutable => self.declare_name(*name, ty),
+                
+                // mutable variables may be assigned a value of any type later
+                MatchAction::DeclMutable => self.declare_name(*name, StaticType::Unknown),
+                
+                MatchAction::AssignLocal | MatchAction::AssignNonLocal => { },
+            },
+            
+            Pattern::Modifier { modifier, pattern } => self.check_assignment(*modifier, pattern, ty),
+            
+            Pattern::Annotated { pattern, annotation } => {
+                let ty = self.check_annotated(ty, annotation);
+                self.check_assignment(action, pattern, ty);
+            }
+            
+            Pattern::Tuple(items) => for item in items.iter() {
+                self.check_assignment(action, item, StaticType::Unknown);
+            },
+            
+            Pattern::Pack(Some(pattern)) => self.check_assignment(action, pattern, StaticType::Value(Type::Tuple)),
+            Pattern::Pack(None) => { },
+            
+            Pattern::Attribute(..) | Pattern::Index(..) => self.check_assign_target(lhs),
+            
+            Pattern::Wildcard | Pattern::Literal(..) | Pattern::Object(..) | Pattern::Alternative(..) => { },
+        }
+    }
+    
+    fn check_assign_target(&mut self, lhs: &Pattern) {
+        match lhs {
+            Pattern::Attribute(target) => { self.check_primary(&target.receiver); },
+            Pattern::Index(target) => {
+                self.check_primary(&target.receiver);
+                self.check_expr_with_symbol(&target.index);
+            }
+            _ => { },
+        }
+    }
+}
+
+
+// Function and Class Definitions
+
+impl TypeChecker<'_> {
+    fn check_function_def(&mut self, fundef: &FunctionDef) -> Rc<FunctionType> {
+        let signature = &fundef.signature;
+        
+        // calling a generator function produces a generator, whatever its body returns
+        if fundef.generator {
+            if let Some(return_type) = &fundef.return_type {
+                self.check_annotation(&StaticType::Value(Type::Iterator), return_type);
+            }
+        }
+        
+        self.frames.push(FunctionFrame {
+            return_type: fundef.return_type.clone().filter(|_| !fundef.generator),
+            returns: None,
+        });
+        self.push_scope();
+        
+        let params = signature.required.iter().map(|param| (param.name, param.mode, param.annotation.as_ref()))
+            .chain(signature.default.iter().map(|param| (param.name, param.mode, param.annotation.as_ref())))
+            .chain(signature.keyword.iter().map(|param| (param.name, param.mode, param.annotation.as_ref())))
+            .chain(signature.keyword_default.iter().map(|param| (param.name, param.mode, param.annotation.as_ref())));
+        
+        for (name, mode, annotation) in params {
+            let ty = match (mode, annotation) {
+                (Access::ReadOnly, Some(annotation)) => StaticType::from_annotation(annotation),
+                _ => StaticType::Unknown,
+            };
+            self.declare_name(name, ty);
+        }
+        
+        let collections = signature.variadic.iter().map(|param| (param, Type::Tuple))
+            .chain(signature.kwargs.iter().map(|param| (param, Type::Dict)));
+        
+        for (param, tag) in collections {
+            let ty = match param.mode {
+                Access::ReadOnly => StaticType::Value(tag),
+                Access::ReadWrite => StaticType::Unknown,
+            };
+            self.declare_name(param.name, ty);
+        }
+        
+        // default values are evaluated inside the function when it is called
+        for param in signature.default.iter().chain(signature.keyword_default.iter()) {
+            let ty = self.check_expr_with_symbol(&param.default);
+            if let Some(annotation) = &param.annotation {
+                self.check_annotation(&ty, annotation);
+            }
+        }
+        
+        if let Some(result) = self.check_expr_block(&fundef.body) {
+            self.check_return(result);
+        }
+        
+        self.pop_scope();
+        let frame = self.frames.pop().unwrap();
+        
+        let returns = match &fundef.return_type {
+            _ if fundef.generator => StaticType::Value(Type::Iterator),
+            Some(return_type) => match StaticType::from_annotation(return_type) {
+                StaticType::Unknown => frame.returns.unwrap_or(StaticType::Unknown),
+                narrowed => narrowed,
+            },
+            None => frame.returns.unwrap_or(StaticType::Unknown),
+        };
+        
+        Rc::new(FunctionType {
+            signature: self.create_signature(signature),
+            params: signature.required.iter().map(|param| param.annotation.clone())
+                .chain(signature.default.iter().map(|param| param.annotation.clone()))
+                .chain(signature.keyword.iter().map(|param| param.annotation.clone()))
+                .chain(signature.keyword_default.iter().map(|param| param.annotation.clone()))
+                .collect(),
+            variadic: signature.variadic.as_ref().and_then(|param| param.annotation.clone()),
+            returns,
+        })
+    }
+    
+    fn create_signature(&self, signature: &SignatureDef) -> Signature {
+        let create_param = |name: &InternSymbol, mode: Access| {
+            Parameter::new(StringSymbol::intern(self.resolve_str(name)), mode)
+        };
+        
+        Signature::new(
+            signature.name.map(|name| StringSymbol::intern(self.resolve_str(&name))),
+            signature.required.iter().map(|param| create_param(&param.name, param.mode)).collect(),
+            signature.default.iter().map(|param| create_param(&param.name, param.mode)).collect(),
+            signature.variadic.as_ref().map(|param| create_param(&param.name, param.mode)),
+        )
+        .with_keywords(
+            signature.keyword.iter().map(|param| create_param(&param.name, param.mode)).collect(),
+            signature.keyword_default.iter().map(|param| create_param(&param.name, param.mode)).collect(),
+            signature.kwargs.as_ref().map(|param| create_param(&param.name, param.mode)),
+        )
+    }
+    
+    fn check_class_def(&mut self, classdef: &ClassDef) {
+        if let Some(base) = &classdef.base {
+            self.check_expr_with_symbol(base);
+        }
+        
+        for field in classdef.fields.iter() {
+            if let Some(default) = &field.default {
+                self.check_expr_with_symbol(default);
+            }
+        }
+        
+        for method in classdef.methods.iter() {
+            self.symbols.push(method.symbol);
+            self.check_function_def(&method.fundef);
+            self.symbols.pop();
+        }
+    }
+}
+
+
+// the result types of builtins that always produce the same type of value
+fn builtin_result_type(name: &str) -> StaticType {
+    let tag = match name {
+        "bool" => Type::Boolean,
+        "int" | "len" => Type::Integer,
+        "float" => Type::Float,
+        "str" | "repr" => Type::String,
+        "list" => Type::List,
+        "dict" => Type::Dict,
+        "iter" | "next" | "globals" => Type::Tuple,
+        "print" | "help" => Type::Nil,
+        "error" => Type::Error,
+        "coroutine" => Type::Coroutine,
+        _ => return StaticType::Unknown,
+    };
+    StaticType::Value(tag)
+}
+
+fn has_unpack(items: &[ExprMeta]) -> bool {
+    items.iter().any(|item| matches!(item.variant(), Expr::Unpack(..)))
+}
+
+fn has_pack(items: &[Pattern]) -> bool {
+    items.iter().any(|item| matches!(item, Pattern::Pack(..)))
+}
+
+fn collect_names(pattern: &Pattern, callback: &mut impl FnMut(InternSymbol)) {
+    match pattern {
+        Pattern::Identifier(name) => callback(*name),
+        Pattern::Modifier { pattern, .. } | Pattern::Annotated { pattern, .. } => collect_names(pattern, callback),
+        Pattern::Tuple(items) => for item in items.iter() {
+            collect_names(item, callback);
+        },
+        Pattern::Pack(Some(pattern)) => collect_names(pattern, callback),
+        _ => { },
+    }
+}
@@ -0,0 +1,33 @@
+use core::fmt;
+use std::error::Error;
+
+use crate::utils;
+use crate::debug::{DebugSymbol, SourceError};
+
+
+/// A problem found by the type checker. These never prevent a program from being compiled.
+#[derive(Debug)]
+pub struct TypeWarning {
+    message: String,
+    symbol: Option<DebugSymbol>,
+}
+
+impl TypeWarning {
+    pub fn new(message: impl ToString, symbol: Option<DebugSymbol>) -> Self {
+        Self { message: message.to_string(), symbol }
+    }
+    
+    pub fn message(&self) -> &str { &self.message }
+}
+
+impl Error for TypeWarning { }
+
+impl SourceError for TypeWarning {
+    fn debug_symbol(&self) -> Option<&DebugSymbol> { self.symbol.as_ref() }
+}
+
+impl fmt::Display for TypeWarning {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        utils::format_error(fmt, "Type warning", Some(self.message.as_str()), None)
+    }
+}
@@ -0,0 +1,125 @@
+#![cfg(test)]
+
+use crate::source::SourceText;
+use crate::debug::SourceError;
+use crate::runtime::strings::StringInterner;
+use crate::typecheck::TypeWarning;
+
+fn check_source(source: &str) -> Vec<TypeWarning> {
+    let mut interner = StringInterner::new();
+    let ast = crate::parse_source(&mut interner, SourceText::from(source.to_string()))
+        .expect("syntax error");
+    
+    crate::check_ast(&interner, &ast)
+}
+
+fn assert_no_warnings(source: &str) {
+    let warnings = check_source(source);
+    assert!(warnings.is_empty(), "unexpected warnings: {:?}", warnings);
+}
+
+fn assert_warning(source: &str, message: &str) {
+    let warnings = check_source(source);
+    assert!(
+        warnings.iter().any(|warning| warning.message().contains(message)),
+        "expected a warning containing \"{}\", got: {:?}", message, warnings
+    );
+}
+
+
+#[test]
+fn typecheck_unsupported_operands() {
+    assert_warning(r#" "abc" + 1 "#, "unsupported operands: 'string' and 'int'");
+    assert_warning(r#" let x = 1 + 2.0; x + "s" "#, "unsupported operands: 'float' and 'string'");
+    assert_warning(" -\"abc\" ", "unsupported operand: 'string'");
+    
+    assert_no_warnings(r#" let s = "abc" + "def"; let n = 3 * 2.5 - 1 "#);
+}
+
+#[test]
+fn typecheck_value_dependent_errors() {
+    // these only fail for some values of the operands
+    assert_no_warnings(" let n = 1; let a = 1 << n; let b = 1 / n ");
+}
+
+#[test]
+fn typecheck_mutable_variables() {
+    assert_no_warnings(r#" var x = 1; x = "s"; x + "t" "#);
+    assert_warning(r#" let x, y = 1, "s"; x + y "#, "unsupported operands: 'int' and 'string'");
+}
+
+#[test]
+fn typecheck_builtin_calls() {
+    assert_warning(" len(1, 2) ", "takes 1 arguments but 2 were given");
+    assert_warning(" int() ", "missing 1 required argument");
+    assert_warning(" str(1) + 1 ", "unsupported operands: 'string' and 'int'");
+    
+    assert_no_warnings(r#" let t = ("abc",); print(1, 2, 3); len(t...) "#);
+}
+
+#[test]
+fn typecheck_shadowed_builtins() {
+    assert_no_warnings(" fun f() len(1, 2) end; let len = fun(a, b) a end; f() ");
+}
+
+#[test]
+fn typecheck_function_calls() {
+    assert_warning(" fun f(a, b = 2) a end; f(1, 2, 3) ", "takes 2 arguments but 3 were given");
+    assert_warning(r#" fun f(x: Int) x end; f("s") "#, "expected type 'Int', got 'string'");
+    assert_warning(r#" fun f() "s" end; f() - 1 "#, "unsupported operands: 'string' and 'int'");
+    assert_warning(r#" fun f() -> Int: "s" end "#, "expected type 'Int', got 'string'");
+    
+    assert_no_warnings(" fun f(x: Int | Float) x end; f(1); f(2.5) ");
+}
+
+#[test]
+fn typecheck_keyword_calls() {
+    assert_warning(" fun f(a, b = 2) a end; f(1, c = 3) ", "unexpected keyword argument \"c\"");
+    assert_warning(" fun f(a, b = 2) a end; f(1, a = 3) ", "multiple values for argument \"a\"");
+    assert_warning(" fun f(a, ..., b) a end; f(1) ", "missing 1 required argument");
+    assert_warning(r#" fun f(a, ..., b: Int) a end; f(1, b = "s") "#, "expected type 'Int', got 'string'");
+    
+    assert_no_warnings(" fun f(a, ..., b, rest...) a end; f(1, b = 2, c = 3); print(1, sep = \"\") ");
+}
+
+#[test]
+fn typecheck_not_callable() {
+    assert_warning(" let x = 5; x() ", "type 'int' is not callable");
+}
+
+#[test]
+fn typecheck_annotations() {
+    assert_warning(r#" let x: Int = "s" "#, "expected type 'Int', got 'string'");
+    assert_warning(r#" let x: Int | nil = 1.5 "#, "expected type 'Int | nil', got 'float'");
+}
+
+#[test]
+fn typecheck_warnings_have_symbols() {
+    let warnings = check_source(r#" "abc" + 1 "#);
+    assert!(warnings.iter().all(|warning| warning.debug_symbol().is_some()));
+}
+
+#[test]
+fn typecheck_match() {
+    assert_warning(r#" match 1 case n then n + "s" end "#, "unsupported operands: 'int' and 'string'");
+    assert_warning(r#" let x = match 1 case 1 then 2 case _ then 3 end; x + "s" "#, "unsupported operands: 'int' and 'string'");
+    
+    assert_no_warnings(r#" let x = match 1 case 1 then 2 end; x + "s" "#);
+}
+
+#[test]
+fn typecheck_interpolated_strings() {
+    assert_warning(r#" $"{1 + "s"}" "#, "unsupported operands: 'int' and 'string'");
+    assert_warning(r#" $"{1}" + 1 "#, "unsupported operands: 'string' and 'int'");
+    
+    assert_no_warnings(r#" let s = $"{1:05}" + "s" "#);
+}
+
+#[test]
+fn typecheck_generators() {
+    assert_warning(" fun f() yield 1 end; let x: Int = f() ", "expected type 'Int', got 'iterator'");
+    assert_warning(" fun f() -> Int: yield 1 end ", "expected type 'Int', got 'iterator'");
+    assert_warning(r#" fun f() yield 1 + "s" end "#, "unsupported operands: 'int' and 'string'");
+    
+    assert_no_warnings(" fun f() -> Iterator: yield 1; return end; for x in f() do x end ");
+}
@@ -0,0 +1,218 @@
+use std::rc::Rc;
+use crate::language::{IntType, FloatType};
+use crate::parser::operator::{UnaryOp, BinaryOp};
+use crate::parser::annotation::TypeExpr;
+use crate::runtime::Variant;
+use crate::runtime::types::Type;
+use crate::runtime::function::Signature;
+use crate::runtime::strings::{StringSymbol, static_symbol};
+use crate::runtime::errors::{ExecResult, ErrorKind, RuntimeError};
+
+
+/// The type of an expression, as far as it can be known without running the program
+#[derive(Debug, Clone)]
+pub enum StaticType {
+    Unknown,
+    Value(Type),
+    Function(Rc<FunctionType>),
+}
+
+#[derive(Debug)]
+pub struct FunctionType {
+    pub signature: Signature,
+    pub params: Box<[Option<TypeExpr>]>,  // annotations of the parameters in the order used by Signature::bind_args()
+    pub variadic: Option<TypeExpr>,
+    pub returns: StaticType,
+}
+
+impl FunctionType {
+    /// Get the annotation of the parameter that receives the argument at the given position
+    pub fn param_annotation(&self, position: usize) -> Option<&TypeExpr> {
+        if position < self.signature.positional_len() {
+            self.params.get(position).and_then(Option::as_ref)
+        } else {
+            self.variadic.as_ref()
+        }
+    }
+    
+    /// Get the annotation of the parameter that receives a keyword argument
+    pub fn keyword_annotation(&self, name: &StringSymbol) -> Option<&TypeExpr> {
+        self.signature.keyword_slot(name)
+            .and_then(|slot| self.params.get(slot))
+            .and_then(Option::as_ref)
+    }
+}
+
+impl StaticType {
+    pub fn type_tag(&self) -> Option<Type> {
+        match self {
+            Self::Unknown => None,
+            Self::Value(tag) => Some(*tag),
+            Self::Function(..) => Some(Type::Function),
+        }
+    }
+    
+    /// The type of a value that may come from either of two expressions
+    pub fn join(&self, other: &StaticType) -> StaticType {
+        match (self.type_tag(), other.type_tag()) {
+            (Some(tag), Some(other_tag)) if tag == other_tag => Self::Value(tag),
+            _ => Self::Unknown,
+        }
+    }
+    
+    /// The type of a value that has satisfied a type annotation
+    pub fn from_annotation(annotation: &TypeExpr) -> StaticType {
+        match annotation {
+            TypeExpr::Nil => Self::Value(Type::Nil),
+            TypeExpr::Builtin(builtin) => builtin.type_tag().map_or(Self::Unknown, Self::Value),
+            TypeExpr::Class(..) => Self::Value(Type::Object),
+            TypeExpr::Union(items) => items.iter()
+                .map(Self::from_annotation)
+                .reduce(|ty, item| ty.join(&item))
+                .unwrap_or(Self::Unknown),
+        }
+    }
+    
+    /// False only if no value of this type could satisfy the annotation
+    pub fn admits(&self, annotation: &TypeExpr) -> bool {
+        let tag = match self.type_tag() {
+            Some(tag) => tag,
+            None => return true,
+        };
+        
+        match annotation {
+            TypeExpr::Nil => tag == Type::Nil,
+            TypeExpr::Builtin(builtin) => builtin.accepts(tag),
+            TypeExpr::Class(..) => tag == Type::Object,
+            TypeExpr::Union(items) => items.iter().any(|item| self.admits(item)),
+        }
+    }
+    
+    // Values used to find out how the runtime treats a type. Only types whose values
+    // can be created without allocating are probed, everything else is left unknown.
+    fn sample_values(&self) -> Option<Vec<Variant>> {
+        let samples = match self.type_tag()? {
+            Type::Nil => vec![ Variant::Nil ],
+            Type::Boolean => vec![ Variant::from(true), Variant::from(false) ],
+            Type::Integer => vec![ Variant::from(1 as IntType), Variant::from(-1 as IntType) ],
+            Type::Float => vec![ Variant::from(1.0 as FloatType), Variant::from(-1.0 as FloatType) ],
+            Type::String => vec![ Variant::from(static_symbol!("")), Variant::from(static_symbol!("a")) ],
+            Type::Tuple => vec![ Variant::from(Vec::new().into_boxed_slice()) ],
+            _ => return None,
+        };
+        Some(samples)
+    }
+}
+
+
+/// The outcome of evaluating an operation on sample values of the operand types
+pub enum Probe {
+    /// the operation is not supported for any values of these types
+    Unsupported(Box<RuntimeError>),
+    Supported(StaticType),
+}
+
+fn is_unsupported(error: &RuntimeError) -> bool {
+    matches!(error.kind(),
+        ErrorKind::InvalidUnaryOperand
+        | ErrorKind::InvalidBinaryOperand
+        | ErrorKind::MethodNotSupported
+    )
+}
+
+// the result type is only known if every sample produced a value of the same type.
+// errors that depend on the operand values (e.g. divide by zero) don't make an operation unsupported
+fn probe_results(results: impl Iterator<Item=ExecResult<Variant>>) -> Probe {
+    let mut result_type = None;
+    let mut unsupported = None;
+    let mut supported = false;
+    
+    for result in results {
+        match result {
+            Ok(value) => {
+                supported = true;
+                let ty = StaticType::Value(value.type_tag());
+                result_type = match result_type {
+                    None => Some(ty),
+                    Some(prev) => Some(ty.join(&prev)),
+                };
+            }
+            Err(error) if is_unsupported(&error) => { unsupported.get_or_insert(error); },
+            Err(..) => {
+                supported = true;
+                result_type = Some(StaticType::Unknown);
+            }
+        }
+    }
+    
+    match unsupported {
+        Some(error) if !supported => Probe::Unsupported(error),
+        _ => Probe::Supported(result_type.unwrap_or(StaticType::Unknown)),
+    }
+}
+
+pub fn probe_unary_op(op: UnaryOp, operand: &StaticType) -> Probe {
+    let samples = match operand.sample_values() {
+        Some(samples) => samples,
+        None => return Probe::Supported(StaticType::Unknown),
+    };
+    
+    let results = samples.iter().map(|value| match op {
+        UnaryOp::Neg => value.apply_neg(),
+        UnaryOp::Pos => value.apply_pos(),
+        UnaryOp::Inv => value.apply_inv(),
+        UnaryOp::Not => value.apply_not(),
+    });
+    
+    probe_results(results)
+}
+
+pub fn probe_binary_op(op: BinaryOp, lhs: &StaticType, rhs: &StaticType) -> Probe {
+    let (lhs_samples, rhs_samples) = match (lhs.sample_values(), rhs.sample_values()) {
+        (Some(lhs_samples), Some(rhs_samples)) => (lhs_samples, rhs_samples),
+        _ => return Probe::Supported(StaticType::Unknown),
+    };
+    
+    let results = lhs_samples.iter()
+        .flat_map(|lhs| rhs_samples.iter().map(move |rhs| (lhs, rhs)))
+        .map(|(lhs, rhs)| match op {
+            BinaryOp::Exp => lhs.apply_pow(rhs),
+            BinaryOp::Mul => lhs.apply_mul(rhs),
+            BinaryOp::Div => lhs.apply_div(rhs),
+            BinaryOp::Mod => lhs.apply_mod(rhs),
+            BinaryOp::Add => lhs.apply_add(rhs),
+            BinaryOp::Sub => lhs.apply_sub(rhs),
+            BinaryOp::LShift => lhs.apply_shl(rhs),
+            BinaryOp::RShift => lhs.apply_shr(rhs),
+            BinaryOp::BitAnd => lhs.apply_and(rhs),
+            BinaryOp::BitXor => lhs.apply_xor(rhs),
+            BinaryOp::BitOr => lhs.apply_or(rhs),
+            BinaryOp::LT => lhs.cmp_lt(rhs).map(Variant::from),
+            BinaryOp::GT => lhs.cmp_gt(rhs).map(Variant::from),
+            BinaryOp::LE => lhs.cmp_le(rhs).map(Variant::from),
+            BinaryOp::GE => lhs.cmp_ge(rhs).map(Variant::from),
+            BinaryOp::EQ => lhs.cmp_eq(rhs).map(Variant::from),
+            BinaryOp::NE => lhs.cmp_ne(rhs).map(Variant::from),
+            
+            // short-circuiting operators don't dispatch to the operands
+            BinaryOp::And | BinaryOp::Or => unreachable!(),
+        });
+    
+    probe_results(results)
+}
+
+/// Find out if values of a type can be called
+pub fn probe_invoke(callee: &StaticType) -> Probe {
+    let samples = match callee.sample_values() {
+        Some(samples) => samples,
+        None => return Probe::Supported(StaticType::Unknown),
+    };
+    
+    let results = samples.iter()
+        .map(|value| value.invoke(&[], &[]).map(|_| Variant::Nil));
+    
+    match probe_results(results) {
+        Probe::Supported(..) => Probe::Supported(StaticType::Unknown),
+        unsupported => unsupported,
+    }
+}
@@ -0,0 +1,373 @@
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::Wake;
+use std::thread::{self, Thread};
+use core::cell::Cell;
+use core::pin::{Pin, pin};
+use core::future::Future;
+use core::task::{Context, Poll, Waker};
+
+use sphinx::engine::{Engine, EngineError};
+use sphinx::language::IntType;
+use sphinx::runtime::{Variant, IntoVariant, FromVariant};
+use sphinx::runtime::errors::ErrorKind;
+
+
+#[test]
+fn run_file() {
+    let mut engine = Engine::new();
+    engine.run_file("tests/list/basic.sph").unwrap();
+    assert!(engine.run_file("tests/missing.sph").is_err());
+}
+
+#[test]
+fn globals_persist() {
+    let mut engine = Engine::new();
+    engine.run_source("var count = 1").unwrap();
+    engine.run_source("count += 1").unwrap();
+    assert_eq!(engine.get::<IntType>("count").unwrap(), 2);
+    
+    engine.set_global("name", "sphinx");
+    engine.run_source("assert name == \"sphinx\"").unwrap();
+    assert_eq!(engine.get::<String>("name").unwrap(), "sphinx");
+    
+    assert!(engine.get_global("missing").is_none());
+}
+
+#[test]
+fn call_script_function() {
+    let mut engine = Engine::new();
+    engine.run_source("
+        fun add(a, b)
+            return a + b
+        end
+        
+        fun make_counter()
+            var count = 0
+            return fun()
+                nonlocal count += 1
+                return count
+            end
+        end
+    ").unwrap();
+    
+    let args = [ 3.into_variant(), 4.into_variant() ];
+    let result = engine.call_global("add", &args).unwrap();
+    assert_eq!(IntType::from_variant(&result).unwrap(), 7);
+    
+    // closures keep their state between calls
+    let counter = engine.call_global("make_counter", &[]).unwrap();
+    engine.set_global("counter", &counter);
+    engine.call(&counter, &[]).unwrap();
+    let result = engine.call(&counter, &[]).unwrap();
+    assert_eq!(IntType::from_variant(&result).unwrap(), 2);
+}
+
+#[test]
+fn call_errors() {
+    let mut engine = Engine::new();
+    engine.run_source("
+        fun fail()
+            throw error(\"failed\")
+        end
+    ").unwrap();
+    
+    match engine.call_global("fail", &[]) {
+        Err(EngineError::Runtime(error)) => assert!(matches!(error.kind(), ErrorKind::UserError)),
+        _ => panic!("expected a runtime error"),
+    }
+    
+    match engine.call_global("fail", &[ Variant::Nil ]) {
+        Err(EngineError::Runtime(error)) => assert!(matches!(error.kind(), ErrorKind::TooManyArguments)),
+        _ => panic!("expected a runtime error"),
+    }
+    
+    assert!(matches!(engine.run_source("let x = "), Err(EngineError::Build(..))));
+}
+
+#[test]
+fn tail_call_traceback() {
+    let mut engine = Engine::new();
+    engine.run_source("
+        fun fail_after(n)
+            if n == 0 then
+                throw error(\"failed\")
+            end
+            return fail_after(n - 1)
+        end
+    ").unwrap();
+    
+    let args = [ 1000.into_variant() ];
+    let traceback = match engine.call_global("fail_after", &args) {
+        Err(EngineError::Runtime(error)) => error.traceback().to_string(),
+        _ => panic!("expected a runtime error"),
+    };
+    
+    // the replaced frames are counted instead of being listed
+    assert!(traceback.contains("1000 frame(s) elided by tail calls"));
+    assert!(traceback.lines().count() < 10);
+}
+
+#[test]
+fn register_closure() {
+    let mut engine = Engine::new();
+    
+    let calls = Rc::new(Cell::new(0));
+    let counter = calls.clone();
+    engine.register_fn("double", &["value"], move |args| {
+        counter.set(counter.get() + 1);
+        let value = IntType::from_variant(&args[0])?;
+        Ok((value * 2).into_variant())
+    });
+    
+    engine.register_fn("count_args", &["args..."], |args| {
+        Ok((args.len() as IntType).into_variant())
+    });
+    
+    engine.run_source("
+        assert double(21) == 42
+        assert count_args() == 0
+        assert count_args(1, 2, 3) == 3
+        
+        let caught = try double() catch err err.kind end
+        assert caught == \"MissingArgumentsError\"
+    ").unwrap();
+    
+    assert_eq!(calls.get(), 1);
+}
+
+#[test]
+fn convert_values() {
+    let mut engine = Engine::new();
+    
+    engine.set_global("items", vec![1 as IntType, 2, 3]);
+    engine.set_global("nothing", None::<IntType>);
+    engine.run_source("
+        assert items == [1, 2, 3]
+        assert nothing == nil
+        items.append(4)
+    ").unwrap();
+    
+    assert_eq!(engine.get::<Vec<IntType>>("items").unwrap(), vec![1, 2, 3, 4]);
+    assert_eq!(engine.get::<Option<IntType>>("nothing").unwrap(), None);
+    
+    // conversions are checked
+    assert!(engine.get::<bool>("items").is_err());
+    assert!(engine.get::<String>("items").is_err());
+}
+
+#[test]
+fn run_tasks() {
+    let mut engine = Engine::new();
+    engine.run_source("
+        var log = []
+        
+        fun wait(frames)
+            var elapsed = 0
+            while elapsed < frames do
+                elapsed += suspend()
+            end
+        end
+        
+        fun actor(name, frames)
+            return fun(dt)
+                log.append(name)
+                wait(frames)
+                log.append(name + \" done\")
+            end
+        end
+        
+        fun broken(dt)
+            suspend()
+            throw error(\"broken\")
+        end
+    ").unwrap();
+    
+    for (name, frames) in [("a", 1), ("b", 3)] {
+        let args = [ name.into_variant(), (frames as IntType).into_variant() ];
+        let task = engine.call_global("actor", &args).unwrap();
+        engine.spawn(&task);
+    }
+    let broken = engine.get_global("broken").unwrap();
+    engine.spawn(&broken);
+    assert_eq!(engine.task_count(), 3);
+    
+    let dt = [ (1 as IntType).into_variant() ];
+    assert!(engine.run_tasks(&dt).is_empty());
+    assert_eq!(engine.get::<Vec<String>>("log").unwrap(), vec!["a", "b"]);
+    
+    // errors are reported and the failed task is removed
+    let errors = engine.run_tasks(&dt);
+    assert!(matches!(errors.as_slice(), [EngineError::Runtime(error)] if matches!(error.kind(), ErrorKind::UserError)));
+    assert_eq!(engine.task_count(), 1);
+    
+    engine.run_tasks(&dt);
+    assert_eq!(engine.task_count(), 1);
+    engine.run_tasks(&dt);
+    assert_eq!(engine.task_count(), 0);
+    assert_eq!(engine.get::<Vec<String>>("log").unwrap(), vec!["a", "b", "a done", "b done"]);
+}
+
+
+// Minimal single-threaded executor for testing async native functions
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+// A future that is pending for some number of polls before it is ready
+struct Delay<T> {
+    polls: usize,
+    value: Option<T>,
+}
+
+impl<T: Unpin> Future for Delay<T> {
+    type Output = T;
+    
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        if self.polls == 0 {
+            return Poll::Ready(self.value.take().unwrap());
+        }
+        self.polls -= 1;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+fn delay<T>(polls: usize, value: T) -> Delay<T> {
+    Delay { polls, value: Some(value) }
+}
+
+#[test]
+fn async_native_functions() {
+    let mut engine = Engine::new();
+    
+    let polled = Rc::new(Cell::new(0));
+    let counter = polled.clone();
+    engine.register_async_fn("fetch", &["key"], move |args| {
+        counter.set(counter.get() + 1);
+        let result = IntType::from_variant(&args[0]).map(|key| (key * 10).into_variant());
+        delay(3, result)
+    });
+    
+    engine.register_async_fn("fail", &[], |_| {
+        delay(1, Err(sphinx::runtime::RuntimeError::invalid_value("failed")))
+    });
+    
+    block_on(engine.run_source_async("
+        # async functions look like a blocking call to scripts
+        let a = fetch(1)
+        let b = fetch(2) + fetch(3)
+        assert a == 10 and b == 50
+        
+        # errors are raised where the function was called
+        let caught = try fail() catch err err.message end
+        assert caught == \"failed\"
+        
+        fun fetch_all(keys)
+            let result = []
+            for key in keys do
+                result.append(fetch(key))
+            end
+            result
+        end
+    ")).unwrap();
+    assert_eq!(polled.get(), 3);
+    
+    let keys = vec![1 as IntType, 2, 3].into_variant();
+    let fetch_all = engine.get_global("fetch_all").unwrap();
+    let result = block_on(engine.call_async(&fetch_all, &[keys])).unwrap();
+    assert_eq!(Vec::<IntType>::from_variant(&result).unwrap(), vec![10, 20, 30]);
+    
+    // uncaught errors include the call site in the traceback
+    match block_on(engine.run_source_async("fail()")) {
+        Err(EngineError::Runtime(error)) => assert!(matches!(error.kind(), ErrorKind::InvalidValue)),
+        _ => panic!("expected a runtime error"),
+    }
+    
+    // async functions can't be used without an async host, or where the VM can't be paused
+    assert!(engine.run_source("fetch(1)").is_err());
+    block_on(engine.run_source_async("
+        fun gen() yield fetch(1) end
+        let caught = try list(gen()) catch err err.kind end
+        assert caught == \"InvalidValueError\"
+    ")).unwrap();
+}
+
+// allocates enough garbage to run the collector
+const COLLECT: &str = "
+    var garbage = nil
+    for i in range(20000) do
+        garbage = [i, (i, i), [i]]
+    end
+";
+
+#[test]
+fn values_survive_collection() {
+    let mut engine = Engine::new();
+    engine.run_source("
+        fun make_counter(start)
+            var items = [start]
+            return fun()
+                items.append(items[-1] + 1)
+                return items[-1]
+            end
+        end
+    ").unwrap();
+    
+    // the closure is only reachable from the host
+    let counter = engine.call_global("make_counter", &[ (10 as IntType).into_variant() ]).unwrap();
+    engine.run_source(COLLECT).unwrap();
+    let result = engine.call(&counter, &[]).unwrap();
+    assert_eq!(IntType::from_variant(&result).unwrap(), 11);
+    
+    // a clone keeps the value alive after the original handle is dropped
+    let copy = counter.clone();
+    drop(counter);
+    engine.run_source(COLLECT).unwrap();
+    let result = engine.call(&copy, &[]).unwrap();
+    assert_eq!(IntType::from_variant(&result).unwrap(), 12);
+}
+
+#[test]
+fn tasks_survive_collection() {
+    let mut engine = Engine::new();
+    engine.run_source("
+        var log = []
+        fun counter(dt)
+            var total = [0]
+            var step = dt
+            while true do
+                total[0] += step
+                log.append(total[0])
+                step = suspend()
+            end
+        end
+    ").unwrap();
+    
+    // the task's coroutine is only reachable from the engine
+    let counter = engine.get_global("counter").unwrap();
+    engine.spawn(&counter);
+    
+    let dt = [ (1 as IntType).into_variant() ];
+    for _ in 0..3 {
+        assert!(engine.run_tasks(&dt).is_empty());
+        engine.run_source(COLLECT).unwrap();
+    }
+    assert_eq!(engine.get::<Vec<IntType>>("log").unwrap(), vec![1, 2, 3]);
+}
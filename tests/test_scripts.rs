@@ -3,7 +3,8 @@ use std::path::Path;
 use sphinx;
 use sphinx::builtins;
 use sphinx::source::ModuleSource;
-use sphinx::codegen::{Program, CompiledProgram};
+use sphinx::BuildOptions;
+use sphinx::codegen::{Program, CompiledProgram, OptLevel, BytecodeWriter, BytecodeReader, BytecodeError};
 use sphinx::runtime::{Module, VirtualMachine};
 use sphinx::runtime::errors::{ExecResult, ErrorKind};
 
@@ -19,10 +20,51 @@ fn build_program(source: &ModuleSource) -> Option<CompiledProgram> {
     }
 }
 
+fn build_optimized(source: &ModuleSource, opt_level: OptLevel) -> Option<CompiledProgram> {
+    let options = BuildOptions {
+        opt_level,
+        ..BuildOptions::default()
+    };
+    
+    match sphinx::build_module_with(source, options) {
+        Err(errors) => {
+            sphinx::print_build_errors(&errors, source);
+            None
+        },
+        
+        Ok((program, _)) => Some(program)
+    }
+}
+
 fn run_test_script(path: &Path) -> ExecResult<()> {
     let source = ModuleSource::File(path.into());
     let build = build_program(&source).expect("build failed");
     
+    run_program(source, build)
+}
+
+// same as run_test_script(), but round trips the program through the bytecode format first
+fn run_bytecode_script(path: &Path) -> ExecResult<()> {
+    let source = ModuleSource::File(path.into());
+    let build = build_program(&source).expect("build failed");
+    
+    let mut writer = BytecodeWriter::new(Vec::new()).with_symbols(true);
+    writer.write_program(&build).expect("write failed");
+    let bytes = writer.into_inner();
+    
+    let build = BytecodeReader::new(bytes.as_slice()).read_program().expect("read failed");
+    
+    run_program(source, build)
+}
+
+fn run_optimized_script(path: &Path, opt_level: OptLevel) -> ExecResult<()> {
+    let source = ModuleSource::File(path.into());
+    let build = build_optimized(&source, opt_level).expect("build failed");
+    
+    run_program(source, build)
+}
+
+fn run_program(source: ModuleSource, build: CompiledProgram) -> ExecResult<()> {
     let program = Program::load(build.program);
     
     let main_env = builtins::create_prelude();
@@ -57,6 +99,13 @@ macro_rules! test_script {
 test_script!(empty_file, "tests/empty_file.sph");
 test_script!(precedence, "tests/precedence.sph");
 
+mod exponent_tests {
+    use super::*;
+    
+    test_script!(basic, "tests/exponent/basic.sph");
+    test_script!(overflow, "tests/exponent/overflow.sph", error: ErrorKind::OverflowError {..});
+}
+
 mod if_tests {
     use super::*;
     
@@ -64,6 +113,7 @@ mod if_tests {
     test_script!(else_, "tests/if/else.sph");
     test_script!(if_, "tests/if/if.sph");
     test_script!(truth, "tests/if/truth.sph");
+    test_script!(branch_value, "tests/if/branch_value.sph");
 }
 
 mod loop_tests {
@@ -80,6 +130,47 @@ mod tuple_tests {
     test_script!(comparison, "tests/tuple/comparison.sph");
 }
 
+mod object_tests {
+    use super::*;
+    
+    test_script!(constructor, "tests/object/constructor.sph");
+    test_script!(unhashable_key, "tests/object/unhashable_key.sph", error: ErrorKind::UnhashableValue {..});
+    test_script!(attributes, "tests/object/attributes.sph");
+    test_script!(index, "tests/object/index.sph");
+    test_script!(update_assignment, "tests/object/update_assignment.sph");
+    test_script!(immutable_attribute, "tests/object/immutable_attribute.sph", error: ErrorKind::CantAssignImmutable {..});
+    test_script!(missing_attribute, "tests/object/missing_attribute.sph", error: ErrorKind::AttributeNotFound {..});
+    test_script!(missing_key, "tests/object/missing_key.sph", error: ErrorKind::KeyNotFound {..});
+    test_script!(not_indexable, "tests/object/not_indexable.sph", error: ErrorKind::MethodNotSupported {..});
+}
+
+mod class_tests {
+    use super::*;
+    
+    test_script!(basic, "tests/class/basic.sph");
+    test_script!(init, "tests/class/init.sph");
+    test_script!(bound_method, "tests/class/bound_method.sph");
+    test_script!(inheritance, "tests/class/inheritance.sph");
+    test_script!(closure, "tests/class/closure.sph");
+    test_script!(immutable_field, "tests/class/immutable_field.sph", error: ErrorKind::CantAssignImmutable {..});
+    test_script!(field_init, "tests/class/field_init.sph");
+    test_script!(missing_method, "tests/class/missing_method.sph", error: ErrorKind::AttributeNotFound {..});
+    test_script!(no_init, "tests/class/no_init.sph", error: ErrorKind::TooManyArguments {..});
+    test_script!(invalid_base, "tests/class/invalid_base.sph", error: ErrorKind::InvalidValue {..});
+    test_script!(no_base, "tests/class/no_base.sph", error: ErrorKind::AttributeNotFound {..});
+    
+    #[test]
+    fn invalid_syntax() {
+        // a malformed member is reported on its own and parsing continues with the next member
+        for text in [" class B fun f() 1 + end end ", " class B var = 1; fun g() 2 end end ", " class B let x = ; var y = 2 end "] {
+            match sphinx::build_module(&ModuleSource::String(text.to_string())) {
+                Err(sphinx::BuildErrors::Syntax(errors)) => assert_eq!(errors.len(), 1, "{}", text),
+                _ => panic!("expected a syntax error: {}", text),
+            }
+        }
+    }
+}
+
 mod while_tests {
     use super::*;
     
@@ -109,6 +200,7 @@ mod variable_tests {
     test_script!(in_nested_block, "tests/variable/in_nested_block.sph");
     test_script!(redeclare_global, "tests/variable/redeclare_global.sph");
     test_script!(assign_to_outer_block, "tests/variable/assign_to_outer_block.sph");
+    test_script!(global_cache, "tests/variable/global_cache.sph");
 }
 
 mod function_tests {
@@ -120,6 +212,14 @@ mod function_tests {
     test_script!(inner_block, "tests/function/inner_block.sph");
     test_script!(missing_arguments, "tests/function/missing_arguments.sph", error: ErrorKind::MissingArguments {..});
     test_script!(argument_unpack, "tests/function/argument_unpack.sph");
+    test_script!(default_args, "tests/function/default_args.sph");
+    test_script!(keyword_args, "tests/function/keyword_args.sph");
+    test_script!(keyword_only, "tests/function/keyword_only.sph");
+    test_script!(kwargs, "tests/function/kwargs.sph");
+    test_script!(keyword_only_missing, "tests/function/keyword_only_missing.sph", error: ErrorKind::MissingArguments {..});
+    test_script!(duplicate_argument, "tests/function/duplicate_argument.sph", error: ErrorKind::DuplicateArgument {..});
+    test_script!(unknown_argument, "tests/function/unknown_argument.sph", error: ErrorKind::UnknownArgument {..});
+    test_script!(tail_call, "tests/function/tail_call.sph");
 }
 
 mod closure_tests {
@@ -128,4 +228,370 @@ mod closure_tests {
     test_script!(open_closure_in_function, "tests/closure/open_closure_in_function.sph");
     test_script!(assign_to_upvalue, "tests/closure/assign_to_upvalue.sph");
     test_script!(nested_closure, "tests/closure/nested_closure.sph");
-}
\ No newline at end of file
+    test_script!(close_on_return, "tests/closure/close_on_return.sph");
+    test_script!(nonlocal_update, "tests/closure/nonlocal_update.sph");
+    
+    #[test]
+    fn update_without_nonlocal() {
+        let text = " fun outer() var x = 1; fun inner() x += 1 end end ";
+        let result = sphinx::build_module(&ModuleSource::String(text.to_string()));
+        assert!(matches!(result, Err(sphinx::BuildErrors::Compile(..))));
+    }
+}
+mod import_tests {
+    use super::*;
+    
+    test_script!(basic, "tests/import/basic.sph");
+    test_script!(package, "tests/import/package.sph");
+    test_script!(immutable_global, "tests/import/immutable_global.sph", error: ErrorKind::CantAssignImmutable {..});
+    test_script!(missing, "tests/import/missing.sph", error: ErrorKind::ImportError {..});
+    test_script!(circular, "tests/import/circular.sph", error: ErrorKind::ImportError {..});
+}
+
+mod exception_tests {
+    use super::*;
+    
+    test_script!(catch, "tests/exception/catch.sph");
+    test_script!(throw, "tests/exception/throw.sph");
+    test_script!(finally, "tests/exception/finally.sph");
+    test_script!(closure, "tests/exception/closure.sph");
+    test_script!(import, "tests/exception/import.sph");
+    test_script!(uncaught, "tests/exception/uncaught.sph", error: ErrorKind::UserError {..});
+    test_script!(throw_value, "tests/exception/throw_value.sph", error: ErrorKind::InvalidValue {..});
+    
+    #[test]
+    fn invalid_syntax() {
+        // errors inside a clause are reported without losing track of the rest of the try-expression
+        for text in [" try 1 + catch e 2 end ", " try 1 catch e 2 + finally 3 end ", " try 1 finally 2 + end "] {
+            let result = sphinx::build_module(&ModuleSource::String(text.to_string()));
+            assert!(matches!(result, Err(sphinx::BuildErrors::Syntax(..))), "{}", text);
+        }
+    }
+    
+    #[test]
+    fn stray_block_keyword() {
+        // a keyword that closes some other kind of block is skipped instead of stopping error recovery
+        for block in ["fun f() {} end", "begin {} end", "while true do {} end"] {
+            for stray in ["else", "case", "catch", "1 + else", "1 + case", "1 + catch"] {
+                let text = block.replace("{}", stray);
+                match sphinx::build_module(&ModuleSource::String(text.clone())) {
+                    Err(sphinx::BuildErrors::Syntax(errors)) => assert_eq!(errors.len(), 1, "{}", text),
+                    _ => panic!("expected a syntax error: {}", text),
+                }
+            }
+        }
+    }
+}
+
+mod register_tests {
+    use super::*;
+    
+    test_script!(operands, "tests/register/operands.sph");
+    test_script!(int_path, "tests/register/int_path.sph");
+    test_script!(store, "tests/register/store.sph");
+}
+
+mod list_tests {
+    use super::*;
+    
+    test_script!(basic, "tests/list/basic.sph");
+    test_script!(methods, "tests/list/methods.sph");
+    test_script!(iterate, "tests/list/iterate.sph");
+    test_script!(index_error, "tests/list/index_error.sph", error: ErrorKind::IndexError {..});
+    test_script!(pop_empty, "tests/list/pop_empty.sph", error: ErrorKind::IndexError {..});
+}
+
+mod dict_tests {
+    use super::*;
+    
+    test_script!(basic, "tests/dict/basic.sph");
+    test_script!(methods, "tests/dict/methods.sph");
+}
+
+mod delete_tests {
+    use super::*;
+    
+    test_script!(global, "tests/delete/global.sph");
+    test_script!(attribute, "tests/delete/attribute.sph");
+    test_script!(item, "tests/delete/item.sph");
+    test_script!(undefined, "tests/delete/undefined.sph", error: ErrorKind::NameNotDefined {..});
+    
+    #[test]
+    fn local_is_compile_error() {
+        let source = ModuleSource::File("tests/delete/local.sph".into());
+        assert!(sphinx::build_module(&source).is_err());
+    }
+}
+
+mod annotation_tests {
+    use super::*;
+    
+    test_script!(declaration, "tests/annotation/declaration.sph");
+    test_script!(function, "tests/annotation/function.sph");
+    test_script!(class, "tests/annotation/class.sph");
+    test_script!(mismatch, "tests/annotation/mismatch.sph", error: ErrorKind::TypeError {..});
+}
+
+mod match_tests {
+    use super::*;
+    
+    test_script!(literal, "tests/match/literal.sph");
+    test_script!(binding, "tests/match/binding.sph");
+    test_script!(tuple, "tests/match/tuple.sph");
+    test_script!(object, "tests/match/object.sph");
+    test_script!(alternative, "tests/match/alternative.sph");
+    test_script!(guard, "tests/match/guard.sph");
+    test_script!(no_match, "tests/match/no_match.sph");
+    test_script!(control_flow, "tests/match/control_flow.sph");
+    
+    fn build_source(text: &str) -> Result<CompiledProgram, sphinx::BuildErrors> {
+        sphinx::build_module(&ModuleSource::String(text.to_string()))
+    }
+    
+    #[test]
+    fn non_exhaustive_warning() {
+        let build = build_source("match 1 case 2 then 3 end").expect("build failed");
+        assert_eq!(build.warnings.len(), 1);
+        
+        let build = build_source("match 1 case (a, b) if a then 3 case x then x end").expect("build failed");
+        assert!(build.warnings.is_empty());
+    }
+    
+    #[test]
+    fn invalid_bindings() {
+        assert!(build_source("match 1 case a | 2 then 3 end").is_err());
+        assert!(build_source("match 1 case (a, a) then 3 end").is_err());
+        assert!(build_source("match 1 case (a..., b...) then 3 end").is_err());
+    }
+}
+
+mod string_tests {
+    use super::*;
+    
+    test_script!(interpolation, "tests/string/interpolation.sph");
+    test_script!(format_spec, "tests/string/format_spec.sph");
+    test_script!(invalid_radix, "tests/string/invalid_radix.sph", error: ErrorKind::TypeError {..});
+    test_script!(escapes, "tests/string/escapes.sph");
+    test_script!(raw, "tests/string/raw.sph");
+    test_script!(multiline, "tests/string/multiline.sph");
+    
+    #[test]
+    fn invalid_interpolation() {
+        for text in [r#" $"{}" "#, r#" $"{1 2}" "#, r#" $"{x:zz}" "#, r#" $"a } b" "#, r#" $"{x:5" "#] {
+            let result = sphinx::build_module(&ModuleSource::String(text.to_string()));
+            assert!(result.is_err(), "{}", text);
+        }
+    }
+    
+    #[test]
+    fn invalid_escapes() {
+        let texts = [
+            r#" "\xFF" "#, r#" "\u{110000}" "#, r#" "\u{D800}" "#, r#" "\u41" "#,
+            r#" "\u{41" "#, r#" "\q" "#, r#" """\q""" "#, r#" """unclosed "#,
+        ];
+        for text in texts {
+            let result = sphinx::build_module(&ModuleSource::String(text.to_string()));
+            assert!(result.is_err(), "{}", text);
+        }
+    }
+}
+
+mod generator_tests {
+    use super::*;
+    
+    test_script!(basic, "tests/generator/basic.sph");
+    test_script!(nested, "tests/generator/nested.sph");
+    test_script!(zip, "tests/generator/zip.sph");
+    test_script!(ordering, "tests/generator/ordering.sph");
+    test_script!(closure, "tests/generator/closure.sph");
+    test_script!(try_, "tests/generator/try.sph");
+    test_script!(error, "tests/generator/error.sph", error: ErrorKind::UserError {..});
+    test_script!(running, "tests/generator/running.sph", error: ErrorKind::InvalidValue {..});
+    
+    #[test]
+    fn yield_outside_function() {
+        for text in [" yield 1 ", " class C yield 1 end ", " fun f() end; yield "] {
+            let result = sphinx::build_module(&ModuleSource::String(text.to_string()));
+            assert!(result.is_err(), "{}", text);
+        }
+    }
+}
+
+mod coroutine_tests {
+    use super::*;
+    
+    test_script!(basic, "tests/coroutine/basic.sph");
+    test_script!(nested, "tests/coroutine/nested.sph");
+    test_script!(closure, "tests/coroutine/closure.sph");
+    test_script!(try_, "tests/coroutine/try.sph");
+    test_script!(error, "tests/coroutine/error.sph", error: ErrorKind::UserError {..});
+    test_script!(finished, "tests/coroutine/finished.sph", error: ErrorKind::InvalidValue {..});
+    test_script!(outside, "tests/coroutine/outside.sph", error: ErrorKind::InvalidValue {..});
+    test_script!(generator, "tests/coroutine/generator.sph", error: ErrorKind::InvalidValue {..});
+}
+
+mod comprehension_tests {
+    use super::*;
+    
+    test_script!(list, "tests/comprehension/list.sph");
+    test_script!(dict, "tests/comprehension/dict.sph");
+    test_script!(tuple, "tests/comprehension/tuple.sph");
+    test_script!(generator, "tests/comprehension/generator.sph");
+    test_script!(closure, "tests/comprehension/closure.sph");
+    
+    #[test]
+    fn invalid_syntax() {
+        for text in [" [x for] ", " [x for x] ", " [x for x in] ", " (x if x for x in xs) ", " [x for x in xs if] ", " (x for x in xs,,) "] {
+            let result = sphinx::build_module(&ModuleSource::String(text.to_string()));
+            assert!(result.is_err(), "{}", text);
+        }
+    }
+}
+
+mod lambda_tests {
+    use super::*;
+    
+    test_script!(basic, "tests/lambda/basic.sph");
+    
+    #[test]
+    fn invalid_syntax() {
+        for text in [" |x x ", " |x, x| x ", " |x: int| x ", " |1| x ", " |x,| x ", " |x| "] {
+            let result = sphinx::build_module(&ModuleSource::String(text.to_string()));
+            assert!(result.is_err(), "{}", text);
+        }
+    }
+}
+
+mod bytecode_tests {
+    use super::*;
+    
+    macro_rules! test_bytecode {
+        ( $name:tt, $path:expr ) => {
+            #[test]
+            fn $name() {
+                if let Err(error) = run_bytecode_script(Path::new($path)) {
+                    panic!("{}{}", error.traceback(), error);
+                }
+            }
+        };
+    }
+    
+    test_bytecode!(precedence, "tests/precedence.sph");
+    test_bytecode!(nested_closure, "tests/closure/nested_closure.sph");
+    test_bytecode!(argument_unpack, "tests/function/argument_unpack.sph");
+    test_bytecode!(class_inheritance, "tests/class/inheritance.sph");
+    test_bytecode!(object_constructor, "tests/object/constructor.sph");
+    test_bytecode!(match_tuple, "tests/match/tuple.sph");
+    test_bytecode!(string_interpolation, "tests/string/interpolation.sph");
+    test_bytecode!(generator_closure, "tests/generator/closure.sph");
+    test_bytecode!(coroutine_nested, "tests/coroutine/nested.sph");
+    test_bytecode!(comprehension_closure, "tests/comprehension/closure.sph");
+    test_bytecode!(register_operands, "tests/register/operands.sph");
+    test_bytecode!(register_int_path, "tests/register/int_path.sph");
+    test_bytecode!(register_store, "tests/register/store.sph");
+    test_bytecode!(function_tail_call, "tests/function/tail_call.sph");
+    
+    #[test]
+    fn malformed() {
+        let source = ModuleSource::File("tests/precedence.sph".into());
+        let build = build_program(&source).expect("build failed");
+        
+        let mut writer = BytecodeWriter::new(Vec::new());
+        writer.write_program(&build).expect("write failed");
+        let mut bytes = writer.into_inner();
+        
+        let result = BytecodeReader::new(&bytes[..bytes.len() - 1]).read_program();
+        assert!(matches!(result, Err(BytecodeError::Malformed(..))));
+        
+        bytes[4] = bytes[4].wrapping_add(1);
+        let result = BytecodeReader::new(bytes.as_slice()).read_program();
+        assert!(matches!(result, Err(BytecodeError::UnsupportedVersion(..))));
+        
+        let result = BytecodeReader::new(&b"not bytecode"[..]).read_program();
+        assert!(matches!(result, Err(BytecodeError::NotBytecode)));
+    }
+    
+    #[test]
+    fn malformed_keyword_param() {
+        let source = ModuleSource::String("fun f(..., key) key end".to_string());
+        let build = build_program(&source).expect("build failed");
+        
+        let mut writer = BytecodeWriter::new(Vec::new());
+        writer.write_program(&build).expect("write failed");
+        let mut bytes = writer.into_inner();
+        assert!(BytecodeReader::new(bytes.as_slice()).read_program().is_ok());
+        
+        // without symbols the function table comes last, ending with the keyword parameter's name and mode,
+        // the keyword default count, the kwargs option, and the upvalue count
+        let name = bytes.len() - (2 + 1 + 4 + 1 + 4);
+        bytes[name..name + 2].copy_from_slice(&u16::MAX.to_le_bytes());
+        let result = BytecodeReader::new(bytes.as_slice()).read_program();
+        assert!(matches!(result, Err(BytecodeError::Malformed(..))));
+    }
+}
+
+// scripts should behave the same way at every optimization level
+mod optimize_tests {
+    use super::*;
+    
+    fn run_differential(path: &Path) {
+        let expected = run_test_script(path).map_err(|error| *error.kind());
+        for opt_level in [ OptLevel::Fold, OptLevel::Peephole ] {
+            let result = run_optimized_script(path, opt_level).map_err(|error| *error.kind());
+            assert_eq!(result, expected, "different result at {:?}", opt_level);
+        }
+    }
+    
+    macro_rules! test_optimized {
+        ( $name:tt, $path:expr ) => {
+            #[test]
+            fn $name() {
+                run_differential(Path::new($path));
+            }
+        };
+    }
+    
+    test_optimized!(folding, "tests/optimize/folding.sph");
+    test_optimized!(control_flow, "tests/optimize/control_flow.sph");
+    
+    test_optimized!(precedence, "tests/precedence.sph");
+    test_optimized!(exponent_overflow, "tests/exponent/overflow.sph");
+    test_optimized!(if_elif, "tests/if/elif.sph");
+    test_optimized!(if_truth, "tests/if/truth.sph");
+    test_optimized!(loop_continue, "tests/loop/continue.sph");
+    test_optimized!(while_continue, "tests/while/continue.sph");
+    test_optimized!(for_continue, "tests/for/continue.sph");
+    test_optimized!(function_default_args, "tests/function/default_args.sph");
+    test_optimized!(closure_close_on_return, "tests/closure/close_on_return.sph");
+    test_optimized!(exception_catch, "tests/exception/catch.sph");
+    test_optimized!(exception_finally, "tests/exception/finally.sph");
+    test_optimized!(exception_uncaught, "tests/exception/uncaught.sph");
+    test_optimized!(match_control_flow, "tests/match/control_flow.sph");
+    test_optimized!(match_guard, "tests/match/guard.sph");
+    test_optimized!(string_interpolation, "tests/string/interpolation.sph");
+    test_optimized!(generator_try, "tests/generator/try.sph");
+    test_optimized!(coroutine_nested, "tests/coroutine/nested.sph");
+    test_optimized!(comprehension_dict, "tests/comprehension/dict.sph");
+    test_optimized!(register_operands, "tests/register/operands.sph");
+    test_optimized!(register_int_path, "tests/register/int_path.sph");
+    test_optimized!(register_store, "tests/register/store.sph");
+    test_optimized!(function_tail_call, "tests/function/tail_call.sph");
+    
+    // the loop body is large enough that jumping back to the start of the loop needs a long jump
+    #[test]
+    fn long_jumps() {
+        let body = "    c += 1\n".repeat(4000);
+        let text = format!(
+            "var c = 0\nvar i = 0\nloop\n{}    i += 1\n    if i == 3 then break end\nend\nassert c == 12000\n",
+            body,
+        );
+        
+        for opt_level in [ OptLevel::None, OptLevel::Peephole ] {
+            let source = ModuleSource::String(text.clone());
+            let build = build_optimized(&source, opt_level).expect("build failed");
+            if let Err(error) = run_program(source, build) {
+                panic!("{}{}", error.traceback(), error);
+            }
+        }
+    }
+}